@@ -1,8 +1,11 @@
 from ansible.plugins.strategy.linear import StrategyModule as LinearStrategyModule
 from ansible.utils.display import Display
 import socket
+import ssl
 import json
 import os
+import queue
+import threading
 import time
 
 display = Display()
@@ -23,6 +26,9 @@ class StrategyModule(LinearStrategyModule):
         print("DEBUG: Piloteer Strategy Init", flush=True)
         super(StrategyModule, self).__init__(tqm)
         self.sock = None
+        self._send_lock = threading.Lock()
+        self._inbox = queue.Queue()
+        self._aborted = False
         self._connect_to_piloteer()
 
     def _connect_to_piloteer(self):
@@ -31,14 +37,25 @@ class StrategyModule(LinearStrategyModule):
         try:
             if ":" in socket_path:
                 # Assume TCP: host:port
-                host, port = socket_path.split(":", 1)
+                host, port = socket_path.rsplit(":", 1)
                 self.sock = socket.socket(socket.AF_INET, socket.SOCK_STREAM)
                 self.sock.connect((host, int(port)))
+                if os.environ.get("PILOTEER_TLS") == "1":
+                    # Verify the controller against PILOTEER_TLS_CA, or the
+                    # system trust store when it's unset
+                    context = ssl.create_default_context(
+                        cafile=os.environ.get("PILOTEER_TLS_CA")
+                    )
+                    self.sock = context.wrap_socket(self.sock, server_hostname=host)
             else:
                 # Assume Unix Socket
                 self.sock = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
                 self.sock.connect(socket_path)
             
+            # Read on a background thread so heartbeat pings are answered
+            # even while a task is running
+            threading.Thread(target=self._read_loop, daemon=True).start()
+
             secret = os.environ.get("PILOTEER_SECRET")
             self._send({"Handshake": {"token": secret}})
             self._wait_for_proceed()
@@ -47,32 +64,122 @@ class StrategyModule(LinearStrategyModule):
             self.sock = None
 
     def _send(self, data):
-        if self.sock:
+        # Read once: _abort may clear self.sock from the reader thread
+        sock = self.sock
+        if sock:
             try:
                 # Use SafeEncoder for robust serialization
                 msg = json.dumps(data, cls=SafeEncoder) + "\n"
             except Exception as e:
                  msg = json.dumps({"Error": f"Serialization Failed: {str(e)}"}) + "\n"
-            self.sock.sendall(msg.encode('utf-8'))
+            with self._send_lock:
+                sock.sendall(msg.encode('utf-8'))
+
+    def _read_loop(self):
+        # Queue every controller message except Ping, which is answered
+        # here; None marks the connection as closed
+        buffer = ""
+        try:
+            while True:
+                chunk = self.sock.recv(4096).decode('utf-8')
+                if not chunk:
+                    break
+                buffer += chunk
+                while "\n" in buffer:
+                    line, buffer = buffer.split("\n", 1)
+                    try:
+                        msg = json.loads(line)
+                    except json.JSONDecodeError:
+                        continue
+                    if msg == "Ping":
+                        self._send("Pong")
+                    elif msg == "Abort":
+                        self._abort()
+                        return
+                    else:
+                        self._inbox.put(msg)
+        except OSError:
+            pass
+        self._inbox.put(None)
+
+    def _abort(self):
+        # The controller is quitting: stop scheduling tasks so the play
+        # fails, then hang up, which tells the controller we're done
+        display.warning("Piloteer is shutting down; aborting the play")
+        self._aborted = True
+        self._tqm.terminate()
+        # Under the send lock so a send in progress finishes first
+        with self._send_lock:
+            sock, self.sock = self.sock, None
+            try:
+                sock.shutdown(socket.SHUT_RDWR)
+                sock.close()
+            except OSError:
+                pass
+        self._inbox.put(None)
+
+    def _is_rescued(self, task):
+        # A failure anywhere under the `block` section of a block that has a
+        # `rescue` section is handled by Ansible and the run continues, so
+        # report it as rescued rather than as a final failure. Each ancestor
+        # is checked through the child on the task's path: a task under a
+        # rescue/always section isn't covered by that block, but may still be
+        # by one further out.
+        child = task
+        parent = getattr(task, '_parent', None)
+        while parent is not None:
+            if getattr(parent, 'rescue', None):
+                uuid = getattr(child, '_uuid', None)
+                if any(getattr(t, '_uuid', None) == uuid for t in getattr(parent, 'block', None) or []):
+                    return True
+            child, parent = parent, getattr(parent, '_parent', None)
+        return False
+
+    def _send_failed_result(self, res, rescued):
+        warnings, deprecations = self._notices(res._return_data)
+        self._send({
+            "TaskResult": {
+                "name": res.task_name,
+                "host": res.host.name,
+                "changed": False,
+                "failed": True,
+                "verbose_result": res._return_data,
+                "rescued": rescued,
+                "warnings": warnings,
+                "deprecations": deprecations
+            }
+        })
+
+    def _notices(self, result):
+        # Ansible attaches `warnings` (strings) and `deprecations` (dicts with
+        # `msg` and optionally `version`/`date`) to module results.
+        if not isinstance(result, dict):
+            return [], []
+        warnings = [str(w) for w in result.get('warnings') or []]
+        deprecations = []
+        for d in result.get('deprecations') or []:
+            if isinstance(d, dict):
+                msg = str(d.get('msg', ''))
+                removal = d.get('version') or d.get('date')
+                if removal:
+                    msg = f"{msg} (removal in {removal})"
+                deprecations.append(msg)
+            else:
+                deprecations.append(str(d))
+        return warnings, deprecations
 
     def _wait_for_proceed(self):
         if not self.sock:
             return
         
-        buffer = ""
         while True:
-            chunk = self.sock.recv(1024).decode('utf-8')
-            if not chunk:
-                break
-            buffer += chunk
-            if "\n" in buffer:
-                line, buffer = buffer.split("\n", 1)
-                try:
-                    msg = json.loads(line)
-                    if msg == "Proceed":
-                        return
-                except json.JSONDecodeError:
-                    pass
+            msg = self._inbox.get()
+            if msg is None:
+                # Leave the marker for the next wait
+                self._inbox.put(None)
+                return
+            if msg == "Proceed":
+                return
 
     def run(self, iterator, play_context):
         self.play_context = play_context
@@ -107,6 +214,8 @@ class StrategyModule(LinearStrategyModule):
             
             self._send({"PlayRecap": {"stats": stats}})
             
+        if self._aborted:
+            return self._tqm.RUN_ERROR
         return result
 
     def _process_pending_results(self, iterator, max_passes=1, one_pass=False):
@@ -143,14 +252,19 @@ class StrategyModule(LinearStrategyModule):
                 cleaned_results.append(res)
                 continue
                 
-            if res.is_failed():
+            if res.is_failed() and self._is_rescued(res._task):
+                # The block's rescue section takes over; nothing to pause for
+                self._send_failed_result(res, rescued=True)
+                cleaned_results.append(res)
+
+            elif res.is_failed():
                 # Task Failed!
                 host = res.host
                 task = res.task_name
                 result_data = res._return_data
                 
                 # Notify Piloteer
-                self._send({"TaskFail": {"name": task, "result": result_data}})
+                self._send({"TaskFail": {"name": task, "host": host.name, "result": result_data}})
                 
                 # Enter "Debug Mode" Loop
                 while True:
@@ -195,30 +309,41 @@ class StrategyModule(LinearStrategyModule):
                         if key:
                             self._variable_manager.extra_vars[key] = val
                             display.display(f"[Piloteer] Modified {key} = {val} (Global/Extra Var)")
+
+                    elif cmd_type == "UnsetVar":
+                        key = cmd_data.get("key")
+                        if key:
+                            self._variable_manager.extra_vars.pop(key, None)
+                            display.display(f"[Piloteer] Unset {key} (Global/Extra Var)")
                             
                     elif cmd_type == "Continue":
-                        self._send({
-                            "TaskResult": {
-                                "name": task,
-                                "host": host.name,
-                                "changed": False,
-                                "failed": True,
-                                "verbose_result": result_data
-                            }
-                        })
+                        self._send_failed_result(res, rescued=False)
                         cleaned_results.append(res)
                         break
                         
+            elif res.is_skipped():
+                # Task Skipped (when: condition was false)
+                self._send({
+                    "TaskSkipped": {
+                        "name": res.task_name,
+                        "host": res.host.name
+                    }
+                })
+                cleaned_results.append(res)
+
             else:
                 # Task Succeeded
                 is_changed = res.is_changed()
+                warnings, deprecations = self._notices(res._return_data)
                 self._send({
                     "TaskResult": {
                         "name": res.task_name,
                         "host": res.host.name,
                         "changed": is_changed,
                         "failed": False,
-                        "verbose_result": res._return_data
+                        "verbose_result": res._return_data,
+                        "warnings": warnings,
+                        "deprecations": deprecations
                     }
                 })
                 cleaned_results.append(res)
@@ -229,33 +354,28 @@ class StrategyModule(LinearStrategyModule):
         if not self.sock:
             return "Continue", None
             
-        buffer = ""
         while True:
-            chunk = self.sock.recv(4096).decode('utf-8')
-            if not chunk:
+            msg = self._inbox.get()
+            if msg is None:
+                self._inbox.put(None)
+                return "Continue", None
+            if msg == "Retry":
+                return "Retry", None
+            elif isinstance(msg, dict) and "ModifyVar" in msg:
+                return "ModifyVar", msg["ModifyVar"]
+            elif isinstance(msg, dict) and "UnsetVar" in msg:
+                return "UnsetVar", msg["UnsetVar"]
+            elif msg == "Continue":
                 return "Continue", None
-            buffer += chunk
-            if "\n" in buffer:
-                line, buffer = buffer.split("\n", 1)
-                try:
-                    msg = json.loads(line)
-                    if msg == "Retry":
-                        return "Retry", None
-                    elif isinstance(msg, dict) and "ModifyVar" in msg:
-                        return "ModifyVar", msg["ModifyVar"]
-                    elif msg == "Continue":
-                        return "Continue", None
-                    elif msg == "Proceed":
-                         return "Continue", None
-                except json.JSONDecodeError:
-                    pass
+            elif msg == "Proceed":
+                 return "Continue", None
     
     def get_hosts_left(self, iterator):
         return super(StrategyModule, self).get_hosts_left(iterator)
 
     def _get_next_task_lockstep(self, hosts, iterator):
         hosts_tasks = super(StrategyModule, self)._get_next_task_lockstep(hosts, iterator)
-        if hosts_tasks:
+        if hosts_tasks and not self._aborted:
             first_host, first_task = hosts_tasks[0]
             if first_task:
                  task_vars = self._tqm._variable_manager.get_vars(host=first_host, task=first_task)
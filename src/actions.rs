@@ -1,5 +1,6 @@
-use crate::app::{Action, ActiveView, AnalysisFocus, App, EditState, MetricsView};
+use crate::app::{Action, ActiveView, AnalysisFocus, App, EditState, MetricsView, VarChange};
 use crate::ipc::Message;
+use crate::ipc_handler::{revert_var_change, send_control, send_var_change};
 use crate::widgets::json_tree::JsonTreeState;
 use anyhow::Result;
 use crossterm::execute;
@@ -36,10 +37,45 @@ impl App {
         }
     }
 
+    /// Copy the YAML block of `reply` if it answers the pending snippet
+    /// request; replies to anything else are left alone.
+    pub fn take_snippet_reply(&mut self, reply: &crate::ai::ChatMessage) {
+        if reply.reply_to.is_none() || reply.reply_to != self.snippet_pending {
+            return;
+        }
+        self.snippet_pending = None;
+        match crate::ai::extract_yaml_block(&reply.content) {
+            Some(snippet) => self.copy_to_clipboard(snippet),
+            None => self.notify("AI reply contained no YAML snippet".to_string()),
+        }
+    }
+
     pub fn prepare_edit(&mut self, key: String) -> io::Result<()> {
         let val = self
             .get_var_value(&key)
             .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Variable not found"))?;
+        self.prepare_edit_value(key, &val)
+    }
+
+    /// Edit the Data Browser's selected leaf. The plugin only sets top-level
+    /// variables, so the edited leaf is written back into a copy of the
+    /// enclosing top-level value, which is what gets sent.
+    pub fn prepare_tree_edit(&mut self) -> Result<(), String> {
+        if self.replay_mode {
+            return Err("Cannot edit variables while replaying".to_string());
+        }
+        let tree = self
+            .analysis_tree
+            .as_ref()
+            .ok_or_else(|| "No data to edit".to_string())?;
+        let (key, base, leaf, path) = tree.edit_target()?;
+        self.prepare_edit_value(key, &leaf)
+            .map_err(|e| e.to_string())?;
+        self.tree_edit = Some((path, base));
+        Ok(())
+    }
+
+    fn prepare_edit_value(&mut self, key: String, val: &serde_json::Value) -> io::Result<()> {
         let content = serde_json::to_string_pretty(&val).map_err(|_| {
             io::Error::new(io::ErrorKind::InvalidData, "Failed to serialize variable")
         })?;
@@ -65,9 +101,13 @@ impl App {
         let content = std::fs::read_to_string(temp_file)
             .map_err(|e| format!("Failed to read temp file: {}", e))?;
         let _ = std::fs::remove_file(temp_file);
-        let val: serde_json::Value =
+        let mut val: serde_json::Value =
             serde_json::from_str(&content).map_err(|e| format!("Invalid JSON: {}", e))?;
         self.edit_state = EditState::Idle;
+        if let Some((path, mut base)) = self.tree_edit.take() {
+            crate::widgets::json_tree::set_by_path(&mut base, &path, val);
+            val = base;
+        }
         Ok((key, val))
     }
 
@@ -76,6 +116,7 @@ impl App {
             let _ = std::fs::remove_file(temp_file);
         }
         self.edit_state = EditState::Idle;
+        self.tree_edit = None;
     }
 }
 
@@ -115,19 +156,19 @@ pub async fn dispatch(
         Action::Proceed => {
             if app.waiting_for_proceed {
                 app.waiting_for_proceed = false;
-                send_ipc(app, Message::Proceed).await;
+                send_control(app, Message::Proceed, "Proceed").await;
             }
         }
         Action::Retry => {
             if app.waiting_for_proceed {
                 app.waiting_for_proceed = false;
-                send_ipc(app, Message::Retry).await;
+                send_control(app, Message::Retry, "Retry").await;
             }
         }
         Action::Continue => {
             if app.waiting_for_proceed {
                 app.waiting_for_proceed = false;
-                send_ipc(app, Message::Continue).await;
+                send_control(app, Message::Continue, "Continue").await;
             }
         }
 
@@ -137,18 +178,17 @@ pub async fn dispatch(
 
         Action::SubmitChat => submit_chat(app, ai_tx).await,
 
+        Action::CopyFixSnippet => copy_fix_snippet(app, ai_tx),
+
+        Action::ChatAboutFailure => chat_about_failure(app, ai_tx),
+
+        Action::UndoVarChange => undo_var_change(app).await,
+
         Action::ApplyFix => {
             if let Some(analysis) = &app.suggestion.clone()
                 && let Some(fix) = &analysis.fix
+                && modify_var(app, fix.key.clone(), fix.value.clone(), "ApplyFix").await
             {
-                send_ipc(
-                    app,
-                    Message::ModifyVar {
-                        key: fix.key.clone(),
-                        value: fix.value.clone(),
-                    },
-                )
-                .await;
                 app.log(
                     format!("Applying Fix: {} = {}", fix.key, fix.value),
                     Some(ratatui::style::Color::Green),
@@ -156,6 +196,38 @@ pub async fn dispatch(
             }
         }
 
+        Action::RerunHint => match crate::session::RerunHint::from_history(&app.history) {
+            Some(hint) => {
+                let args = hint.args();
+                app.log(
+                    format!(
+                        "Re-run changed/failed tasks ({}): ansible-playbook <playbook> {}",
+                        hint.tasks.join(", "),
+                        args
+                    ),
+                    Some(ratatui::style::Color::Cyan),
+                );
+                app.log(
+                    "Ansible can't run an arbitrary subset: tasks after the start point run again. \
+                     Tag the listed tasks and use --tags for a tighter selection."
+                        .to_string(),
+                    Some(ratatui::style::Color::DarkGray),
+                );
+                app.copy_to_clipboard(args);
+            }
+            None => app.notify("No changed or failed tasks to re-run".to_string()),
+        },
+
+        Action::ConfirmVarChange => {
+            if let Some((change, action)) = app.pending_var_change.take() {
+                send_var_change(app, change.clone(), action).await;
+                app.log(
+                    format!("Overwrote {} = {}", change.key, change.value),
+                    Some(ratatui::style::Color::Green),
+                );
+            }
+        }
+
         Action::ToggleFollow => {
             app.auto_scroll = !app.auto_scroll;
             if app.auto_scroll {
@@ -165,42 +237,53 @@ pub async fn dispatch(
 
         Action::ToggleAnalysis => {
             if app.active_view == ActiveView::Analysis {
-                app.active_view = ActiveView::Dashboard;
+                app.set_view(ActiveView::Dashboard);
             } else {
-                app.active_view = ActiveView::Analysis;
+                app.set_view(ActiveView::Analysis);
                 app.scroll_offset = 0;
                 refresh_analysis_tree(app);
             }
         }
 
         Action::AnalysisNext => {
-            if !app.history.is_empty() {
-                app.analysis_index = (app.analysis_index + 1).min(app.history.len() - 1);
+            if app.step_analysis(true) {
                 app.scroll_offset = 0;
                 refresh_analysis_tree(app);
+                reanalyze_selected(app);
             }
         }
 
         Action::AnalysisPrev => {
-            if !app.history.is_empty() {
-                app.analysis_index = app.analysis_index.saturating_sub(1);
+            if app.step_analysis(false) {
+                app.scroll_offset = 0;
+                refresh_analysis_tree(app);
+                reanalyze_selected(app);
+            }
+        }
+
+        Action::AnalysisSelect(idx) => {
+            if idx != app.analysis_index {
+                app.analysis_index = idx;
                 app.scroll_offset = 0;
                 refresh_analysis_tree(app);
+                reanalyze_selected(app);
             }
         }
 
         Action::ToggleMetrics => {
-            app.active_view = if app.active_view == ActiveView::Metrics {
+            app.set_view(if app.active_view == ActiveView::Metrics {
                 ActiveView::Dashboard
             } else {
                 ActiveView::Metrics
-            };
+            });
         }
 
         Action::ToggleMetricsView => {
             app.metrics_view = match app.metrics_view {
                 MetricsView::Dashboard => MetricsView::Heatmap,
-                MetricsView::Heatmap => MetricsView::Dashboard,
+                MetricsView::Heatmap => MetricsView::Histogram,
+                MetricsView::Histogram => MetricsView::Hosts,
+                MetricsView::Hosts => MetricsView::Dashboard,
             };
         }
 
@@ -209,7 +292,7 @@ pub async fn dispatch(
             match serde_json::to_value(&session) {
                 Ok(json) => match crate::query::run_query(&query_str, &json) {
                     Ok(result) => {
-                        app.active_view = ActiveView::Analysis;
+                        app.set_view(ActiveView::Analysis);
                         app.analysis_tree = Some(JsonTreeState::new(result));
                         app.analysis_focus = AnalysisFocus::DataBrowser;
                         app.notify(format!("Query: {}", query_str));
@@ -227,8 +310,7 @@ pub async fn dispatch(
             {
                 tree.get_selected_content()
             } else {
-                app.failed_result
-                    .as_ref()
+                app.inspector_result()
                     .and_then(|r| serde_json::to_string_pretty(r).ok())
             };
             if let Some(c) = content {
@@ -236,6 +318,16 @@ pub async fn dispatch(
             }
         }
 
+        Action::YankSubtree => {
+            if let Some(content) = app
+                .analysis_tree
+                .as_ref()
+                .and_then(|t| t.get_selected_subtree_json())
+            {
+                app.copy_to_clipboard(content);
+            }
+        }
+
         Action::YankVisual => {
             if app.active_view == ActiveView::Analysis
                 && app.visual_mode
@@ -275,14 +367,50 @@ pub async fn dispatch(
 
 // ── Private helpers ──────────────────────────────────────────────────────────
 
-async fn send_ipc(app: &App, msg: Message) {
-    if let Some(tx) = &app.ipc_tx {
-        let _ = tx.send(msg).await;
+/// Send a `ModifyVar`, audited as `action`, unless the key already has a
+/// change the playbook hasn't picked up; in that case hold it in
+/// `pending_var_change` until the user confirms the overwrite. Returns whether the change was sent.
+async fn modify_var(
+    app: &mut App,
+    key: String,
+    value: serde_json::Value,
+    action: &'static str,
+) -> bool {
+    let change = VarChange {
+        task: app.current_task.clone(),
+        key,
+        value,
+    };
+    if app.find_var_conflict(&change.key).is_some() {
+        app.pending_var_change = Some((change, action));
+        return false;
     }
+    send_var_change(app, change, action).await;
+    true
+}
+
+/// Restore the value the most recent `ModifyVar` replaced, or unset the
+/// variable if it didn't exist before. The undone change no longer counts as
+/// applied, so editing the key again won't ask to overwrite.
+async fn undo_var_change(app: &mut App) {
+    let Some((key, previous)) = app.var_undo.pop() else {
+        app.notify("Nothing to undo".to_string());
+        return;
+    };
+    revert_var_change(app, &key, previous.clone()).await;
+    if let Some(pos) = app.applied_changes.iter().rposition(|c| c.key == key) {
+        app.applied_changes.remove(pos);
+    }
+    let shown = previous.map_or("unset".to_string(), |v| v.to_string());
+    app.log(
+        format!("Reverted {} = {}", key, shown),
+        Some(ratatui::style::Color::Yellow),
+    );
+    app.notify(format!("Reverted {} to {}", key, shown));
 }
 
 fn refresh_analysis_tree(app: &mut App) {
-    if let Some(task) = app.history.get(app.analysis_index) {
+    if let Some(task) = app.history_entry(app.analysis_index) {
         let json_data = task
             .verbose_result
             .as_ref()
@@ -294,12 +422,72 @@ fn refresh_analysis_tree(app: &mut App) {
                     serde_json::json!({ "message": "No verbose data captured." })
                 }
             });
-        app.analysis_tree = Some(JsonTreeState::new(json_data));
+        let flat_view = app.analysis_tree.as_ref().is_some_and(|t| t.flat_view);
+        let mut tree = JsonTreeState::new(json_data);
+        if flat_view {
+            tree.toggle_flat_view();
+        }
+        app.analysis_tree = Some(tree);
     } else {
         app.analysis_tree = None;
     }
 }
 
+/// Request a fresh AI analysis of the selected historical failure when
+/// replaying with `--reanalyze`. The result comes back as `Message::AiAnalysis`,
+/// or `Message::AiAnalysisFailed` when the request fails.
+fn reanalyze_selected(app: &mut App) {
+    let Some(idx) = app.take_reanalysis_request() else {
+        return;
+    };
+    let (Some(client), Some(tx)) = (app.ai_client.clone(), app.reanalysis_tx.clone()) else {
+        return;
+    };
+    let Some(task) = app.history_entry(idx) else {
+        return;
+    };
+    let task_name = task.name.clone();
+    let error = task
+        .error
+        .clone()
+        .or_else(|| {
+            task.verbose_result
+                .as_ref()
+                .and_then(|d| d.msg().map(str::to_string))
+        })
+        .unwrap_or_else(|| "Task Failed".to_string());
+    // The context the task failed with, not whatever task is current now
+    let vars = task.task_vars.clone().unwrap_or(serde_json::json!({}));
+    let facts = task.facts.clone();
+
+    app.asking_ai = true;
+    app.notify(format!("Re-analyzing '{}'...", task_name));
+
+    tokio::spawn(async move {
+        match client
+            .analyze_failure(&task_name, &error, &vars, facts.as_ref())
+            .await
+        {
+            Ok(analysis) => {
+                let _ = tx
+                    .send(Message::AiAnalysis {
+                        task: task_name,
+                        analysis,
+                    })
+                    .await;
+            }
+            Err(e) => {
+                let _ = tx
+                    .send(Message::AiAnalysisFailed {
+                        task: task_name,
+                        error: e.to_string(),
+                    })
+                    .await;
+            }
+        }
+    });
+}
+
 async fn launch_editor(app: &mut App, terminal: &mut Option<Terminal<CrosstermBackend<Stdout>>>) {
     let EditState::EditingValue { temp_file, .. } = &app.edit_state else {
         return;
@@ -322,15 +510,9 @@ async fn launch_editor(app: &mut App, terminal: &mut Option<Terminal<CrosstermBa
     match status {
         Ok(s) if s.success() => match app.apply_edit() {
             Ok((key, value)) => {
-                send_ipc(
-                    app,
-                    Message::ModifyVar {
-                        key: key.clone(),
-                        value,
-                    },
-                )
-                .await;
-                app.notify(format!("Updated Variable: {}", key));
+                if modify_var(app, key.clone(), value, "ModifyVar").await {
+                    app.notify(format!("Updated Variable: {}", key));
+                }
             }
             Err(e) => app.notify(format!("Edit Failed: {}", e)),
         },
@@ -345,13 +527,32 @@ async fn launch_editor(app: &mut App, terminal: &mut Option<Terminal<CrosstermBa
     }
 }
 
+/// Check the quota right before a provider call, showing the quota banner
+/// when it is exhausted.
+fn quota_allows(app: &mut App) -> bool {
+    if app.check_ai_quota() {
+        return true;
+    }
+    let banner = app.quota_banner().unwrap_or_default();
+    app.notify(banner);
+    false
+}
+
 async fn ask_ai(app: &mut App) {
     let Some(client) = app.ai_client.clone() else {
+        heuristic_analysis(app);
+        return;
+    };
+    if !quota_allows(app) {
+        return;
+    }
+    // The analysis lands in the app (and the task's history entry), not the plugin
+    let Some(tx) = app.pilot_result_tx.clone() else {
         return;
     };
-    let Some(tx) = app.ipc_tx.clone() else { return };
 
     app.asking_ai = true;
+    app.pilot_stream.clear();
     app.log(
         "Asking AI Pilot...".to_string(),
         Some(ratatui::style::Color::Magenta),
@@ -364,12 +565,28 @@ async fn ask_ai(app: &mut App) {
         .unwrap_or_else(|| "Unknown".to_string());
     let vars = app.task_vars.clone().unwrap_or(serde_json::json!({}));
     let facts = app.facts.clone();
+    let stream_tx = app.pilot_stream_tx.clone();
 
     tokio::spawn(async move {
-        if let Ok(analysis) = client
-            .analyze_failure(&task_name, "Task Failed", &vars, facts.as_ref())
-            .await
-        {
+        let result = match stream_tx {
+            Some(chunks) => {
+                client
+                    .analyze_failure_streaming(
+                        &task_name,
+                        "Task Failed",
+                        &vars,
+                        facts.as_ref(),
+                        chunks,
+                    )
+                    .await
+            }
+            None => {
+                client
+                    .analyze_failure(&task_name, "Task Failed", &vars, facts.as_ref())
+                    .await
+            }
+        };
+        if let Ok(analysis) = result {
             let _ = tx
                 .send(Message::AiAnalysis {
                     task: task_name,
@@ -380,6 +597,27 @@ async fn ask_ai(app: &mut App) {
     });
 }
 
+/// Offline stand-in for `ask_ai` when no AI provider is configured.
+fn heuristic_analysis(app: &mut App) {
+    let Some(task) = app.failed_task.clone() else {
+        app.notify("No failed task to analyze".to_string());
+        return;
+    };
+    let Some(analysis) = crate::heuristics::analyze(app.failed_result.as_ref()) else {
+        app.notify("No known failure signature; configure an AI provider for analysis".to_string());
+        return;
+    };
+    app.log(
+        format!("Offline analysis for '{}' (no AI configured)", task),
+        Some(ratatui::style::Color::Cyan),
+    );
+    if let Some(entry) = app.history.iter_mut().rev().find(|t| t.name == task) {
+        entry.analysis = Some(analysis.clone());
+    }
+    app.suggestion = Some(analysis);
+    app.notify("Analysis Ready. Press 'v' to view.".to_string());
+}
+
 async fn submit_chat(app: &mut App, ai_tx: &mpsc::Sender<anyhow::Result<crate::ai::ChatMessage>>) {
     let Some(client) = app.ai_client.clone() else {
         app.notify("AI Client not configured.".to_string());
@@ -394,23 +632,30 @@ async fn submit_chat(app: &mut App, ai_tx: &mpsc::Sender<anyhow::Result<crate::a
 
     let lower = input.to_lowercase();
     match lower.as_str() {
-        "p" | "proceed" => handle_chat_ipc(app, Message::Proceed, "⏩ Proceeding...").await,
+        "p" | "proceed" => {
+            handle_chat_ipc(app, Message::Proceed, "Proceed (chat)", "⏩ Proceeding...").await
+        }
         "c" | "continue" => {
             handle_chat_ipc(
                 app,
                 Message::Continue,
+                "Continue (chat)",
                 "▶️ Continuing (skip failed task)...",
             )
             .await
         }
-        "r" | "retry" => handle_chat_ipc(app, Message::Retry, "🔄 Retrying task...").await,
+        "r" | "retry" => {
+            handle_chat_ipc(app, Message::Retry, "Retry (chat)", "🔄 Retrying task...").await
+        }
         _ if input.starts_with('/') => handle_slash_command(app, &input, &client).await,
+        _ if !quota_allows(app) => app.chat_input = input,
         _ => {
             app.chat_loading = true;
             let user_msg = crate::ai::ChatMessage {
                 role: "user".to_string(),
                 content: input,
                 collapsed: false,
+                reply_to: None,
             };
             app.chat_history.push(user_msg);
             app.chat_scroll = app.chat_history.len().saturating_sub(1) as u16;
@@ -424,10 +669,104 @@ async fn submit_chat(app: &mut App, ai_tx: &mpsc::Sender<anyhow::Result<crate::a
     }
 }
 
-async fn handle_chat_ipc(app: &mut App, msg: Message, feedback: &str) {
+/// Copy the failure's fix as a ready-to-paste Ansible snippet. With an AI
+/// client the snippet is written through the chat pipeline and copied when the
+/// reply arrives; otherwise the suggested variable fix becomes a `set_fact`.
+fn copy_fix_snippet(app: &mut App, ai_tx: &mpsc::Sender<anyhow::Result<crate::ai::ChatMessage>>) {
+    let task = app.failed_task.clone().or_else(|| app.current_task.clone());
+    let client = match app.ai_client.clone() {
+        Some(client) if app.check_ai_quota() => client,
+        _ => {
+            match app.suggestion.as_ref().and_then(|a| a.fix.as_ref()) {
+                Some(fix) => {
+                    let snippet = crate::ai::fix_as_set_fact(task.as_deref(), fix);
+                    app.copy_to_clipboard(snippet);
+                }
+                None => app.notify("No suggested fix to copy".to_string()),
+            }
+            return;
+        }
+    };
+    if app.failed_task.is_none() && app.suggestion.is_none() {
+        app.notify("No failure or fix to turn into a snippet".to_string());
+        return;
+    }
+
+    let prompt = crate::ai::AiClient::build_snippet_prompt(
+        task.as_deref(),
+        app.suggestion.as_ref(),
+        app.failed_result.as_ref(),
+    );
+    app.chat_history.push(crate::ai::ChatMessage {
+        role: "user".to_string(),
+        content: prompt,
+        collapsed: true,
+        reply_to: None,
+    });
+    app.chat_scroll = app.chat_history.len().saturating_sub(1) as u16;
+    app.chat_loading = true;
+    app.ai_request_seq += 1;
+    let request_id = app.ai_request_seq;
+    app.snippet_pending = Some(request_id);
+    app.notify("Generating Ansible snippet...".to_string());
+
+    let history = app.chat_history.clone();
+    let tx = ai_tx.clone();
+    tokio::spawn(async move {
+        let reply = client
+            .chat(history)
+            .await
+            .map(|msg| crate::ai::ChatMessage {
+                reply_to: Some(request_id),
+                ..msg
+            });
+        let _ = tx.send(reply).await;
+    });
+}
+
+/// Open the chat with the failed task's context as the first question, so
+/// the conversation starts from the failure instead of a blank prompt.
+fn chat_about_failure(app: &mut App, ai_tx: &mpsc::Sender<anyhow::Result<crate::ai::ChatMessage>>) {
+    let Some(client) = app.ai_client.clone() else {
+        app.notify("AI Client not configured.".to_string());
+        return;
+    };
+    if app.failed_task.is_none() {
+        app.notify("No failed task to ask about".to_string());
+        return;
+    }
+    if !quota_allows(app) {
+        return;
+    }
+
+    let context = crate::ai::AiClient::build_context_summary(
+        app.current_task.as_deref(),
+        app.task_vars.as_ref(),
+        app.failed_task.as_deref(),
+        app.failed_result.as_ref(),
+    );
+    app.chat_history.push(crate::ai::ChatMessage {
+        role: "user".to_string(),
+        content: format!("{}\n\nWhy did this fail?", context),
+        collapsed: false,
+        reply_to: None,
+    });
+    app.chat_active = true;
+    app.chat_mode = crate::app::ChatMode::Insert;
+    app.chat_scroll = app.chat_history.len().saturating_sub(1) as u16;
+    app.chat_loading = true;
+
+    let history = app.chat_history.clone();
+    let tx = ai_tx.clone();
+    tokio::spawn(async move {
+        let _ = tx.send(client.chat(history).await).await;
+    });
+}
+
+async fn handle_chat_ipc(app: &mut App, msg: Message, action: &str, feedback: &str) {
     let content = if app.waiting_for_proceed {
         app.waiting_for_proceed = false;
-        send_ipc(app, msg).await;
+        send_control(app, msg, action).await;
         feedback.to_string()
     } else {
         "Not waiting for proceed.".to_string()
@@ -435,6 +774,9 @@ async fn handle_chat_ipc(app: &mut App, msg: Message, feedback: &str) {
     push_system_msg(app, content);
 }
 
+/// First line of a `/context` message; `/clear` keeps messages starting with it.
+const CONTEXT_HEADER: &str = "📋 Current Context:";
+
 async fn handle_slash_command(app: &mut App, input: &str, client: &crate::ai::AiClient) {
     let parts: Vec<&str> = input.split_whitespace().collect();
     let content = match parts.first().copied() {
@@ -450,10 +792,24 @@ async fn handle_slash_command(app: &mut App, input: &str, client: &crate::ai::Ai
                 s.push_str("\nUse /model <name> to switch.");
                 s
             } else if let Some(name) = parts.get(1) {
-                if let Some(c) = &mut app.ai_client {
-                    c.set_model(name);
+                match &mut app.ai_client {
+                    Some(c) => {
+                        let known = c.set_model(name);
+                        let provider = c.config().provider.clone().unwrap_or_default();
+                        let mut s = format!(
+                            "Switched to model: {} ({}). Conversation kept.",
+                            name, provider
+                        );
+                        if !known {
+                            s.push_str(&format!(
+                                "\n⚠️ Unrecognised model name; assuming an OpenAI-compatible API at {}.",
+                                c.config().api_base
+                            ));
+                        }
+                        s
+                    }
+                    None => "AI is not configured.".to_string(),
                 }
-                format!("Switched to model: {}", name)
             } else {
                 "Usage: /model [name]".to_string()
             }
@@ -465,12 +821,23 @@ async fn handle_slash_command(app: &mut App, input: &str, client: &crate::ai::Ai
                 app.failed_task.as_deref(),
                 app.failed_result.as_ref(),
             );
-            format!("📋 Current Context:\n\n{}", ctx)
+            app.notify("Context added to chat".to_string());
+            format!("{}\n\n{}", CONTEXT_HEADER, ctx)
+        }
+        Some("/clear") => {
+            // Injected context acts as the system prompt; everything else goes
+            app.chat_history
+                .retain(|m| m.role == "system" && m.content.starts_with(CONTEXT_HEADER));
+            app.chat_scroll = 0;
+            app.chat_selected_index = None;
+            app.notify("Chat cleared".to_string());
+            return;
         }
         Some("/help") => "Chat Commands:\n\
             /model          — List available models\n\
             /model <name>   — Switch to a model\n\
-            /context        — Show current task context\n\
+            /context        — Add current task context to the chat\n\
+            /clear          — Clear the conversation (keeps added context)\n\
             /help           — Show this help\n\
             \nQuick Actions:\n\
             p / proceed     — Proceed to next task\n\
@@ -490,6 +857,7 @@ fn push_system_msg(app: &mut App, content: String) {
         role: "system".to_string(),
         content,
         collapsed: false,
+        reply_to: None,
     });
     app.chat_scroll = app.chat_history.len().saturating_sub(1) as u16;
 }
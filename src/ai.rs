@@ -5,6 +5,7 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::io::Write;
 use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
 
 pub mod anthropic;
 pub mod google;
@@ -16,6 +17,57 @@ pub struct AiClient {
     model: String,
     quota_tracker: Arc<Mutex<QuotaTracker>>,
     config: Config,
+    /// (latency ms, tokens) of completed calls not yet collected by the UI
+    call_metrics: Arc<Mutex<Vec<(u64, u32)>>>,
+}
+
+/// Non-success HTTP status from a provider, kept typed so the retry policy can
+/// tell server errors (retried) from client errors (not retried).
+#[derive(Debug)]
+pub struct ProviderHttpError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl ProviderHttpError {
+    pub fn new(status: reqwest::StatusCode, message: String) -> Self {
+        Self {
+            status: status.as_u16(),
+            message,
+        }
+    }
+}
+
+impl std::fmt::Display for ProviderHttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ProviderHttpError {}
+
+/// Whether a failed provider request is worth retrying: transport failures
+/// and 5xx responses are, client errors, quota exhaustion and unparseable
+/// replies are not.
+pub fn is_retryable(error: &anyhow::Error) -> bool {
+    for cause in error.chain() {
+        if cause.is::<crate::quota::QuotaExceeded>() {
+            return false;
+        }
+        if let Some(http) = cause.downcast_ref::<ProviderHttpError>() {
+            return http.status >= 500;
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            if let Some(status) = e.status() {
+                return status.is_server_error();
+            }
+            return e.is_connect() || e.is_timeout() || e.is_request();
+        }
+        if cause.is::<std::io::Error>() {
+            return true;
+        }
+    }
+    false
 }
 
 #[derive(Debug)]
@@ -31,6 +83,43 @@ pub struct QuotaStatus {
     pub used_usd: f64,
     pub limit_usd: Option<f64>,
     pub reset_in: std::time::Duration,
+    /// USD per 1k tokens used to estimate cost for the current model
+    pub rate_per_1k_usd: f64,
+    /// Percentage of a limit at which [`QuotaStatus::is_warning`] trips
+    pub warn_pct: f64,
+    /// UTC day the usage counts belong to
+    pub day: chrono::NaiveDate,
+}
+
+impl QuotaStatus {
+    /// Whether either the token or the USD limit has been reached.
+    pub fn is_exceeded(&self) -> bool {
+        self.limit_tokens.is_some_and(|l| self.used_tokens >= l)
+            || self.limit_usd.is_some_and(|l| self.used_usd >= l)
+    }
+
+    /// Whether usage has crossed `warn_pct` of either limit but not yet
+    /// reached it.
+    pub fn is_warning(&self) -> bool {
+        let near = |used: f64, limit: f64| used >= limit * self.warn_pct / 100.0;
+        !self.is_exceeded()
+            && (self
+                .limit_tokens
+                .is_some_and(|l| near(self.used_tokens as f64, l as f64))
+                || self.limit_usd.is_some_and(|l| near(self.used_usd, l)))
+    }
+
+    /// Notification text for a quota nearing its limit.
+    pub fn warning_message(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(limit) = self.limit_tokens {
+            parts.push(format!("{} / {} tokens", self.used_tokens, limit));
+        }
+        if let Some(limit) = self.limit_usd {
+            parts.push(format!("${:.2} / ${:.2}", self.used_usd, limit));
+        }
+        format!("AI quota nearly used: {}", parts.join(", "))
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -39,16 +128,73 @@ pub struct ChatMessage {
     pub content: String,
     #[serde(skip)]
     pub collapsed: bool,
+    /// Id of the request this reply answers, when the caller waits for it
+    #[serde(skip)]
+    pub reply_to: Option<u64>,
 }
 
 #[async_trait]
 pub trait AiProvider: Send + Sync {
     async fn send_message(&self, messages: Vec<ChatMessage>) -> Result<ProviderResponse>;
+    /// Like `send_message`, but emits partial content on `chunks` as it
+    /// arrives. Providers without streaming send the whole reply once.
+    async fn send_message_streaming(
+        &self,
+        messages: Vec<ChatMessage>,
+        chunks: mpsc::Sender<String>,
+    ) -> Result<ProviderResponse> {
+        let response = self.send_message(messages).await?;
+        let _ = chunks.send(response.content.clone()).await;
+        Ok(response)
+    }
     async fn list_models(&self) -> Result<Vec<String>> {
         Ok(vec![]) // Default: no dynamic discovery
     }
+    /// Offered by the model picker when `list_models` fails or finds nothing.
+    fn default_models(&self) -> Vec<&'static str> {
+        DEFAULT_MODELS.to_vec()
+    }
 }
 
+const ANALYSIS_PROMPT: &str = "You are an expert Ansible debugger. \
+    Analyze the following task failure and provided variables. \
+    Explain why it failed and suggest a specific variable change or fix.";
+
+/// Appended to every analysis prompt, including overrides.
+const ANALYSIS_JSON_FORMAT: &str = "Output ONLY valid JSON in the following format: \
+    { \"analysis\": \"...explanation...\", \"fix\": { \"key\": \"variable_name\", \"value\": ...val... } } \
+    If no fix is possible, omit the \"fix\" field.";
+
+const CHAT_PROMPT: &str = "You are a helpful AI assistant integrated into Ansible Piloteer. \
+    Help the user debug playbooks, explain errors, and suggest fixes. \
+    When providing code, use markdown code blocks.";
+
+/// Contents of a configured prompt file. Read on every call so edits apply
+/// without a restart; an unreadable or empty file falls back to the default.
+fn read_prompt_override(path: Option<&str>, kind: &str) -> Option<String> {
+    let path = path?;
+    match std::fs::read_to_string(path) {
+        Ok(text) if !text.trim().is_empty() => Some(text),
+        Ok(_) => None,
+        Err(e) => {
+            tracing::warn!(path, error = %e, "Cannot read {} prompt, using the default", kind);
+            None
+        }
+    }
+}
+
+/// Fallback model list for providers without defaults of their own.
+const DEFAULT_MODELS: &[&str] = &[
+    "gpt-5-latest",
+    "gpt-5-mini",
+    "gemini-flash-latest",
+    "gemini-3.0-flash-preview",
+    "gemini-3.0-pro-preview",
+    "claude-opus-4-6",
+    "claude-sonnet-4-5",
+    "claude-haiku-4-5",
+];
+
 // --- OpenAI Implementation ---
 
 pub struct OpenAiProvider {
@@ -73,6 +219,77 @@ impl OpenAiProvider {
 struct ChatRequest {
     model: String,
     messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+#[derive(Serialize)]
+struct StreamOptions {
+    include_usage: bool,
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamChunk {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamChoice {
+    #[serde(default)]
+    delta: StreamDelta,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct StreamDelta {
+    content: Option<String>,
+}
+
+/// Reassembles an OpenAI-style `text/event-stream` body. Bytes are buffered
+/// until a full line arrives, so events and UTF-8 sequences split across
+/// network chunks are handled.
+#[derive(Default)]
+struct SseAccumulator {
+    pending: Vec<u8>,
+    content: String,
+    tokens: u32,
+    done: bool,
+}
+
+impl SseAccumulator {
+    /// Feed raw body bytes, returning the content deltas completed by them.
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(bytes);
+        let mut deltas = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim().strip_prefix("data:") else {
+                continue;
+            };
+            let data = data.trim();
+            if data == "[DONE]" {
+                self.done = true;
+                continue;
+            }
+            let Ok(chunk) = serde_json::from_str::<StreamChunk>(data) else {
+                continue;
+            };
+            if let Some(usage) = chunk.usage {
+                self.tokens = usage.total_tokens;
+            }
+            for choice in chunk.choices {
+                if let Some(text) = choice.delta.content.filter(|t| !t.is_empty()) {
+                    self.content.push_str(&text);
+                    deltas.push(text);
+                }
+            }
+        }
+        deltas
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -102,6 +319,8 @@ impl AiProvider for OpenAiProvider {
         let request = ChatRequest {
             model: self.model.clone(),
             messages,
+            stream: false,
+            stream_options: None,
         };
 
         let url = format!("{}/chat/completions", self.api_base.trim_end_matches('/'));
@@ -117,6 +336,16 @@ impl AiProvider for OpenAiProvider {
             .await
             .context(format!("Failed to send request to AI at {}", url))?;
 
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(ProviderHttpError::new(
+                status,
+                format!("AI request failed ({}): {}", status, body),
+            )
+            .into());
+        }
+
         let chat_response: ChatResponse = response
             .json()
             .await
@@ -133,6 +362,64 @@ impl AiProvider for OpenAiProvider {
         }
     }
 
+    async fn send_message_streaming(
+        &self,
+        messages: Vec<ChatMessage>,
+        chunks: mpsc::Sender<String>,
+    ) -> Result<ProviderResponse> {
+        let request = ChatRequest {
+            model: self.model.clone(),
+            messages,
+            stream: true,
+            stream_options: Some(StreamOptions {
+                include_usage: true,
+            }),
+        };
+
+        let url = format!("{}/chat/completions", self.api_base.trim_end_matches('/'));
+        let mut builder = self.client.post(&url);
+
+        if let Some(key) = &self.api_key {
+            builder = builder.header("Authorization", format!("Bearer {}", key));
+        }
+
+        let mut response = builder
+            .json(&request)
+            .send()
+            .await
+            .context(format!("Failed to send request to AI at {}", url))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(ProviderHttpError::new(
+                status,
+                format!("AI request failed ({}): {}", status, body),
+            )
+            .into());
+        }
+
+        let mut sse = SseAccumulator::default();
+        while !sse.done
+            && let Some(bytes) = response
+                .chunk()
+                .await
+                .context("Failed to read AI response stream")?
+        {
+            for delta in sse.push(&bytes) {
+                let _ = chunks.send(delta).await;
+            }
+        }
+
+        if sse.content.is_empty() {
+            return Err(anyhow::anyhow!("No response content from AI"));
+        }
+        Ok(ProviderResponse {
+            content: sse.content,
+            tokens: sse.tokens,
+        })
+    }
+
     async fn list_models(&self) -> Result<Vec<String>> {
         let url = format!("{}/models", self.api_base.trim_end_matches('/'));
         let mut builder = self.client.get(&url);
@@ -146,6 +433,10 @@ impl AiProvider for OpenAiProvider {
             .context("Failed to parse models response")?;
         Ok(models.data.into_iter().map(|m| m.id).collect())
     }
+
+    fn default_models(&self) -> Vec<&'static str> {
+        vec!["gpt-5-latest", "gpt-5-mini"]
+    }
 }
 
 // --- End OpenAI Implementation ---
@@ -177,8 +468,6 @@ pub struct Fix {
 
 impl AiClient {
     pub fn new(config: Config) -> Self {
-        let quota_tracker = Arc::new(Mutex::new(QuotaTracker::load().unwrap_or_default()));
-
         // Factory logic for provider
         let provider: Box<dyn AiProvider> = if let Some(p) = &config.provider {
             match p.as_str() {
@@ -192,10 +481,18 @@ impl AiClient {
             Box::new(OpenAiProvider::new(&config))
         };
 
+        Self::with_provider(config, provider)
+    }
+
+    /// Build a client around an explicit provider instead of choosing one
+    /// from `config.provider`.
+    pub fn with_provider(config: Config, provider: Box<dyn AiProvider>) -> Self {
+        let quota_tracker = Arc::new(Mutex::new(QuotaTracker::load().unwrap_or_default()));
         Self {
             provider: Arc::new(provider),
             model: config.model.clone(),
             quota_tracker,
+            call_metrics: Arc::new(Mutex::new(Vec::new())),
             config,
         }
     }
@@ -205,6 +502,11 @@ impl AiClient {
         &self.model
     }
 
+    /// Returns the configuration this client was built from
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
     /// Build a context summary string from the current app state for injection
     /// into the chat system prompt or as a /context response.
     pub fn build_context_summary(
@@ -222,24 +524,13 @@ impl AiClient {
             parts.push(format!("**Failed Task:** {}", ft));
         }
         if let Some(fr) = failed_result {
-            let json_str = serde_json::to_string_pretty(fr).unwrap_or_else(|_| fr.to_string());
-            // Truncate very large results
-            let truncated = if json_str.len() > 2000 {
-                format!("{}\n... (truncated)", &json_str[..2000])
-            } else {
-                json_str
-            };
+            let truncated = summarize_json(fr, CONTEXT_JSON_LIMIT);
             parts.push(format!("**Failure Details:**\n```json\n{}\n```", truncated));
         }
         if let Some(v) = vars
             && !v.is_null()
         {
-            let json_str = serde_json::to_string_pretty(v).unwrap_or_else(|_| v.to_string());
-            let truncated = if json_str.len() > 2000 {
-                format!("{}\n... (truncated)", &json_str[..2000])
-            } else {
-                json_str
-            };
+            let truncated = summarize_json(v, CONTEXT_JSON_LIMIT);
             parts.push(format!("**Task Variables:**\n```json\n{}\n```", truncated));
         }
 
@@ -250,6 +541,37 @@ impl AiClient {
         }
     }
 
+    /// Prompt asking the AI to turn a failure and its suggested fix into a
+    /// ready-to-paste Ansible YAML snippet, sent through the chat pipeline.
+    pub fn build_snippet_prompt(
+        task: Option<&str>,
+        analysis: Option<&Analysis>,
+        failed_result: Option<&serde_json::Value>,
+    ) -> String {
+        let mut prompt = String::from(
+            "Turn the following Ansible failure into a playbook change. \
+             Reply with a single ```yaml code block containing ready-to-paste Ansible \
+             task(s) — a `set_fact`, `vars` entry, or corrected module invocation — \
+             followed by at most two sentences explaining it.",
+        );
+        if let Some(t) = task {
+            prompt.push_str(&format!("\n\nTask: {}", t));
+        }
+        if let Some(a) = analysis {
+            prompt.push_str(&format!("\n\nAnalysis: {}", a.analysis));
+            if let Some(fix) = &a.fix {
+                prompt.push_str(&format!("\nSuggested fix: {} = {}", fix.key, fix.value));
+            }
+        }
+        if let Some(fr) = failed_result {
+            prompt.push_str(&format!(
+                "\n\nFailure Details:\n```json\n{}\n```",
+                summarize_json(fr, CONTEXT_JSON_LIMIT)
+            ));
+        }
+        prompt
+    }
+
     // [NEW] Chat Interface
     pub async fn chat(&self, history: Vec<ChatMessage>) -> Result<ChatMessage> {
         // Create AI span
@@ -273,16 +595,14 @@ impl AiClient {
                 0,
                 ChatMessage {
                     role: "system".to_string(),
-                    content: "You are a helpful AI assistant integrated into Ansible Piloteer. \
-                           Help the user debug playbooks, explain errors, and suggest fixes. \
-                           When providing code, use markdown code blocks."
-                        .to_string(),
+                    content: self.chat_prompt(),
                     collapsed: false,
+                    reply_to: None,
                 },
             );
         }
 
-        let response = match self.provider.send_message(messages).await {
+        let response = match self.send_with_retry(messages, None).await {
             Ok(r) => r,
             Err(e) => {
                 crate::telemetry::record_error_on_current_span(&format!(
@@ -304,11 +624,18 @@ impl AiClient {
 
         // Update Quota
         if let Ok(mut tracker) = self.quota_tracker.lock() {
-            let _ = tracker.add_usage(response.tokens, &self.model);
+            let _ = tracker.add_usage(
+                response.tokens,
+                &self.model,
+                self.config.model_rates.as_ref(),
+            );
         }
 
         // Record metrics
         let duration_ms = start.elapsed().as_millis() as i64;
+        if let Ok(mut metrics) = self.call_metrics.lock() {
+            metrics.push((duration_ms as u64, response.tokens));
+        }
         crate::telemetry::add_attributes_to_current_span(vec![
             opentelemetry::KeyValue::new("ai.response_time_ms", duration_ms),
             opentelemetry::KeyValue::new("ai.tokens_used", response.tokens as i64),
@@ -319,6 +646,7 @@ impl AiClient {
             role: "assistant".to_string(),
             content: response.content,
             collapsed: false,
+            reply_to: None,
         })
     }
 
@@ -335,27 +663,40 @@ impl AiClient {
                 result
             }
             _ => {
-                // Fallback to defaults
-                vec![
-                    "gpt-5-latest".to_string(),
-                    "gpt-5-mini".to_string(),
-                    "gemini-flash-latest".to_string(),
-                    "gemini-3.0-flash-preview".to_string(),
-                    "gemini-3.0-pro-preview".to_string(),
-                    "claude-opus-4-6".to_string(),
-                    "claude-sonnet-4-5".to_string(),
-                    "claude-haiku-4-5".to_string(),
-                    self.model.clone(),
-                ]
-                .into_iter()
-                .collect::<std::collections::HashSet<_>>()
-                .into_iter()
-                .collect()
+                // Fallback to the provider's defaults
+                let mut result: Vec<String> = self
+                    .provider
+                    .default_models()
+                    .into_iter()
+                    .map(str::to_string)
+                    .collect();
+                if !result.contains(&self.model) {
+                    result.push(self.model.clone());
+                }
+                result.sort();
+                result
             }
         }
     }
 
-    pub fn set_model(&mut self, model_name: &str) {
+    /// System prompt for `analyze_failure`: the `analysis_prompt_path`
+    /// override or the built-in instructions, followed by the JSON format
+    /// `parse_response` expects.
+    fn analysis_prompt(&self) -> String {
+        let instructions =
+            read_prompt_override(self.config.analysis_prompt_path.as_deref(), "analysis")
+                .unwrap_or_else(|| ANALYSIS_PROMPT.to_string());
+        format!("{} {}", instructions.trim_end(), ANALYSIS_JSON_FORMAT)
+    }
+
+    fn chat_prompt(&self) -> String {
+        read_prompt_override(self.config.chat_prompt_path.as_deref(), "chat")
+            .unwrap_or_else(|| CHAT_PROMPT.to_string())
+    }
+
+    /// Switch model, picking the provider from its name prefix. Returns
+    /// false for unrecognised names, which get an OpenAI-compatible provider.
+    pub fn set_model(&mut self, model_name: &str) -> bool {
         self.model = model_name.to_string();
         self.config.model = model_name.to_string();
 
@@ -383,7 +724,9 @@ impl AiClient {
             // Unknown prefix — use OpenAI-compatible as default
             self.config.provider = Some("openai".to_string());
             self.provider = Arc::new(Box::new(OpenAiProvider::new(&self.config)));
+            return false;
         }
+        true
     }
 
     pub async fn analyze_failure(
@@ -392,6 +735,31 @@ impl AiClient {
         error_msg: &str,
         vars: &serde_json::Value,
         facts: Option<&serde_json::Value>,
+    ) -> Result<Analysis> {
+        self.analyze(task_name, error_msg, vars, facts, None).await
+    }
+
+    /// `analyze_failure`, streaming the raw reply on `chunks` as the provider
+    /// produces it. The assembled reply is parsed the same way once complete.
+    pub async fn analyze_failure_streaming(
+        &self,
+        task_name: &str,
+        error_msg: &str,
+        vars: &serde_json::Value,
+        facts: Option<&serde_json::Value>,
+        chunks: mpsc::Sender<String>,
+    ) -> Result<Analysis> {
+        self.analyze(task_name, error_msg, vars, facts, Some(chunks))
+            .await
+    }
+
+    async fn analyze(
+        &self,
+        task_name: &str,
+        error_msg: &str,
+        vars: &serde_json::Value,
+        facts: Option<&serde_json::Value>,
+        chunks: Option<mpsc::Sender<String>>,
     ) -> Result<Analysis> {
         // Create AI span
         let ai_span = crate::telemetry::create_child_span(
@@ -410,12 +778,7 @@ impl AiClient {
             tracker.check_limit(&self.config)?;
         }
 
-        let system_prompt = "You are an expert Ansible debugger. \
-            Analyze the following task failure and provided variables. \
-            Explain why it failed and suggest a specific variable change or fix. \
-            Output ONLY valid JSON in the following format: \
-            { \"analysis\": \"...explanation...\", \"fix\": { \"key\": \"variable_name\", \"value\": ...val... } } \
-            If no fix is possible, omit the \"fix\" field.";
+        let system_prompt = self.analysis_prompt();
 
         let user_content = format!(
             "Task: {}\nError: {}\nVariables: {}\nFacts: {}",
@@ -432,18 +795,20 @@ impl AiClient {
         let messages = vec![
             ChatMessage {
                 role: "system".to_string(),
-                content: system_prompt.to_string(),
+                content: system_prompt,
                 collapsed: false,
+                reply_to: None,
             },
             ChatMessage {
                 role: "user".to_string(),
                 content: user_content.clone(),
                 collapsed: false,
+                reply_to: None,
             },
         ];
 
         // Call Provider
-        let response = match self.provider.send_message(messages).await {
+        let response = match self.send_with_retry(messages, chunks).await {
             Ok(r) => r,
             Err(e) => {
                 crate::telemetry::record_error_on_current_span(&format!(
@@ -478,11 +843,18 @@ impl AiClient {
 
         // Update Quota
         if let Ok(mut tracker) = self.quota_tracker.lock() {
-            let _ = tracker.add_usage(response.tokens, &self.model);
+            let _ = tracker.add_usage(
+                response.tokens,
+                &self.model,
+                self.config.model_rates.as_ref(),
+            );
         }
 
         // Record metrics
         let duration_ms = start.elapsed().as_millis() as i64;
+        if let Ok(mut metrics) = self.call_metrics.lock() {
+            metrics.push((duration_ms as u64, response.tokens));
+        }
         crate::telemetry::add_attributes_to_current_span(vec![
             opentelemetry::KeyValue::new("ai.response_time_ms", duration_ms),
             opentelemetry::KeyValue::new("ai.tokens_used", response.tokens as i64),
@@ -493,6 +865,58 @@ impl AiClient {
         Ok(analysis)
     }
 
+    /// `provider.send_message` (or `send_message_streaming` when `chunks` is
+    /// given) under the configured retry policy: up to `retry_max_attempts`
+    /// tries with exponential backoff from `retry_base_ms`, for retryable
+    /// errors only. A stream that already forwarded part of the reply is not
+    /// retried, so `chunks` never sees a reply twice. Records `ai.retries`.
+    async fn send_with_retry(
+        &self,
+        messages: Vec<ChatMessage>,
+        chunks: Option<mpsc::Sender<String>>,
+    ) -> Result<ProviderResponse> {
+        let max_attempts = self.config.retry_max_attempts.max(1);
+        let mut retries = 0;
+        let result = loop {
+            let (sent, streamed) = match &chunks {
+                Some(tx) => {
+                    let (attempt_tx, mut attempt_rx) = mpsc::channel(32);
+                    let forward = async {
+                        let mut streamed = false;
+                        while let Some(chunk) = attempt_rx.recv().await {
+                            streamed = true;
+                            let _ = tx.send(chunk).await;
+                        }
+                        streamed
+                    };
+                    tokio::join!(
+                        self.provider
+                            .send_message_streaming(messages.clone(), attempt_tx),
+                        forward
+                    )
+                }
+                None => (self.provider.send_message(messages.clone()).await, false),
+            };
+            match sent {
+                Err(e) if !streamed && retries + 1 < max_attempts && is_retryable(&e) => {
+                    let delay = self
+                        .config
+                        .retry_base_ms
+                        .saturating_mul(1 << retries.min(16));
+                    tracing::warn!(error = %e, attempt = retries + 1, delay_ms = delay, "AI request failed, retrying");
+                    tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                    retries += 1;
+                }
+                other => break other,
+            }
+        };
+        crate::telemetry::add_attributes_to_current_span(vec![opentelemetry::KeyValue::new(
+            "ai.retries",
+            retries as i64,
+        )]);
+        result
+    }
+
     pub fn get_usage(&self) -> (u32, f64) {
         if let Ok(tracker) = self.quota_tracker.lock() {
             (tracker.usage_today_tokens, tracker.cost_today_usd)
@@ -501,16 +925,34 @@ impl AiClient {
         }
     }
 
+    /// Drain the (latency ms, tokens) pairs of calls finished since the
+    /// last collection.
+    pub fn take_call_metrics(&self) -> Vec<(u64, u32)> {
+        self.call_metrics
+            .lock()
+            .map(|mut m| std::mem::take(&mut *m))
+            .unwrap_or_default()
+    }
+
     pub fn get_quota_status(&self) -> QuotaStatus {
-        let (used_tokens, used_usd, reset_in) = if let Ok(tracker) = self.quota_tracker.lock() {
-            (
-                tracker.usage_today_tokens,
-                tracker.cost_today_usd,
-                tracker.time_until_reset(),
-            )
-        } else {
-            (0, 0.0, std::time::Duration::from_secs(0))
-        };
+        let (used_tokens, used_usd, reset_in, day) =
+            if let Ok(mut tracker) = self.quota_tracker.lock() {
+                // Yesterday's usage doesn't count against today's limits
+                tracker.check_reset();
+                (
+                    tracker.usage_today_tokens,
+                    tracker.cost_today_usd,
+                    tracker.time_until_reset(),
+                    tracker.last_reset.date_naive(),
+                )
+            } else {
+                (
+                    0,
+                    0.0,
+                    std::time::Duration::from_secs(0),
+                    chrono::Utc::now().date_naive(),
+                )
+            };
 
         QuotaStatus {
             used_tokens,
@@ -518,6 +960,12 @@ impl AiClient {
             used_usd,
             limit_usd: self.config.quota_limit_usd,
             reset_in,
+            rate_per_1k_usd: crate::quota::rate_per_1k(
+                &self.model,
+                self.config.model_rates.as_ref(),
+            ),
+            warn_pct: self.config.quota_warn_pct,
+            day,
         }
     }
 
@@ -573,6 +1021,10 @@ impl AiClient {
     }
 
     // Extracted for testing
+    /// Parse an analysis reply. Models often wrap the JSON in prose or
+    /// markdown, so the first balanced `{...}` object that parses as an
+    /// `Analysis` is used; a reply without one becomes a fix-less analysis
+    /// holding the raw text. Only an empty reply is an error.
     pub fn parse_response(content: &str) -> Result<Analysis> {
         // Cleanup markdown code blocks if present
         let clean_content = content
@@ -581,18 +1033,302 @@ impl AiClient {
             .trim_start_matches("```")
             .trim_end_matches("```")
             .trim();
+        if clean_content.is_empty() {
+            anyhow::bail!("Empty AI response");
+        }
+
+        if let Ok(analysis) = serde_json::from_str::<Analysis>(clean_content) {
+            return Ok(analysis);
+        }
+        let mut rest = clean_content;
+        while let Some((object, after)) = next_json_object(rest) {
+            if let Ok(analysis) = serde_json::from_str::<Analysis>(object) {
+                return Ok(analysis);
+            }
+            rest = after;
+        }
+        Ok(Analysis {
+            analysis: clean_content.to_string(),
+            fix: None,
+            tokens_used: 0,
+        })
+    }
+}
+
+/// The next top-level balanced `{...}` in `text` and the text after it.
+/// Braces inside JSON strings are ignored.
+fn next_json_object(text: &str) -> Option<(&str, &str)> {
+    let start = text.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    let end = start + i + 1;
+                    return Some((&text[start..end], &text[end..]));
+                }
+            }
+            _ => {}
+        }
+    }
+    // Unbalanced: retry from the next brace
+    next_json_object(&text[start + 1..])
+}
+
+// --- Context truncation ---
+
+/// Maximum characters of pretty JSON included per section of the AI context.
+const CONTEXT_JSON_LIMIT: usize = 2000;
+
+/// Format a variable fix as a `set_fact` task, used when the AI is not
+/// available to write a richer snippet.
+pub fn fix_as_set_fact(task: Option<&str>, fix: &Fix) -> String {
+    use serde_yaml::{Mapping, Value};
+
+    let mut vars = Mapping::new();
+    vars.insert(
+        Value::String(fix.key.clone()),
+        serde_yaml::to_value(&fix.value).unwrap_or(Value::Null),
+    );
+    let name = match task {
+        Some(t) => format!("Fix for '{}'", t),
+        None => "Apply suggested fix".to_string(),
+    };
+    // Mapping keeps insertion order, so `name` comes first as in playbooks
+    let mut entry = Mapping::new();
+    entry.insert(Value::String("name".to_string()), Value::String(name));
+    entry.insert(
+        Value::String("ansible.builtin.set_fact".to_string()),
+        Value::Mapping(vars),
+    );
+    serde_yaml::to_string(&vec![entry]).unwrap_or_default()
+}
+
+/// First fenced code block in a reply (preferring ```yaml / ```yml).
+pub fn extract_yaml_block(content: &str) -> Option<String> {
+    let blocks: Vec<(&str, &str)> = content
+        .split("```")
+        .skip(1)
+        .step_by(2)
+        .map(|block| block.split_once('\n').unwrap_or((block, "")))
+        .collect();
+    blocks
+        .iter()
+        .find(|(lang, _)| matches!(lang.trim(), "yaml" | "yml"))
+        .or_else(|| blocks.first())
+        .map(|(_, body)| body.trim_end().to_string() + "\n")
+}
+
+/// Pretty-print `value` within `max_chars` characters.
+///
+/// Values that fit are returned verbatim. Larger values are summarized
+/// structurally first (long arrays become `"[N items]"`, deep objects become
+/// `"{N keys}"`, long strings are shortened) so the result stays valid JSON;
+/// only if that is still too long is the text cut, always on a char boundary.
+pub fn summarize_json(value: &serde_json::Value, max_chars: usize) -> String {
+    let full = serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string());
+    if full.chars().count() <= max_chars {
+        return full;
+    }
+
+    let summarized = summarize_value(value, 0);
+    let text = serde_json::to_string_pretty(&summarized).unwrap_or_else(|_| summarized.to_string());
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+
+    let cut: String = text.chars().take(max_chars).collect();
+    format!("{}\n... (truncated)", cut)
+}
 
-        serde_json::from_str::<Analysis>(clean_content).context(format!(
-            "Failed to parse AI JSON response: {}",
-            clean_content
-        ))
+fn summarize_value(value: &serde_json::Value, depth: usize) -> serde_json::Value {
+    use serde_json::Value;
+    const MAX_ARRAY_ITEMS: usize = 5;
+    const MAX_DEPTH: usize = 3;
+    const MAX_STRING_CHARS: usize = 200;
+
+    match value {
+        Value::Array(items) if items.len() > MAX_ARRAY_ITEMS || depth >= MAX_DEPTH => {
+            Value::String(format!("[{} items]", items.len()))
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|v| summarize_value(v, depth + 1))
+                .collect(),
+        ),
+        Value::Object(map) if depth >= MAX_DEPTH => {
+            Value::String(format!("{{{} keys}}", map.len()))
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), summarize_value(v, depth + 1)))
+                .collect(),
+        ),
+        Value::String(s) if s.chars().count() > MAX_STRING_CHARS => {
+            let head: String = s.chars().take(MAX_STRING_CHARS).collect();
+            Value::String(format!("{}…", head))
+        }
+        other => other.clone(),
+    }
+}
+
+// --- Model capabilities ---
+
+/// Static capability metadata for a model family.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelCapabilities {
+    pub provider: &'static str,
+    pub streaming: bool,
+    pub tool_use: bool,
+    /// Approximate context window in tokens
+    pub context_window: u32,
+    pub pricing_tier: &'static str,
+}
+
+/// Capability table keyed by model-name prefix, grouped by provider. Lookups
+/// take the longest matching prefix, so entry order doesn't affect results.
+const MODEL_CAPABILITIES: &[(&str, ModelCapabilities)] = &[
+    ("gpt-5-mini", caps("openai", true, true, 400_000, "low")),
+    ("gpt-5", caps("openai", true, true, 400_000, "high")),
+    ("gpt-4.1-mini", caps("openai", true, true, 1_047_576, "low")),
+    ("gpt-4.1", caps("openai", true, true, 1_047_576, "medium")),
+    ("gpt-4o-mini", caps("openai", true, true, 128_000, "low")),
+    ("gpt-4o", caps("openai", true, true, 128_000, "medium")),
+    ("gpt-4-turbo", caps("openai", true, true, 128_000, "high")),
+    ("gpt-4", caps("openai", true, true, 8_192, "high")),
+    ("gpt-3.5", caps("openai", true, true, 16_385, "low")),
+    (
+        "claude-opus",
+        caps("anthropic", true, true, 200_000, "high"),
+    ),
+    (
+        "claude-sonnet",
+        caps("anthropic", true, true, 200_000, "medium"),
+    ),
+    (
+        "claude-haiku",
+        caps("anthropic", true, true, 200_000, "low"),
+    ),
+    ("claude", caps("anthropic", true, true, 200_000, "medium")),
+    (
+        "gemini-3.0-pro",
+        caps("google", true, true, 1_000_000, "high"),
+    ),
+    (
+        "gemini-3.0-flash",
+        caps("google", true, true, 1_000_000, "low"),
+    ),
+    ("gemini-flash", caps("google", true, true, 1_000_000, "low")),
+    ("gemini", caps("google", true, true, 1_000_000, "medium")),
+    ("gemma", caps("google", true, false, 8_192, "free")),
+];
+
+const fn caps(
+    provider: &'static str,
+    streaming: bool,
+    tool_use: bool,
+    context_window: u32,
+    pricing_tier: &'static str,
+) -> ModelCapabilities {
+    ModelCapabilities {
+        provider,
+        streaming,
+        tool_use,
+        context_window,
+        pricing_tier,
+    }
+}
+
+/// Look up capability metadata for a model by its longest matching prefix.
+pub fn model_capabilities(model: &str) -> Option<ModelCapabilities> {
+    let model = model.rsplit('/').next().unwrap_or(model); // "models/gemini-..."
+    MODEL_CAPABILITIES
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, c)| *c)
+}
+
+/// Model IDs known without asking a provider, for listing alongside the
+/// discovered ones.
+pub fn known_models() -> Vec<&'static str> {
+    DEFAULT_MODELS.to_vec()
+}
+
+/// Instantiate every provider that has credentials in `config`, for model
+/// discovery across providers.
+pub fn configured_providers(config: &Config) -> Vec<(&'static str, Box<dyn AiProvider>)> {
+    let mut providers: Vec<(&'static str, Box<dyn AiProvider>)> = Vec::new();
+    if config.openai_api_key.is_some() || config.api_base != "https://api.openai.com/v1" {
+        providers.push(("openai", Box::new(OpenAiProvider::new(config))));
+    }
+    if config.anthropic_api_key.is_some() {
+        providers.push((
+            "anthropic",
+            Box::new(anthropic::AnthropicProvider::new(config)),
+        ));
+    }
+    if config.google_api_key.is_some()
+        || config.auth_token.is_some()
+        || config.provider.as_deref() == Some("google")
+    {
+        providers.push(("google", Box::new(google::GoogleProvider::new(config))));
     }
+    if config.vertex_project_id.is_some() {
+        providers.push(("vertex", Box::new(vertex::VertexAiProvider::new(config))));
+    }
+    providers
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_quota_status_warning() {
+        let mut status = QuotaStatus {
+            used_tokens: 850,
+            limit_tokens: Some(1000),
+            used_usd: 0.0,
+            limit_usd: None,
+            reset_in: std::time::Duration::from_secs(0),
+            rate_per_1k_usd: 0.0,
+            warn_pct: 90.0,
+            day: chrono::Utc::now().date_naive(),
+        };
+        assert!(!status.is_warning());
+        status.used_tokens = 900;
+        assert!(status.is_warning());
+        assert_eq!(
+            status.warning_message(),
+            "AI quota nearly used: 900 / 1000 tokens"
+        );
+        // Past the limit it is exceeded, no longer a warning
+        status.used_tokens = 1000;
+        assert!(status.is_exceeded());
+        assert!(!status.is_warning());
+
+        status.used_tokens = 0;
+        status.used_usd = 4.6;
+        status.limit_usd = Some(5.0);
+        assert!(status.is_warning());
+    }
+
     #[test]
     fn test_parse_valid_json() {
         let input = r#"
@@ -626,8 +1362,372 @@ mod tests {
 
     #[test]
     fn test_parse_invalid_json() {
-        let input = "Not JSON";
-        let result = AiClient::parse_response(input);
-        assert!(result.is_err());
+        // Prose without any JSON becomes the analysis text
+        let result = AiClient::parse_response("Not JSON").unwrap();
+        assert_eq!(result.analysis, "Not JSON");
+        assert!(result.fix.is_none());
+        assert!(AiClient::parse_response("  ```json\n```  ").is_err());
+    }
+
+    #[test]
+    fn test_parse_json_after_leading_prose() {
+        let input = r#"Sure! Here is my analysis of the failure:
+        {"analysis": "Port {80} is taken", "fix": {"key": "http_port", "value": 8080}}"#;
+        let result = AiClient::parse_response(input).unwrap();
+        assert_eq!(result.analysis, "Port {80} is taken");
+        assert_eq!(result.fix.unwrap().value, 8080);
+    }
+
+    #[test]
+    fn test_parse_json_with_trailing_commentary() {
+        let input = r#"```json
+        {"analysis": "Quote \" and brace } in text", "fix": null}
+        ```
+        Let me know if you need {more} help."#;
+        let result = AiClient::parse_response(input).unwrap();
+        assert_eq!(result.analysis, "Quote \" and brace } in text");
+        assert!(result.fix.is_none());
+    }
+
+    #[test]
+    fn test_parse_skips_non_analysis_objects() {
+        let input = r#"Given vars {"a": 1} the answer is {"analysis": "a must be 2"}"#;
+        let result = AiClient::parse_response(input).unwrap();
+        assert_eq!(result.analysis, "a must be 2");
+    }
+
+    #[test]
+    fn test_model_capabilities_lookup() {
+        let gpt = model_capabilities("gpt-4o-mini-2024-07-18").unwrap();
+        assert_eq!(gpt.provider, "openai");
+        assert_eq!(gpt.pricing_tier, "low");
+
+        let gpt4 = model_capabilities("gpt-4").unwrap();
+        assert_eq!(gpt4.context_window, 8_192);
+        // Not the 8k gpt-4 family it shares a prefix with
+        let gpt41 = model_capabilities("gpt-4.1-2025-04-14").unwrap();
+        assert_eq!(gpt41.context_window, 1_047_576);
+        assert_eq!(
+            model_capabilities("gpt-4.1-mini").unwrap().pricing_tier,
+            "low"
+        );
+
+        // Every statically listed model is a real ID with known capabilities
+        for model in known_models() {
+            assert!(model_capabilities(model).is_some(), "{}", model);
+        }
+
+        let claude = model_capabilities("claude-sonnet-4-5").unwrap();
+        assert_eq!(claude.provider, "anthropic");
+        assert_eq!(claude.pricing_tier, "medium");
+
+        let gemini = model_capabilities("models/gemini-flash-latest").unwrap();
+        assert_eq!(gemini.provider, "google");
+        assert_eq!(gemini.context_window, 1_000_000);
+
+        assert!(model_capabilities("llama3:8b").is_none());
+    }
+
+    #[test]
+    fn test_context_truncation_multibyte() {
+        // Each "é" is 2 bytes; a byte slice at 2000 could split a char
+        let value = serde_json::json!({ "msg": "é".repeat(3000) });
+        let out = summarize_json(&value, 2000);
+        assert!(out.chars().count() <= 2000 + "\n... (truncated)".len());
+        assert!(out.contains("é"));
+
+        let summary = AiClient::build_context_summary(None, Some(&value), None, None);
+        assert!(summary.contains("**Task Variables:**"));
+    }
+
+    #[test]
+    fn test_context_truncation_large_nested() {
+        let items: Vec<_> = (0..500).map(|i| serde_json::json!({"id": i})).collect();
+        let value = serde_json::json!({
+            "packages": items,
+            "rc": 1,
+            "deep": {"a": {"b": {"c": {"d": 1}}}},
+        });
+        let out = summarize_json(&value, 2000);
+        // Structure is preserved and still parses as JSON
+        let parsed: serde_json::Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(parsed["packages"], "[500 items]");
+        assert_eq!(parsed["rc"], 1);
+        assert_eq!(parsed["deep"]["a"]["b"], "{1 keys}");
+    }
+
+    #[test]
+    fn test_snippet_prompt_assembly() {
+        let analysis = Analysis {
+            analysis: "Port 80 is already in use".to_string(),
+            fix: Some(Fix {
+                key: "http_port".to_string(),
+                value: serde_json::json!(8080),
+            }),
+            tokens_used: 0,
+        };
+        let failed = serde_json::json!({"msg": "bind: address already in use"});
+
+        let prompt =
+            AiClient::build_snippet_prompt(Some("Start nginx"), Some(&analysis), Some(&failed));
+        assert!(prompt.contains("```yaml"));
+        assert!(prompt.contains("Task: Start nginx"));
+        assert!(prompt.contains("Analysis: Port 80 is already in use"));
+        assert!(prompt.contains("Suggested fix: http_port = 8080"));
+        assert!(prompt.contains("address already in use"));
+
+        let bare = AiClient::build_snippet_prompt(None, None, None);
+        assert!(!bare.contains("Task:"));
+        assert!(!bare.contains("Suggested fix"));
+    }
+
+    #[test]
+    fn test_fix_snippet_formatting() {
+        let fix = Fix {
+            key: "http_port".to_string(),
+            value: serde_json::json!(8080),
+        };
+        assert_eq!(
+            fix_as_set_fact(Some("Start nginx"), &fix),
+            "- name: Fix for 'Start nginx'\n  ansible.builtin.set_fact:\n    http_port: 8080\n"
+        );
+
+        let reply = "Use this:\n```yaml\n- set_fact:\n    a: 1\n```\nDone.";
+        assert_eq!(
+            extract_yaml_block(reply).as_deref(),
+            Some("- set_fact:\n    a: 1\n")
+        );
+        assert_eq!(extract_yaml_block("no code here"), None);
+    }
+
+    #[test]
+    fn test_sse_accumulator_reassembles_stream() {
+        let mut sse = SseAccumulator::default();
+        let first = "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n\
+                     data: {\"choices\":[{\"delta\":{\"content\":\"{\\\"analysis\\\": \\\"caf\"}}]}\n";
+        assert_eq!(
+            sse.push(first.as_bytes()),
+            vec!["{\"analysis\": \"caf".to_string()]
+        );
+        // "é" split across two network chunks
+        let second = "data: {\"choices\":[{\"delta\":{\"content\":\"é\\\"}\"}}]}\n\n".as_bytes();
+        let (a, b) = second.split_at(second.len() - 12);
+        assert!(sse.push(a).is_empty());
+        assert_eq!(sse.push(b), vec!["é\"}".to_string()]);
+        assert!(!sse.done);
+
+        sse.push(b"data: {\"choices\":[],\"usage\":{\"total_tokens\":42}}\n\ndata: [DONE]\n\n");
+        assert!(sse.done);
+        assert_eq!(sse.tokens, 42);
+
+        let analysis = AiClient::parse_response(&sse.content).unwrap();
+        assert_eq!(analysis.analysis, "café");
+    }
+
+    #[tokio::test]
+    async fn test_streaming_falls_back_to_single_chunk() {
+        struct Fixed;
+        #[async_trait]
+        impl AiProvider for Fixed {
+            async fn send_message(&self, _: Vec<ChatMessage>) -> Result<ProviderResponse> {
+                Ok(ProviderResponse {
+                    content: "whole reply".to_string(),
+                    tokens: 3,
+                })
+            }
+        }
+
+        let (tx, mut rx) = mpsc::channel(4);
+        let response = Fixed.send_message_streaming(vec![], tx).await.unwrap();
+        assert_eq!(response.content, "whole reply");
+        assert_eq!(rx.recv().await.as_deref(), Some("whole reply"));
+        assert!(rx.recv().await.is_none());
+    }
+
+    fn test_config() -> Config {
+        Config {
+            model: "test".to_string(),
+            api_base: "http://localhost".to_string(),
+            retry_base_ms: 1,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_prompt_override_files() {
+        let client = AiClient::new(test_config());
+        assert!(client.analysis_prompt().starts_with(ANALYSIS_PROMPT));
+        assert_eq!(client.chat_prompt(), CHAT_PROMPT);
+
+        let dir = tempfile::tempdir().unwrap();
+        let analysis = dir.path().join("analysis.txt");
+        let chat = dir.path().join("chat.txt");
+        std::fs::write(&analysis, "Prefer ansible.builtin FQCNs.\n").unwrap();
+        std::fs::write(&chat, "Réponds en français.").unwrap();
+        let mut config = test_config();
+        config.analysis_prompt_path = Some(analysis.to_string_lossy().to_string());
+        config.chat_prompt_path = Some(chat.to_string_lossy().to_string());
+        let client = AiClient::new(config);
+
+        let prompt = client.analysis_prompt();
+        assert!(prompt.starts_with("Prefer ansible.builtin FQCNs."));
+        assert!(!prompt.contains(ANALYSIS_PROMPT));
+        // The output format survives the override
+        assert!(prompt.ends_with(ANALYSIS_JSON_FORMAT));
+        assert_eq!(client.chat_prompt(), "Réponds en français.");
+
+        // A missing file falls back to the built-in prompt
+        let mut config = test_config();
+        config.chat_prompt_path = Some(dir.path().join("nope.txt").to_string_lossy().to_string());
+        assert_eq!(AiClient::new(config).chat_prompt(), CHAT_PROMPT);
+    }
+
+    #[tokio::test]
+    async fn test_list_models_falls_back_to_provider_defaults() {
+        struct Offline;
+        #[async_trait]
+        impl AiProvider for Offline {
+            async fn send_message(&self, _: Vec<ChatMessage>) -> Result<ProviderResponse> {
+                anyhow::bail!("offline")
+            }
+            async fn list_models(&self) -> Result<Vec<String>> {
+                anyhow::bail!("offline")
+            }
+            fn default_models(&self) -> Vec<&'static str> {
+                vec!["model-b", "model-a"]
+            }
+        }
+
+        let mut config = test_config();
+        config.model = "custom".to_string();
+        let client = AiClient::with_provider(config, Box::new(Offline));
+        assert_eq!(
+            client.list_models().await,
+            vec!["custom", "model-a", "model-b"]
+        );
+    }
+
+    #[test]
+    fn test_retryable_errors() {
+        let http = |status| {
+            anyhow::Error::from(ProviderHttpError {
+                status,
+                message: String::new(),
+            })
+        };
+        assert!(is_retryable(&http(502)));
+        assert!(!is_retryable(&http(400)));
+        assert!(!is_retryable(&http(429)));
+        assert!(is_retryable(&anyhow::Error::from(std::io::Error::from(
+            std::io::ErrorKind::ConnectionReset
+        ))));
+        assert!(!is_retryable(&anyhow::Error::from(
+            crate::quota::QuotaExceeded("limit".to_string())
+        )));
+        assert!(!is_retryable(&anyhow::anyhow!(
+            "Failed to parse AI response"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_send_retries_server_errors_with_backoff() {
+        struct Flaky {
+            failures: Mutex<Vec<u16>>,
+            calls: Arc<Mutex<u32>>,
+        }
+        #[async_trait]
+        impl AiProvider for Flaky {
+            async fn send_message(&self, _: Vec<ChatMessage>) -> Result<ProviderResponse> {
+                *self.calls.lock().unwrap() += 1;
+                match self.failures.lock().unwrap().pop() {
+                    Some(status) => Err(ProviderHttpError {
+                        status,
+                        message: format!("HTTP {}", status),
+                    }
+                    .into()),
+                    None => Ok(ProviderResponse {
+                        content: "ok".to_string(),
+                        tokens: 1,
+                    }),
+                }
+            }
+        }
+
+        let config = test_config();
+        let client = |failures: Vec<u16>| {
+            let calls = Arc::new(Mutex::new(0));
+            let provider = Flaky {
+                failures: Mutex::new(failures),
+                calls: calls.clone(),
+            };
+            (
+                AiClient::with_provider(config.clone(), Box::new(provider)),
+                calls,
+            )
+        };
+
+        // Two 5xx then success: succeeds on the third attempt
+        let (c, calls) = client(vec![503, 500]);
+        assert_eq!(c.send_with_retry(vec![], None).await.unwrap().content, "ok");
+        assert_eq!(*calls.lock().unwrap(), 3);
+
+        // Attempts are capped
+        let (c, calls) = client(vec![503, 503, 503, 503]);
+        assert!(c.send_with_retry(vec![], None).await.is_err());
+        assert_eq!(*calls.lock().unwrap(), 3);
+
+        // Client errors are not retried
+        let (c, calls) = client(vec![401]);
+        assert!(c.send_with_retry(vec![], None).await.is_err());
+        assert_eq!(*calls.lock().unwrap(), 1);
+
+        // Streaming requests go through the same policy
+        let (c, calls) = client(vec![502]);
+        let (tx, mut rx) = mpsc::channel(4);
+        assert_eq!(
+            c.send_with_retry(vec![], Some(tx)).await.unwrap().content,
+            "ok"
+        );
+        assert_eq!(*calls.lock().unwrap(), 2);
+        assert_eq!(rx.recv().await.as_deref(), Some("ok"));
+    }
+
+    #[tokio::test]
+    async fn test_partial_stream_is_not_retried() {
+        struct DropsMidStream {
+            calls: Arc<Mutex<u32>>,
+        }
+        #[async_trait]
+        impl AiProvider for DropsMidStream {
+            async fn send_message(&self, _: Vec<ChatMessage>) -> Result<ProviderResponse> {
+                unreachable!("streaming only")
+            }
+            async fn send_message_streaming(
+                &self,
+                _: Vec<ChatMessage>,
+                chunks: mpsc::Sender<String>,
+            ) -> Result<ProviderResponse> {
+                *self.calls.lock().unwrap() += 1;
+                let _ = chunks.send("Half a ".to_string()).await;
+                Err(ProviderHttpError {
+                    status: 503,
+                    message: "HTTP 503".to_string(),
+                }
+                .into())
+            }
+        }
+
+        let calls = Arc::new(Mutex::new(0));
+        let client = AiClient::with_provider(
+            test_config(),
+            Box::new(DropsMidStream {
+                calls: calls.clone(),
+            }),
+        );
+        let (tx, mut rx) = mpsc::channel(4);
+        assert!(client.send_with_retry(vec![], Some(tx)).await.is_err());
+        assert_eq!(*calls.lock().unwrap(), 1);
+        assert_eq!(rx.recv().await.as_deref(), Some("Half a "));
+        assert!(rx.recv().await.is_none());
     }
 }
@@ -1,4 +1,4 @@
-use crate::ai::{AiProvider, ChatMessage, ProviderResponse};
+use crate::ai::{AiProvider, ChatMessage, ProviderHttpError, ProviderResponse};
 use crate::config::Config;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
@@ -124,9 +124,17 @@ impl AiProvider for AnthropicProvider {
             if let Ok(err) = serde_json::from_str::<AnthropicErrorResponse>(&body)
                 && let Some(detail) = err.error
             {
-                anyhow::bail!("Anthropic API Error ({}): {}", status, detail.message);
+                return Err(ProviderHttpError::new(
+                    status,
+                    format!("Anthropic API Error ({}): {}", status, detail.message),
+                )
+                .into());
             }
-            anyhow::bail!("Anthropic API Error ({}): {}", status, body);
+            return Err(ProviderHttpError::new(
+                status,
+                format!("Anthropic API Error ({}): {}", status, body),
+            )
+            .into());
         }
 
         let parsed: AnthropicResponse =
@@ -171,4 +179,8 @@ impl AiProvider for AnthropicProvider {
         models.sort();
         Ok(models)
     }
+
+    fn default_models(&self) -> Vec<&'static str> {
+        vec!["claude-opus-4-6", "claude-sonnet-4-5", "claude-haiku-4-5"]
+    }
 }
@@ -1,4 +1,4 @@
-use crate::ai::{AiProvider, ProviderResponse};
+use crate::ai::{AiProvider, ProviderHttpError, ProviderResponse};
 use crate::config::Config;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
@@ -143,8 +143,13 @@ impl AiProvider for GoogleProvider {
             .context(format!("Failed to send request to Google AI at {}", url))?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
-            return Err(anyhow::anyhow!("Google AI Request Failed: {}", error_text));
+            return Err(ProviderHttpError::new(
+                status,
+                format!("Google AI Request Failed: {}", error_text),
+            )
+            .into());
         }
 
         let gen_response: GenerateContentResponse = response
@@ -228,4 +233,12 @@ impl AiProvider for GoogleProvider {
 
         Ok(models)
     }
+
+    fn default_models(&self) -> Vec<&'static str> {
+        vec![
+            "gemini-flash-latest",
+            "gemini-3.0-flash-preview",
+            "gemini-3.0-pro-preview",
+        ]
+    }
 }
@@ -1,4 +1,4 @@
-use crate::ai::{AiProvider, ProviderResponse};
+use crate::ai::{AiProvider, ProviderHttpError, ProviderResponse};
 use crate::config::Config;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
@@ -196,8 +196,11 @@ impl VertexAiProvider {
             .context("Vertex AI Gemini request failed")?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Vertex AI Error: {}", error_text);
+            return Err(
+                ProviderHttpError::new(status, format!("Vertex AI Error: {}", error_text)).into(),
+            );
         }
 
         let gen_response: GenerateContentResponse = response
@@ -270,8 +273,13 @@ impl VertexAiProvider {
             .context("Vertex AI Claude request failed")?;
 
         if !response.status().is_success() {
+            let status = response.status();
             let error_text = response.text().await.unwrap_or_default();
-            anyhow::bail!("Vertex AI Claude Error: {}", error_text);
+            return Err(ProviderHttpError::new(
+                status,
+                format!("Vertex AI Claude Error: {}", error_text),
+            )
+            .into());
         }
 
         let parsed: ClaudeVertexResponse = response
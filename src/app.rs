@@ -22,15 +22,22 @@ pub enum Action {
     ToggleAnalysis,
     AnalysisNext,
     AnalysisPrev,
+    AnalysisSelect(usize),
     Yank,
     YankVisual,
     YankWithCount,
+    YankSubtree,
     SaveSession,
     ExportReport,
     ToggleMetrics,
     ToggleMetricsView,
     ToggleBreakpoint,
     SubmitChat,
+    ConfirmVarChange,
+    RerunHint,
+    CopyFixSnippet,
+    ChatAboutFailure,
+    UndoVarChange,
     None,
 }
 
@@ -106,6 +113,125 @@ pub enum AnalysisFocus {
 pub enum MetricsView {
     Dashboard,
     Heatmap,
+    Histogram,
+    Hosts,
+}
+
+/// Display order of the Analysis task list. Sorting is applied at view time
+/// over indices into `history`; the history itself stays in execution order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskSort {
+    Execution,
+    DurationDesc,
+    Status,
+}
+
+impl TaskSort {
+    pub fn next(self) -> Self {
+        match self {
+            TaskSort::Execution => TaskSort::DurationDesc,
+            TaskSort::DurationDesc => TaskSort::Status,
+            TaskSort::Status => TaskSort::Execution,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TaskSort::Execution => "execution order",
+            TaskSort::DurationDesc => "duration",
+            TaskSort::Status => "status",
+        }
+    }
+}
+
+/// Order of the host list modal. Count sorts put the busiest hosts first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HostSort {
+    Name,
+    Ok,
+    Changed,
+    Failed,
+}
+
+impl HostSort {
+    pub fn next(self) -> Self {
+        match self {
+            HostSort::Name => HostSort::Ok,
+            HostSort::Ok => HostSort::Changed,
+            HostSort::Changed => HostSort::Failed,
+            HostSort::Failed => HostSort::Name,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HostSort::Name => "name",
+            HostSort::Ok => "ok",
+            HostSort::Changed => "changed",
+            HostSort::Failed => "failed",
+        }
+    }
+}
+
+/// How the dashboard inspector renders its value; kept across tasks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InspectorFormat {
+    Json,
+    Yaml,
+}
+
+impl InspectorFormat {
+    pub fn next(self) -> Self {
+        match self {
+            InspectorFormat::Json => InspectorFormat::Yaml,
+            InspectorFormat::Yaml => InspectorFormat::Json,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InspectorFormat::Json => "JSON",
+            InspectorFormat::Yaml => "YAML",
+        }
+    }
+
+    /// Syntax name passed to `SyntaxHighlighter::highlight`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            InspectorFormat::Json => "json",
+            InspectorFormat::Yaml => "yaml",
+        }
+    }
+
+    pub fn render(self, value: &serde_json::Value) -> String {
+        match self {
+            InspectorFormat::Json => {
+                serde_json::to_string_pretty(value).unwrap_or_else(|_| "Invalid JSON".to_string())
+            }
+            InspectorFormat::Yaml => serde_yaml::to_string(value)
+                .map(|y| y.trim_end().to_string())
+                .unwrap_or_else(|e| format!("YAML conversion failed: {}", e)),
+        }
+    }
+}
+
+/// What headless mode writes to stdout (`PILOTEER_HEADLESS_FORMAT`).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum HeadlessFormat {
+    #[default]
+    Prose,
+    /// One JSON object per run event; other messages go to stderr
+    Jsonl,
+}
+
+impl HeadlessFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "" | "prose" | "text" => Some(HeadlessFormat::Prose),
+            "jsonl" | "json" => Some(HeadlessFormat::Jsonl),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -117,7 +243,7 @@ pub enum ChatMode {
 
 // ── Supporting data types ────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct TaskHistory {
     pub name: String,
     pub host: String,
@@ -127,6 +253,35 @@ pub struct TaskHistory {
     pub error: Option<String>,
     pub verbose_result: Option<crate::execution::ExecutionDetails>,
     pub analysis: Option<crate::ai::Analysis>,
+    /// Failed, but handled by a `block`/`rescue` so the run continued
+    #[serde(default)]
+    pub rescued: bool,
+    /// Not run because its `when:` condition was false
+    #[serde(default)]
+    pub skipped: bool,
+    /// Wall-clock start and end; None in sessions saved before they were recorded
+    #[serde(default)]
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Variables and facts the task failed with, so a later re-analysis sees
+    /// the same context. Only kept for failures.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_vars: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facts: Option<serde_json::Value>,
+}
+
+impl TaskHistory {
+    /// An OK, unchanged result of `name` on `host`; set the rest with struct
+    /// update syntax.
+    pub fn new(name: impl Into<String>, host: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            host: host.into(),
+            ..Default::default()
+        }
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -135,6 +290,86 @@ pub struct HostStatus {
     pub ok_tasks: usize,
     pub changed_tasks: usize,
     pub failed_tasks: usize,
+    #[serde(default)]
+    pub rescued_tasks: usize,
+    #[serde(default)]
+    pub skipped_tasks: usize,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+pub enum WarningKind {
+    Warning,
+    Deprecation,
+}
+
+impl WarningKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            WarningKind::Warning => "WARNING",
+            WarningKind::Deprecation => "DEPRECATION",
+        }
+    }
+}
+
+/// A warning or deprecation notice Ansible attached to a task result.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AnsibleWarning {
+    pub kind: WarningKind,
+    pub message: String,
+    pub task: String,
+    pub host: String,
+}
+
+/// Identical notices grouped across tasks and hosts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WarningSummary {
+    pub kind: WarningKind,
+    pub message: String,
+    pub count: usize,
+    pub tasks: Vec<String>,
+}
+
+/// Pause on `task` only when the JMESPath `expr` is truthy against the task's
+/// variables (facts are available as `ansible_facts`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionalBreakpoint {
+    pub task: String,
+    pub expr: String,
+}
+
+/// A `ModifyVar` sent to Ansible, remembered so a later edit of the same key
+/// can be flagged before it overwrites one the playbook hasn't picked up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarChange {
+    pub task: Option<String>,
+    pub key: String,
+    pub value: serde_json::Value,
+}
+
+/// Snapshot of the inspector content frozen by the "pin inspector" toggle:
+/// the viewed task as a history entry, with the result shown as its
+/// `verbose_result` alongside the vars, facts and analysis it had.
+#[derive(Debug, Clone)]
+pub struct InspectorPin {
+    pub entry: TaskHistory,
+}
+
+/// Playbook state of one plugin client, stashed while another client is the
+/// one the app is showing and replying to.
+#[derive(Debug, Default)]
+pub struct ClientState {
+    pub current_task: Option<String>,
+    pub task_vars: Option<serde_json::Value>,
+    pub facts: Option<serde_json::Value>,
+    pub task_start_time: Option<std::time::Instant>,
+    pub task_started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub failed_task: Option<String>,
+    pub failed_host: Option<String>,
+    pub failed_result: Option<serde_json::Value>,
+    pub waiting_for_proceed: bool,
+    pub headless_wait: Option<(std::time::Instant, String)>,
 }
 
 // ── App state ────────────────────────────────────────────────────────────────
@@ -150,7 +385,9 @@ pub struct App {
     pub task_vars: Option<serde_json::Value>,
     pub facts: Option<serde_json::Value>,
     pub task_start_time: Option<std::time::Instant>,
+    pub task_started_at: Option<chrono::DateTime<chrono::Utc>>,
     pub failed_task: Option<String>,
+    pub failed_host: Option<String>,
     pub failed_result: Option<serde_json::Value>,
     pub waiting_for_proceed: bool,
     pub ai_client: Option<AiClient>,
@@ -175,15 +412,24 @@ pub struct App {
     pub analysis_tree: Option<crate::widgets::json_tree::JsonTreeState>,
     pub clipboard: ClipboardHandler,
     pub notification: Option<(String, std::time::Instant)>,
+    /// The current notification is a warning, drawn in red
+    pub notification_alert: bool,
     pub replay_mode: bool,
     // Host tracking
     pub host_facts: std::collections::HashMap<String, serde_json::Value>,
     pub host_filter: Option<String>,
     pub show_host_list: bool,
     pub hosts: std::collections::HashMap<String, HostStatus>,
-    pub host_list_index: usize,
-    pub show_detail_view: bool,
+    // Last task each host finished, kept alongside `history` so the Hosts
+    // Timeline doesn't search it on every frame
+    pub host_last_task: std::collections::HashMap<String, String>,
+    // Host list selection by name, so live count updates that reorder the
+    // list don't move it onto another host
+    pub host_list_selected: Option<String>,
+    pub value_viewer: Option<crate::widgets::value_viewer::ValueViewer>,
     pub metrics_view: MetricsView,
+    // First host row shown in the Hosts Timeline metrics view
+    pub host_progress_scroll: usize,
     // Scripted testing
     pub test_script: Vec<ScriptAction>,
     // IPC
@@ -208,7 +454,7 @@ pub struct App {
     // Tracing
     pub playbook_span: Option<opentelemetry::global::BoxedSpan>,
     pub playbook_span_guard: Option<opentelemetry::ContextGuard>,
-    pub task_spans: std::collections::HashMap<String, opentelemetry::global::BoxedSpan>,
+    pub task_spans: std::collections::HashMap<String, crate::telemetry::TaskSpan>,
     pub play_span: Option<opentelemetry::global::BoxedSpan>,
     pub play_span_guard: Option<opentelemetry::ContextGuard>,
     // Event velocity metrics
@@ -217,7 +463,152 @@ pub struct App {
     pub last_velocity_update: std::time::Instant,
     // Breakpoints / edit
     pub breakpoints: std::collections::HashSet<String>,
+    /// Pause before every task (`--step`, toggled with `S`)
+    pub step_mode: bool,
+    pub conditional_breakpoints: Vec<ConditionalBreakpoint>,
+    /// Task whose breakpoint condition is being typed into the search prompt
+    pub breakpoint_expr_task: Option<String>,
     pub edit_state: EditState,
+    // Facts dump (--dump-facts)
+    pub facts_dump_dir: Option<std::path::PathBuf>,
+    // Replay re-analysis (--reanalyze)
+    pub reanalyze: bool,
+    pub reanalysis_tx: Option<mpsc::Sender<Message>>,
+    pub reanalysis_requested: std::collections::HashSet<usize>,
+    pub fresh_analyses: std::collections::HashSet<usize>,
+    // AI quota
+    pub quota_exhausted_until: Option<std::time::Instant>,
+    /// Quota day the near-limit warning was shown for; it is not repeated
+    /// until the tracker moves on to a new day
+    pub quota_warned: Option<chrono::NaiveDate>,
+    // Folded-stack task timings (--report *.folded)
+    pub span_timings: crate::telemetry::SpanTimings,
+    // Pinned inspector (keeps showing one task while the run continues)
+    pub inspector_pin: Option<InspectorPin>,
+    // Free-form session note (saved with the session, shown in reports)
+    pub session_note: Option<String>,
+    pub note_input: Option<String>,
+    // Applied variable modifications and an overwrite awaiting confirmation,
+    // with the action it is audited as
+    pub applied_changes: Vec<VarChange>,
+    pub pending_var_change: Option<(VarChange, &'static str)>,
+    // Analysis task list ordering
+    pub task_sort: TaskSort,
+    // Host list modal ordering
+    pub host_sort: HostSort,
+    // History overflow (max_history_in_memory)
+    pub max_history_in_memory: Option<usize>,
+    pub history_spill: Option<crate::spill::HistorySpill>,
+    pub(crate) spill_cursor: usize,
+    // Request id of the pending snippet reply, whose YAML block is copied
+    pub snippet_pending: Option<u64>,
+    // Last id given to an AI request whose reply is waited for
+    pub ai_request_seq: u64,
+    // Blocking error shown instead of an empty session (e.g. missing ansible-playbook)
+    pub startup_error: Option<String>,
+    // Live follow: dashboard shows only this host's log lines and latest result
+    pub follow_host: Option<String>,
+    pub follow_result: Option<serde_json::Value>,
+    // Values sent with ModifyVar, verified against the next TaskStart's vars
+    pub expected_vars: std::collections::HashMap<String, serde_json::Value>,
+    // AI explicitly disabled (--no-ai / PILOTEER_NO_AI): hide AI controls
+    pub ai_disabled: bool,
+    // Ansible warnings/deprecations collected from task results
+    pub warnings: Vec<AnsibleWarning>,
+    pub show_warnings: bool,
+    // View active before the last switch, for the toggle-last-view key
+    pub previous_view: Option<ActiveView>,
+    // Partial Pilot reply while an analysis streams in
+    pub pilot_stream: String,
+    pub pilot_stream_tx: Option<mpsc::Sender<String>>,
+    // Data Browser edit in progress: leaf path within its top-level value, and that value
+    pub tree_edit: Option<(
+        Vec<crate::widgets::json_tree::PathSegment>,
+        serde_json::Value,
+    )>,
+    // Concurrent plugin clients: the one being handled and the others' state
+    pub ipc_clients: Option<crate::ipc::ClientRegistry>,
+    pub active_client: Option<crate::ipc::ClientId>,
+    pub client_states: std::collections::HashMap<crate::ipc::ClientId, ClientState>,
+    // Headless auto-proceed: how long to wait for a command (--headless-timeout)
+    // and the deadline/task the active client is waiting on
+    pub headless_timeout: Option<std::time::Duration>,
+    pub headless_wait: Option<(std::time::Instant, String)>,
+    pub headless_format: HeadlessFormat,
+    // Finished Pilot analyses, routed back into the event loop
+    pub pilot_result_tx: Option<mpsc::Sender<Message>>,
+    // Log lines kept in memory (0 = unbounded)
+    pub log_buffer_size: usize,
+    // Value each sent ModifyVar replaced (None: the variable was unset),
+    // newest last, for `u` to restore
+    pub var_undo: Vec<(String, Option<serde_json::Value>)>,
+    // Latency (ms) and tokens of recent AI calls, for the Metrics dashboard
+    pub ai_latencies: VecDeque<u64>,
+    pub ai_tokens: VecDeque<u32>,
+    // Where the Analysis task list was last drawn, for mouse hit-testing
+    pub history_pane: Option<ratatui::layout::Rect>,
+    pub history_list_offset: usize,
+    // `--replay-timeline` playback; None for live runs and static replays
+    pub timeline: Option<crate::timeline::TimelinePlayer>,
+    // Stall detection: when the plugin last sent anything, and whether the
+    // current silence was already reported
+    pub stall_timeout: Option<std::time::Duration>,
+    pub last_ipc_message: std::time::Instant,
+    pub stall_warned: bool,
+    // Control decisions are appended here when `audit_log` is on
+    pub audit_log: Option<crate::audit::AuditLog>,
+    // Dashboard inspector rendering, toggled with `t`
+    pub inspector_format: InspectorFormat,
+    // Inspector text width from the last draw, for wrap-aware search scrolling
+    pub inspector_width: u16,
+    // Index into `inspector_matches()` of the active search match
+    pub inspector_match: Option<usize>,
+    // Incoming plugin messages are appended here (PILOTEER_RECORD_IPC)
+    pub ipc_recorder: Option<crate::ipc_record::IpcRecorder>,
+    // Messages come from a recording (--replay-ipc): like `replay_mode`, the
+    // run isn't archived, audited or reported in the exit code
+    pub ipc_replay: bool,
+}
+
+/// Row at which byte `col` of line `line` appears once `content` is
+/// word-wrapped to `width` columns, as the inspector renders it. A zero
+/// width (not drawn yet) counts unwrapped lines.
+pub fn wrapped_row(content: &str, line: usize, col: usize, width: u16) -> u16 {
+    use ratatui::widgets::{Paragraph, Wrap};
+
+    if width == 0 {
+        return line as u16;
+    }
+    let rows = |text: &str| {
+        Paragraph::new(text)
+            .wrap(Wrap { trim: false })
+            .line_count(width)
+            .max(1)
+    };
+    let mut lines = content.lines();
+    let before: usize = lines.by_ref().take(line).map(rows).sum();
+    let within = lines.next().and_then(|l| l.get(..col)).map_or(1, rows);
+    (before + within - 1) as u16
+}
+
+/// Host a per-host log line refers to: task results (`Task 'x' on host:
+/// STATUS`) and unreachable notices.
+pub fn log_line_host(msg: &str) -> Option<&str> {
+    if let Some(rest) = msg.strip_prefix("Task '") {
+        let (_, after) = rest.rsplit_once("' on ")?;
+        return after.rsplit_once(": ").map(|(host, _)| host);
+    }
+    let rest = msg.strip_prefix("⚠️  Host ")?;
+    rest.split_once(" unreachable during task")
+        .map(|(host, _)| host)
+}
+
+/// Variable value as shown in feedback messages: strings unquoted.
+fn display_var(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
 }
 
 // ── App methods ──────────────────────────────────────────────────────────────
@@ -226,22 +617,34 @@ use crate::config::Config;
 
 impl App {
     pub fn new(config: Config) -> Self {
-        let enable_ai = config.openai_api_key.is_some()
-            || config.api_base != "https://api.openai.com/v1"
-            || config.auth_token.is_some()
-            || config.provider.as_deref() == Some("google");
+        let enable_ai = !config.no_ai
+            && (config.openai_api_key.is_some()
+                || config.api_base != "https://api.openai.com/v1"
+                || config.auth_token.is_some()
+                || config.provider.as_deref() == Some("google"));
 
         let ai_client = enable_ai.then(|| AiClient::new(config.clone()));
+        let highlighter = SyntaxHighlighter::new(&config.highlight_theme);
+        let theme_warning = (highlighter.theme() != config.highlight_theme).then(|| {
+            format!(
+                "Unknown highlight_theme '{}', using {}. Available: {}",
+                config.highlight_theme,
+                highlighter.theme(),
+                highlighter.theme_names().join(", ")
+            )
+        });
 
-        Self {
+        let mut app = Self {
             running: true,
             logs: VecDeque::new(),
             history: Vec::new(),
             current_task: None,
             task_start_time: None,
+            task_started_at: None,
             task_vars: None,
             facts: None,
             failed_task: None,
+            failed_host: None,
             failed_result: None,
             waiting_for_proceed: false,
             ipc_tx: None,
@@ -251,7 +654,7 @@ impl App {
             asking_ai: false,
             show_help: false,
             scroll_offset: 0,
-            highlighter: SyntaxHighlighter::new(),
+            highlighter,
             search_query: String::new(),
             search_active: false,
             search_index: None,
@@ -264,16 +667,19 @@ impl App {
             analysis_index: 0,
             analysis_focus: AnalysisFocus::TaskList,
             analysis_tree: None,
-            clipboard: ClipboardHandler::new(),
+            clipboard: ClipboardHandler::new(config.clipboard_osc52),
             notification: None,
+            notification_alert: false,
             replay_mode: false,
             host_facts: std::collections::HashMap::new(),
             host_filter: None,
             show_host_list: false,
             hosts: std::collections::HashMap::new(),
-            host_list_index: 0,
-            show_detail_view: false,
+            host_last_task: std::collections::HashMap::new(),
+            host_list_selected: None,
+            value_viewer: None,
             metrics_view: MetricsView::Dashboard,
+            host_progress_scroll: 0,
             test_script: Vec::new(),
             unreachable_hosts: std::collections::HashSet::new(),
             chat_active: false,
@@ -298,7 +704,108 @@ impl App {
             event_counter: 0,
             last_velocity_update: std::time::Instant::now(),
             breakpoints: std::collections::HashSet::new(),
+            step_mode: false,
+            conditional_breakpoints: Vec::new(),
+            breakpoint_expr_task: None,
             edit_state: EditState::Idle,
+            facts_dump_dir: None,
+            reanalyze: false,
+            reanalysis_tx: None,
+            reanalysis_requested: std::collections::HashSet::new(),
+            fresh_analyses: std::collections::HashSet::new(),
+            quota_exhausted_until: None,
+            quota_warned: None,
+            span_timings: crate::telemetry::SpanTimings::default(),
+            inspector_pin: None,
+            session_note: None,
+            note_input: None,
+            applied_changes: Vec::new(),
+            pending_var_change: None,
+            task_sort: TaskSort::Execution,
+            host_sort: HostSort::Name,
+            max_history_in_memory: config.max_history_in_memory,
+            history_spill: None,
+            spill_cursor: 0,
+            snippet_pending: None,
+            ai_request_seq: 0,
+            startup_error: None,
+            follow_host: None,
+            follow_result: None,
+            expected_vars: std::collections::HashMap::new(),
+            ai_disabled: config.no_ai,
+            warnings: Vec::new(),
+            show_warnings: false,
+            previous_view: None,
+            pilot_stream: String::new(),
+            pilot_stream_tx: None,
+            tree_edit: None,
+            ipc_clients: None,
+            active_client: None,
+            client_states: std::collections::HashMap::new(),
+            headless_timeout: None,
+            headless_wait: None,
+            headless_format: HeadlessFormat::Prose,
+            pilot_result_tx: None,
+            log_buffer_size: config.log_buffer_size,
+            var_undo: Vec::new(),
+            ai_latencies: VecDeque::new(),
+            ai_tokens: VecDeque::new(),
+            history_pane: None,
+            history_list_offset: 0,
+            timeline: None,
+            stall_timeout: (config.stall_timeout_secs > 0)
+                .then(|| std::time::Duration::from_secs(config.stall_timeout_secs)),
+            last_ipc_message: std::time::Instant::now(),
+            stall_warned: false,
+            audit_log: None,
+            inspector_format: InspectorFormat::Json,
+            inspector_width: 0,
+            inspector_match: None,
+            ipc_recorder: None,
+            ipc_replay: false,
+        };
+        if let Some(warning) = theme_warning {
+            app.log(warning, Some(ratatui::style::Color::Yellow));
+        }
+        app
+    }
+
+    /// Host the current task runs on, from its facts or vars.
+    pub fn current_host(&self) -> Option<&str> {
+        [&self.facts, &self.task_vars]
+            .into_iter()
+            .flatten()
+            .find_map(|v| v.get("inventory_hostname").and_then(|h| h.as_str()))
+    }
+
+    /// Record a control decision on the current task in the audit log, if
+    /// one is open. A failed write is shown but doesn't stop the run.
+    pub fn audit(&mut self, action: &str, var: Option<(&str, &serde_json::Value)>) {
+        let task = self.current_task.clone();
+        let host = self.current_host().map(str::to_string);
+        let Some(log) = &mut self.audit_log else {
+            return;
+        };
+        if let Err(e) = log.record(action, task.as_deref(), host.as_deref(), var) {
+            self.log(
+                format!("Failed to write audit log: {}", e),
+                Some(ratatui::style::Color::Red),
+            );
+        }
+    }
+
+    /// Append an incoming plugin message to the IPC recording, if one is
+    /// open. A failed write closes the recording rather than the run.
+    pub fn record_ipc(&mut self, client: crate::ipc::ClientId, msg: &Message) {
+        let Some(recorder) = &mut self.ipc_recorder else {
+            return;
+        };
+        if let Err(e) = recorder.record(client, msg) {
+            self.ipc_recorder = None;
+            self.log(
+                format!("Failed to write IPC recording, stopped recording: {}", e),
+                Some(ratatui::style::Color::Red),
+            );
         }
     }
 
@@ -312,17 +819,26 @@ impl App {
 
     pub fn notify(&mut self, msg: String) {
         self.notification = Some((msg, std::time::Instant::now()));
+        self.notification_alert = false;
+    }
+
+    /// Like `notify`, but drawn in red for problems that need attention.
+    pub fn alert(&mut self, msg: String) {
+        self.notify(msg);
+        self.notification_alert = true;
     }
 
     pub fn log(&mut self, msg: String, color: Option<ratatui::style::Color>) {
         self.logs
             .push_back((msg, color.unwrap_or(ratatui::style::Color::White)));
         self.event_counter += 1;
-        if self.logs.len() > 1000 {
-            self.logs.pop_front();
+        if self.log_buffer_size > 0 {
+            while self.logs.len() > self.log_buffer_size {
+                self.logs.pop_front();
+            }
         }
         if self.auto_scroll {
-            self.log_scroll = self.logs.len() as u16;
+            self.log_scroll = u16::try_from(self.logs.len()).unwrap_or(u16::MAX);
         }
     }
 
@@ -340,6 +856,17 @@ impl App {
         }
     }
 
+    /// Seed `breakpoints` from the breakpoints file written before the run.
+    pub fn load_breakpoint_file(&mut self) {
+        let Ok(path) = Config::get_breakpoints_path() else {
+            return;
+        };
+        match Config::load_breakpoints(&path) {
+            Ok(tasks) => self.breakpoints.extend(tasks),
+            Err(e) => self.log(format!("Ignoring breakpoints file: {:#}", e), None),
+        }
+    }
+
     pub fn set_task(
         &mut self,
         name: String,
@@ -349,22 +876,32 @@ impl App {
         self.current_task = Some(name);
         self.task_vars = Some(vars);
         self.task_start_time = Some(std::time::Instant::now());
+        self.task_started_at = Some(chrono::Utc::now());
 
         if let Some(f) = &facts
             && let Some(host) = f.get("inventory_hostname").and_then(|h| h.as_str())
         {
             let host_name = host.to_string();
             self.host_facts.insert(host_name.clone(), f.clone());
+            if let Some(dir) = &self.facts_dump_dir
+                && let Err(e) = write_host_facts(dir, &host_name, f)
+            {
+                let msg = format!("Failed to dump facts for {}: {}", host_name, e);
+                self.log(msg, Some(ratatui::style::Color::Red));
+            }
             self.hosts.entry(host_name.clone()).or_insert(HostStatus {
                 name: host_name,
                 ok_tasks: 0,
                 changed_tasks: 0,
                 failed_tasks: 0,
+                rescued_tasks: 0,
+                skipped_tasks: 0,
             });
         }
 
         self.facts = facts;
         self.failed_task = None;
+        self.failed_host = None;
         self.failed_result = None;
         self.waiting_for_proceed = true;
     }
@@ -372,10 +909,12 @@ impl App {
     pub fn set_failed(
         &mut self,
         name: String,
+        host: Option<String>,
         result: serde_json::Value,
         facts: Option<serde_json::Value>,
     ) {
         self.failed_task = Some(name);
+        self.failed_host = host;
         self.failed_result = Some(result);
         if let Some(f) = facts {
             self.facts = Some(f);
@@ -395,22 +934,39 @@ impl App {
         verbose_result: Option<crate::execution::ExecutionDetails>,
         analysis: Option<crate::ai::Analysis>,
     ) {
+        if self.follow_host.as_deref() == Some(host.as_str()) {
+            self.follow_result = verbose_result
+                .as_ref()
+                .map(|d| d.inner().clone())
+                .or_else(|| error.clone().map(serde_json::Value::String));
+        }
+        let (task_vars, facts) = if failed {
+            (self.task_vars.clone(), self.facts.clone())
+        } else {
+            (None, None)
+        };
         self.history.push(TaskHistory {
-            name: name.clone(),
-            host: host.clone(),
             changed,
             failed,
             duration,
             error,
             verbose_result,
             analysis,
+            started_at: self.task_started_at,
+            finished_at: Some(chrono::Utc::now()),
+            task_vars,
+            facts,
+            ..TaskHistory::new(name.clone(), host.clone())
         });
+        self.host_last_task.insert(host.clone(), name);
 
         let entry = self.hosts.entry(host.clone()).or_insert(HostStatus {
             name: host,
             ok_tasks: 0,
             changed_tasks: 0,
             failed_tasks: 0,
+            rescued_tasks: 0,
+            skipped_tasks: 0,
         });
         if failed {
             entry.failed_tasks += 1;
@@ -419,6 +975,116 @@ impl App {
         } else {
             entry.ok_tasks += 1;
         }
+
+        self.spill_old_history();
+    }
+
+    /// Move verbose results, and failures' vars and facts, of entries beyond
+    /// `max_history_in_memory` to the on-disk spill. The entries themselves
+    /// stay in `history` as a lightweight index.
+    fn spill_old_history(&mut self) {
+        let Some(cap) = self.max_history_in_memory else {
+            return;
+        };
+        while self.history.len() - self.spill_cursor > cap {
+            let idx = self.spill_cursor;
+            let entry = &self.history[idx];
+            let spilled = crate::spill::SpilledTask {
+                verbose_result: entry.verbose_result.clone(),
+                task_vars: entry.task_vars.clone(),
+                facts: entry.facts.clone(),
+            };
+            if spilled.is_empty() {
+                self.spill_cursor += 1;
+                continue;
+            }
+            if self.history_spill.is_none() {
+                match crate::spill::HistorySpill::new() {
+                    Ok(spill) => self.history_spill = Some(spill),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Failed to create history spill file");
+                        return;
+                    }
+                }
+            }
+            if let Some(spill) = &mut self.history_spill
+                && let Err(e) = spill.store(idx, &spilled)
+            {
+                tracing::warn!(error = %e, "Failed to spill task history");
+                return;
+            }
+            let entry = &mut self.history[idx];
+            entry.verbose_result = None;
+            entry.task_vars = None;
+            entry.facts = None;
+            self.spill_cursor += 1;
+        }
+    }
+
+    /// History entry `idx` with its verbose result, vars and facts, reloading
+    /// them from the spill file if they were moved out of memory.
+    pub fn history_entry(&self, idx: usize) -> Option<TaskHistory> {
+        let mut task = self.history.get(idx)?.clone();
+        if let Some(spill) = &self.history_spill {
+            match spill.load(idx) {
+                Ok(Some(spilled)) => {
+                    task.verbose_result = spilled.verbose_result;
+                    task.task_vars = spilled.task_vars;
+                    task.facts = spilled.facts;
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!(error = %e, idx, "Failed to reload spilled history"),
+            }
+        }
+        Some(task)
+    }
+
+    /// The complete history, including spilled verbose results.
+    pub fn full_history(&self) -> Vec<TaskHistory> {
+        if self.history_spill.is_none() {
+            return self.history.clone();
+        }
+        (0..self.history.len())
+            .filter_map(|i| self.history_entry(i))
+            .collect()
+    }
+
+    /// Reclassify the most recent result as rescued: it stays in history for
+    /// debugging but counts towards the host's rescued tasks, not failures.
+    pub fn mark_last_rescued(&mut self) {
+        let Some(task) = self.history.last_mut() else {
+            return;
+        };
+        if !task.failed || task.rescued {
+            return;
+        }
+        task.rescued = true;
+        if let Some(host) = self.hosts.get_mut(&task.host) {
+            host.failed_tasks = host.failed_tasks.saturating_sub(1);
+            host.rescued_tasks += 1;
+        }
+    }
+
+    /// Record a task Ansible skipped on `host` (its `when:` was false).
+    pub fn record_skipped(&mut self, name: String, host: String) {
+        self.host_last_task.insert(host.clone(), name.clone());
+        self.history.push(TaskHistory {
+            skipped: true,
+            started_at: self.task_started_at,
+            finished_at: Some(chrono::Utc::now()),
+            ..TaskHistory::new(name, host.clone())
+        });
+        self.hosts
+            .entry(host.clone())
+            .or_insert(HostStatus {
+                name: host,
+                ok_tasks: 0,
+                changed_tasks: 0,
+                failed_tasks: 0,
+                rescued_tasks: 0,
+                skipped_tasks: 0,
+            })
+            .skipped_tasks += 1;
     }
 
     pub fn set_unreachable(
@@ -429,6 +1095,9 @@ impl App {
         result: serde_json::Value,
     ) {
         self.unreachable_hosts.insert(host.clone());
+        if self.follow_host.as_deref() == Some(host.as_str()) {
+            self.follow_result = Some(result.clone());
+        }
         self.log(
             format!(
                 "⚠️  Host {} unreachable during task '{}': {}",
@@ -436,18 +1105,49 @@ impl App {
             ),
             Some(ratatui::style::Color::Red),
         );
+        self.host_last_task.insert(host.clone(), task.clone());
         self.history.push(TaskHistory {
-            name: task,
-            host,
-            changed: false,
             failed: true,
-            duration: 0.0,
             error: Some(serde_json::to_string(&result).unwrap_or(error)),
-            verbose_result: None,
-            analysis: None,
+            started_at: self.task_started_at,
+            finished_at: Some(chrono::Utc::now()),
+            task_vars: self.task_vars.clone(),
+            facts: self.facts.clone(),
+            ..TaskHistory::new(task, host)
         });
     }
 
+    /// Any message from the plugin resets the stall timer.
+    pub fn note_ipc_activity(&mut self) {
+        self.last_ipc_message = std::time::Instant::now();
+        self.stall_warned = false;
+    }
+
+    /// Warn once per silence when a connected plugin has sent nothing for
+    /// `stall_timeout`. Pauses where the controller is waiting on us, and
+    /// replays, don't count.
+    pub fn check_stall(&mut self, now: std::time::Instant) {
+        let Some(timeout) = self.stall_timeout else {
+            return;
+        };
+        if self.replay_mode
+            || !self.client_connected
+            || self.stall_warned
+            || self.waiting_for_proceed
+            || self.failed_task.is_some()
+            || now.saturating_duration_since(self.last_ipc_message) < timeout
+        {
+            return;
+        }
+        self.stall_warned = true;
+        let msg = format!(
+            "No activity for {}s — controller may be stalled",
+            timeout.as_secs()
+        );
+        self.log(msg.clone(), Some(ratatui::style::Color::Red));
+        self.alert(msg);
+    }
+
     pub fn update_velocity(&mut self) {
         if self.last_velocity_update.elapsed() >= std::time::Duration::from_secs(1) {
             self.event_velocity.push_back(self.event_counter);
@@ -459,6 +1159,642 @@ impl App {
         }
     }
 
+    /// Record one AI call's latency and token count, keeping the last 100.
+    pub fn record_ai_call(&mut self, latency_ms: u64, tokens: u32) {
+        self.ai_latencies.push_back(latency_ms);
+        self.ai_tokens.push_back(tokens);
+        if self.ai_latencies.len() > 100 {
+            self.ai_latencies.pop_front();
+            self.ai_tokens.pop_front();
+        }
+    }
+
+    /// Pull metrics for AI calls the client finished since the last check.
+    pub fn collect_ai_metrics(&mut self) {
+        let calls = self
+            .ai_client
+            .as_ref()
+            .map(|c| c.take_call_metrics())
+            .unwrap_or_default();
+        for (latency_ms, tokens) in calls {
+            self.record_ai_call(latency_ms, tokens);
+        }
+    }
+
+    /// Indices into `history` in the order the Analysis task list shows them,
+    /// after the host filter and the current sort mode.
+    pub fn task_display_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.history.len())
+            .filter(|&i| {
+                self.host_filter
+                    .as_ref()
+                    .is_none_or(|h| &self.history[i].host == h)
+            })
+            .collect();
+        match self.task_sort {
+            TaskSort::Execution => {}
+            TaskSort::DurationDesc => order.sort_by(|&a, &b| {
+                self.history[b]
+                    .duration
+                    .total_cmp(&self.history[a].duration)
+            }),
+            TaskSort::Status => {
+                let rank = |t: &TaskHistory| match (t.failed, t.changed) {
+                    (true, _) => 0,
+                    (false, true) => 1,
+                    (false, false) if t.skipped => 3,
+                    (false, false) => 2,
+                };
+                order.sort_by(|&a, &b| {
+                    let (ta, tb) = (&self.history[a], &self.history[b]);
+                    rank(ta).cmp(&rank(tb)).then_with(|| ta.name.cmp(&tb.name))
+                });
+            }
+        }
+        order
+    }
+
+    /// Hosts in the order the host list shows them, after `host_sort`; ties
+    /// fall back to the host name.
+    pub fn host_list_order(&self) -> Vec<&HostStatus> {
+        let mut hosts: Vec<&HostStatus> = self.hosts.values().collect();
+        let count = |h: &HostStatus| match self.host_sort {
+            HostSort::Name => 0,
+            HostSort::Ok => h.ok_tasks,
+            HostSort::Changed => h.changed_tasks,
+            HostSort::Failed => h.failed_tasks,
+        };
+        hosts.sort_by(|a, b| count(b).cmp(&count(a)).then_with(|| a.name.cmp(&b.name)));
+        hosts
+    }
+
+    /// Row of `host_list_selected` in `host_list_order`, falling back to the
+    /// first row when nothing is selected or the host is gone.
+    pub fn host_list_index(&self) -> usize {
+        self.host_list_selected
+            .as_ref()
+            .and_then(|name| self.host_list_order().iter().position(|h| &h.name == name))
+            .unwrap_or(0)
+    }
+
+    /// Move the Analysis selection one entry forward or back in display order.
+    /// Returns `false` when there is nothing to select.
+    pub fn step_analysis(&mut self, forward: bool) -> bool {
+        let order = self.task_display_order();
+        let Some(&first) = order.first() else {
+            return false;
+        };
+        self.analysis_index = match order.iter().position(|&i| i == self.analysis_index) {
+            Some(pos) if forward => order[(pos + 1).min(order.len() - 1)],
+            Some(pos) => order[pos.saturating_sub(1)],
+            None => first,
+        };
+        true
+    }
+
+    /// `history` index of the task list row at screen cell (`column`, `row`),
+    /// if the click landed on a task inside the last drawn list.
+    pub fn history_index_at(&self, column: u16, row: u16) -> Option<usize> {
+        let pane = self.history_pane?;
+        // Rows inside the border
+        if column <= pane.x
+            || column >= pane.right().saturating_sub(1)
+            || row <= pane.y
+            || row >= pane.bottom().saturating_sub(1)
+        {
+            return None;
+        }
+        let pos = self.history_list_offset + (row - pane.y - 1) as usize;
+        self.task_display_order().get(pos).copied()
+    }
+
+    /// Next (or previous) failed task in the Analysis list order after the
+    /// selected one, wrapping around. Only tasks the list shows count.
+    pub fn find_failed_task(&self, forward: bool) -> Option<usize> {
+        let order = self.task_display_order();
+        let len = order.len();
+        let start = order.iter().position(|&i| i == self.analysis_index);
+        (1..=len)
+            .map(|step| match (start, forward) {
+                (Some(pos), true) => order[(pos + step) % len],
+                (Some(pos), false) => order[(pos + len - step) % len],
+                (None, true) => order[step - 1],
+                (None, false) => order[len - step],
+            })
+            .find(|&i| self.history[i].failed)
+    }
+
+    /// Collect the warnings and deprecations reported with a task result.
+    pub fn record_warnings(
+        &mut self,
+        task: &str,
+        host: &str,
+        warnings: Vec<String>,
+        deprecations: Vec<String>,
+    ) {
+        let notices = warnings
+            .into_iter()
+            .map(|m| (WarningKind::Warning, m))
+            .chain(
+                deprecations
+                    .into_iter()
+                    .map(|m| (WarningKind::Deprecation, m)),
+            );
+        for (kind, message) in notices {
+            self.warnings.push(AnsibleWarning {
+                kind,
+                message,
+                task: task.to_string(),
+                host: host.to_string(),
+            });
+        }
+    }
+
+    /// Process exit code summarising the run: 3 if a host was unreachable,
+    /// 2 if a task failed (rescued failures don't count), 4 with `strict`
+    /// if a task changed, else 0.
+    pub fn exit_code(&self, strict: bool) -> i32 {
+        if !self.unreachable_hosts.is_empty() {
+            3
+        } else if self.history.iter().any(|t| t.failed && !t.rescued) {
+            2
+        } else if strict && self.history.iter().any(|t| t.changed) {
+            4
+        } else {
+            0
+        }
+    }
+
+    /// `(warnings, deprecations)` counts.
+    pub fn warning_counts(&self) -> (usize, usize) {
+        let deprecations = self
+            .warnings
+            .iter()
+            .filter(|w| w.kind == WarningKind::Deprecation)
+            .count();
+        (self.warnings.len() - deprecations, deprecations)
+    }
+
+    /// Notices grouped by kind and message, most frequent first; each lists
+    /// the distinct tasks that raised it in first-seen order.
+    pub fn warning_summary(&self) -> Vec<WarningSummary> {
+        let mut summary: Vec<WarningSummary> = Vec::new();
+        for w in &self.warnings {
+            match summary
+                .iter_mut()
+                .find(|s| s.kind == w.kind && s.message == w.message)
+            {
+                Some(s) => {
+                    s.count += 1;
+                    if !s.tasks.contains(&w.task) {
+                        s.tasks.push(w.task.clone());
+                    }
+                }
+                None => summary.push(WarningSummary {
+                    kind: w.kind,
+                    message: w.message.clone(),
+                    count: 1,
+                    tasks: vec![w.task.clone()],
+                }),
+            }
+        }
+        // Stable sort keeps first-seen order among equal counts
+        summary.sort_by(|a, b| b.count.cmp(&a.count).then(a.kind.cmp(&b.kind)));
+        summary
+    }
+
+    /// Scroll the Hosts Timeline by `delta` rows, keeping at least one host
+    /// in view.
+    /// The task shown for `host` in the Hosts Timeline and whether it is
+    /// still running there: the current task until the host reports it
+    /// finished, then the last one it finished.
+    pub fn host_task(&self, host: &str) -> Option<(&str, bool)> {
+        let last = self.host_last_task.get(host).map(String::as_str);
+        let live = !self.replay_mode || self.timeline.is_some();
+        match self.current_task.as_deref() {
+            Some(current)
+                if live && last != Some(current) && !self.unreachable_hosts.contains(host) =>
+            {
+                Some((current, true))
+            }
+            _ => last.map(|task| (task, false)),
+        }
+    }
+
+    pub fn scroll_host_progress(&mut self, delta: isize) {
+        let last = self.hosts.len().saturating_sub(1);
+        self.host_progress_scroll = self
+            .host_progress_scroll
+            .saturating_add_signed(delta)
+            .min(last);
+    }
+
+    /// Switch views, remembering the one left behind.
+    pub fn set_view(&mut self, view: ActiveView) {
+        if view != self.active_view {
+            self.previous_view = Some(self.active_view);
+            self.active_view = view;
+        }
+    }
+
+    /// Swap back to the previously active view, if there was one.
+    pub fn toggle_last_view(&mut self) {
+        if let Some(prev) = self.previous_view {
+            self.set_view(prev);
+        }
+    }
+
+    /// Set (or with an empty `expr`, clear) the condition for pausing on
+    /// `task`. Invalid expressions are rejected.
+    pub fn set_conditional_breakpoint(&mut self, task: String, expr: &str) -> Result<(), String> {
+        let expr = expr.trim();
+        if !expr.is_empty() {
+            crate::query::validate_query(expr)?;
+        }
+        self.conditional_breakpoints.retain(|b| b.task != task);
+        if !expr.is_empty() {
+            self.conditional_breakpoints.push(ConditionalBreakpoint {
+                task,
+                expr: expr.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The condition of a conditional breakpoint on `task` that holds for the
+    /// current task variables, if any. Evaluation errors are logged and count
+    /// as not matching.
+    pub fn conditional_breakpoint_hit(&mut self, task: &str) -> Option<String> {
+        let mut data = match &self.task_vars {
+            Some(serde_json::Value::Object(vars)) => vars.clone(),
+            _ => serde_json::Map::new(),
+        };
+        if let Some(facts) = &self.facts {
+            data.entry("ansible_facts").or_insert_with(|| facts.clone());
+        }
+        let data = serde_json::Value::Object(data);
+
+        let mut hit = None;
+        let mut errors = Vec::new();
+        for bp in self
+            .conditional_breakpoints
+            .iter()
+            .filter(|b| b.task == task)
+        {
+            match crate::query::run_query(&bp.expr, &data) {
+                Ok(v) if crate::query::is_truthy(&v) => {
+                    hit = Some(bp.expr.clone());
+                    break;
+                }
+                Ok(_) => {}
+                Err(e) => errors.push(format!("Breakpoint condition `{}` failed: {}", bp.expr, e)),
+            }
+        }
+        for e in errors {
+            self.log(e, Some(ratatui::style::Color::Red));
+        }
+        hit
+    }
+
+    /// Remember a value sent with `ModifyVar` so the next `TaskStart` can
+    /// confirm it took effect.
+    pub fn expect_var(&mut self, key: String, value: serde_json::Value) {
+        self.expected_vars.insert(key, value);
+    }
+
+    /// Compare pending expected values against the current task's variables,
+    /// log one confirmation or warning per key and return them. Expectations
+    /// stay pending while no task variables are available.
+    pub fn check_var_expectations(&mut self) -> Vec<(String, bool)> {
+        if self.expected_vars.is_empty() || !self.task_vars.as_ref().is_some_and(|v| v.is_object())
+        {
+            return Vec::new();
+        }
+        let mut expected: Vec<_> = self.expected_vars.drain().collect();
+        expected.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut results = Vec::new();
+        for (key, value) in expected {
+            let actual = self.get_var_value(&key);
+            let ok = actual.as_ref() == Some(&value);
+            let msg = if ok {
+                format!("var `{}` now = {} as expected", key, display_var(&value))
+            } else {
+                format!(
+                    "warning: var `{}` is still {} (expected {})",
+                    key,
+                    actual.as_ref().map_or("unset".to_string(), display_var),
+                    display_var(&value)
+                )
+            };
+            let color = if ok {
+                ratatui::style::Color::Green
+            } else {
+                ratatui::style::Color::Yellow
+            };
+            self.log(msg.clone(), Some(color));
+            self.notify(msg.clone());
+            results.push((msg, ok));
+        }
+        results
+    }
+
+    /// The most recent modification of `key` that the playbook hasn't picked
+    /// up yet (still awaited by the next `TaskStart`), whichever task it was
+    /// made on.
+    pub fn find_var_conflict(&self, key: &str) -> Option<&VarChange> {
+        if !self.expected_vars.contains_key(key) {
+            return None;
+        }
+        self.applied_changes.iter().rev().find(|c| c.key == key)
+    }
+
+    /// Freeze the inspector on what it currently shows, or unpin and resume
+    /// following the live failure.
+    pub fn toggle_inspector_pin(&mut self) {
+        if self.inspector_pin.take().is_some() {
+            self.notify("Inspector unpinned — following live run".to_string());
+        } else {
+            let result = self.inspector_result().cloned();
+            let live_failure = result.is_some() && result == self.failed_result;
+            // A followed host's result comes from its latest history entry
+            let mut entry = match &self.follow_host {
+                Some(host) if !live_failure => {
+                    self.history.iter().rev().find(|t| &t.host == host).cloned()
+                }
+                _ => None,
+            }
+            .unwrap_or_else(|| TaskHistory {
+                failed: self.failed_task.is_some(),
+                analysis: self.suggestion.clone(),
+                started_at: self.task_started_at,
+                task_vars: self.task_vars.clone(),
+                facts: self.facts.clone(),
+                ..TaskHistory::new(
+                    self.failed_task
+                        .clone()
+                        .or_else(|| self.current_task.clone())
+                        .unwrap_or_default(),
+                    self.failed_host
+                        .as_deref()
+                        .or(self.current_host())
+                        .unwrap_or_default(),
+                )
+            });
+            entry.verbose_result = result.map(crate::execution::ExecutionDetails::new);
+            self.inspector_pin = Some(InspectorPin { entry });
+            self.notify("Inspector pinned (p to unpin)".to_string());
+        }
+    }
+
+    /// Analysis shown in the Pilot pane: the pinned task's if pinned,
+    /// otherwise the live suggestion.
+    pub fn pilot_analysis(&self) -> Option<&crate::ai::Analysis> {
+        match &self.inspector_pin {
+            Some(pin) => pin.entry.analysis.as_ref(),
+            None => self.suggestion.as_ref(),
+        }
+    }
+
+    /// Result shown in the inspector: the pinned snapshot if any, otherwise
+    /// the live failure.
+    pub fn inspector_result(&self) -> Option<&serde_json::Value> {
+        match &self.inspector_pin {
+            Some(pin) => pin.entry.verbose_result.as_ref().map(|d| d.inner()),
+            // Only the followed host's own failure replaces its latest result
+            None if self.follow_host.is_some() => {
+                let failed_host = self.failed_host.as_deref().or(self.current_host());
+                if failed_host == self.follow_host.as_deref() {
+                    self.failed_result.as_ref().or(self.follow_result.as_ref())
+                } else {
+                    self.follow_result.as_ref()
+                }
+            }
+            None => self.failed_result.as_ref(),
+        }
+    }
+
+    /// The inspector's value rendered in `inspector_format`, exactly as drawn.
+    pub fn inspector_text(&self) -> Option<String> {
+        self.inspector_result()
+            .map(|r| self.inspector_format.render(r))
+    }
+
+    /// Search matches in the inspector text as `(line, start, end)`, with
+    /// byte offsets within the line, in display order.
+    pub fn inspector_matches(&self) -> Vec<(usize, usize, usize)> {
+        if self.search_query.is_empty() {
+            return Vec::new();
+        }
+        self.inspector_text()
+            .map(|content| self.inspector_matches_in(&content))
+            .unwrap_or_default()
+    }
+
+    /// [`App::inspector_matches`] for `content` already rendered by
+    /// [`App::inspector_text`], so a frame doesn't render the value twice.
+    pub fn inspector_matches_in(&self, content: &str) -> Vec<(usize, usize, usize)> {
+        if self.search_query.is_empty() {
+            return Vec::new();
+        }
+        let pattern = crate::search::SearchPattern::new(&self.search_query);
+        content
+            .lines()
+            .enumerate()
+            .flat_map(|(i, line)| {
+                pattern
+                    .match_ranges(line)
+                    .into_iter()
+                    .map(move |(start, end)| (i, start, end))
+            })
+            .collect()
+    }
+
+    /// Make match `idx` (wrapping around) the active inspector match and
+    /// scroll so its wrapped row is at the top of the pane.
+    pub fn select_inspector_match(&mut self, idx: usize) {
+        let matches = self.inspector_matches();
+        if matches.is_empty() {
+            self.inspector_match = None;
+            return;
+        }
+        let idx = idx % matches.len();
+        let (line, _, end) = matches[idx];
+        let content = self.inspector_text().unwrap_or_default();
+        self.inspector_match = Some(idx);
+        self.scroll_offset = wrapped_row(&content, line, end, self.inspector_width);
+    }
+
+    /// Make `client` the active plugin client: stash the current client's
+    /// task state and restore `client`'s, and reply through its channel.
+    pub fn switch_client(&mut self, client: crate::ipc::ClientId) {
+        if self.active_client == Some(client) {
+            return;
+        }
+        if let Some(prev) = self.active_client {
+            let state = ClientState {
+                current_task: self.current_task.take(),
+                task_vars: self.task_vars.take(),
+                facts: self.facts.take(),
+                task_start_time: self.task_start_time.take(),
+                task_started_at: self.task_started_at.take(),
+                failed_task: self.failed_task.take(),
+                failed_host: self.failed_host.take(),
+                failed_result: self.failed_result.take(),
+                waiting_for_proceed: std::mem::take(&mut self.waiting_for_proceed),
+                headless_wait: self.headless_wait.take(),
+            };
+            self.client_states.insert(prev, state);
+        }
+        if self.active_client.is_some() || self.client_states.contains_key(&client) {
+            let state = self.client_states.remove(&client).unwrap_or_default();
+            self.current_task = state.current_task;
+            self.task_vars = state.task_vars;
+            self.facts = state.facts;
+            self.task_start_time = state.task_start_time;
+            self.task_started_at = state.task_started_at;
+            self.failed_task = state.failed_task;
+            self.failed_host = state.failed_host;
+            self.failed_result = state.failed_result;
+            self.waiting_for_proceed = state.waiting_for_proceed;
+            self.headless_wait = state.headless_wait;
+        }
+        self.active_client = Some(client);
+        if let Some(clients) = &self.ipc_clients {
+            self.ipc_tx = clients.sender(client);
+        }
+    }
+
+    /// Forget a disconnected client. The app stays connected while any other
+    /// client is.
+    pub fn drop_client(&mut self, client: crate::ipc::ClientId) {
+        self.client_states.remove(&client);
+        if self.active_client == Some(client) {
+            self.active_client = None;
+            self.waiting_for_proceed = false;
+            self.headless_wait = None;
+        }
+        if !self.client_states.is_empty() {
+            self.client_connected = true;
+        }
+    }
+
+    /// When the active client is not paused but another one is, switch to
+    /// the paused one so its breakpoint or failure isn't left hidden.
+    pub fn focus_waiting_client(&mut self) {
+        if self.waiting_for_proceed {
+            return;
+        }
+        let Some(client) = self
+            .client_states
+            .iter()
+            .filter(|(_, s)| s.waiting_for_proceed)
+            .map(|(id, _)| *id)
+            .min()
+        else {
+            return;
+        };
+        self.switch_client(client);
+        let task = self.current_task.clone().unwrap_or_default();
+        self.log(
+            format!("Client {} waiting at: {}", client, task),
+            Some(ratatui::style::Color::Magenta),
+        );
+    }
+
+    /// Follow `host` in the live dashboard, or stop following if it is the
+    /// host already followed. Recorded history is unaffected.
+    pub fn toggle_follow_host(&mut self, host: &str) {
+        self.follow_result = None;
+        if self.follow_host.as_deref() == Some(host) {
+            self.follow_host = None;
+            self.notify(format!("Stopped following {}", host));
+        } else {
+            self.follow_host = Some(host.to_string());
+            self.notify(format!("Following {}", host));
+        }
+    }
+
+    /// Whether a log line is shown under the current live follow. Lines not
+    /// tied to a host (plays, task starts, AI output) always are.
+    pub fn follows_log_line(&self, msg: &str) -> bool {
+        match (&self.follow_host, log_line_host(msg)) {
+            (Some(followed), Some(host)) => followed == host,
+            _ => true,
+        }
+    }
+
+    /// Index of the selected history entry if it should get a fresh AI
+    /// analysis during a `--reanalyze` replay. The entry is marked as
+    /// requested so navigating back and forth does not spend quota twice.
+    pub fn take_reanalysis_request(&mut self) -> Option<usize> {
+        if !self.replay_mode || !self.reanalyze {
+            return None;
+        }
+        let idx = self.analysis_index;
+        let task = self.history.get(idx)?;
+        if !task.failed || self.reanalysis_requested.contains(&idx) {
+            return None;
+        }
+        self.ai_client.as_ref()?;
+        if !self.check_ai_quota() {
+            self.notify("AI quota exhausted — skipping re-analysis".to_string());
+            return None;
+        }
+        self.reanalysis_requested.insert(idx);
+        Some(idx)
+    }
+
+    /// Block AI actions until the quota resets in `reset_in`.
+    pub fn mark_quota_exhausted(&mut self, reset_in: std::time::Duration) {
+        self.quota_exhausted_until = Some(std::time::Instant::now() + reset_in);
+    }
+
+    /// Whether AI actions are disabled because the daily quota is exhausted.
+    pub fn ai_quota_blocked(&self) -> bool {
+        self.quota_exhausted_until
+            .is_some_and(|until| std::time::Instant::now() < until)
+    }
+
+    /// Banner text shown while AI actions are blocked by the quota.
+    pub fn quota_banner(&self) -> Option<String> {
+        let until = self.quota_exhausted_until?;
+        let remaining = until.checked_duration_since(std::time::Instant::now())?;
+        let mins = remaining.as_secs() / 60;
+        Some(format!(
+            "AI quota exhausted — resets in {}h {:02}m",
+            mins / 60,
+            mins % 60
+        ))
+    }
+
+    /// Check the client's quota before an AI request. Returns `false` (and
+    /// records the exhaustion) when the request must not be sent. Crossing
+    /// the warning threshold notifies once but lets the request through.
+    pub fn check_ai_quota(&mut self) -> bool {
+        if self.ai_quota_blocked() {
+            return false;
+        }
+        let Some(status) = self.ai_client.as_ref().map(|c| c.get_quota_status()) else {
+            return true;
+        };
+        self.apply_quota_status(&status)
+    }
+
+    /// Act on a quota reading: block AI once a limit is hit, and warn once
+    /// per quota day when one is close. Returns whether AI may be used.
+    pub fn apply_quota_status(&mut self, status: &crate::ai::QuotaStatus) -> bool {
+        if status.is_exceeded() {
+            self.mark_quota_exhausted(status.reset_in);
+            return false;
+        }
+        if status.is_warning() && self.quota_warned != Some(status.day) {
+            self.quota_warned = Some(status.day);
+            self.notify(status.warning_message());
+        }
+        true
+    }
+
     pub fn save_session(&self, filename: &str) -> std::io::Result<()> {
         crate::session::Session::from_app(self).save(filename)
     }
@@ -475,4 +1811,132 @@ impl App {
         app.current_task = Some("REPLAY MODE".to_string());
         Ok(app)
     }
+
+    /// Turn a loaded replay into a timeline: the recorded tasks are taken
+    /// out of history and fed back in by `advance_timeline`.
+    pub fn start_timeline(&mut self, speed: f64) {
+        let tasks = std::mem::take(&mut self.history);
+        self.logs.clear();
+        self.hosts.clear();
+        self.host_last_task.clear();
+        self.play_recap = None;
+        self.current_task = None;
+        self.log(
+            format!("Timeline replay of {} tasks at {}x", tasks.len(), speed),
+            Some(ratatui::style::Color::Cyan),
+        );
+        self.timeline = Some(crate::timeline::TimelinePlayer::new(tasks, speed));
+    }
+
+    /// Apply every timeline event due by now. Called on each tick.
+    pub fn advance_timeline(&mut self) {
+        let Some(player) = self.timeline.as_mut() else {
+            return;
+        };
+        if player.is_finished() {
+            return;
+        }
+        for event in player.poll(std::time::Instant::now()) {
+            self.apply_timeline_event(event);
+        }
+    }
+
+    /// `n`: emit the next timeline event immediately.
+    pub fn step_timeline(&mut self) {
+        if let Some(event) = self.timeline.as_mut().and_then(|p| p.step()) {
+            self.apply_timeline_event(event);
+        }
+    }
+
+    /// Space: pause or resume the timeline.
+    pub fn toggle_timeline_pause(&mut self) {
+        if let Some(player) = self.timeline.as_mut() {
+            player.toggle_pause();
+            let msg = if player.paused {
+                "Timeline paused (n to step)"
+            } else {
+                "Timeline resumed"
+            };
+            self.notify(msg.to_string());
+        }
+    }
+
+    fn apply_timeline_event(&mut self, event: crate::timeline::TimelineEvent) {
+        match event {
+            crate::timeline::TimelineEvent::Started { name, host } => {
+                self.log(
+                    format!("Task: {} [{}]", name, host),
+                    Some(ratatui::style::Color::White),
+                );
+                self.current_task = Some(name);
+                self.task_start_time = Some(std::time::Instant::now());
+            }
+            crate::timeline::TimelineEvent::Finished(task) => {
+                let (status, color) = if task.skipped {
+                    ("SKIPPED", ratatui::style::Color::DarkGray)
+                } else {
+                    crate::ipc_handler::task_status(task.failed, task.changed, task.rescued)
+                };
+                self.log(
+                    format!("Task '{}' on {}: {}", task.name, task.host, status),
+                    Some(color),
+                );
+                if let Some(error) = &task.error {
+                    self.log(format!("  {}", error), Some(ratatui::style::Color::Red));
+                }
+                // A failure is re-recorded with the vars and facts it ran with,
+                // which re-analysis reads back
+                if task.failed {
+                    self.task_vars = task.task_vars.clone();
+                    self.facts = task.facts.clone();
+                }
+                let recorded = (task.started_at, task.finished_at);
+                self.record_task_result(
+                    task.name,
+                    task.host,
+                    task.changed,
+                    task.failed,
+                    task.duration,
+                    task.error,
+                    task.verbose_result,
+                    task.analysis,
+                );
+                // Keep the original run's timestamps, not the playback's
+                if let Some(last) = self.history.last_mut() {
+                    (last.started_at, last.finished_at) = recorded;
+                }
+                if task.rescued {
+                    self.mark_last_rescued();
+                }
+            }
+        }
+        if self.timeline.as_ref().is_some_and(|p| p.is_finished()) {
+            self.current_task = None;
+            self.log(
+                "Timeline replay complete".to_string(),
+                Some(ratatui::style::Color::Cyan),
+            );
+        }
+    }
+}
+
+/// Write a host's facts to `<dir>/<host>.json`, replacing any previous dump.
+///
+/// The file is written to a temporary sibling first and renamed into place so
+/// readers never observe a partially written document.
+pub fn write_host_facts(
+    dir: &std::path::Path,
+    host: &str,
+    facts: &serde_json::Value,
+) -> std::io::Result<std::path::PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let file_name: String = host
+        .chars()
+        .map(|c| if c == '/' || c == '\\' { '_' } else { c })
+        .collect();
+    let path = dir.join(format!("{}.json", file_name));
+    let tmp_path = dir.join(format!(".{}.json.tmp", file_name));
+    std::fs::write(&tmp_path, serde_json::to_vec_pretty(facts)?)?;
+    std::fs::rename(&tmp_path, &path)?;
+    Ok(path)
 }
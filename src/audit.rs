@@ -0,0 +1,167 @@
+//! Append-only JSONL record of the control decisions made during a run
+//! (Proceed, Retry, Continue, variable changes, applied AI fixes), for
+//! compliance reviews. Opt-in with `audit_log = true`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// Written in place of variable values unless `audit_log_values` is set.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Key fragments whose values are treated as secrets
+const SENSITIVE_KEY_PARTS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "private_key",
+    "credential",
+];
+
+/// Whether `key` looks like it names a secret, case-insensitively.
+pub fn is_sensitive_key(key: &str) -> bool {
+    let lower = key.to_lowercase();
+    SENSITIVE_KEY_PARTS.iter().any(|p| lower.contains(p))
+}
+
+/// `value` of the variable `key` with the value of every secret-looking key,
+/// at any depth, replaced by [`REDACTED`]. Used for audit entries and traced
+/// variable changes alike.
+pub fn redact_secrets(key: &str, value: &serde_json::Value) -> serde_json::Value {
+    if is_sensitive_key(key) {
+        return REDACTED.into();
+    }
+    match value {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| (k.clone(), redact_secrets(k, v)))
+            .collect(),
+        serde_json::Value::Array(items) => items.iter().map(|v| redact_secrets("", v)).collect(),
+        other => other.clone(),
+    }
+}
+
+/// One line of the audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub action: String,
+    pub task: Option<String>,
+    pub host: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<serde_json::Value>,
+}
+
+#[derive(Debug)]
+pub struct AuditLog {
+    file: File,
+    include_values: bool,
+}
+
+impl AuditLog {
+    /// Open `path` for appending, creating it and its directory if needed.
+    /// Variable values are only written when `include_values` is set.
+    pub fn open(path: &Path, include_values: bool) -> std::io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        Ok(Self {
+            file,
+            include_values,
+        })
+    }
+
+    /// Append an entry for `action`; `var` is the variable it set, if any.
+    /// Secret-looking keys are redacted even when values are included.
+    pub fn record(
+        &mut self,
+        action: &str,
+        task: Option<&str>,
+        host: Option<&str>,
+        var: Option<(&str, &serde_json::Value)>,
+    ) -> std::io::Result<()> {
+        let entry = AuditEntry {
+            timestamp: Utc::now(),
+            action: action.to_string(),
+            task: task.map(str::to_string),
+            host: host.map(str::to_string),
+            key: var.map(|(k, _)| k.to_string()),
+            value: var.map(|(k, v)| {
+                if self.include_values {
+                    redact_secrets(k, v)
+                } else {
+                    REDACTED.into()
+                }
+            }),
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        // One write per line so concurrent appenders never interleave
+        self.file.write_all(line.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_secrets_at_any_depth() {
+        assert!(is_sensitive_key("DB_Password"));
+        assert!(is_sensitive_key("vault_token"));
+        assert!(!is_sensitive_key("port"));
+
+        assert_eq!(redact_secrets("api_key", &json!({"a": 1})), json!(REDACTED));
+        assert_eq!(redact_secrets("port", &json!(8080)), json!(8080));
+        assert_eq!(
+            redact_secrets(
+                "db",
+                &json!({"host": "db1", "users": [{"name": "app", "passwd": "x"}]})
+            ),
+            json!({"host": "db1", "users": [{"name": "app", "passwd": REDACTED}]})
+        );
+    }
+
+    #[test]
+    fn test_record_redacts_secrets_when_values_are_included() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let mut log = AuditLog::open(&path, true).unwrap();
+        log.record(
+            "ModifyVar",
+            Some("t"),
+            None,
+            Some(("db", &json!({"host": "db1", "secret": "s3cr3t"}))),
+        )
+        .unwrap();
+        log.record(
+            "ModifyVar",
+            Some("t"),
+            None,
+            Some(("db_password", &json!("x"))),
+        )
+        .unwrap();
+
+        let entries: Vec<AuditEntry> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(
+            entries[0].value,
+            Some(json!({"host": "db1", "secret": REDACTED}))
+        );
+        assert_eq!(entries[1].value, Some(json!(REDACTED)));
+    }
+}
@@ -1,4 +1,6 @@
 use arboard::Clipboard;
+use base64::Engine;
+use std::io::Write;
 
 pub struct ClipboardHandler {
     clipboard: Option<Clipboard>,
@@ -6,22 +8,53 @@ pub struct ClipboardHandler {
 
 impl Default for ClipboardHandler {
     fn default() -> Self {
-        Self::new()
+        Self::new(false)
     }
 }
 
 impl ClipboardHandler {
-    pub fn new() -> Self {
-        let clipboard = Clipboard::new().ok();
+    /// With `force_osc52`, the system clipboard is never opened and every
+    /// copy goes through the terminal (see `osc52_sequence`).
+    pub fn new(force_osc52: bool) -> Self {
+        let clipboard = if force_osc52 {
+            None
+        } else {
+            Clipboard::new().ok()
+        };
         Self { clipboard }
     }
 
+    /// Copy via the system clipboard, falling back to OSC 52 when there is
+    /// none (e.g. over SSH without a display) or it rejects the text.
     pub fn set_text(&mut self, text: String) -> Result<(), Box<dyn std::error::Error>> {
-        if let Some(clipboard) = &mut self.clipboard {
-            clipboard.set_text(text)?;
-            Ok(())
-        } else {
-            Err("Clipboard not available".into())
+        if let Some(clipboard) = &mut self.clipboard
+            && clipboard.set_text(text.clone()).is_ok()
+        {
+            return Ok(());
         }
+        let mut stdout = std::io::stdout();
+        stdout.write_all(osc52_sequence(&text).as_bytes())?;
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+/// Terminal escape asking the terminal emulator (iTerm2, kitty, WezTerm,
+/// tmux with `set-clipboard on`, ...) to put `text` on the local clipboard.
+pub fn osc52_sequence(text: &str) -> String {
+    format!(
+        "\x1b]52;c;{}\x07",
+        base64::engine::general_purpose::STANDARD.encode(text)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("hello"), "\x1b]52;c;aGVsbG8=\x07");
+        assert_eq!(osc52_sequence(""), "\x1b]52;c;\x07");
     }
 }
@@ -1,18 +1,19 @@
 use anyhow::{Context, Result};
-use config::{Config as ConfigLoader, Environment, File};
+use config::{Config as ConfigLoader, Environment, File, FileFormat};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::env;
 use std::fs;
-use std::path::PathBuf; // [NEW]
+use std::path::{Path, PathBuf}; // [NEW]
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct Config {
     pub openai_api_key: Option<String>,
     pub socket_path: String,
     pub model: String,
     pub api_base: String,
-    #[allow(dead_code)]
+    /// `tracing` filter for `log_file`, e.g. `debug` or `ansible_piloteer::ipc=trace`.
     pub log_level: String,
     pub auth_token: Option<String>,
     pub bind_addr: Option<String>,
@@ -30,11 +31,162 @@ pub struct Config {
     pub anthropic_api_key: Option<String>,        // [NEW] Phase 35
     pub vertex_project_id: Option<String>,        // [NEW] Phase 35
     pub vertex_location: Option<String>,          // [NEW] Phase 35
+    /// Task-name globs eligible for automatic AI fix application (headless
+    /// `--auto-analyze`). `None` allows no task, so fixes are never applied
+    /// unattended until an allowlist is configured.
+    pub auto_fix_tasks: Option<Vec<String>>,
+    /// Variable-name globs that are never modified automatically. `None` uses
+    /// [`DEFAULT_AUTO_FIX_DENY_VARS`].
+    pub auto_fix_deny_vars: Option<Vec<String>>,
+    /// Additional handshake secrets keyed by client label, accepted alongside
+    /// `secret_token` (e.g. during a rotation).
+    pub secret_tokens: Option<HashMap<String, String>>,
+    /// File with one accepted secret per line (`token` or `label=token`).
+    pub secrets_file: Option<String>,
+    /// Keep verbose results (and failures' vars and facts) of only this many
+    /// recent tasks in memory; older ones are spilled to a temp file and
+    /// reloaded on demand.
+    pub max_history_in_memory: Option<usize>,
+    /// Never create an AI client, even when keys or endpoints are configured.
+    pub no_ai: bool,
+    /// Attempts per AI request, including the first, on transport/5xx errors.
+    pub retry_max_attempts: u32,
+    /// Delay before the first AI retry; doubles on each further attempt.
+    pub retry_base_ms: u64,
+    /// Log lines kept in memory; the oldest are dropped beyond this. `0`
+    /// keeps everything.
+    pub log_buffer_size: usize,
+    /// USD per 1k tokens by model-name prefix, overriding the built-in
+    /// price table used for quota cost estimates.
+    pub model_rates: Option<HashMap<String, f64>>,
+    /// Percentage of a daily quota limit at which the TUI starts warning.
+    pub quota_warn_pct: f64,
+    /// Seconds without any message from a connected plugin before the TUI
+    /// warns that the controller may be stalled. `0` disables the check.
+    pub stall_timeout_secs: u64,
+    /// Append diagnostic logs (IPC connections, AI retries, ...) at
+    /// `log_level` to this file. Off by default; stderr belongs to the TUI.
+    pub log_file: Option<String>,
+    /// PEM certificate and private key for serving `bind_addr` over TLS.
+    /// Without them TCP is plaintext, secret token and task vars included.
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    /// Failed handshakes from one TCP address within
+    /// `auth_failure_window_secs` before it is refused for
+    /// `auth_lockout_secs`. `0` disables the lockout.
+    pub auth_max_failures: u32,
+    pub auth_failure_window_secs: u64,
+    pub auth_lockout_secs: u64,
+    /// Seconds between heartbeat pings to each plugin; a plugin that stays
+    /// silent for three intervals is disconnected. `0` disables heartbeats.
+    pub heartbeat_interval_secs: u64,
+    /// Trace exporter: `zipkin` (OTLP/HTTP to `zipkin_endpoint`), `otlp`
+    /// (OTLP/gRPC to `otlp_endpoint`) or `none`. Sampling and the service
+    /// name come from the `zipkin_*` settings either way.
+    pub otel_exporter: String,
+    /// OTLP/gRPC collector address, e.g. `http://otel-collector:4317`
+    pub otlp_endpoint: Option<String>,
+    /// Append every Proceed/Retry/Continue/ModifyVar/ApplyFix decision to a
+    /// JSONL audit log. Decisions not made at the keyboard are suffixed with
+    /// their source, e.g. `Retry (chat)` or `Proceed (headless)`.
+    pub audit_log: bool,
+    /// Audit log location (default: `audit.jsonl` in the config directory)
+    pub audit_log_path: Option<String>,
+    /// Write variable values to the audit log instead of `[REDACTED]`;
+    /// secret-looking keys stay redacted
+    pub audit_log_values: bool,
+    /// File replacing the built-in failure-analysis instructions; the JSON
+    /// output format is still appended so replies can be parsed
+    pub analysis_prompt_path: Option<String>,
+    /// File replacing the built-in chat system prompt
+    pub chat_prompt_path: Option<String>,
+    /// Always copy through the terminal's OSC 52 escape instead of the system
+    /// clipboard, e.g. in SSH sessions with a clipboard-less X11 forward
+    pub clipboard_osc52: bool,
+    /// syntect theme for highlighted JSON, e.g. `InspiredGitHub` on light
+    /// terminals; unknown names fall back to the default with a warning
+    pub highlight_theme: String,
+    /// Skip saving each run to the session archive in the config directory
+    pub no_archive: bool,
 }
 
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            openai_api_key: None,
+            socket_path: "/tmp/piloteer.sock".to_string(),
+            model: "gpt-4-turbo-preview".to_string(),
+            api_base: "https://api.openai.com/v1".to_string(),
+            log_level: "info".to_string(),
+            auth_token: None,
+            bind_addr: None,
+            secret_token: None,
+            quota_limit_tokens: None,
+            quota_limit_usd: None,
+            google_api_key: None,
+            google_client_id: None,
+            google_client_secret: None,
+            zipkin_endpoint: None,
+            zipkin_service_name: "ansible-piloteer".to_string(),
+            zipkin_sample_rate: 1.0,
+            filters: None,
+            provider: None,
+            anthropic_api_key: None,
+            vertex_project_id: None,
+            vertex_location: Some("us-central1".to_string()),
+            auto_fix_tasks: None,
+            auto_fix_deny_vars: None,
+            secret_tokens: None,
+            secrets_file: None,
+            max_history_in_memory: None,
+            no_ai: false,
+            retry_max_attempts: 3,
+            retry_base_ms: 500,
+            log_buffer_size: 1000,
+            model_rates: None,
+            quota_warn_pct: 90.0,
+            stall_timeout_secs: 60,
+            log_file: None,
+            tls_cert_path: None,
+            tls_key_path: None,
+            auth_max_failures: 5,
+            auth_failure_window_secs: 60,
+            auth_lockout_secs: 300,
+            heartbeat_interval_secs: 15,
+            otel_exporter: "zipkin".to_string(),
+            otlp_endpoint: None,
+            audit_log: false,
+            audit_log_path: None,
+            audit_log_values: false,
+            analysis_prompt_path: None,
+            chat_prompt_path: None,
+            clipboard_osc52: false,
+            highlight_theme: crate::highlight::DEFAULT_THEME.to_string(),
+            no_archive: false,
+        }
+    }
+}
+
+/// Connection and privilege variables an AI fix must never touch unattended.
+pub const DEFAULT_AUTO_FIX_DENY_VARS: &[&str] = &[
+    "ansible_become*",
+    "ansible_*pass*",
+    "ansible_ssh_*",
+    "ansible_connection",
+    "ansible_host",
+    "ansible_user",
+];
+
 impl Config {
     pub fn new() -> Result<Self> {
-        Self::load_from_env()
+        Self::load(None)
+    }
+
+    /// Load the config with the named `[profiles.<name>]` table merged over
+    /// the base values. Without an explicit name, `PILOTEER_PROFILE` (or a
+    /// top-level `profile` key) selects one.
+    pub fn load(profile: Option<&str>) -> Result<Self> {
+        Self::load_from_env(profile)
     }
 
     pub fn get_config_dir() -> Result<PathBuf> {
@@ -50,38 +202,22 @@ impl Config {
         Ok(Self::get_config_dir()?.join("auth.json"))
     }
 
-    fn load_from_env() -> Result<Self> {
-        let mut builder = ConfigLoader::builder()
-            .set_default("socket_path", "/tmp/piloteer.sock")?
-            .set_default("model", "gpt-4-turbo-preview")?
-            .set_default("api_base", "https://api.openai.com/v1")?
-            .set_default("log_level", "info")?
-            .set_default("auth_token", None::<String>)?
-            .set_default("auth_token", None::<String>)?
-            .set_default("bind_addr", None::<String>)?
-            .set_default("secret_token", None::<String>)?
-            .set_default("quota_limit_tokens", None::<u32>)?
-            .set_default("quota_limit_usd", None::<f64>)?
-            .set_default("google_api_key", None::<String>)? // [NEW]
-            .set_default("google_client_id", None::<String>)?
-            .set_default("google_client_secret", None::<String>)?
-            .set_default("zipkin_endpoint", None::<String>)?
-            .set_default("zipkin_service_name", "ansible-piloteer")?
-            .set_default("zipkin_sample_rate", 1.0)?
-            .set_default("filters", None::<HashMap<String, String>>)? // [NEW]
-            .set_default("provider", None::<String>)? // [NEW]
-            .set_default("anthropic_api_key", None::<String>)? // [NEW] Phase 35
-            .set_default("vertex_project_id", None::<String>)? // [NEW] Phase 35
-            .set_default("vertex_location", "us-central1")? // [NEW] Phase 35
-            .add_source(File::with_name("piloteer").required(false)) // CWD
-            .add_source(Environment::with_prefix("PILOTEER"));
-
-        // Load specific config file from ~/.config/ansible-piloteer/piloteer.toml
+    fn load_from_env(profile: Option<&str>) -> Result<Self> {
+        // Unset keys fall back to `Config::default()` via `#[serde(default)]`
+        let mut builder =
+            ConfigLoader::builder().add_source(File::with_name("piloteer").required(false)); // CWD
+
+        // Load specific config files from ~/.config/ansible-piloteer:
+        // piloteer.toml, then config.yaml
         if let Ok(config_dir) = Self::get_config_dir() {
             let config_path = config_dir.join("piloteer.toml");
             if config_path.exists() {
                 builder = builder.add_source(File::from(config_path).required(false));
             }
+            let yaml_path = config_dir.join("config.yaml");
+            if yaml_path.exists() {
+                builder = builder.add_source(Self::yaml_source(&yaml_path)?);
+            }
         }
 
         // Try to load auth config
@@ -91,7 +227,16 @@ impl Config {
             builder = builder.add_source(File::from(auth_path).required(false));
         }
 
-        let s = builder.build()?;
+        // Environment variables take precedence over every config file
+        builder = builder.add_source(Environment::with_prefix("PILOTEER"));
+
+        let mut s = builder.build()?;
+        let profile = profile
+            .map(str::to_string)
+            .or_else(|| s.get_string("profile").ok());
+        if let Some(name) = profile {
+            s = Self::apply_profile(s, &name)?;
+        }
         let mut config: Config = s.try_deserialize()?;
 
         // Manually load auth token from new auth.json structure if not already set
@@ -131,6 +276,55 @@ impl Config {
         Ok(config)
     }
 
+    /// Read a YAML config file, failing with the file name and parse error
+    /// rather than letting a typo fall back to defaults.
+    fn yaml_source(path: &std::path::Path) -> Result<File<config::FileSourceString, FileFormat>> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&text)
+            .with_context(|| format!("Malformed YAML in config file {}", path.display()))?;
+        if !matches!(
+            parsed,
+            serde_yaml::Value::Mapping(_) | serde_yaml::Value::Null
+        ) {
+            anyhow::bail!(
+                "Config file {} must contain a mapping of settings",
+                path.display()
+            );
+        }
+        Ok(File::from_str(&text, FileFormat::Yaml))
+    }
+
+    /// Layer `[profiles.<name>]` over the loaded values. Environment variables
+    /// are re-applied on top so an explicit `PILOTEER_*` still wins.
+    fn apply_profile(base: ConfigLoader, name: &str) -> Result<ConfigLoader> {
+        let overrides: serde_json::Value = base
+            .get(&format!("profiles.{}", name))
+            .with_context(|| format!("Config profile '{}' not found", name))?;
+        Ok(ConfigLoader::builder()
+            .add_source(base)
+            .add_source(File::from_str(&overrides.to_string(), FileFormat::Json))
+            .add_source(Environment::with_prefix("PILOTEER"))
+            .build()?)
+    }
+
+    /// Whether an AI fix for `fix_key` may be applied to `task_name` without
+    /// user confirmation: the task must match the allowlist and the variable
+    /// must not match the denylist.
+    pub fn auto_fix_allowed(&self, task_name: &str, fix_key: &str) -> bool {
+        let task_ok = match &self.auto_fix_tasks {
+            Some(globs) => globs.iter().any(|g| glob_match(g, task_name)),
+            None => false,
+        };
+        let denied = match &self.auto_fix_deny_vars {
+            Some(globs) => globs.iter().any(|g| glob_match(g, fix_key)),
+            None => DEFAULT_AUTO_FIX_DENY_VARS
+                .iter()
+                .any(|g| glob_match(g, fix_key)),
+        };
+        task_ok && !denied
+    }
+
     pub fn load_auth_data() -> Result<HashMap<String, HashMap<String, String>>> {
         let path = Self::get_auth_config_path()?;
         if path.exists() {
@@ -165,6 +359,51 @@ impl Config {
             Ok(None)
         }
     }
+
+    /// File of task names to break on, seeded before a run (e.g. by the
+    /// `piloteer_breakpoint` MCP tool) and loaded when the TUI starts.
+    pub fn get_breakpoints_path() -> Result<PathBuf> {
+        Ok(Self::get_config_dir()?.join("breakpoints.json"))
+    }
+
+    pub fn load_breakpoints(path: &Path) -> Result<BTreeSet<String>> {
+        if !path.exists() {
+            return Ok(BTreeSet::new());
+        }
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid breakpoints file {}", path.display()))
+    }
+
+    pub fn save_breakpoints(path: &Path, breakpoints: &BTreeSet<String>) -> Result<()> {
+        let file = fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, breakpoints)?;
+        Ok(())
+    }
+}
+
+/// Minimal glob matching supporting `*` (any run) and `?` (any single char).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
 }
 
 #[cfg(test)]
@@ -238,4 +477,184 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_profile_precedence() {
+        let _guard = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let tmp = tempfile::tempdir().unwrap();
+        let orig_home = env::var("HOME").ok();
+        let orig_cwd = env::current_dir().ok();
+        unsafe {
+            env::set_var("HOME", tmp.path());
+            env::remove_var("PILOTEER_MODEL");
+            env::remove_var("PILOTEER_PROFILE");
+        }
+        let _ = env::set_current_dir(tmp.path());
+
+        let config_dir = tmp.path().join(".config").join("ansible-piloteer");
+        fs::create_dir_all(&config_dir).unwrap();
+        fs::write(
+            config_dir.join("piloteer.toml"),
+            r#"
+model = "base-model"
+api_base = "https://base.example/v1"
+
+[profiles.prod]
+model = "prod-model"
+quota_limit_usd = 5.0
+
+[profiles.dev]
+api_base = "http://localhost:8080/v1"
+"#,
+        )
+        .unwrap();
+
+        // No profile: base values only
+        let base = Config::load(None).unwrap();
+        assert_eq!(base.model, "base-model");
+        assert!(base.quota_limit_usd.is_none());
+
+        // Explicit profile overrides base values and keeps the rest
+        let prod = Config::load(Some("prod")).unwrap();
+        assert_eq!(prod.model, "prod-model");
+        assert_eq!(prod.quota_limit_usd, Some(5.0));
+        assert_eq!(prod.api_base, "https://base.example/v1");
+
+        // PILOTEER_PROFILE selects a profile; the explicit name beats it, and
+        // PILOTEER_* variables still beat profile values
+        unsafe {
+            env::set_var("PILOTEER_PROFILE", "dev");
+        }
+        assert_eq!(Config::new().unwrap().api_base, "http://localhost:8080/v1");
+        assert_eq!(Config::load(Some("prod")).unwrap().model, "prod-model");
+        unsafe {
+            env::set_var("PILOTEER_MODEL", "env-model");
+        }
+        assert_eq!(Config::load(Some("prod")).unwrap().model, "env-model");
+
+        assert!(Config::load(Some("missing")).is_err());
+
+        unsafe {
+            env::remove_var("PILOTEER_MODEL");
+            env::remove_var("PILOTEER_PROFILE");
+            if let Some(h) = orig_home {
+                env::set_var("HOME", h);
+            }
+        }
+        if let Some(d) = orig_cwd {
+            let _ = env::set_current_dir(d);
+        }
+    }
+
+    #[test]
+    fn test_auto_fix_allowlist() {
+        let _guard = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let tmp = tempfile::tempdir().unwrap();
+        let orig_home = env::var("HOME").ok();
+        unsafe {
+            env::set_var("HOME", tmp.path());
+        }
+        let mut config = Config::new().unwrap();
+        unsafe {
+            if let Some(h) = orig_home {
+                env::set_var("HOME", h);
+            }
+        }
+
+        // No allowlist: no task is eligible
+        assert!(!config.auto_fix_allowed("Install nginx", "nginx_version"));
+
+        config.auto_fix_tasks = Some(vec!["Install *".to_string(), "restart ?b".to_string()]);
+        assert!(config.auto_fix_allowed("Install nginx", "nginx_version"));
+        // Dangerous vars are denied even on allowed tasks
+        assert!(!config.auto_fix_allowed("Install nginx", "ansible_become_pass"));
+        assert!(config.auto_fix_allowed("restart db", "db_port"));
+        assert!(!config.auto_fix_allowed("restart web", "web_port"));
+        assert!(!config.auto_fix_allowed("Deploy app", "app_version"));
+
+        config.auto_fix_deny_vars = Some(vec!["*_version".to_string()]);
+        assert!(!config.auto_fix_allowed("Install nginx", "nginx_version"));
+        assert!(config.auto_fix_allowed("Install nginx", "ansible_become_pass"));
+    }
+
+    #[test]
+    fn test_yaml_config_file() {
+        let _guard = CONFIG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let tmp = tempfile::tempdir().unwrap();
+        let orig_home = env::var("HOME").ok();
+        let orig_cwd = env::current_dir().ok();
+        unsafe {
+            env::set_var("HOME", tmp.path());
+            env::remove_var("PILOTEER_MODEL");
+            env::remove_var("PILOTEER_PROFILE");
+            env::remove_var("PILOTEER_PROVIDER");
+        }
+        let _ = env::set_current_dir(tmp.path());
+
+        let config_dir = tmp.path().join(".config").join("ansible-piloteer");
+        fs::create_dir_all(&config_dir).unwrap();
+        let yaml_path = config_dir.join("config.yaml");
+        fs::write(
+            &yaml_path,
+            r#"
+model: yaml-model
+api_base: http://localhost:11434/v1
+provider: openai
+quota_limit_tokens: 50000
+quota_limit_usd: 2.5
+zipkin_endpoint: http://zipkin:9411/api/v2/spans
+zipkin_sample_rate: 0.25
+filters:
+  slow: "task_history[?duration > `10`]"
+"#,
+        )
+        .unwrap();
+
+        let config = Config::new().unwrap();
+        assert_eq!(config.model, "yaml-model");
+        assert_eq!(config.api_base, "http://localhost:11434/v1");
+        assert_eq!(config.provider.as_deref(), Some("openai"));
+        assert_eq!(config.quota_limit_tokens, Some(50000));
+        assert_eq!(config.quota_limit_usd, Some(2.5));
+        assert_eq!(
+            config.zipkin_endpoint.as_deref(),
+            Some("http://zipkin:9411/api/v2/spans")
+        );
+        assert_eq!(config.zipkin_sample_rate, 0.25);
+        assert_eq!(
+            config.filters.unwrap()["slow"],
+            "task_history[?duration > `10`]"
+        );
+        // Unset keys keep their defaults
+        assert_eq!(config.socket_path, "/tmp/piloteer.sock");
+
+        // Environment variables win over the file
+        unsafe {
+            env::set_var("PILOTEER_MODEL", "env-model");
+        }
+        assert_eq!(Config::new().unwrap().model, "env-model");
+        unsafe {
+            env::remove_var("PILOTEER_MODEL");
+        }
+
+        // Malformed YAML is an error naming the file, not a silent default
+        fs::write(&yaml_path, "model: [unclosed\n").unwrap();
+        let err = format!("{:#}", Config::new().unwrap_err());
+        assert!(err.contains("config.yaml"), "{}", err);
+        fs::write(&yaml_path, "- just\n- a list\n").unwrap();
+        assert!(Config::new().is_err());
+
+        // An empty file is fine
+        fs::write(&yaml_path, "").unwrap();
+        assert_eq!(Config::new().unwrap().model, "gpt-4-turbo-preview");
+
+        unsafe {
+            if let Some(h) = orig_home {
+                env::set_var("HOME", h);
+            }
+        }
+        if let Some(d) = orig_cwd {
+            let _ = env::set_current_dir(d);
+        }
+    }
 }
@@ -0,0 +1,283 @@
+//! Compare two saved sessions: which tasks started failing, flipped their
+//! `changed` state or got slower, and which hosts dropped off the network.
+
+use crate::app::TaskHistory;
+use crate::session::Session;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Slowdowns smaller than this are noise, whatever the percentage.
+const MIN_REGRESSION_SECS: f64 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskRef {
+    pub host: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangedFlip {
+    pub host: String,
+    pub name: String,
+    pub before: bool,
+    pub after: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DurationRegression {
+    pub host: String,
+    pub name: String,
+    pub before: f64,
+    pub after: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SessionDiff {
+    /// Failing now, but not in the base run (or absent from it)
+    pub newly_failing: Vec<TaskRef>,
+    pub changed_flips: Vec<ChangedFlip>,
+    pub duration_regressions: Vec<DurationRegression>,
+    pub newly_unreachable: Vec<String>,
+}
+
+impl SessionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.newly_failing.is_empty()
+            && self.changed_flips.is_empty()
+            && self.duration_regressions.is_empty()
+            && self.newly_unreachable.is_empty()
+    }
+
+    /// Human-readable summary, with ANSI colors when `color` is set.
+    pub fn render_text(&self, color: bool) -> String {
+        let paint = |code: &str, s: &str| {
+            if color {
+                format!("\x1b[{}m{}\x1b[0m", code, s)
+            } else {
+                s.to_string()
+            }
+        };
+        let mut out = String::new();
+        if self.is_empty() {
+            out.push_str("No differences found.\n");
+            return out;
+        }
+
+        if !self.newly_failing.is_empty() {
+            let _ = writeln!(out, "{}", paint("1;31", "Newly failing tasks:"));
+            for t in &self.newly_failing {
+                let _ = writeln!(out, "  {} [{}]", paint("31", &t.name), t.host);
+            }
+        }
+        if !self.changed_flips.is_empty() {
+            let _ = writeln!(out, "{}", paint("1;33", "Changed state flipped:"));
+            for f in &self.changed_flips {
+                let arrow = if f.after {
+                    "ok -> changed"
+                } else {
+                    "changed -> ok"
+                };
+                let _ = writeln!(out, "  {} [{}] {}", f.name, f.host, paint("33", arrow));
+            }
+        }
+        if !self.duration_regressions.is_empty() {
+            let _ = writeln!(out, "{}", paint("1;35", "Duration regressions:"));
+            for r in &self.duration_regressions {
+                let _ = writeln!(
+                    out,
+                    "  {} [{}] {:.2}s -> {}",
+                    r.name,
+                    r.host,
+                    r.before,
+                    paint("35", &format!("{:.2}s", r.after))
+                );
+            }
+        }
+        if !self.newly_unreachable.is_empty() {
+            let _ = writeln!(out, "{}", paint("1;31", "Newly unreachable hosts:"));
+            for h in &self.newly_unreachable {
+                let _ = writeln!(out, "  {}", paint("31", h));
+            }
+        }
+        out
+    }
+}
+
+/// Last run of each (host, task) pair, in order of first appearance.
+fn latest_runs(history: &[TaskHistory]) -> Vec<(&str, &str, &TaskHistory)> {
+    let mut index: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut runs: Vec<(&str, &str, &TaskHistory)> = Vec::new();
+    for task in history.iter().filter(|t| t.name != "Play Recap") {
+        let key = (task.host.as_str(), task.name.as_str());
+        match index.get(&key) {
+            Some(&i) => runs[i].2 = task,
+            None => {
+                index.insert(key, runs.len());
+                runs.push((key.0, key.1, task));
+            }
+        }
+    }
+    runs
+}
+
+/// Compare `compare` against `base`. A task has regressed when it takes more
+/// than `threshold_pct` percent (and at least half a second) longer.
+pub fn diff_sessions(base: &Session, compare: &Session, threshold_pct: f64) -> SessionDiff {
+    let base_runs: HashMap<(&str, &str), &TaskHistory> = latest_runs(&base.history)
+        .into_iter()
+        .map(|(h, n, t)| ((h, n), t))
+        .collect();
+
+    let mut diff = SessionDiff::default();
+    for (host, name, after) in latest_runs(&compare.history) {
+        let before = base_runs.get(&(host, name)).copied();
+        let failing = |t: &TaskHistory| t.failed && !t.rescued;
+
+        if failing(after) && !before.is_some_and(failing) {
+            diff.newly_failing.push(TaskRef {
+                host: host.to_string(),
+                name: name.to_string(),
+            });
+        }
+        let Some(before) = before else { continue };
+        if before.changed != after.changed {
+            diff.changed_flips.push(ChangedFlip {
+                host: host.to_string(),
+                name: name.to_string(),
+                before: before.changed,
+                after: after.changed,
+            });
+        }
+        let allowed = (before.duration * threshold_pct / 100.0).max(MIN_REGRESSION_SECS);
+        if after.duration - before.duration > allowed {
+            diff.duration_regressions.push(DurationRegression {
+                host: host.to_string(),
+                name: name.to_string(),
+                before: before.duration,
+                after: after.duration,
+            });
+        }
+    }
+
+    let mut unreachable: Vec<String> = compare
+        .unreachable_hosts
+        .difference(&base.unreachable_hosts)
+        .cloned()
+        .collect();
+    unreachable.sort();
+    diff.newly_unreachable = unreachable;
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn task(host: &str, name: &str, changed: bool, failed: bool, duration: f64) -> TaskHistory {
+        TaskHistory {
+            changed,
+            failed,
+            duration,
+            ..TaskHistory::new(name, host)
+        }
+    }
+
+    fn session(history: Vec<TaskHistory>, unreachable: &[&str]) -> Session {
+        Session {
+            schema_version: crate::session::SESSION_SCHEMA_VERSION,
+            timestamp: Utc::now(),
+            history,
+            logs: Vec::new(),
+            facts: None,
+            task_vars: None,
+            hosts: HashMap::new(),
+            play_recap: None,
+            unreachable_hosts: unreachable.iter().map(|h| h.to_string()).collect(),
+            session_note: None,
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_sessions() {
+        let base = session(
+            vec![
+                task("web1", "install", true, false, 2.0),
+                task("web1", "configure", false, false, 1.0),
+                task("web1", "restart", false, true, 0.2),
+                task("db1", "migrate", false, false, 10.0),
+            ],
+            &["old1"],
+        );
+        let compare = session(
+            vec![
+                task("web1", "install", false, false, 2.4),
+                task("web1", "configure", false, true, 1.0),
+                task("web1", "restart", false, true, 0.2),
+                task("db1", "migrate", false, false, 16.0),
+                task("db1", "seed", false, true, 0.1),
+            ],
+            &["old1", "web2"],
+        );
+
+        let diff = diff_sessions(&base, &compare, 50.0);
+        // Already failing in base is not "newly" failing; tasks new to the run are
+        assert_eq!(
+            diff.newly_failing,
+            vec![
+                TaskRef {
+                    host: "web1".into(),
+                    name: "configure".into()
+                },
+                TaskRef {
+                    host: "db1".into(),
+                    name: "seed".into()
+                },
+            ]
+        );
+        assert_eq!(
+            diff.changed_flips,
+            vec![ChangedFlip {
+                host: "web1".into(),
+                name: "install".into(),
+                before: true,
+                after: false,
+            }]
+        );
+        // +0.4s on install is under the floor; +6s (60%) on migrate is over 50%
+        assert_eq!(diff.duration_regressions.len(), 1);
+        assert_eq!(diff.duration_regressions[0].name, "migrate");
+        assert_eq!(diff.newly_unreachable, vec!["web2".to_string()]);
+
+        let text = diff.render_text(false);
+        assert!(text.contains("Newly failing tasks:\n  configure [web1]\n"));
+        assert!(text.contains("install [web1] changed -> ok"));
+        assert!(text.contains("migrate [db1] 10.00s -> 16.00s"));
+        assert!(!text.contains('\x1b'));
+        assert!(diff.render_text(true).contains("\x1b[31mweb2\x1b[0m"));
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["newly_unreachable"][0], "web2");
+    }
+
+    #[test]
+    fn test_diff_uses_last_run_and_ignores_rescued() {
+        let base = session(vec![task("h", "t", false, true, 1.0)], &[]);
+        let mut rescued = task("h", "r", false, true, 1.0);
+        rescued.rescued = true;
+        // A retried task is judged by its final run
+        let compare = session(
+            vec![
+                task("h", "t", false, true, 1.0),
+                task("h", "t", false, false, 1.0),
+                rescued,
+            ],
+            &[],
+        );
+        let diff = diff_sessions(&base, &compare, 50.0);
+        assert!(diff.is_empty());
+        assert_eq!(diff.render_text(false), "No differences found.\n");
+    }
+}
@@ -54,3 +54,43 @@ impl ExecutionDetails {
         &self.0
     }
 }
+
+/// Open the log file capturing `ansible-playbook` stdout/stderr under `dir`.
+///
+/// Returns `Ok(None)` without touching the filesystem when debug files are
+/// disabled. The file name embeds the process id so concurrent Piloteer
+/// instances never share (or truncate) each other's logs.
+pub fn open_child_log(
+    dir: &std::path::Path,
+    enabled: bool,
+) -> std::io::Result<Option<(std::fs::File, std::path::PathBuf)>> {
+    if !enabled {
+        return Ok(None);
+    }
+    let path = dir.join(format!("ansible_child_{}.log", std::process::id()));
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    Ok(Some((file, path)))
+}
+
+/// Locate `program` in the directories of a `PATH`-style list, as the shell
+/// would. Used to detect a missing `ansible-playbook` before spawning it.
+pub fn find_in_path(program: &str, path: Option<&std::ffi::OsStr>) -> Option<std::path::PathBuf> {
+    std::env::split_paths(path?)
+        .map(|dir| dir.join(program))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &std::path::Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &std::path::Path) -> bool {
+    path.is_file()
+}
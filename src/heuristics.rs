@@ -0,0 +1,248 @@
+//! Rule-based failure analysis used when no AI provider is configured.
+//! Matches the failed result against common Ansible error signatures and
+//! returns a canned `Analysis`, with a variable fix where one usually helps.
+
+use crate::ai::{Analysis, Fix};
+use serde_json::Value;
+
+/// Prefix on every heuristic analysis so it isn't mistaken for an AI reply.
+pub const HEURISTIC_PREFIX: &str = "[Offline heuristic]";
+
+struct Signature {
+    /// Lowercase substrings, any of which identifies the failure
+    needles: &'static [&'static str],
+    explanation: &'static str,
+    /// Variable and the JSON value to set it to
+    fix: Option<(&'static str, &'static str)>,
+}
+
+/// Checked in order; the first match wins.
+const SIGNATURES: &[Signature] = &[
+    Signature {
+        needles: &[
+            "permission denied (publickey",
+            "authentication failed",
+            "incorrect sudo password",
+            "missing sudo password",
+        ],
+        explanation: "Authentication failed. Check ansible_user and the SSH key or \
+            password for this host, and the become password if escalation is used.",
+        fix: None,
+    },
+    Signature {
+        needles: &[
+            "unreachable",
+            "timed out",
+            "timeout",
+            "connection refused",
+            "no route to host",
+        ],
+        explanation: "The host could not be reached in time. Check that it is up, that SSH \
+            is reachable from the controller and that the inventory address is right. \
+            Slow links may just need a longer connection timeout.",
+        fix: Some(("ansible_timeout", "60")),
+    },
+    Signature {
+        needles: &[
+            "permission denied",
+            "operation not permitted",
+            "must be root",
+            "are you root",
+        ],
+        explanation: "The task lacks the privileges it needs. It most likely has to run \
+            with privilege escalation (become), or the remote user needs access to the path.",
+        fix: Some(("ansible_become", "true")),
+    },
+    Signature {
+        needles: &[
+            "no package matching",
+            "unable to locate package",
+            "no match for argument",
+            "no package",
+            "not found in any repository",
+        ],
+        explanation: "The package manager could not find the package. Check the package \
+            name for this distribution, refresh the package cache (update_cache) or enable \
+            the repository that provides it.",
+        fix: None,
+    },
+    Signature {
+        needles: &["no module named"],
+        explanation: "A Python library the module needs is missing on the target, or \
+            Ansible picked the wrong Python interpreter. Install the library or point \
+            ansible_python_interpreter at an interpreter that has it.",
+        fix: Some(("ansible_python_interpreter", "\"/usr/bin/python3\"")),
+    },
+    Signature {
+        needles: &[
+            "couldn't resolve module/action",
+            "module not found",
+            "was not found in configured module paths",
+        ],
+        explanation: "Ansible does not know this module. Check its spelling and fully \
+            qualified name, and install the collection that provides it \
+            (ansible-galaxy collection install <namespace.collection>).",
+        fix: None,
+    },
+];
+
+/// Result fields that carry the error text. Keys and module arguments
+/// (e.g. a `timeout:` option) are never matched.
+const MESSAGE_FIELDS: &[&str] = &["msg", "stderr", "module_stderr", "exception"];
+
+/// Analyse a failure without an AI provider. `result` is the failed task's
+/// result; `None` means no known signature matched.
+pub fn analyze(result: Option<&Value>) -> Option<Analysis> {
+    let text = match result {
+        Some(Value::String(s)) => s.clone(),
+        Some(v) => {
+            let mut messages = Vec::new();
+            collect_messages(v, &mut messages);
+            messages.join("\n")
+        }
+        None => return None,
+    };
+    let lower = text.to_lowercase();
+
+    // Undefined variables name the culprit, so handle them before the table
+    if lower.contains("is undefined") {
+        let explanation = match undefined_variable(&text) {
+            Some(var) => format!(
+                "The variable '{}' is not defined for this host. Define it in the \
+                 inventory, group_vars or play vars, or give it a default with \
+                 {{{{ {} | default(...) }}}}.",
+                var, var
+            ),
+            None => "A variable used by the task is not defined for this host. Define it \
+                     or give it a default with the `default` filter."
+                .to_string(),
+        };
+        return Some(canned(&explanation, None));
+    }
+
+    SIGNATURES
+        .iter()
+        .find(|sig| sig.needles.iter().any(|n| lower.contains(n)))
+        .map(|sig| {
+            let fix = sig.fix.map(|(key, value)| Fix {
+                key: key.to_string(),
+                value: serde_json::from_str(value).unwrap_or(Value::Null),
+            });
+            canned(sig.explanation, fix)
+        })
+}
+
+/// The error strings of `result` and, for loops, of each item's result.
+/// Ansible flags unreachable hosts with `unreachable: true`, which counts as
+/// an "unreachable" message.
+fn collect_messages<'a>(result: &'a Value, out: &mut Vec<&'a str>) {
+    if result.get("unreachable").and_then(Value::as_bool) == Some(true) {
+        out.push("unreachable");
+    }
+    for field in MESSAGE_FIELDS {
+        if let Some(text) = result.get(field).and_then(Value::as_str) {
+            out.push(text);
+        }
+    }
+    if let Some(items) = result.get("results").and_then(Value::as_array) {
+        for item in items {
+            collect_messages(item, out);
+        }
+    }
+}
+
+fn canned(explanation: &str, fix: Option<Fix>) -> Analysis {
+    Analysis {
+        analysis: format!("{} {}", HEURISTIC_PREFIX, explanation),
+        fix,
+        tokens_used: 0,
+    }
+}
+
+/// The name in Ansible's "'foo' is undefined" message.
+fn undefined_variable(text: &str) -> Option<&str> {
+    let end = text.find("' is undefined")?;
+    let start = text[..end].rfind('\'')? + 1;
+    let name = &text[start..end];
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+    valid.then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_signatures() {
+        let analysis = |v: Value| analyze(Some(&v)).unwrap();
+
+        let denied = analysis(json!({"msg": "Destination /etc not writable: Permission denied"}));
+        assert!(denied.analysis.starts_with(HEURISTIC_PREFIX));
+        let fix = denied.fix.unwrap();
+        assert_eq!(
+            (fix.key.as_str(), fix.value),
+            ("ansible_become", json!(true))
+        );
+
+        let package = analysis(json!({"msg": "No package matching 'ngnix' is available"}));
+        assert!(package.analysis.contains("package manager"));
+        assert!(package.fix.is_none());
+
+        let timeout = analysis(json!({"msg": "Failed to connect: Connection timed out"}));
+        assert_eq!(timeout.fix.unwrap().key, "ansible_timeout");
+
+        let module = analysis(json!({"msg": "couldn't resolve module/action 'community.foo.bar'"}));
+        assert!(module.analysis.contains("collection"));
+
+        let python =
+            analysis(json!({"module_stderr": "ModuleNotFoundError: No module named 'docker'"}));
+        assert_eq!(python.fix.unwrap().key, "ansible_python_interpreter");
+    }
+
+    #[test]
+    fn test_undefined_variable_is_named() {
+        let result = json!({"msg": "The task includes an option with an undefined variable. \
+            The error was: 'app_port' is undefined"});
+        let analysis = analyze(Some(&result)).unwrap();
+        assert!(analysis.analysis.contains("'app_port' is not defined"));
+        assert!(analysis.analysis.contains("{{ app_port | default(...) }}"));
+        assert!(analysis.fix.is_none());
+    }
+
+    #[test]
+    fn test_only_error_messages_are_matched() {
+        // A `timeout` module argument is not a connection timeout
+        let result = json!({
+            "msg": "Status code was 404 and not [200]: HTTP Error 404: Not Found",
+            "invocation": {"module_args": {"url": "http://example.com", "timeout": 30}},
+            "status": 404
+        });
+        assert!(analyze(Some(&result)).is_none());
+
+        let unreachable = json!({"unreachable": true, "msg": "Data could not be sent"});
+        assert_eq!(
+            analyze(Some(&unreachable)).unwrap().fix.unwrap().key,
+            "ansible_timeout"
+        );
+
+        // Loop items are searched through their own messages
+        let looped = json!({
+            "msg": "One or more items failed",
+            "results": [{"msg": "ok"}, {"stderr": "mkdir: Permission denied"}]
+        });
+        assert_eq!(
+            analyze(Some(&looped)).unwrap().fix.unwrap().key,
+            "ansible_become"
+        );
+    }
+
+    #[test]
+    fn test_unknown_failure() {
+        assert!(analyze(Some(&json!({"msg": "Something odd happened"}))).is_none());
+        assert!(analyze(None).is_none());
+    }
+}
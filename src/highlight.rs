@@ -5,33 +5,58 @@ use syntect::highlighting::ThemeSet;
 use syntect::parsing::SyntaxSet;
 use syntect::util::LinesWithEndings;
 
+/// Theme used when `highlight_theme` is unset or unknown.
+pub const DEFAULT_THEME: &str = "base16-ocean.dark";
+
 #[derive(Debug)]
 pub struct SyntaxHighlighter {
     ps: SyntaxSet,
     ts: ThemeSet,
+    theme: String,
 }
 
 impl Default for SyntaxHighlighter {
     fn default() -> Self {
-        Self::new()
+        Self::new(DEFAULT_THEME)
     }
 }
 
 impl SyntaxHighlighter {
-    pub fn new() -> Self {
+    /// Highlight with the named syntect theme, falling back to
+    /// `DEFAULT_THEME` (with a warning) if there is no such theme.
+    pub fn new(theme: &str) -> Self {
+        let ts = ThemeSet::load_defaults();
+        let theme = if ts.themes.contains_key(theme) {
+            theme.to_string()
+        } else {
+            tracing::warn!(theme, "Unknown highlight theme, using {}", DEFAULT_THEME);
+            DEFAULT_THEME.to_string()
+        };
         Self {
             ps: SyntaxSet::load_defaults_newlines(),
-            ts: ThemeSet::load_defaults(),
+            ts,
+            theme,
         }
     }
 
+    /// Name of the theme in use.
+    pub fn theme(&self) -> &str {
+        &self.theme
+    }
+
+    /// Names accepted by `new`, sorted.
+    pub fn theme_names(&self) -> Vec<&str> {
+        // ThemeSet keeps its themes in a BTreeMap
+        self.ts.themes.keys().map(String::as_str).collect()
+    }
+
     pub fn highlight<'a>(&self, code: &'a str, extension: &str) -> Text<'a> {
         let syntax = self
             .ps
             .find_syntax_by_extension(extension)
             .unwrap_or_else(|| self.ps.find_syntax_plain_text());
 
-        let mut h = HighlightLines::new(syntax, &self.ts.themes["base16-ocean.dark"]);
+        let mut h = HighlightLines::new(syntax, &self.ts.themes[&self.theme]);
         let mut lines = Vec::new();
 
         for line in LinesWithEndings::from(code) {
@@ -56,3 +81,19 @@ impl SyntaxHighlighter {
 // For now, initializing it every frame is expensive.
 // We should put it in App state or minimal lazy_static.
 // Putting in App is better.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_selection() {
+        let light = SyntaxHighlighter::new("InspiredGitHub");
+        assert_eq!(light.theme(), "InspiredGitHub");
+        assert!(light.theme_names().contains(&DEFAULT_THEME));
+        assert!(!light.highlight("{\"a\": 1}", "json").lines.is_empty());
+
+        let unknown = SyntaxHighlighter::new("no-such-theme");
+        assert_eq!(unknown.theme(), DEFAULT_THEME);
+    }
+}
@@ -40,6 +40,21 @@ impl App {
                 return Action::None;
             }
 
+            // A count prefix only survives digits and the keys that use it
+            if !matches!(
+                key.code,
+                KeyCode::Char('0'..='9' | 'j' | 'k' | 'y') | KeyCode::Up | KeyCode::Down
+            ) {
+                self.pending_count = None;
+            }
+
+            if self.startup_error.is_some() {
+                return match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => Action::Quit,
+                    _ => Action::None,
+                };
+            }
+
             if key.code == KeyCode::Char('?') && !self.chat_active {
                 self.show_help = !self.show_help;
                 return Action::None;
@@ -49,6 +64,35 @@ impl App {
                 return self.handle_chat_key(key);
             }
 
+            if self.note_input.is_some() {
+                return self.handle_note_key(key);
+            }
+
+            if self.value_viewer.is_some() {
+                return self.handle_value_viewer_key(key);
+            }
+
+            if self.show_warnings {
+                if matches!(
+                    key.code,
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('W')
+                ) {
+                    self.show_warnings = false;
+                }
+                return Action::None;
+            }
+
+            if self.pending_var_change.is_some() {
+                return match key.code {
+                    KeyCode::Char('y') | KeyCode::Enter => Action::ConfirmVarChange,
+                    _ => {
+                        self.pending_var_change = None;
+                        self.notify("Kept previous value".to_string());
+                        Action::None
+                    }
+                };
+            }
+
             if let action @ Action::EditVar = self.handle_var_selection_key(key) {
                 return action;
             }
@@ -66,19 +110,23 @@ impl App {
 
             // Tab cycling between views
             if key.code == KeyCode::Tab {
-                self.active_view = match self.active_view {
+                self.set_view(match self.active_view {
                     ActiveView::Dashboard => ActiveView::Analysis,
                     ActiveView::Analysis => ActiveView::Metrics,
                     ActiveView::Metrics => ActiveView::Dashboard,
-                };
+                });
                 return Action::None;
             }
             if key.code == KeyCode::BackTab {
-                self.active_view = match self.active_view {
+                self.set_view(match self.active_view {
                     ActiveView::Dashboard => ActiveView::Metrics,
                     ActiveView::Analysis => ActiveView::Dashboard,
                     ActiveView::Metrics => ActiveView::Analysis,
-                };
+                });
+                return Action::None;
+            }
+            if matches!(key.code, KeyCode::Char('`') | KeyCode::Backspace) {
+                self.toggle_last_view();
                 return Action::None;
             }
 
@@ -86,6 +134,21 @@ impl App {
                 return self.handle_analysis_key(key);
             }
 
+            if self.timeline.is_some() {
+                match key.code {
+                    KeyCode::Char(' ') => {
+                        self.toggle_timeline_pause();
+                        return Action::None;
+                    }
+                    // `n` still jumps between matches while a search is active
+                    KeyCode::Char('n') if self.search_query.is_empty() => {
+                        self.step_timeline();
+                        return Action::None;
+                    }
+                    _ => {}
+                }
+            }
+
             // Global keys
             match key.code {
                 KeyCode::Char('q') | KeyCode::Esc => {
@@ -110,6 +173,10 @@ impl App {
                     };
                     return Action::None;
                 }
+                KeyCode::Char('a') if self.ai_disabled => {
+                    self.notify("AI is disabled (--no-ai)".to_string());
+                    return Action::None;
+                }
                 KeyCode::Char('a') => return Action::AskAi,
                 KeyCode::Char('f') => return Action::ApplyFix,
                 KeyCode::Char('F') => return Action::ToggleFollow,
@@ -128,6 +195,7 @@ impl App {
                 KeyCode::Char('/') => {
                     self.search_active = true;
                     self.search_query.clear();
+                    self.inspector_match = None;
                     return Action::Search;
                 }
                 KeyCode::Char('n') => {
@@ -138,10 +206,38 @@ impl App {
                     self.find_prev_match();
                     return Action::PrevMatch;
                 }
+                KeyCode::Char('C') if self.ai_disabled => {
+                    self.notify("AI is disabled (--no-ai)".to_string());
+                    return Action::None;
+                }
                 KeyCode::Char('C') => {
                     self.chat_active = !self.chat_active;
                     return Action::None;
                 }
+                KeyCode::Char('R') => return Action::RerunHint,
+                KeyCode::Char('W') => {
+                    self.show_warnings = true;
+                    return Action::None;
+                }
+                KeyCode::Char('Y') => return Action::CopyFixSnippet,
+                KeyCode::Char('A') if self.ai_disabled => {
+                    self.notify("AI is disabled (--no-ai)".to_string());
+                    return Action::None;
+                }
+                KeyCode::Char('A') => return Action::ChatAboutFailure,
+                KeyCode::Char('u') => return Action::UndoVarChange,
+                KeyCode::Char('S') => {
+                    self.step_mode = !self.step_mode;
+                    self.notify(format!(
+                        "Step mode {}",
+                        if self.step_mode { "ON" } else { "OFF" }
+                    ));
+                    return Action::None;
+                }
+                KeyCode::Char('M') => {
+                    self.note_input = Some(self.session_note.clone().unwrap_or_default());
+                    return Action::None;
+                }
                 _ => {}
             }
 
@@ -152,19 +248,36 @@ impl App {
             match key.code {
                 KeyCode::Char('y') => return Action::Yank,
                 KeyCode::Char('v') => {
-                    self.active_view = if self.active_view == ActiveView::Analysis {
+                    self.set_view(if self.active_view == ActiveView::Analysis {
                         ActiveView::Dashboard
                     } else {
                         ActiveView::Analysis
-                    };
+                    });
                     return Action::None;
                 }
                 KeyCode::Char('m') => {
-                    self.active_view = if self.active_view == ActiveView::Metrics {
+                    self.set_view(if self.active_view == ActiveView::Metrics {
                         ActiveView::Dashboard
                     } else {
                         ActiveView::Metrics
-                    };
+                    });
+                    return Action::None;
+                }
+                KeyCode::Char('t') if self.active_view == ActiveView::Metrics => {
+                    return Action::ToggleMetricsView;
+                }
+                KeyCode::Char('j') | KeyCode::Down
+                    if self.active_view == ActiveView::Metrics
+                        && self.metrics_view == crate::app::MetricsView::Hosts =>
+                {
+                    self.scroll_host_progress(1);
+                    return Action::None;
+                }
+                KeyCode::Char('k') | KeyCode::Up
+                    if self.active_view == ActiveView::Metrics
+                        && self.metrics_view == crate::app::MetricsView::Hosts =>
+                {
+                    self.scroll_host_progress(-1);
                     return Action::None;
                 }
                 _ => {}
@@ -178,6 +291,19 @@ impl App {
                         self.scroll_offset = self.scroll_offset.saturating_add(3);
                     }
                 }
+                crossterm::event::MouseEventKind::Down(crossterm::event::MouseButton::Left)
+                    if self.active_view == ActiveView::Analysis =>
+                {
+                    if let Some(idx) = self.history_index_at(mouse.column, mouse.row) {
+                        self.analysis_focus = AnalysisFocus::TaskList;
+                        return Action::AnalysisSelect(idx);
+                    }
+                    if let Some(tree) = &mut self.analysis_tree
+                        && tree.click(mouse.column, mouse.row)
+                    {
+                        self.analysis_focus = AnalysisFocus::DataBrowser;
+                    }
+                }
                 crossterm::event::MouseEventKind::ScrollUp => {
                     if self.chat_active {
                         self.chat_auto_scroll = false;
@@ -192,6 +318,16 @@ impl App {
         Action::None
     }
 
+    fn jump_to_failed(&mut self, forward: bool) -> Action {
+        match self.find_failed_task(forward) {
+            Some(idx) => Action::AnalysisSelect(idx),
+            None => {
+                self.notify("No failed tasks".to_string());
+                Action::None
+            }
+        }
+    }
+
     fn handle_chat_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
         match self.chat_mode {
             ChatMode::Insert => self.handle_chat_insert_key(key),
@@ -206,10 +342,8 @@ impl App {
                 self.chat_mode = ChatMode::Normal;
                 self.chat_selected_index = Some(self.chat_history.len().saturating_sub(1));
             }
-            KeyCode::Enter => {
-                if !self.chat_input.trim().is_empty() {
-                    return Action::SubmitChat;
-                }
+            KeyCode::Enter if !self.chat_input.trim().is_empty() => {
+                return Action::SubmitChat;
             }
             KeyCode::Char(c) => self.chat_input.push(c),
             KeyCode::Backspace => {
@@ -248,26 +382,22 @@ impl App {
                 self.chat_mode = ChatMode::Search;
                 self.chat_search_query.clear();
             }
-            KeyCode::Char('j') | KeyCode::Down => {
-                if !self.chat_history.is_empty() {
-                    let idx = self.chat_selected_index.unwrap_or(0);
-                    if idx < self.chat_history.len() - 1 {
-                        self.chat_selected_index = Some(idx + 1);
-                        if (idx + 1) as u16 >= self.chat_scroll + 10 {
-                            self.chat_scroll = self.chat_scroll.saturating_add(1);
-                        }
+            KeyCode::Char('j') | KeyCode::Down if !self.chat_history.is_empty() => {
+                let idx = self.chat_selected_index.unwrap_or(0);
+                if idx < self.chat_history.len() - 1 {
+                    self.chat_selected_index = Some(idx + 1);
+                    if (idx + 1) as u16 >= self.chat_scroll + 10 {
+                        self.chat_scroll = self.chat_scroll.saturating_add(1);
                     }
                 }
             }
-            KeyCode::Char('k') | KeyCode::Up => {
-                if !self.chat_history.is_empty() {
-                    self.chat_auto_scroll = false;
-                    let idx = self.chat_selected_index.unwrap_or(0);
-                    if idx > 0 {
-                        self.chat_selected_index = Some(idx - 1);
-                        if (idx - 1) as u16 + 2 < self.chat_scroll {
-                            self.chat_scroll = self.chat_scroll.saturating_sub(1);
-                        }
+            KeyCode::Char('k') | KeyCode::Up if !self.chat_history.is_empty() => {
+                self.chat_auto_scroll = false;
+                let idx = self.chat_selected_index.unwrap_or(0);
+                if idx > 0 {
+                    self.chat_selected_index = Some(idx - 1);
+                    if (idx - 1) as u16 + 2 < self.chat_scroll {
+                        self.chat_scroll = self.chat_scroll.saturating_sub(1);
                     }
                 }
             }
@@ -417,6 +547,19 @@ impl App {
             KeyCode::Esc => {
                 self.search_active = false;
                 self.search_query.clear();
+                self.breakpoint_expr_task = None;
+            }
+            KeyCode::Enter if self.breakpoint_expr_task.is_some() => {
+                self.search_active = false;
+                let task = self.breakpoint_expr_task.take().unwrap_or_default();
+                let expr = std::mem::take(&mut self.search_query);
+                match self.set_conditional_breakpoint(task.clone(), &expr) {
+                    Ok(()) if expr.trim().is_empty() => {
+                        self.notify(format!("Conditional breakpoint removed: {}", task))
+                    }
+                    Ok(()) => self.notify(format!("Breakpoint set: {} when {}", task, expr.trim())),
+                    Err(e) => self.notify(format!("Invalid condition: {}", e)),
+                }
             }
             KeyCode::Enter => {
                 self.search_active = false;
@@ -428,6 +571,10 @@ impl App {
                     }
                     return Action::None;
                 }
+                if let Some(err) = crate::search::SearchPattern::new(&query).error() {
+                    let err = err.lines().last().unwrap_or(err).trim().to_string();
+                    self.notify(format!("Invalid regex ({}); matching as plain text", err));
+                }
                 if self.active_view == ActiveView::Analysis
                     && let Some(tree) = &mut self.analysis_tree
                 {
@@ -444,48 +591,115 @@ impl App {
         Action::None
     }
 
+    fn handle_note_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
+        let Some(buffer) = &mut self.note_input else {
+            return Action::None;
+        };
+        match key.code {
+            KeyCode::Esc => self.note_input = None,
+            KeyCode::Enter => {
+                let note = buffer.trim().to_string();
+                self.session_note = (!note.is_empty()).then_some(note);
+                self.note_input = None;
+            }
+            KeyCode::Char(c) => buffer.push(c),
+            KeyCode::Backspace => {
+                buffer.pop();
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn handle_value_viewer_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
+        let Some(viewer) = &mut self.value_viewer else {
+            return Action::None;
+        };
+        let page = viewer.height.saturating_sub(1).max(1);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('i') => self.value_viewer = None,
+            KeyCode::Down | KeyCode::Char('j') => viewer.scroll_down(1),
+            KeyCode::Up | KeyCode::Char('k') => viewer.scroll_up(1),
+            KeyCode::PageDown => viewer.scroll_down(page),
+            KeyCode::PageUp => viewer.scroll_up(page),
+            KeyCode::Home | KeyCode::Char('g') => viewer.scroll = 0,
+            KeyCode::Right | KeyCode::Char('l') if !viewer.wrap => viewer.h_scroll += 8,
+            KeyCode::Left | KeyCode::Char('h') => {
+                viewer.h_scroll = viewer.h_scroll.saturating_sub(8)
+            }
+            KeyCode::Char('w') => viewer.toggle_wrap(),
+            KeyCode::Char('x') => viewer.toggle_hex(),
+            _ => {}
+        }
+        Action::None
+    }
+
     fn handle_host_list_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
         let host_count = self.hosts.len();
-        let mut sorted_hosts: Vec<String> = self.hosts.keys().cloned().collect();
-        sorted_hosts.sort();
+        let sorted_hosts: Vec<String> = self
+            .host_list_order()
+            .into_iter()
+            .map(|h| h.name.clone())
+            .collect();
+        let index = self.host_list_index();
+        let selected = sorted_hosts.get(index);
 
         match key.code {
             KeyCode::Esc => self.show_host_list = false,
-            KeyCode::Down | KeyCode::Char('j') => {
-                if host_count > 0 {
-                    self.host_list_index = (self.host_list_index + 1) % host_count;
-                }
+            KeyCode::Down | KeyCode::Char('j') if host_count > 0 => {
+                self.host_list_selected = sorted_hosts.get((index + 1) % host_count).cloned();
             }
-            KeyCode::Up | KeyCode::Char('k') => {
-                if host_count > 0 {
-                    self.host_list_index = if self.host_list_index == 0 {
-                        host_count - 1
-                    } else {
-                        self.host_list_index - 1
-                    };
-                }
+            KeyCode::Up | KeyCode::Char('k') if host_count > 0 => {
+                let prev = if index == 0 {
+                    host_count - 1
+                } else {
+                    index - 1
+                };
+                self.host_list_selected = sorted_hosts.get(prev).cloned();
             }
             KeyCode::Enter => {
-                if host_count > 0
-                    && let Some(host) = sorted_hosts.get(self.host_list_index)
-                {
+                if let Some(host) = selected {
                     self.host_filter = Some(host.clone());
                     self.show_host_list = false;
+                    // Don't leave the selection on a task the filter hides
+                    let order = self.task_display_order();
+                    if !order.contains(&self.analysis_index)
+                        && let Some(&first) = order.first()
+                    {
+                        return Action::AnalysisSelect(first);
+                    }
                 }
             }
             KeyCode::Char('x') => {
                 self.host_filter = None;
                 self.show_host_list = false;
             }
+            KeyCode::Char('s') => {
+                // Pin the highlighted host so it stays selected in the new order
+                self.host_list_selected = selected.cloned();
+                self.host_sort = self.host_sort.next();
+            }
+            KeyCode::Char('m') => {
+                if let Some(host) = selected {
+                    self.host_filter = Some(host.clone());
+                    self.set_view(ActiveView::Metrics);
+                    self.show_host_list = false;
+                }
+            }
+            KeyCode::Char('F') => {
+                if let Some(host) = selected {
+                    self.toggle_follow_host(host);
+                    self.show_host_list = false;
+                }
+            }
             KeyCode::Char('f') => {
-                if host_count > 0
-                    && let Some(host) = sorted_hosts.get(self.host_list_index)
-                    && let Some(facts) = self.host_facts.get(host)
+                if let Some(host) = selected
+                    && let Some(facts) = self.host_facts.get(host).cloned()
                 {
-                    self.active_view = ActiveView::Analysis;
+                    self.set_view(ActiveView::Analysis);
                     self.analysis_focus = AnalysisFocus::DataBrowser;
                     self.analysis_tree =
-                        Some(crate::widgets::json_tree::JsonTreeState::new(facts.clone()));
+                        Some(crate::widgets::json_tree::JsonTreeState::new(facts).editable());
                     self.show_host_list = false;
                 }
             }
@@ -521,11 +735,7 @@ impl App {
 
         match key.code {
             KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('v') => {
-                if self.show_detail_view {
-                    self.show_detail_view = false;
-                    return Action::None;
-                }
-                self.active_view = ActiveView::Dashboard;
+                self.set_view(ActiveView::Dashboard);
                 self.analysis_focus = AnalysisFocus::TaskList;
                 return Action::None;
             }
@@ -561,6 +771,33 @@ impl App {
                     KeyCode::Up | KeyCode::Char('k') => return Action::AnalysisPrev,
                     KeyCode::Down | KeyCode::Char('j') => return Action::AnalysisNext,
                     KeyCode::Char('b') => return Action::ToggleBreakpoint,
+                    KeyCode::Char('F') => return self.jump_to_failed(true),
+                    KeyCode::Char('f')
+                        if key
+                            .modifiers
+                            .contains(crossterm::event::KeyModifiers::CONTROL) =>
+                    {
+                        return self.jump_to_failed(false);
+                    }
+                    KeyCode::Char('B') => {
+                        if let Some(task) = self.history.get(self.analysis_index) {
+                            let task = task.name.clone();
+                            self.search_query = self
+                                .conditional_breakpoints
+                                .iter()
+                                .find(|b| b.task == task)
+                                .map(|b| b.expr.clone())
+                                .unwrap_or_default();
+                            self.breakpoint_expr_task = Some(task);
+                            self.search_active = true;
+                        }
+                        return Action::None;
+                    }
+                    KeyCode::Char('s') => {
+                        self.task_sort = self.task_sort.next();
+                        self.notify(format!("Sort: {}", self.task_sort.label()));
+                        return Action::None;
+                    }
                     _ => {}
                 },
                 AnalysisFocus::DataBrowser => {
@@ -569,7 +806,7 @@ impl App {
                             KeyCode::Char(c @ '0'..='9') => {
                                 let digit = c.to_digit(10).unwrap() as usize;
                                 self.pending_count =
-                                    Some(self.pending_count.unwrap_or(0) * 10 + digit);
+                                    Some(push_count_digit(self.pending_count, digit));
                                 return Action::None;
                             }
                             KeyCode::Up | KeyCode::Char('k') => {
@@ -601,6 +838,37 @@ impl App {
                                 tree.toggle_collapse();
                                 return Action::None;
                             }
+                            KeyCode::Char('z') => {
+                                tree.collapse_siblings();
+                                return Action::None;
+                            }
+                            KeyCode::Char('Y') => return Action::YankSubtree,
+                            KeyCode::Char('o') => {
+                                tree.toggle_flat_view();
+                                return Action::None;
+                            }
+                            KeyCode::Char('e') => {
+                                return match self.prepare_tree_edit() {
+                                    Ok(()) => Action::EditVar,
+                                    Err(e) => {
+                                        self.notify(e);
+                                        Action::None
+                                    }
+                                };
+                            }
+                            KeyCode::Char('i') => {
+                                match tree.selected_value() {
+                                    Some(value) => {
+                                        self.value_viewer =
+                                            Some(crate::widgets::value_viewer::ValueViewer::new(
+                                                tree.get_selected_path().unwrap_or_default(),
+                                                value,
+                                            ));
+                                    }
+                                    None => self.notify("No value on this line".to_string()),
+                                }
+                                return Action::None;
+                            }
                             KeyCode::Left | KeyCode::Char('h') => {
                                 if key
                                     .modifiers
@@ -643,7 +911,25 @@ impl App {
     }
 
     fn handle_dashboard_key(&mut self, key: crossterm::event::KeyEvent) -> Action {
+        if let KeyCode::Char(c @ '0'..='9') = key.code {
+            let digit = c.to_digit(10).unwrap() as usize;
+            self.pending_count = Some(push_count_digit(self.pending_count, digit));
+            return Action::None;
+        }
+        // Any other key ends a count prefix; only Up/Down use it
+        let count = u16::try_from(self.pending_count.take().unwrap_or(1)).unwrap_or(u16::MAX);
         match key.code {
+            KeyCode::Char('p') => {
+                self.toggle_inspector_pin();
+            }
+            KeyCode::Char('t') => {
+                self.inspector_format = self.inspector_format.next();
+                self.scroll_offset = 0;
+                self.notify(format!(
+                    "Inspector format: {}",
+                    self.inspector_format.label()
+                ));
+            }
             KeyCode::Right => {
                 self.dashboard_focus = DashboardFocus::Inspector;
             }
@@ -653,18 +939,18 @@ impl App {
             KeyCode::Up => match self.dashboard_focus {
                 DashboardFocus::Logs => {
                     self.auto_scroll = false;
-                    self.log_scroll = self.log_scroll.saturating_sub(1);
+                    self.log_scroll = self.log_scroll.saturating_sub(count);
                 }
                 DashboardFocus::Inspector => {
-                    self.scroll_offset = self.scroll_offset.saturating_sub(1);
+                    self.scroll_offset = self.scroll_offset.saturating_sub(count);
                 }
             },
             KeyCode::Down => match self.dashboard_focus {
                 DashboardFocus::Logs => {
-                    self.log_scroll = self.log_scroll.saturating_add(1);
+                    self.log_scroll = self.log_scroll.saturating_add(count);
                 }
                 DashboardFocus::Inspector => {
-                    self.scroll_offset = self.scroll_offset.saturating_add(1);
+                    self.scroll_offset = self.scroll_offset.saturating_add(count);
                 }
             },
             KeyCode::PageUp => match self.dashboard_focus {
@@ -693,22 +979,22 @@ impl App {
         if self.search_query.is_empty() || self.active_view != ActiveView::Dashboard {
             return;
         }
+        let pattern = crate::search::SearchPattern::new(&self.search_query);
         match self.dashboard_focus {
             DashboardFocus::Logs => {
                 let start = self.search_index.unwrap_or(0);
-                let q = self.search_query.to_lowercase();
                 let found = self
                     .logs
                     .iter()
                     .enumerate()
                     .skip(start + 1)
-                    .find(|(_, (msg, _))| msg.to_lowercase().contains(&q))
+                    .find(|(_, (msg, _))| pattern.is_match(msg))
                     .or_else(|| {
                         self.logs
                             .iter()
                             .enumerate()
                             .take(start + 1)
-                            .find(|(_, (msg, _))| msg.to_lowercase().contains(&q))
+                            .find(|(_, (msg, _))| pattern.is_match(msg))
                     });
                 if let Some((i, _)) = found {
                     self.search_index = Some(i);
@@ -717,18 +1003,8 @@ impl App {
                 }
             }
             DashboardFocus::Inspector => {
-                let content = self
-                    .failed_result
-                    .as_ref()
-                    .and_then(|r| serde_json::to_string_pretty(r).ok())
-                    .unwrap_or_default();
-                if let Some(idx) = content
-                    .to_lowercase()
-                    .find(&self.search_query.to_lowercase())
-                {
-                    self.scroll_offset =
-                        content[..idx].chars().filter(|&c| c == '\n').count() as u16;
-                }
+                let next = self.inspector_match.map_or(0, |i| i + 1);
+                self.select_inspector_match(next);
             }
         }
     }
@@ -737,22 +1013,32 @@ impl App {
         if self.search_query.is_empty() || self.active_view != ActiveView::Dashboard {
             return;
         }
+        if self.dashboard_focus == DashboardFocus::Inspector {
+            let count = self.inspector_matches().len();
+            if count > 0 {
+                let prev = self
+                    .inspector_match
+                    .map_or(count - 1, |i| (i % count + count - 1) % count);
+                self.select_inspector_match(prev);
+            }
+            return;
+        }
         if let DashboardFocus::Logs = self.dashboard_focus {
+            let pattern = crate::search::SearchPattern::new(&self.search_query);
             let start = self.search_index.unwrap_or(self.logs.len());
-            let q = self.search_query.to_lowercase();
             let found = (0..start)
                 .rev()
                 .find(|&i| {
                     self.logs
                         .get(i)
-                        .map(|(msg, _)| msg.to_lowercase().contains(&q))
+                        .map(|(msg, _)| pattern.is_match(msg))
                         .unwrap_or(false)
                 })
                 .or_else(|| {
                     (start..self.logs.len()).rev().find(|&i| {
                         self.logs
                             .get(i)
-                            .map(|(msg, _)| msg.to_lowercase().contains(&q))
+                            .map(|(msg, _)| pattern.is_match(msg))
                             .unwrap_or(false)
                     })
                 });
@@ -763,3 +1049,8 @@ impl App {
         }
     }
 }
+
+/// Append `digit` to a typed count prefix, saturating on long digit runs.
+fn push_count_digit(count: Option<usize>, digit: usize) -> usize {
+    count.unwrap_or(0).saturating_mul(10).saturating_add(digit)
+}
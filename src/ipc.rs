@@ -1,14 +1,25 @@
 use anyhow::Result;
 use opentelemetry::trace::Span;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::net::{TcpListener, TcpStream, UnixListener, UnixStream}; // [NEW]
+use tokio::sync::mpsc;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum Message {
     Handshake {
         token: Option<String>,
+        /// Label of the matched secret, filled in by the controller after
+        /// authentication (ignored when sent by the plugin).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        /// Sent by remote-control clients (e.g. the MCP `piloteer_resume`
+        /// tool) rather than the strategy plugin.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        control: bool,
     },
     TaskStart {
         name: String,
@@ -23,6 +34,9 @@ pub enum Message {
         name: String,
         result: serde_json::Value,
         facts: Option<serde_json::Value>,
+        /// Host the task failed on; older plugins don't send it
+        #[serde(default)]
+        host: Option<String>,
     },
     TaskResult {
         name: String,
@@ -30,6 +44,15 @@ pub enum Message {
         changed: bool,
         failed: bool, // Track if it eventually failed or was recovered
         verbose_result: Option<crate::execution::ExecutionDetails>,
+        /// The failure was handled by an enclosing `rescue` section
+        #[serde(default)]
+        rescued: bool,
+        /// Ansible `warnings` from the result
+        #[serde(default)]
+        warnings: Vec<String>,
+        /// Ansible `deprecations` from the result, flattened to messages
+        #[serde(default)]
+        deprecations: Vec<String>,
     },
     TaskUnreachable {
         name: String,
@@ -37,21 +60,275 @@ pub enum Message {
         error: String,
         result: serde_json::Value,
     },
+    /// The task's `when:` condition was false on `host`
+    TaskSkipped {
+        name: String,
+        host: String,
+    },
     Proceed,
     Retry,
     ModifyVar {
         key: String,
         value: serde_json::Value,
     },
+    /// Remove a variable set earlier with `ModifyVar`, e.g. when undoing a
+    /// change to a variable that wasn't defined before
+    UnsetVar {
+        key: String,
+    },
     AiAnalysis {
         task: String,
         analysis: crate::ai::Analysis,
     },
+    /// A `--reanalyze` request for `task` failed; its earlier analysis stands
+    AiAnalysisFailed {
+        task: String,
+        error: String,
+    },
     Continue,
     PlayRecap {
         stats: serde_json::Value,
     },
     ClientDisconnected, // [NEW] Phase 3: Connection Handling
+    /// Heartbeat sent by the controller; the plugin answers with `Pong`.
+    Ping,
+    Pong,
+    /// The controller is quitting: the plugin fails the play and closes its
+    /// connection, which acknowledges the abort.
+    Abort,
+    /// The controller's answer to a remote-control command: whether it was
+    /// sent on to the plugin, and why not when it wasn't.
+    ControlReply {
+        applied: bool,
+        detail: String,
+    },
+}
+
+/// Identifies one plugin connection to the controller.
+pub type ClientId = u64;
+
+/// Outgoing channels of the connected plugin clients, shared between the IPC
+/// server (which registers each connection after its handshake) and the app
+/// (which replies to whichever client it is handling).
+#[derive(Debug, Clone, Default)]
+pub struct ClientRegistry {
+    clients: Arc<Mutex<HashMap<ClientId, mpsc::Sender<Message>>>>,
+    /// Where the app answers the remote-control command it is handling
+    control_reply: Arc<Mutex<Option<mpsc::Sender<Message>>>>,
+    /// Held while a remote-control command awaits its reply, so each reply
+    /// reaches the client that sent the command
+    control_turn: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl ClientRegistry {
+    pub fn insert(&self, id: ClientId, tx: mpsc::Sender<Message>) {
+        self.clients.lock().unwrap().insert(id, tx);
+    }
+
+    pub fn remove(&self, id: ClientId) {
+        self.clients.lock().unwrap().remove(&id);
+    }
+
+    pub fn sender(&self, id: ClientId) -> Option<mpsc::Sender<Message>> {
+        self.clients.lock().unwrap().get(&id).cloned()
+    }
+
+    /// Every connected client's outgoing channel.
+    pub fn senders(&self) -> Vec<mpsc::Sender<Message>> {
+        self.clients.lock().unwrap().values().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Wait until no other remote-control command is awaiting its reply.
+    pub async fn control_turn(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.control_turn.lock().await
+    }
+
+    pub fn set_control_reply(&self, tx: Option<mpsc::Sender<Message>>) {
+        *self.control_reply.lock().unwrap() = tx;
+    }
+
+    pub fn take_control_reply(&self) -> Option<mpsc::Sender<Message>> {
+        self.control_reply.lock().unwrap().take()
+    }
+}
+
+/// Secrets accepted during the handshake, optionally labelled per client.
+///
+/// Several secrets can be active at once so a rotation does not break
+/// connections from clients still using the previous one. An empty set
+/// disables authentication.
+#[derive(Debug, Clone, Default)]
+pub struct AcceptedSecrets {
+    secrets: Vec<(String, Option<String>)>,
+}
+
+/// Outcome of checking a handshake token against [`AcceptedSecrets`].
+#[derive(Debug, PartialEq)]
+pub enum HandshakeAuth<'a> {
+    Accepted { label: Option<&'a str> },
+    Rejected,
+}
+
+/// Split a `secrets_file` line into its token and optional label. The line
+/// is only read as `label=token` when the label is a plain name and the token
+/// doesn't start with `=`, so padded base64 tokens (`abc==`) stay whole.
+pub fn parse_secret_line(line: &str) -> (String, Option<String>) {
+    if let Some((label, token)) = line.split_once('=') {
+        let (label, token) = (label.trim(), token.trim());
+        let plain_label = !label.is_empty()
+            && label
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if plain_label && !token.is_empty() && !token.starts_with('=') {
+            return (token.to_string(), Some(label.to_string()));
+        }
+    }
+    (line.to_string(), None)
+}
+
+impl AcceptedSecrets {
+    /// Collect the single `secret_token`, the labelled `secret_tokens` map and
+    /// the lines of `secrets_file` (`token` or `label=token`).
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        let mut set = Self::default();
+        if let Some(token) = &config.secret_token {
+            set.add(token.clone(), None);
+        }
+        if let Some(map) = &config.secret_tokens {
+            let mut labelled: Vec<_> = map.iter().collect();
+            labelled.sort();
+            for (label, token) in labelled {
+                set.add(token.clone(), Some(label.clone()));
+            }
+        }
+        if let Some(path) = &config.secrets_file {
+            match std::fs::read_to_string(path) {
+                Ok(content) => {
+                    for line in content.lines().map(str::trim) {
+                        if line.is_empty() || line.starts_with('#') {
+                            continue;
+                        }
+                        let (token, label) = parse_secret_line(line);
+                        set.add(token, label);
+                    }
+                }
+                Err(e) => tracing::warn!(path = %path, error = %e, "Failed to read secrets file"),
+            }
+        }
+        set
+    }
+
+    pub fn add(&mut self, token: String, label: Option<String>) {
+        self.secrets.push((token, label));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+
+    /// Secret for a plugin Piloteer launches itself: `secret_token` when
+    /// set, otherwise the first of `secret_tokens` (by label) or
+    /// `secrets_file`.
+    pub fn launch_secret(&self) -> Option<&str> {
+        self.secrets.first().map(|(token, _)| token.as_str())
+    }
+
+    /// Check a handshake token. Any configured secret authorizes; with no
+    /// secrets configured every client is accepted.
+    pub fn authorize(&self, token: Option<&str>) -> HandshakeAuth<'_> {
+        if self.secrets.is_empty() {
+            return HandshakeAuth::Accepted { label: None };
+        }
+        let Some(token) = token else {
+            return HandshakeAuth::Rejected;
+        };
+        match self.secrets.iter().find(|(t, _)| t == token) {
+            Some((_, label)) => HandshakeAuth::Accepted {
+                label: label.as_deref(),
+            },
+            None => HandshakeAuth::Rejected,
+        }
+    }
+}
+
+/// Failed handshakes per peer IP, shared by every connection task. After
+/// `max_failures` bad tokens within `window` the address is refused for
+/// `lockout`. Unix socket peers have no IP and are never limited.
+#[derive(Debug, Clone, Default)]
+pub struct AuthLimiter {
+    /// `0` disables the limiter
+    max_failures: u32,
+    window: std::time::Duration,
+    lockout: std::time::Duration,
+    state: Arc<Mutex<AuthLimiterState>>,
+}
+
+#[derive(Debug, Default)]
+struct AuthLimiterState {
+    failures: HashMap<std::net::IpAddr, Vec<std::time::Instant>>,
+    locked_until: HashMap<std::net::IpAddr, std::time::Instant>,
+}
+
+impl AuthLimiter {
+    pub fn new(
+        max_failures: u32,
+        window: std::time::Duration,
+        lockout: std::time::Duration,
+    ) -> Self {
+        Self {
+            max_failures,
+            window,
+            lockout,
+            state: Arc::default(),
+        }
+    }
+
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        Self::new(
+            config.auth_max_failures,
+            std::time::Duration::from_secs(config.auth_failure_window_secs),
+            std::time::Duration::from_secs(config.auth_lockout_secs),
+        )
+    }
+
+    /// Whether connections from `ip` are currently refused.
+    pub fn is_locked(&self, ip: std::net::IpAddr, now: std::time::Instant) -> bool {
+        let mut state = self.state.lock().unwrap();
+        match state.locked_until.get(&ip) {
+            Some(&until) if now < until => true,
+            Some(_) => {
+                state.locked_until.remove(&ip);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Count a bad token from `ip`. Returns true when this failure locks the
+    /// address out.
+    pub fn record_failure(&self, ip: std::net::IpAddr, now: std::time::Instant) -> bool {
+        if self.max_failures == 0 {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap();
+        let failures = state.failures.entry(ip).or_default();
+        failures.retain(|&t| now.saturating_duration_since(t) < self.window);
+        failures.push(now);
+        if failures.len() < self.max_failures as usize {
+            return false;
+        }
+        state.failures.remove(&ip);
+        state.locked_until.insert(ip, now + self.lockout);
+        true
+    }
 }
 
 pub enum Listener {
@@ -61,13 +338,68 @@ pub enum Listener {
 
 pub struct IpcServer {
     listener: Listener,
+    /// Wraps accepted TCP connections in TLS before any framing
+    tls: Option<tokio_rustls::TlsAcceptor>,
+    /// Peers locked out here are dropped as soon as they connect
+    limiter: AuthLimiter,
+}
+
+/// Time an accepted TCP client gets to complete the TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// TLS for the TCP listener from `tls_cert_path`/`tls_key_path` (PEM). None
+/// when neither is set, or when listening on a Unix socket.
+pub fn server_tls(config: &crate::config::Config) -> Result<Option<tokio_rustls::TlsAcceptor>> {
+    use tokio_rustls::rustls::pki_types::pem::PemObject;
+    use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+    let (cert_path, key_path) = match (&config.tls_cert_path, &config.tls_key_path) {
+        (None, None) => return Ok(None),
+        (Some(cert), Some(key)) => (cert, key),
+        _ => anyhow::bail!("tls_cert_path and tls_key_path must be set together"),
+    };
+    if config.bind_addr.is_none() {
+        return Ok(None);
+    }
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| anyhow::anyhow!("Cannot read TLS certificate {}: {}", cert_path, e))?;
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|e| anyhow::anyhow!("Cannot read TLS key {}: {}", key_path, e))?;
+    let config = tokio_rustls::rustls::ServerConfig::builder_with_provider(Arc::new(
+        tokio_rustls::rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)?;
+    Ok(Some(tokio_rustls::TlsAcceptor::from(Arc::new(config))))
+}
+
+/// TLS client trusting the CA certificates in the PEM file `ca`.
+pub fn client_tls(ca: &str) -> Result<tokio_rustls::TlsConnector> {
+    use tokio_rustls::rustls::pki_types::CertificateDer;
+    use tokio_rustls::rustls::pki_types::pem::PemObject;
+
+    let mut roots = tokio_rustls::rustls::RootCertStore::empty();
+    for cert in CertificateDer::pem_file_iter(ca)
+        .map_err(|e| anyhow::anyhow!("Cannot read PILOTEER_TLS_CA {}: {}", ca, e))?
+    {
+        roots.add(cert.map_err(|e| anyhow::anyhow!("Invalid certificate in {}: {}", ca, e))?)?;
+    }
+    let config = tokio_rustls::rustls::ClientConfig::builder_with_provider(Arc::new(
+        tokio_rustls::rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    Ok(tokio_rustls::TlsConnector::from(Arc::new(config)))
 }
 
 impl IpcServer {
     pub async fn new<P: AsRef<Path>>(socket_path: P, bind_addr: Option<&str>) -> Result<Self> {
         let listener = if let Some(addr) = bind_addr {
             let tcp = TcpListener::bind(addr).await?;
-            println!("IpcServer listening on TCP {}", addr);
+            tracing::info!(addr, "IpcServer listening on TCP");
             Listener::Tcp(tcp)
         } else {
             if socket_path.as_ref().exists() {
@@ -77,26 +409,108 @@ impl IpcServer {
             Listener::Unix(unix)
         };
 
-        Ok(Self { listener })
+        Ok(Self {
+            listener,
+            tls: None,
+            limiter: AuthLimiter::default(),
+        })
     }
 
-    pub async fn accept(&self) -> Result<IpcConnection> {
+    pub fn is_tls(&self) -> bool {
+        self.tls.is_some() && matches!(self.listener, Listener::Tcp(_))
+    }
+
+    /// Refuse TCP connections from addresses `limiter` has locked out.
+    pub fn with_auth_limiter(mut self, limiter: AuthLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Serve TCP clients over TLS. Has no effect on a Unix socket listener.
+    pub fn with_tls(mut self, tls: Option<tokio_rustls::TlsAcceptor>) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Take the next connection off the listener. Any TLS handshake is left
+    /// to [`AcceptedStream::establish`], so a slow client can't hold up the
+    /// accept loop.
+    pub async fn accept(&self) -> Result<AcceptedStream> {
         match &self.listener {
             Listener::Unix(l) => {
                 let (stream, _) = l.accept().await?;
-                Ok(IpcConnection::new(ConnectionStream::Unix(stream)))
+                Ok(AcceptedStream {
+                    stream: ConnectionStream::Unix(stream),
+                    peer: None,
+                    tls: None,
+                })
             }
             Listener::Tcp(l) => {
-                let (stream, _) = l.accept().await?;
-                Ok(IpcConnection::new(ConnectionStream::Tcp(stream)))
+                let (stream, peer) = loop {
+                    let (stream, peer) = l.accept().await?;
+                    if !self.limiter.is_locked(peer.ip(), std::time::Instant::now()) {
+                        break (stream, peer);
+                    }
+                    tracing::debug!(%peer, "Refused connection from locked-out address");
+                };
+                Ok(AcceptedStream {
+                    stream: ConnectionStream::Tcp(stream),
+                    peer: Some(peer),
+                    tls: self.tls.clone(),
+                })
             }
         }
     }
 }
 
+/// A connection taken off the listener, before its TLS handshake.
+pub struct AcceptedStream {
+    stream: ConnectionStream,
+    peer: Option<std::net::SocketAddr>,
+    tls: Option<tokio_rustls::TlsAcceptor>,
+}
+
+impl AcceptedStream {
+    /// Complete the TLS handshake, if the listener serves TLS, within
+    /// `TLS_HANDSHAKE_TIMEOUT`.
+    pub async fn establish(self) -> Result<IpcConnection> {
+        let stream = match (self.tls, self.stream) {
+            (Some(tls), ConnectionStream::Tcp(tcp)) => {
+                let peer = self
+                    .peer
+                    .map_or_else(|| "client".to_string(), |p| p.to_string());
+                let stream = tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, tls.accept(tcp))
+                    .await
+                    .map_err(|_| anyhow::anyhow!("TLS handshake with {} timed out", peer))?
+                    .map_err(|e| anyhow::anyhow!("TLS handshake with {} failed: {}", peer, e))?;
+                ConnectionStream::TlsServer(Box::new(stream))
+            }
+            (_, stream) => stream,
+        };
+        let mut conn = IpcConnection::new(stream);
+        conn.peer = self.peer;
+        Ok(conn)
+    }
+}
+
+/// The address a playbook on this machine should dial to reach a listener
+/// bound to `bind_addr`: a wildcard host (`0.0.0.0`, `::`) becomes
+/// `localhost`, which is also the name a local TLS certificate is valid for.
+pub fn local_connect_addr(bind_addr: &str) -> String {
+    let Some((host, port)) = bind_addr.rsplit_once(':') else {
+        return bind_addr.to_string();
+    };
+    match host.trim_start_matches('[').trim_end_matches(']') {
+        "0.0.0.0" | "::" | "" => format!("localhost:{}", port),
+        _ => bind_addr.to_string(),
+    }
+}
+
 pub enum ConnectionStream {
     Unix(UnixStream),
     Tcp(TcpStream),
+    TlsServer(Box<tokio_rustls::server::TlsStream<TcpStream>>),
+    TlsClient(Box<tokio_rustls::client::TlsStream<TcpStream>>),
 }
 
 impl AsyncRead for ConnectionStream {
@@ -108,6 +522,8 @@ impl AsyncRead for ConnectionStream {
         match self.get_mut() {
             ConnectionStream::Unix(s) => std::pin::Pin::new(s).poll_read(cx, buf),
             ConnectionStream::Tcp(s) => std::pin::Pin::new(s).poll_read(cx, buf),
+            ConnectionStream::TlsServer(s) => std::pin::Pin::new(s).poll_read(cx, buf),
+            ConnectionStream::TlsClient(s) => std::pin::Pin::new(s).poll_read(cx, buf),
         }
     }
 }
@@ -121,6 +537,8 @@ impl AsyncWrite for ConnectionStream {
         match self.get_mut() {
             ConnectionStream::Unix(s) => std::pin::Pin::new(s).poll_write(cx, buf),
             ConnectionStream::Tcp(s) => std::pin::Pin::new(s).poll_write(cx, buf),
+            ConnectionStream::TlsServer(s) => std::pin::Pin::new(s).poll_write(cx, buf),
+            ConnectionStream::TlsClient(s) => std::pin::Pin::new(s).poll_write(cx, buf),
         }
     }
 
@@ -131,6 +549,8 @@ impl AsyncWrite for ConnectionStream {
         match self.get_mut() {
             ConnectionStream::Unix(s) => std::pin::Pin::new(s).poll_flush(cx),
             ConnectionStream::Tcp(s) => std::pin::Pin::new(s).poll_flush(cx),
+            ConnectionStream::TlsServer(s) => std::pin::Pin::new(s).poll_flush(cx),
+            ConnectionStream::TlsClient(s) => std::pin::Pin::new(s).poll_flush(cx),
         }
     }
 
@@ -141,17 +561,61 @@ impl AsyncWrite for ConnectionStream {
         match self.get_mut() {
             ConnectionStream::Unix(s) => std::pin::Pin::new(s).poll_shutdown(cx),
             ConnectionStream::Tcp(s) => std::pin::Pin::new(s).poll_shutdown(cx),
+            ConnectionStream::TlsServer(s) => std::pin::Pin::new(s).poll_shutdown(cx),
+            ConnectionStream::TlsClient(s) => std::pin::Pin::new(s).poll_shutdown(cx),
         }
     }
 }
 
 pub struct IpcConnection {
-    stream: ConnectionStream,
+    /// Kept for the life of the connection so bytes read past one message
+    /// stay buffered for the next
+    stream: BufReader<ConnectionStream>,
+    /// The line being received; a `receive` cancelled mid-line (e.g. by a
+    /// `select!` branch) resumes from here on the next call
+    pending: Vec<u8>,
+    /// Remote address of an accepted TCP connection
+    peer: Option<std::net::SocketAddr>,
 }
 
 impl IpcConnection {
     pub fn new(stream: ConnectionStream) -> Self {
-        Self { stream }
+        Self {
+            stream: BufReader::new(stream),
+            pending: Vec::new(),
+            peer: None,
+        }
+    }
+
+    pub fn peer_ip(&self) -> Option<std::net::IpAddr> {
+        self.peer.map(|p| p.ip())
+    }
+
+    /// Connect to a running controller. Like the plugin's `PILOTEER_SOCKET`,
+    /// `host:port` means TCP and anything else is a Unix socket path. As for
+    /// the plugin, `PILOTEER_TLS=1` makes TCP use TLS, verified against the
+    /// CA certificate in `PILOTEER_TLS_CA`.
+    pub async fn connect(addr: &str) -> Result<Self> {
+        if addr.contains(':') && std::env::var("PILOTEER_TLS").as_deref() == Ok("1") {
+            let ca = std::env::var("PILOTEER_TLS_CA")
+                .map_err(|_| anyhow::anyhow!("PILOTEER_TLS=1 needs PILOTEER_TLS_CA"))?;
+            return Self::connect_tls(addr, &client_tls(&ca)?).await;
+        }
+        let stream = if addr.contains(':') {
+            ConnectionStream::Tcp(TcpStream::connect(addr).await?)
+        } else {
+            ConnectionStream::Unix(UnixStream::connect(addr).await?)
+        };
+        Ok(Self::new(stream))
+    }
+
+    /// Connect to a TLS controller at `host:port`, verifying it as `host`.
+    pub async fn connect_tls(addr: &str, tls: &tokio_rustls::TlsConnector) -> Result<Self> {
+        let host = addr.rsplit_once(':').map_or(addr, |(h, _)| h);
+        let name = tokio_rustls::rustls::pki_types::ServerName::try_from(host.to_string())?;
+        let tcp = TcpStream::connect(addr).await?;
+        let stream = tls.connect(name, tcp).await?;
+        Ok(Self::new(ConnectionStream::TlsClient(Box::new(stream))))
     }
 
     pub async fn send(&mut self, msg: &Message) -> Result<()> {
@@ -174,17 +638,19 @@ impl IpcConnection {
         result
     }
 
+    /// Read the next message. Cancel-safe: a partially read line is kept
+    /// and completed by the next call.
     pub async fn receive(&mut self) -> Result<Option<Message>> {
         let mut span =
             crate::telemetry::start_span("ipc.receive", opentelemetry::trace::SpanKind::Consumer);
 
         let result: Result<Option<Message>> = async {
-            let mut reader = BufReader::new(&mut self.stream);
-            let mut line = String::new();
-            if reader.read_line(&mut line).await? == 0 {
+            self.stream.read_until(b'\n', &mut self.pending).await?;
+            if self.pending.is_empty() {
                 return Ok(None);
             }
-            let msg = serde_json::from_str(&line)?;
+            let line = std::mem::take(&mut self.pending);
+            let msg = serde_json::from_slice(&line)?;
             Ok(Some(msg))
         }
         .await;
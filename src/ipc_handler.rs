@@ -1,73 +1,67 @@
-use crate::app::{App, ScriptActionType};
-use crate::ipc::{IpcServer, Message};
-use std::time::Duration;
+use crate::app::{App, HeadlessFormat, ScriptActionType, VarChange};
+use crate::ipc::{
+    AcceptedSecrets, AuthLimiter, ClientId, ClientRegistry, HandshakeAuth, IpcConnection,
+    IpcServer, Message,
+};
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 // ── IPC server task ──────────────────────────────────────────────────────────
 
+/// Accept plugin connections, each served by its own task so several
+/// playbooks can share one controller. Incoming messages are tagged with
+/// their client id; replies go out through the client's entry in `clients`.
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_ipc_server(
     socket_path: String,
     bind_addr: Option<String>,
-    secret_token: Option<String>,
-    to_app_tx: mpsc::Sender<Message>,
-    mut from_app_rx: mpsc::Receiver<Message>,
+    tls: Option<tokio_rustls::TlsAcceptor>,
+    secrets: AcceptedSecrets,
+    limiter: AuthLimiter,
+    heartbeat: Duration,
+    to_app_tx: mpsc::Sender<(ClientId, Message)>,
+    clients: ClientRegistry,
 ) {
     tokio::spawn(async move {
         let server = match IpcServer::new(&socket_path, bind_addr.as_deref()).await {
-            Ok(s) => s,
+            Ok(s) => s.with_tls(tls).with_auth_limiter(limiter.clone()),
             Err(e) => {
-                eprintln!("Failed to start IPC: {}", e);
+                tracing::error!(error = %e, socket_path, ?bind_addr, "Failed to start IPC server");
                 return;
             }
         };
+        if bind_addr.is_some() && !server.is_tls() {
+            tracing::warn!("TCP listener is plaintext: set tls_cert_path/tls_key_path");
+        }
+        tracing::info!(socket_path, ?bind_addr, "IPC server started");
 
+        let mut next_id: ClientId = 0;
         loop {
             match server.accept().await {
-                Ok(mut conn) => {
-                    let mut connected = true;
-                    while connected {
-                        tokio::select! {
-                            incoming = conn.receive() => match incoming {
-                                Ok(Some(msg)) => {
-                                    if let Message::Handshake { token } = &msg {
-                                        let ok = match &secret_token {
-                                            Some(expected) => token.as_deref() == Some(expected),
-                                            None => true,
-                                        };
-                                        if !ok {
-                                            eprintln!("Authentication Failed: Invalid Token");
-                                            break;
-                                        }
-                                        let _ = to_app_tx.send(Message::Handshake { token: token.clone() }).await;
-                                        continue;
-                                    }
-                                    if to_app_tx.send(msg).await.is_err() {
-                                        return;
-                                    }
-                                }
-                                Ok(None) => {
-                                    let _ = to_app_tx.send(Message::ClientDisconnected).await;
-                                    connected = false;
-                                }
-                                Err(_) => {
-                                    let _ = to_app_tx.send(Message::ClientDisconnected).await;
-                                    connected = false;
-                                }
-                            },
-                            outgoing = from_app_rx.recv() => match outgoing {
-                                Some(msg) => {
-                                    if conn.send(&msg).await.is_err() {
-                                        let _ = to_app_tx.send(Message::ClientDisconnected).await;
-                                        connected = false;
-                                    }
-                                }
-                                None => return,
-                            },
+                Ok(accepted) => {
+                    next_id += 1;
+                    let id = next_id;
+                    tracing::debug!(client = id, "Connection accepted");
+                    let (secrets, limiter, to_app_tx, clients) = (
+                        secrets.clone(),
+                        limiter.clone(),
+                        to_app_tx.clone(),
+                        clients.clone(),
+                    );
+                    tokio::spawn(async move {
+                        match accepted.establish().await {
+                            Ok(conn) => {
+                                serve_client(
+                                    conn, id, secrets, limiter, heartbeat, to_app_tx, clients,
+                                )
+                                .await
+                            }
+                            Err(e) => tracing::warn!(client = id, error = %e, "Connection dropped"),
                         }
-                    }
+                    });
                 }
                 Err(e) => {
-                    eprintln!("Accept error: {}", e);
+                    tracing::warn!(error = %e, "Accept error");
                     tokio::time::sleep(Duration::from_millis(1000)).await;
                 }
             }
@@ -75,15 +69,269 @@ pub fn spawn_ipc_server(
     });
 }
 
+/// Serve one connection: authenticate its handshake, register its outgoing
+/// channel and relay messages both ways until it disconnects. A control
+/// handshake hands the connection to [`serve_control`] instead.
+async fn serve_client(
+    mut conn: IpcConnection,
+    id: ClientId,
+    secrets: AcceptedSecrets,
+    limiter: AuthLimiter,
+    heartbeat: Duration,
+    to_app_tx: mpsc::Sender<(ClientId, Message)>,
+    clients: ClientRegistry,
+) {
+    let (tx, mut from_app_rx) = mpsc::channel::<Message>(100);
+    let mut registered = false;
+    // Ping every `heartbeat` once registered; a client silent for
+    // HEARTBEAT_MISSES intervals is treated as gone
+    let mut ping = tokio::time::interval(heartbeat.max(Duration::from_millis(1)));
+    ping.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut last_seen = Instant::now();
+    loop {
+        tokio::select! {
+            incoming = conn.receive() => match incoming {
+                Ok(Some(msg)) => {
+                    last_seen = Instant::now();
+                    if matches!(msg, Message::Pong) {
+                        continue;
+                    }
+                    if let Message::Handshake { token, control, .. } = &msg {
+                        if *control {
+                            // Nothing to control without a playbook attached
+                            if !registered && !clients.is_empty() {
+                                serve_control(
+                                    conn,
+                                    token.clone(),
+                                    secrets,
+                                    limiter,
+                                    to_app_tx,
+                                    clients,
+                                )
+                                .await;
+                            }
+                            return;
+                        }
+                        let HandshakeAuth::Accepted { label } = secrets.authorize(token.as_deref()) else {
+                            tracing::warn!(client = id, "Authentication failed: invalid token");
+                            note_auth_failure(&limiter, &conn);
+                            break;
+                        };
+                        clients.insert(id, tx.clone());
+                        registered = true;
+                        let _ = to_app_tx
+                            .send((
+                                id,
+                                Message::Handshake {
+                                    token: None,
+                                    label: label.map(str::to_string),
+                                    control: false,
+                                },
+                            ))
+                            .await;
+                        continue;
+                    }
+                    if to_app_tx.send((id, msg)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => {
+                    tracing::debug!(client = id, "Connection closed (EOF)");
+                    break;
+                }
+                Err(e) => {
+                    tracing::debug!(client = id, error = %e, "Connection error");
+                    break;
+                }
+            },
+            Some(msg) = from_app_rx.recv() => {
+                if conn.send(&msg).await.is_err() {
+                    break;
+                }
+            }
+            _ = ping.tick(), if registered && !heartbeat.is_zero() => {
+                if last_seen.elapsed() >= heartbeat * HEARTBEAT_MISSES {
+                    tracing::warn!(client = id, "No heartbeat reply; dropping connection");
+                    break;
+                }
+                if conn.send(&Message::Ping).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    if registered {
+        clients.remove(id);
+        let _ = to_app_tx.send((id, Message::ClientDisconnected)).await;
+    }
+}
+
+/// How long a remote-control command waits for the app to say whether it
+/// was applied.
+const CONTROL_REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serve a remote-control client connected alongside a plugin: after an
+/// authorized control handshake (acknowledged in kind), forward its
+/// Proceed/Retry/Continue commands to the app and answer each with the app's
+/// `ControlReply`, until it disconnects.
+async fn serve_control(
+    mut conn: IpcConnection,
+    token: Option<String>,
+    secrets: AcceptedSecrets,
+    limiter: AuthLimiter,
+    to_app_tx: mpsc::Sender<(ClientId, Message)>,
+    clients: ClientRegistry,
+) {
+    if secrets.authorize(token.as_deref()) == HandshakeAuth::Rejected {
+        tracing::warn!("Authentication failed: invalid token (control client)");
+        note_auth_failure(&limiter, &conn);
+        return;
+    }
+    let ack = Message::Handshake {
+        token: None,
+        label: None,
+        control: true,
+    };
+    if conn.send(&ack).await.is_err() {
+        return;
+    }
+    while let Ok(Some(msg)) = conn.receive().await {
+        if !matches!(msg, Message::Proceed | Message::Retry | Message::Continue) {
+            continue;
+        }
+        let _turn = clients.control_turn().await;
+        let (reply_tx, mut reply_rx) = mpsc::channel(1);
+        clients.set_control_reply(Some(reply_tx));
+        // Control clients act on whichever playbook the app is handling
+        if to_app_tx.send((CONTROL_CLIENT, msg)).await.is_err() {
+            return;
+        }
+        let reply = tokio::time::timeout(CONTROL_REPLY_TIMEOUT, reply_rx.recv())
+            .await
+            .ok()
+            .flatten();
+        clients.set_control_reply(None);
+        let reply = reply.unwrap_or_else(|| Message::ControlReply {
+            applied: false,
+            detail: "No reply from the controller".to_string(),
+        });
+        if conn.send(&reply).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Count a rejected handshake against the peer's address, logging when it
+/// gets the address locked out.
+fn note_auth_failure(limiter: &AuthLimiter, conn: &IpcConnection) {
+    if let Some(ip) = conn.peer_ip()
+        && limiter.record_failure(ip, std::time::Instant::now())
+    {
+        tracing::warn!(%ip, "Too many failed handshakes; refusing connections from this address");
+    }
+}
+
+/// Heartbeat intervals without any message from a plugin before its
+/// connection is considered dead.
+pub const HEARTBEAT_MISSES: u32 = 3;
+
+/// Ask every connected plugin to abort its run and wait up to `grace` for
+/// them to acknowledge by disconnecting. Anything else they send meanwhile
+/// is dropped. Returns whether all of them did; with no clients left it
+/// returns `true` straight away.
+pub async fn abort_clients(
+    clients: &ClientRegistry,
+    from_clients: &mut mpsc::Receiver<(ClientId, Message)>,
+    grace: Duration,
+) -> bool {
+    for tx in clients.senders() {
+        let _ = tx.send(Message::Abort).await;
+    }
+    let deadline = tokio::time::Instant::now() + grace;
+    while !clients.is_empty() {
+        match tokio::time::timeout_at(deadline, from_clients.recv()).await {
+            Ok(Some(_)) => {}
+            Ok(None) => break,
+            Err(_) => return false,
+        }
+    }
+    true
+}
+
+/// Client id used for commands from remote-control connections, which do
+/// not switch the app's active client.
+pub const CONTROL_CLIENT: ClientId = 0;
+
+/// What the first plugin connection did during `--dry-run`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HandshakeCheck {
+    Accepted {
+        label: Option<String>,
+    },
+    /// The token matched none of the accepted secrets
+    Rejected,
+    /// Connected, but sent something else or hung up before a handshake
+    NoHandshake,
+}
+
+/// Accept plugin connections on `server` until one sends its first message,
+/// and authorize it exactly as [`serve_client`] would. Remote-control
+/// connections are skipped; `--dry-run` has no playbook for them to control.
+pub async fn await_handshake(
+    server: &IpcServer,
+    secrets: &AcceptedSecrets,
+) -> anyhow::Result<HandshakeCheck> {
+    loop {
+        let mut conn = server.accept().await?.establish().await?;
+        return Ok(match conn.receive().await {
+            Ok(Some(Message::Handshake { control: true, .. })) => continue,
+            Ok(Some(Message::Handshake { token, .. })) => {
+                match secrets.authorize(token.as_deref()) {
+                    HandshakeAuth::Accepted { label } => HandshakeCheck::Accepted {
+                        label: label.map(str::to_string),
+                    },
+                    HandshakeAuth::Rejected => HandshakeCheck::Rejected,
+                }
+            }
+            _ => HandshakeCheck::NoHandshake,
+        });
+    }
+}
+
 // ── IPC message handler ──────────────────────────────────────────────────────
 
+/// Handle a message from plugin client `client` against that client's task
+/// state, then surface any other client left paused.
+pub async fn handle_client_message(
+    app: &mut App,
+    client: ClientId,
+    msg: Message,
+    headless: bool,
+    auto_analyze: bool,
+) {
+    if client != CONTROL_CLIENT {
+        app.switch_client(client);
+        app.note_ipc_activity();
+    }
+    let disconnected = matches!(msg, Message::ClientDisconnected);
+    handle_message(app, msg, headless, auto_analyze).await;
+    if disconnected {
+        app.drop_client(client);
+    }
+    app.focus_waiting_client();
+}
+
 pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_analyze: bool) {
     match msg {
-        Message::Handshake { .. } => {
+        Message::Handshake { label, .. } => {
             app.client_connected = true;
-            app.log("Connected".to_string(), Some(ratatui::style::Color::Cyan));
+            let msg = match label {
+                Some(l) => format!("Connected (client: {})", l),
+                None => "Connected".to_string(),
+            };
+            app.log(msg, Some(ratatui::style::Color::Cyan));
             if headless {
-                println!("Headless: Ansible Connected");
+                note(app, "Headless: Ansible Connected");
             }
 
             let span = crate::telemetry::create_root_span(
@@ -102,8 +350,10 @@ pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_an
                 format!("Play Started: {} (Hosts: {})", name, host_pattern),
                 Some(ratatui::style::Color::Cyan),
             );
+            abandon_task_spans(app);
             app.play_span_guard = None;
             app.play_span = None;
+            app.span_timings.start_play(&name);
 
             let span = crate::telemetry::create_child_span(
                 format!("play: {}", name),
@@ -127,27 +377,40 @@ pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_an
             );
             app.task_start_time = Some(std::time::Instant::now());
             app.set_task(name.clone(), task_vars.clone(), facts.clone());
+            for (msg, _) in app.check_var_expectations() {
+                if headless {
+                    note(app, format!("Headless: {}", msg));
+                }
+            }
+            if headless {
+                emit(app, HeadlessEvent::TaskStart { task: &name });
+            }
 
-            let task_span = crate::telemetry::create_child_span(
-                format!("task: {}", name),
-                vec![opentelemetry::KeyValue::new("task.name", name.clone())],
-            );
-            app.task_spans.insert(name.clone(), task_span);
+            app.task_spans
+                .insert(name.clone(), crate::telemetry::TaskSpan::start(&name));
 
             if let Some(idx) = app
                 .test_script
                 .iter()
                 .position(|a| a.task_name == *name && !a.on_failure)
             {
-                println!("Headless: Executing Script Action for TaskStart: {}", name);
+                note(
+                    app,
+                    format!("Headless: Executing Script Action for TaskStart: {}", name),
+                );
                 let script = app.test_script.remove(idx);
                 run_script_actions(app, script.actions).await;
             } else if headless {
-                println!("Headless: Task Captured: {}", name);
-                tokio::time::sleep(Duration::from_millis(500)).await;
-                app.waiting_for_proceed = false;
-                send_ipc(app, Message::Proceed).await;
-                println!("Headless: Auto-Proceeding...");
+                if let Some(timeout) = app.headless_timeout {
+                    // Proceeded by a remote command or by check_headless_timeout
+                    app.waiting_for_proceed = true;
+                    app.headless_wait = Some((std::time::Instant::now() + timeout, name.clone()));
+                } else {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    app.waiting_for_proceed = false;
+                    send_control(app, Message::Proceed, "Proceed (headless)").await;
+                    note(app, "Headless: Auto-Proceeding...");
+                }
             } else if app.breakpoints.contains(&name) {
                 app.waiting_for_proceed = true;
                 app.log(
@@ -155,6 +418,19 @@ pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_an
                     Some(ratatui::style::Color::Magenta),
                 );
                 app.notify(format!("Breakpoint Hit: {}", name));
+            } else if let Some(expr) = app.conditional_breakpoint_hit(&name) {
+                app.waiting_for_proceed = true;
+                app.log(
+                    format!("Conditional Breakpoint Hit: {} ({})", name, expr),
+                    Some(ratatui::style::Color::Magenta),
+                );
+                app.notify(format!("Breakpoint Hit: {} when {}", name, expr));
+            } else if app.step_mode {
+                app.waiting_for_proceed = true;
+                app.log(
+                    format!("Step: paused before {}", name),
+                    Some(ratatui::style::Color::Magenta),
+                );
             } else {
                 app.waiting_for_proceed = false;
                 send_ipc(app, Message::Proceed).await;
@@ -165,13 +441,15 @@ pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_an
             name,
             result: _,
             facts,
+            host,
         } => {
             app.log(
                 format!("Task Failed: {}", name),
                 Some(ratatui::style::Color::Red),
             );
 
-            if let Some(span) = app.task_spans.get_mut(&name) {
+            if let Some(task_span) = app.task_spans.get_mut(&name) {
+                let span = &mut task_span.span;
                 crate::telemetry::record_error_on_span(span, &format!("Task '{}' failed", name));
                 crate::telemetry::add_span_attributes(
                     span,
@@ -180,10 +458,16 @@ pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_an
             }
 
             if headless {
-                println!("Headless: Task Failed: {}", name);
+                emit(
+                    app,
+                    HeadlessEvent::TaskFail {
+                        task: &name,
+                        host: host.as_deref(),
+                    },
+                );
                 handle_headless_failure(app, &name, auto_analyze).await;
             } else {
-                app.set_failed(name, serde_json::Value::Null, facts.clone());
+                app.set_failed(name, host, serde_json::Value::Null, facts.clone());
             }
         }
 
@@ -193,29 +477,46 @@ pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_an
             changed,
             failed,
             verbose_result,
+            rescued,
+            warnings,
+            deprecations,
         } => {
-            let (status, color) = task_status(failed, changed);
+            let (status, color) = task_status(failed, changed, rescued);
             app.log(
                 format!("Task '{}' on {}: {}", name, host, status),
                 Some(color),
             );
 
-            if let Some(span) = app.task_spans.remove(&name) {
-                crate::telemetry::end_span(
-                    span,
-                    vec![
-                        opentelemetry::KeyValue::new("task.host", host.clone()),
-                        opentelemetry::KeyValue::new("task.changed", changed),
-                        opentelemetry::KeyValue::new("task.failed", failed),
-                        opentelemetry::KeyValue::new("task.status", status),
-                    ],
-                );
-            }
+            end_task_span(
+                app,
+                &name,
+                vec![
+                    opentelemetry::KeyValue::new("task.host", host.clone()),
+                    opentelemetry::KeyValue::new("task.changed", changed),
+                    opentelemetry::KeyValue::new("task.failed", failed),
+                    opentelemetry::KeyValue::new("task.status", status),
+                ],
+            );
 
             let duration = app
                 .task_start_time
                 .map(|t| t.elapsed().as_secs_f64())
                 .unwrap_or(0.0);
+            app.record_warnings(&name, &host, warnings, deprecations);
+            if headless {
+                emit(
+                    app,
+                    HeadlessEvent::TaskResult {
+                        task: &name,
+                        host: &host,
+                        status,
+                        changed,
+                        failed,
+                        rescued,
+                        duration,
+                    },
+                );
+            }
             app.record_task_result(
                 name.clone(),
                 host,
@@ -226,9 +527,8 @@ pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_an
                 verbose_result,
                 None,
             );
-
-            if headless {
-                println!("Headless: Task Result: {}", status);
+            if rescued {
+                app.mark_last_rescued();
             }
         }
 
@@ -238,19 +538,37 @@ pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_an
             error,
             result,
         } => {
+            end_task_span(
+                app,
+                &name,
+                vec![
+                    opentelemetry::KeyValue::new("task.host", host.clone()),
+                    opentelemetry::KeyValue::new("task.status", "UNREACHABLE"),
+                ],
+            );
             app.set_unreachable(name.clone(), host.clone(), error.clone(), result);
 
             if headless {
-                println!("Headless: Host {} unreachable: {}", host, error);
+                emit(
+                    app,
+                    HeadlessEvent::HostUnreachable {
+                        task: &name,
+                        host: &host,
+                        error: &error,
+                    },
+                );
 
                 if let Some(idx) = app
                     .test_script
                     .iter()
                     .position(|a| a.task_name == *name && a.on_failure)
                 {
-                    println!(
-                        "Headless: Executing Script Action for TaskUnreachable: {}",
-                        name
+                    note(
+                        app,
+                        format!(
+                            "Headless: Executing Script Action for TaskUnreachable: {}",
+                            name
+                        ),
                     );
                     let script = app.test_script.remove(idx);
                     run_script_actions(app, script.actions).await;
@@ -258,11 +576,37 @@ pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_an
             }
         }
 
+        Message::TaskSkipped { name, host } => {
+            app.log(
+                format!("Task '{}' on {}: SKIPPED", name, host),
+                Some(ratatui::style::Color::DarkGray),
+            );
+            end_task_span(
+                app,
+                &name,
+                vec![
+                    opentelemetry::KeyValue::new("task.host", host.clone()),
+                    opentelemetry::KeyValue::new("task.status", "SKIPPED"),
+                ],
+            );
+            if headless {
+                emit(
+                    app,
+                    HeadlessEvent::TaskSkipped {
+                        task: &name,
+                        host: &host,
+                    },
+                );
+            }
+            app.record_skipped(name, host);
+        }
+
         Message::PlayRecap { stats } => {
             app.log(
                 format!("Play Recap Received: {:?}", stats),
                 Some(ratatui::style::Color::Cyan),
             );
+            abandon_task_spans(app);
             app.play_span_guard = None;
             app.play_span = None;
             app.record_task_result(
@@ -282,21 +626,55 @@ pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_an
                 None,
             );
             app.playbook_span_guard = None;
+            if headless {
+                emit(app, HeadlessEvent::PlayRecap { stats: &stats });
+            }
         }
 
         Message::AiAnalysis { task, analysis } => {
             app.asking_ai = false;
+            app.collect_ai_metrics();
+            app.pilot_stream.clear();
             app.suggestion = Some(analysis.clone());
             app.log(
                 format!("AI Analysis Received for '{}'", task),
                 Some(ratatui::style::Color::Cyan),
             );
-            if let Some(item) = app.history.iter_mut().rev().find(|t| t.name == task) {
-                item.analysis = Some(analysis);
+            // In a --reanalyze replay, prefer the entry that requested the analysis
+            let requested = app.reanalysis_requested.iter().copied().find(|i| {
+                !app.fresh_analyses.contains(i)
+                    && app.history.get(*i).is_some_and(|t| t.name == task)
+            });
+            let target = requested.or_else(|| app.history.iter().rposition(|t| t.name == task));
+            if let Some(idx) = target {
+                app.history[idx].analysis = Some(analysis);
+                if app.replay_mode {
+                    app.fresh_analyses.insert(idx);
+                }
             }
             app.notify("AI Analysis Ready. Press 'v' to view.".to_string());
         }
 
+        Message::AiAnalysisFailed { task, error } => {
+            app.asking_ai = false;
+            // Keep the entry's previous analysis; revisiting it asks again
+            if let Some(idx) = app.reanalysis_requested.iter().copied().find(|i| {
+                !app.fresh_analyses.contains(i)
+                    && app.history.get(*i).is_some_and(|t| t.name == task)
+            }) {
+                app.reanalysis_requested.remove(&idx);
+            }
+            app.log(
+                format!("Re-analysis of '{}' failed: {}", task, error),
+                Some(ratatui::style::Color::Red),
+            );
+            app.notify(format!("Re-analysis failed: {}", error));
+        }
+
+        // Heartbeats stay between serve_client and the plugin; Abort only
+        // ever goes out to it
+        Message::Ping | Message::Pong | Message::Abort | Message::ControlReply { .. } => {}
+
         Message::ClientDisconnected => {
             app.client_connected = false;
             app.log(
@@ -304,24 +682,313 @@ pub async fn handle_message(app: &mut App, msg: Message, headless: bool, auto_an
                 Some(ratatui::style::Color::Red),
             );
             if headless {
-                println!("Headless: Client Disconnected");
+                note(app, "Headless: Client Disconnected");
             }
         }
 
-        Message::ModifyVar { .. } | Message::Proceed | Message::Retry | Message::Continue => {}
+        // Forwarded from a remote-control client; same effect as the keys
+        Message::Proceed | Message::Retry | Message::Continue => {
+            let reply = remote_resume(app, msg).await;
+            if let Some(tx) = app
+                .ipc_clients
+                .as_ref()
+                .and_then(|clients| clients.take_control_reply())
+            {
+                let _ = tx.send(reply).await;
+            }
+        }
+
+        Message::ModifyVar { .. } | Message::UnsetVar { .. } => {}
     }
 }
 
 // ── Private helpers ──────────────────────────────────────────────────────────
 
+/// Apply a remote Proceed/Retry/Continue the way the keys would: `continue`
+/// away from a failure proceeds (the plugin only waits for `Proceed` at
+/// breakpoints and step pauses), and `retry` needs a failure to retry.
+/// Returns the `ControlReply` for the remote client.
+async fn remote_resume(app: &mut App, msg: Message) -> Message {
+    let name = match msg {
+        Message::Proceed => "Proceed",
+        Message::Retry => "Retry",
+        _ => "Continue",
+    };
+    let ignored = if !app.waiting_for_proceed {
+        Some("not paused")
+    } else if matches!(msg, Message::Retry) && app.failed_task.is_none() {
+        Some("not paused on a failure")
+    } else {
+        None
+    };
+    if let Some(reason) = ignored {
+        app.log(
+            format!("Remote: {} ignored ({})", name, reason),
+            Some(ratatui::style::Color::DarkGray),
+        );
+        return Message::ControlReply {
+            applied: false,
+            detail: format!("{} ignored: {}", name, reason),
+        };
+    }
+
+    let (msg, name) = match msg {
+        Message::Continue if app.failed_task.is_none() => (Message::Proceed, "Proceed"),
+        msg => (msg, name),
+    };
+    app.waiting_for_proceed = false;
+    app.log(
+        format!("Remote: {}", name),
+        Some(ratatui::style::Color::Cyan),
+    );
+    send_control(app, msg, &format!("{} (remote)", name)).await;
+    Message::ControlReply {
+        applied: true,
+        detail: format!("Sent {}", name),
+    }
+}
+
+/// With `--headless-timeout`, auto-proceed a headless task once nothing has
+/// resumed it before its deadline. Every client keeps its own deadline, so
+/// stashed clients are checked as well as the active one.
+pub async fn check_headless_timeout(app: &mut App) {
+    let now = Instant::now();
+    proceed_expired_wait(app, now).await;
+    let mut expired: Vec<ClientId> = app
+        .client_states
+        .iter()
+        .filter(|(_, s)| {
+            s.waiting_for_proceed
+                && s.headless_wait
+                    .as_ref()
+                    .is_some_and(|(deadline, _)| now >= *deadline)
+        })
+        .map(|(id, _)| *id)
+        .collect();
+    expired.sort_unstable();
+    for client in expired {
+        app.switch_client(client);
+        proceed_expired_wait(app, now).await;
+    }
+}
+
+/// Auto-proceed the active client if its headless deadline has passed.
+async fn proceed_expired_wait(app: &mut App, now: Instant) {
+    let Some((deadline, _)) = &app.headless_wait else {
+        return;
+    };
+    if !app.waiting_for_proceed {
+        app.headless_wait = None;
+        return;
+    }
+    if now < *deadline {
+        return;
+    }
+    let Some((_, task)) = app.headless_wait.take() else {
+        return;
+    };
+    let msg = format!("Headless: timeout waiting on task {}", task);
+    note(app, &msg);
+    app.log(msg, Some(ratatui::style::Color::Yellow));
+    app.waiting_for_proceed = false;
+    send_control(app, Message::Proceed, "Proceed (headless timeout)").await;
+    note(app, "Headless: Auto-Proceeding...");
+}
+
 async fn send_ipc(app: &App, msg: Message) {
     if let Some(tx) = &app.ipc_tx {
         let _ = tx.send(msg).await;
     }
 }
 
-fn task_status(failed: bool, changed: bool) -> (&'static str, ratatui::style::Color) {
-    if failed {
+/// Send a control decision to the active client and record it in the audit
+/// log as `action`, with the variable a `ModifyVar` sets. Every Proceed,
+/// Retry, Continue and ModifyVar a user, script or headless run decides on
+/// goes through here; protocol replies (handshake, play start) don't.
+pub async fn send_control(app: &mut App, msg: Message, action: &str) {
+    let var = match &msg {
+        Message::ModifyVar { key, value } => Some((key.as_str(), value)),
+        _ => None,
+    };
+    app.audit(action, var);
+    send_ipc(app, msg).await;
+}
+
+/// Send a variable change from any source (editor, AI fix, test script,
+/// headless recovery), audited as `action`. The value it replaces is kept for
+/// undo, the change is traced on the task's span, and the next `TaskStart`
+/// is checked for it.
+pub async fn send_var_change(app: &mut App, change: VarChange, action: &str) {
+    // A change sent earlier but not yet reflected in task_vars is the
+    // current value as far as the playbook is concerned
+    let previous = app
+        .expected_vars
+        .get(&change.key)
+        .cloned()
+        .or_else(|| app.get_var_value(&change.key));
+    app.var_undo.push((change.key.clone(), previous));
+    send_control(
+        app,
+        Message::ModifyVar {
+            key: change.key.clone(),
+            value: change.value.clone(),
+        },
+        action,
+    )
+    .await;
+    trace_var_change(app, &change.key, &change.value);
+    app.expect_var(change.key.clone(), change.value.clone());
+    app.applied_changes.push(change);
+}
+
+/// Put `key` back to `previous`, or unset it when it had no value before.
+pub async fn revert_var_change(app: &mut App, key: &str, previous: Option<serde_json::Value>) {
+    match previous {
+        Some(value) => {
+            send_control(
+                app,
+                Message::ModifyVar {
+                    key: key.to_string(),
+                    value: value.clone(),
+                },
+                "ModifyVar",
+            )
+            .await;
+            trace_var_change(app, key, &value);
+            app.expect_var(key.to_string(), value);
+        }
+        None => {
+            send_control(
+                app,
+                Message::UnsetVar {
+                    key: key.to_string(),
+                },
+                "UnsetVar",
+            )
+            .await;
+            trace_var_change(app, key, &serde_json::Value::Null);
+            app.expected_vars.remove(key);
+        }
+    }
+}
+
+/// Record a variable change on the current task's trace span.
+fn trace_var_change(app: &mut App, key: &str, value: &serde_json::Value) {
+    if let Some(task) = &app.current_task
+        && let Some(task_span) = app.task_spans.get_mut(task)
+    {
+        crate::telemetry::record_var_modification(&mut task_span.span, key, value);
+    }
+}
+
+/// End task `name`'s span, if still open, and time it for the folded-stack
+/// export. Only the first host to finish a task closes it.
+fn end_task_span(app: &mut App, name: &str, attributes: Vec<opentelemetry::KeyValue>) {
+    if let Some(task_span) = app.task_spans.remove(name) {
+        app.span_timings.end_task(name, task_span, attributes);
+    }
+}
+
+/// End the spans of tasks that never reported a result before their play
+/// ended, untimed, so they don't stay open for the rest of the run.
+fn abandon_task_spans(app: &mut App) {
+    for (_, task_span) in app.task_spans.drain() {
+        crate::telemetry::end_span(
+            task_span.span,
+            vec![opentelemetry::KeyValue::new("task.status", "ABANDONED")],
+        );
+    }
+}
+
+// ── Headless output ─────────────────────────────────────────────────────────
+
+/// A run event reported on stdout in headless mode, either as the usual
+/// prose line or as one JSON object (`PILOTEER_HEADLESS_FORMAT=jsonl`).
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum HeadlessEvent<'a> {
+    TaskStart {
+        task: &'a str,
+    },
+    TaskResult {
+        task: &'a str,
+        host: &'a str,
+        status: &'a str,
+        changed: bool,
+        failed: bool,
+        rescued: bool,
+        duration: f64,
+    },
+    TaskFail {
+        task: &'a str,
+        /// `null` from plugins that don't report the failing host
+        host: Option<&'a str>,
+    },
+    TaskSkipped {
+        task: &'a str,
+        host: &'a str,
+    },
+    HostUnreachable {
+        task: &'a str,
+        host: &'a str,
+        error: &'a str,
+    },
+    PlayRecap {
+        stats: &'a serde_json::Value,
+    },
+}
+
+impl HeadlessEvent<'_> {
+    /// The stdout line for this event, if `format` prints one.
+    pub fn render(&self, format: HeadlessFormat) -> Option<String> {
+        match format {
+            HeadlessFormat::Jsonl => serde_json::to_string(self).ok(),
+            HeadlessFormat::Prose => match self {
+                HeadlessEvent::TaskStart { task } => {
+                    Some(format!("Headless: Task Captured: {}", task))
+                }
+                HeadlessEvent::TaskResult { status, .. } => {
+                    Some(format!("Headless: Task Result: {}", status))
+                }
+                HeadlessEvent::TaskFail { task, host } => Some(match host {
+                    Some(host) => format!("Headless: Task Failed: {} on {}", task, host),
+                    None => format!("Headless: Task Failed: {}", task),
+                }),
+                HeadlessEvent::TaskSkipped { task, host } => {
+                    Some(format!("Headless: Task Skipped: {} on {}", task, host))
+                }
+                HeadlessEvent::HostUnreachable { host, error, .. } => {
+                    Some(format!("Headless: Host {} unreachable: {}", host, error))
+                }
+                HeadlessEvent::PlayRecap { .. } => None,
+            },
+        }
+    }
+}
+
+fn emit(app: &App, event: HeadlessEvent) {
+    if let Some(line) = event.render(app.headless_format) {
+        println!("{}", line);
+    }
+}
+
+/// Headless progress and closing messages that aren't events. In jsonl mode
+/// they go to stderr so stdout stays one JSON object per line.
+pub fn note(app: &App, msg: impl std::fmt::Display) {
+    match app.headless_format {
+        HeadlessFormat::Prose => println!("{}", msg),
+        HeadlessFormat::Jsonl => eprintln!("{}", msg),
+    }
+}
+
+pub fn task_status(
+    failed: bool,
+    changed: bool,
+    rescued: bool,
+) -> (&'static str, ratatui::style::Color) {
+    if failed && rescued {
+        ("RESCUED", ratatui::style::Color::Magenta)
+    } else if failed {
         ("FAILED", ratatui::style::Color::Red)
     } else if changed {
         ("CHANGED", ratatui::style::Color::Yellow)
@@ -334,36 +1001,41 @@ async fn run_script_actions(app: &mut App, actions: Vec<ScriptActionType>) {
     for action in actions {
         match action {
             ScriptActionType::Pause => {
-                println!("Headless: Pausing (Scripted)...");
+                note(app, "Headless: Pausing (Scripted)...");
                 tokio::time::sleep(Duration::from_secs(5)).await;
             }
             ScriptActionType::Continue => {
-                send_ipc(app, Message::Continue).await;
+                send_control(app, Message::Continue, "Continue (script)").await;
             }
             ScriptActionType::Resume => {
-                println!("Headless: Resuming (Scripted)...");
-                send_ipc(app, Message::Proceed).await;
+                note(app, "Headless: Resuming (Scripted)...");
+                send_control(app, Message::Proceed, "Proceed (script)").await;
             }
             ScriptActionType::Retry => {
-                send_ipc(app, Message::Retry).await;
+                send_control(app, Message::Retry, "Retry (script)").await;
             }
             ScriptActionType::EditVar { key, value } => {
-                println!("Headless: ModifyVar {} = {}", key, value);
-                send_ipc(app, Message::ModifyVar { key, value }).await;
+                note(app, format!("Headless: ModifyVar {} = {}", key, value));
+                let change = VarChange {
+                    task: app.current_task.clone(),
+                    key,
+                    value,
+                };
+                send_var_change(app, change, "ModifyVar (script)").await;
             }
             ScriptActionType::ExecuteCommand { cmd } => {
-                println!("Headless: Executing Command: {}", cmd);
+                note(app, format!("Headless: Executing Command: {}", cmd));
                 let result = std::process::Command::new("sh")
                     .arg("-c")
                     .arg(&cmd)
                     .output();
                 match result {
-                    Ok(o) => println!(" Command Finished: status={}", o.status),
-                    Err(e) => println!(" Command Failed: {}", e),
+                    Ok(o) => note(app, format!(" Command Finished: status={}", o.status)),
+                    Err(e) => note(app, format!(" Command Failed: {}", e)),
                 }
             }
             ScriptActionType::AskAi => {
-                println!("Headless: Asking AI (Scripted)...");
+                note(app, "Headless: Asking AI (Scripted)...");
                 if let Some(client) = &app.ai_client.clone() {
                     let vars = app.task_vars.clone().unwrap_or(serde_json::json!({}));
                     let facts = app.facts.clone();
@@ -373,32 +1045,41 @@ async fn run_script_actions(app: &mut App, actions: Vec<ScriptActionType>) {
                         .await
                     {
                         Ok(analysis) => {
-                            println!(
-                                "Headless: AI Analysis Received: {:.50}...",
-                                analysis.analysis
+                            note(
+                                app,
+                                format!(
+                                    "Headless: AI Analysis Received: {:.50}...",
+                                    analysis.analysis
+                                ),
                             );
                             app.suggestion = Some(analysis);
                         }
-                        Err(e) => println!("Headless: AI Request Failed: {}", e),
+                        Err(e) => note(app, format!("Headless: AI Request Failed: {}", e)),
                     }
                 }
             }
             ScriptActionType::ApplyFix => {}
             ScriptActionType::AssertAiContext { contains } => {
-                println!("Headless: Asserting AI Context...");
+                note(app, "Headless: Asserting AI Context...");
                 match (&app.suggestion, contains) {
                     (Some(s), Some(text)) => {
                         if s.analysis.contains(&text) {
-                            println!("Headless: Assertion PASSED: Analysis contains '{}'", text);
+                            note(
+                                app,
+                                format!("Headless: Assertion PASSED: Analysis contains '{}'", text),
+                            );
                         } else {
-                            println!(
-                                "Headless: Assertion FAILED: Analysis does NOT contain '{}'",
-                                text
+                            note(
+                                app,
+                                format!(
+                                    "Headless: Assertion FAILED: Analysis does NOT contain '{}'",
+                                    text
+                                ),
                             );
                         }
                     }
-                    (Some(_), None) => println!("Headless: Assertion PASSED: AI Context present."),
-                    (None, _) => println!("Headless: Assertion FAILED: No AI Context found."),
+                    (Some(_), None) => note(app, "Headless: Assertion PASSED: AI Context present."),
+                    (None, _) => note(app, "Headless: Assertion FAILED: No AI Context found."),
                 }
             }
         }
@@ -416,19 +1097,42 @@ async fn handle_headless_failure(app: &mut App, name: &str, auto_analyze: bool)
         .iter()
         .position(|a| a.task_name == *name && a.on_failure)
     {
-        println!("Headless: Executing Script Action for TaskFail: {}", name);
+        note(
+            app,
+            format!("Headless: Executing Script Action for TaskFail: {}", name),
+        );
         let script = app.test_script.remove(idx);
         run_script_actions(app, script.actions).await;
     } else if let Some(client) = client {
         if auto_analyze {
-            println!("Headless: Analyzing Failure...");
+            note(app, "Headless: Analyzing Failure...");
             if let Ok(analysis) = client
                 .analyze_failure(name, "Task Failed", &vars, facts.as_ref())
                 .await
             {
-                println!("\n🤖 AI ANALYSIS:\n{}\n", analysis.analysis);
+                note(app, format!("\n🤖 AI ANALYSIS:\n{}\n", analysis.analysis));
                 if let Some(fix) = &analysis.fix {
-                    println!("💡 SUGGESTED FIX: {} = {}\n", fix.key, fix.value);
+                    note(
+                        app,
+                        format!("💡 SUGGESTED FIX: {} = {}\n", fix.key, fix.value),
+                    );
+                    if client.config().auto_fix_allowed(name, &fix.key) {
+                        note(app, format!("Headless: Auto-applying fix to '{}'", name));
+                        let change = VarChange {
+                            task: app.current_task.clone(),
+                            key: fix.key.clone(),
+                            value: fix.value.clone(),
+                        };
+                        send_var_change(app, change, "ApplyFix (headless)").await;
+                    } else {
+                        note(
+                            app,
+                            format!(
+                                "Headless: Fix for '{}' not eligible for auto-apply (see auto_fix_tasks / auto_fix_deny_vars)",
+                                name
+                            ),
+                        );
+                    }
                 }
                 if let Some(tx) = &app.ipc_tx {
                     let _ = tx
@@ -443,19 +1147,20 @@ async fn handle_headless_failure(app: &mut App, name: &str, auto_analyze: bool)
             .analyze_failure(name, "Task Failed", &vars, facts.as_ref())
             .await
         {
-            println!("Headless: AI Analysis Tokens: {}", analysis.tokens_used);
+            note(
+                app,
+                format!("Headless: AI Analysis Tokens: {}", analysis.tokens_used),
+            );
         }
     }
 
     // Default headless recovery: reset var and retry
-    send_ipc(
-        app,
-        Message::ModifyVar {
-            key: "should_fail".to_string(),
-            value: serde_json::json!(false),
-        },
-    )
-    .await;
+    let change = VarChange {
+        task: app.current_task.clone(),
+        key: "should_fail".to_string(),
+        value: serde_json::json!(false),
+    };
+    send_var_change(app, change, "ModifyVar (headless)").await;
     tokio::time::sleep(Duration::from_millis(500)).await;
-    send_ipc(app, Message::Retry).await;
+    send_control(app, Message::Retry, "Retry (headless)").await;
 }
@@ -0,0 +1,141 @@
+//! Capture of the plugin message stream as a JSONL fixture
+//! (`PILOTEER_RECORD_IPC=<path>`) and its playback without a socket
+//! (`--replay-ipc <path>`). Unlike `--replay`, which loads the final
+//! session, this replays the live events in their original order and timing.
+
+use crate::ipc::{ClientId, Message};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+/// One line of a recording.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedMessage {
+    /// Milliseconds since the recording started
+    pub offset_ms: u64,
+    pub client: ClientId,
+    pub message: Message,
+}
+
+#[derive(Debug)]
+pub struct IpcRecorder {
+    file: File,
+    started: Instant,
+}
+
+impl IpcRecorder {
+    /// Start a new recording at `path`, replacing any previous one.
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        Ok(Self {
+            file: File::create(path)?,
+            started: Instant::now(),
+        })
+    }
+
+    /// Append `msg` as received from `client`. Handshake tokens are dropped
+    /// so fixtures can be shared.
+    pub fn record(&mut self, client: ClientId, msg: &Message) -> std::io::Result<()> {
+        let message = match msg {
+            Message::Handshake { label, control, .. } => Message::Handshake {
+                token: None,
+                label: label.clone(),
+                control: *control,
+            },
+            other => other.clone(),
+        };
+        let entry = RecordedMessage {
+            offset_ms: self.started.elapsed().as_millis() as u64,
+            client,
+            message,
+        };
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())
+    }
+}
+
+/// Read a recording made with [`IpcRecorder`]. Blank lines are skipped.
+pub fn load(path: &Path) -> Result<Vec<RecordedMessage>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("cannot read {}", path.display()))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .with_context(|| format!("{}:{}: invalid recorded message", path.display(), i + 1))
+        })
+        .collect()
+}
+
+/// Feed `records` into the app's message channel at their recorded offsets,
+/// then close it as a disconnecting plugin would.
+pub fn spawn_replay(records: Vec<RecordedMessage>, tx: mpsc::Sender<(ClientId, Message)>) {
+    tokio::spawn(async move {
+        let started = tokio::time::Instant::now();
+        for record in records {
+            tokio::time::sleep_until(started + Duration::from_millis(record.offset_ms)).await;
+            if tx.send((record.client, record.message)).await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_and_replay_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.jsonl");
+
+        let mut recorder = IpcRecorder::create(&path).unwrap();
+        recorder
+            .record(
+                1,
+                &Message::Handshake {
+                    token: Some("s3cret".to_string()),
+                    label: None,
+                    control: false,
+                },
+            )
+            .unwrap();
+        recorder
+            .record(
+                1,
+                &Message::PlayStart {
+                    name: "Deploy".to_string(),
+                    host_pattern: "all".to_string(),
+                },
+            )
+            .unwrap();
+        drop(recorder);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(!content.contains("s3cret"));
+
+        let records = load(&path).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let (tx, mut rx) = mpsc::channel(10);
+        spawn_replay(records, tx);
+        let (client, first) = rx.recv().await.unwrap();
+        assert_eq!(client, 1);
+        assert!(matches!(first, Message::Handshake { token: None, .. }));
+        assert!(matches!(
+            rx.recv().await,
+            Some((1, Message::PlayStart { name, .. })) if name == "Deploy"
+        ));
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -1,21 +1,29 @@
 pub mod actions;
 pub mod ai;
 pub mod app;
+pub mod audit;
 pub mod auth;
 pub mod clipboard;
 pub mod config;
+pub mod diff;
 pub mod execution;
+pub mod heuristics;
 pub mod highlight;
 pub mod input;
 pub mod ipc;
 pub mod ipc_handler;
+pub mod ipc_record;
 pub mod mcp;
 pub mod plugin;
+pub mod preflight;
 pub mod query;
 pub mod quota;
 pub mod repl;
 pub mod report;
+pub mod search;
 pub mod session;
+pub mod spill;
 pub mod telemetry;
+pub mod timeline;
 pub mod ui;
 pub mod widgets;
@@ -1,5 +1,5 @@
 use ansible_piloteer::{actions, auth, ipc_handler, ui};
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use crossterm::event;
 use crossterm::execute;
@@ -9,9 +9,9 @@ use std::io;
 use std::time::Duration;
 use tokio::sync::mpsc;
 
-use ansible_piloteer::app::{App, TaskHistory};
+use ansible_piloteer::app::{App, HeadlessFormat, TaskHistory};
 use ansible_piloteer::config::Config;
-use ansible_piloteer::ipc::Message;
+use ansible_piloteer::ipc::{ClientId, Message};
 
 type DefaultTerminal = Terminal<CrosstermBackend<io::Stdout>>;
 
@@ -33,6 +33,23 @@ EXAMPLES:
   # Generate an execution report
   ansible-piloteer my_playbook.yml --report report.md
 
+  # Export task timings as folded stacks and render a flamegraph
+  ansible-piloteer my_playbook.yml --report timings.folded
+  inferno-flamegraph timings.folded > timings.svg
+
+  # List AI models with capabilities (optionally filtered)
+  ansible-piloteer models claude
+
+  # Check the environment before a run (non-zero exit on failure)
+  ansible-piloteer config validate
+
+  # List archived sessions, then delete all but the newest 10 older than a week
+  ansible-piloteer sessions list
+  ansible-piloteer sessions prune --keep 10 --older-than 7d
+
+  # Compare two archived sessions
+  ansible-piloteer diff yesterday.json.gz today.json.gz
+
   # Query session data (one-off query)
   ansible-piloteer query --input session.json.gz \"task_history[?failed].name\"
   
@@ -66,6 +83,12 @@ CONFIGURATION (Environment Variables):
     ANSIBLE_STRATEGY          Must be set to 'piloteer'
     ANSIBLE_STRATEGY_PLUGINS  Path to 'ansible_plugin/strategies' dir
     PILOTEER_HEADLESS         Run without TUI (for CI/CD)
+    PILOTEER_RECORD_IPC       Record incoming plugin messages to this JSONL file
+                              (play back with --replay-ipc)
+    PILOTEER_HEADLESS_FORMAT  Headless stdout: 'prose' (default) or 'jsonl', one JSON
+                              object per event (task_start, task_result, task_fail, ...)
+    PILOTEER_LOG_BUFFER_SIZE  Log lines kept in memory (default: 1000; 0 = unbounded,
+                              memory then grows with the run)
   
   AI Features:
     OPENAI_API_KEY            OpenAI API key (required for AI features)
@@ -82,35 +105,67 @@ CONFIGURATION (Environment Variables):
     PILOTEER_GOOGLE_CLIENT_ID      Google OAuth client ID
     PILOTEER_GOOGLE_CLIENT_SECRET  Google OAuth client secret
 
+EXIT CODES (playbook runs):
+  0   All tasks succeeded (rescued failures count as handled)
+  1   Piloteer itself failed (bad arguments, config, socket, ...)
+  2   At least one task failed
+  3   At least one host was unreachable (takes precedence over 2)
+  4   --strict only: no failures, but at least one task changed
+
 TUI CONTROLS:
   General:
     q / Esc     Quit
     ?           Toggle Help
+    ` / Bksp    Switch to the previous view
   Debugging:
     r           Retry failed task
     c           Continue (ignore failure)
     e           Edit variables
     a           Ask AI Pilot
     f           Apply AI Fix
+    Y           Copy fix as Ansible YAML snippet
+    A           Ask about the failure in chat (context prefilled)
+    u           Undo the last variable change
   Log View:
-    /           Search logs
+    /           Search logs (prefix /re/ for a regex)
     n / N       Next / Previous match
     l           Toggle log filter (All/Failed/Changed)
     F           Follow mode (Auto-scroll)
+    p           Pin/Unpin Inspector on the current task
+    S           Toggle step mode (pause before every task)
+    W           Ansible warnings and deprecations
   Analysis Mode:
     v           Toggle Mode / Visual Selection
     Tab         Switch Pane (Task List <-> Data Browser)
+    s           Sort Task List (order / duration / status)
+    F / Ctrl+f  Jump to next / previous failed task
+    b           Toggle breakpoint on the selected task
+    B           Conditional breakpoint (JMESPath over task vars, e.g. env == 'prod')
     j / k       Navigate (supports count: 10j moves 10 lines)
     0-9         Enter count for next command
     h / l       Collapse/Expand
     Enter       Expand/Collapse
+    z           Collapse siblings of selected node
+    o           Toggle flat 'path = value' view
+    i           Full value viewer (w: wrap, x: hexdump)
+    e           Edit selected leaf in $EDITOR and send it as a variable change
     w           Toggle Text Wrapping
     /           Search Tree
     n / N       Next / Previous match
     y           Yank to clipboard (single/visual/count-based)
+    Y           Yank the selected node's whole subtree as JSON
   Session:
     Ctrl+s      Save Session Snapshot
+    M           Edit Session Note
+    R           Copy re-run args for changed/failed tasks
     --replay    Replay execution from file
+    --reanalyze With --replay, re-ask the AI about failed tasks as you browse
+    --replay-timeline  Play a session back task by task (--speed N; space pause, n step)
+    --replay-ipc  Replay a message stream recorded with PILOTEER_RECORD_IPC
+    --step      Pause before every task (toggle live with S)
+    --dump-facts  Write per-host facts to ~/.config/ansible-piloteer/facts/
+    --debug-files Write ansible-playbook output to ./ansible_child_<pid>.log
+    --dry-run   Check that the plugin connects and authenticates, then exit
 
 DISTRIBUTED MODE:
   1. Start this CLI as a server:
@@ -119,6 +174,16 @@ DISTRIBUTED MODE:
      export PILOTEER_SOCKET=192.168.1.5:9000
      export PILOTEER_SECRET=1234
      ansible-playbook ...
+
+PLUGIN PROTOCOL (newline-delimited JSON):
+  Plugin -> controller: Handshake, PlayStart, TaskStart, TaskFail, TaskResult,
+                        TaskUnreachable, TaskSkipped, PlayRecap, Pong
+  Controller -> plugin: Proceed, Retry, Continue, ModifyVar, UnsetVar, Ping,
+                        Abort
+  Ping      Sent every heartbeat_interval_secs; answer with Pong
+  Abort     Sent on quit: fail the play and close the connection. The
+            controller waits a few seconds for the disconnect, then kills
+            the ansible-playbook it started.
 "
 )]
 struct Cli {
@@ -129,7 +194,7 @@ struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
-    /// Export execution report (.json or .md)
+    /// Export execution report (.json, .md, .html, or .folded for flamegraph timings)
     #[arg(long)]
     report: Option<String>,
 
@@ -152,6 +217,71 @@ struct Cli {
     /// Replay a saved session file
     #[arg(long)]
     replay: Option<String>,
+
+    /// With --replay, fetch a fresh AI analysis when navigating to failed tasks
+    #[arg(long, requires = "replay")]
+    reanalyze: bool,
+
+    /// Play a saved session back in real time, task by task
+    #[arg(long, conflicts_with = "replay")]
+    replay_timeline: Option<String>,
+
+    /// Playback speed multiplier for --replay-timeline (0.1 to 100)
+    #[arg(
+        long,
+        default_value_t = 1.0,
+        requires = "replay_timeline",
+        value_parser = ansible_piloteer::timeline::parse_speed
+    )]
+    speed: f64,
+
+    /// Feed a message stream recorded with PILOTEER_RECORD_IPC back into the
+    /// app at its original timing, without a socket or playbook
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["replay", "replay_timeline"])]
+    replay_ipc: Option<String>,
+
+    /// Check that the plugin can connect and authenticate, then exit: runs the
+    /// playbook (if given) with --check and waits for its handshake
+    #[arg(long, conflicts_with_all = ["replay", "replay_timeline"])]
+    dry_run: bool,
+
+    /// How long --dry-run waits for the plugin's handshake
+    #[arg(long, value_name = "SECS", default_value_t = 30, requires = "dry_run")]
+    dry_run_timeout: u64,
+
+    /// Dump each host's facts to ~/.config/ansible-piloteer/facts/<host>.json
+    #[arg(long)]
+    dump_facts: bool,
+
+    /// Write ansible-playbook output to ansible_child_<pid>.log (off by default)
+    #[arg(long)]
+    debug_files: bool,
+
+    /// Pause before every task, as if each had a breakpoint (ignored in headless mode)
+    #[arg(long)]
+    step: bool,
+
+    /// Also exit non-zero (4) when any task reported `changed`, for drift-gate CI
+    #[arg(long)]
+    strict: bool,
+
+    /// In headless mode, wait up to SECS for a scripted or remote command on each
+    /// task before auto-proceeding, logging a timeout (default: proceed after 500ms)
+    #[arg(long, value_name = "SECS")]
+    headless_timeout: Option<u64>,
+
+    /// Disable all AI features, even if API keys are configured (or PILOTEER_NO_AI)
+    #[arg(long)]
+    no_ai: bool,
+
+    /// Don't save this run to the session archive (or PILOTEER_NO_ARCHIVE);
+    /// --report is unaffected
+    #[arg(long)]
+    no_archive: bool,
+
+    /// Config profile to merge over the base config (`[profiles.<name>]`, or PILOTEER_PROFILE)
+    #[arg(long)]
+    profile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -169,8 +299,36 @@ enum Commands {
         #[arg(short, long, default_value = "pretty-json")]
         format: String,
     },
+    /// Compare two saved sessions: new failures, changed flips, slowdowns, unreachable hosts
+    Diff {
+        /// Earlier session file (e.g. yesterday's archive)
+        base: String,
+        /// Later session file to compare against the base
+        compare: String,
+        /// Output format: text or json
+        #[arg(short, long, default_value = "text")]
+        format: String,
+        /// Percent slowdown that counts as a duration regression
+        #[arg(long, default_value_t = 50.0)]
+        threshold: f64,
+    },
+    /// List or prune the auto-saved session archive
+    Sessions {
+        #[command(subcommand)]
+        cmd: SessionsCmd,
+    },
+    /// Inspect the Piloteer configuration
+    Config {
+        #[command(subcommand)]
+        cmd: ConfigCmd,
+    },
     /// Start MCP stdio server for IDE integration
     Mcp,
+    /// List available AI models with capability annotations
+    Models {
+        /// Only show models whose name contains this text
+        search: Option<String>,
+    },
     /// Install the Piloteer Ansible strategy plugin to ~/.ansible/plugins/strategy/
     Init {
         /// Force overwrite even if the plugin is already installed
@@ -179,6 +337,30 @@ enum Commands {
     },
 }
 
+#[derive(Subcommand)]
+enum ConfigCmd {
+    /// Preflight check: strategy plugin, AI credentials and socket/bind address.
+    /// Exits non-zero if any check fails
+    Validate,
+}
+
+#[derive(Subcommand)]
+enum SessionsCmd {
+    /// Show each archived session with its task and failure counts
+    List,
+    /// Delete old archived sessions
+    Prune {
+        /// Always keep this many of the newest sessions
+        #[arg(long, default_value_t = 20)]
+        keep: usize,
+        /// Only delete sessions older than this (e.g. 7d, 12h, 2w). Combines
+        /// with --keep: only sessions past the newest --keep AND older than
+        /// this are deleted
+        #[arg(long, value_parser = ansible_piloteer::session::parse_age)]
+        older_than: Option<chrono::Duration>,
+    },
+}
+
 #[derive(Subcommand)]
 enum AuthCmd {
     Login {
@@ -205,15 +387,34 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
     let auto_analyze = cli.auto_analyze;
 
-    let config = Config::new().unwrap_or_else(|e| {
+    // Validation reports a broken config as a failed check instead of exiting
+    if let Some(Commands::Config {
+        cmd: ConfigCmd::Validate,
+    }) = &cli.command
+    {
+        return handle_config_validate(cli.profile.as_deref());
+    }
+
+    let mut config = Config::load(cli.profile.as_deref()).unwrap_or_else(|e| {
         eprintln!("Failed to load config: {}", e);
         std::process::exit(1);
     });
+    if cli.no_ai {
+        config.no_ai = true;
+    }
+    if cli.no_archive {
+        config.no_archive = true;
+    }
 
     if let Err(e) = ansible_piloteer::telemetry::init_tracing(&config) {
         eprintln!("Warning: Failed to initialize tracing: {}", e);
     }
+    if let Err(e) = ansible_piloteer::telemetry::init_logging(&config) {
+        eprintln!("Warning: Failed to initialize logging: {:#}", e);
+    }
 
+    // Playbook outcome for CI; subcommands report errors through `result`
+    let mut exit_code = 0;
     let result = match cli.command {
         Some(Commands::Auth { cmd }) => handle_auth(cmd, config).await,
         Some(Commands::Query {
@@ -221,7 +422,16 @@ async fn main() -> Result<()> {
             input,
             format,
         }) => handle_query(query, input, format, config),
+        Some(Commands::Diff {
+            base,
+            compare,
+            format,
+            threshold,
+        }) => handle_diff(&base, &compare, &format, threshold),
+        Some(Commands::Sessions { cmd }) => handle_sessions(cmd),
+        Some(Commands::Config { .. }) => unreachable!("handled before loading the config"),
         Some(Commands::Mcp) => ansible_piloteer::mcp::run_stdio_server().await,
+        Some(Commands::Models { search }) => handle_models(search, config).await,
         Some(Commands::Init { force }) => match ansible_piloteer::plugin::install_plugin(force) {
             Ok(path) => {
                 println!("✓ Strategy plugin installed to: {}", path.display());
@@ -231,24 +441,51 @@ async fn main() -> Result<()> {
             }
             Err(e) => Err(e),
         },
+        None if cli.dry_run => {
+            ansible_piloteer::plugin::ensure_plugin();
+            run_dry_run(
+                config,
+                cli.ansible_args,
+                cli.bind,
+                cli.secret,
+                cli.verbose,
+                cli.debug_files,
+                Duration::from_secs(cli.dry_run_timeout),
+            )
+            .await
+        }
         None => {
             // Auto-install the strategy plugin on every TUI startup
             ansible_piloteer::plugin::ensure_plugin();
             run_tui(
+                config,
                 cli.ansible_args,
                 cli.report,
                 cli.bind,
                 cli.secret,
                 cli.verbose,
-                cli.replay,
+                cli.replay.or(cli.replay_timeline.clone()),
+                cli.replay_timeline.is_some().then_some(cli.speed),
                 auto_analyze,
+                cli.dump_facts,
+                cli.debug_files,
+                cli.reanalyze,
+                cli.step,
+                cli.headless_timeout,
+                cli.strict,
+                cli.replay_ipc,
             )
             .await
+            .map(|code| exit_code = code)
         }
     };
 
     ansible_piloteer::telemetry::shutdown_tracing();
-    result
+    result?;
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+    Ok(())
 }
 
 // ── Subcommand handlers ──────────────────────────────────────────────────────
@@ -313,6 +550,92 @@ fn save_token(profile: &str, backend: &str, token: &str) {
     }
 }
 
+fn handle_config_validate(profile: Option<&str>) -> Result<()> {
+    use ansible_piloteer::preflight::{self, CheckStatus};
+    let checks = preflight::validate(profile);
+    print!("{}", preflight::render(&checks));
+    let failed = checks
+        .iter()
+        .filter(|c| c.status == CheckStatus::Fail)
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{} check(s) failed", failed);
+    }
+    println!("All critical checks passed.");
+    Ok(())
+}
+
+fn handle_diff(base: &str, compare: &str, format: &str, threshold: f64) -> Result<()> {
+    use std::io::IsTerminal;
+    let load = |path: &str| {
+        ansible_piloteer::session::Session::load(path)
+            .map_err(|e| anyhow::anyhow!("Error loading session from {}: {}", path, e))
+    };
+    let diff = ansible_piloteer::diff::diff_sessions(&load(base)?, &load(compare)?, threshold);
+    match format {
+        "text" => print!("{}", diff.render_text(std::io::stdout().is_terminal())),
+        "json" => println!("{}", serde_json::to_string_pretty(&diff)?),
+        other => anyhow::bail!("Unknown format: {}. Supported: text, json", other),
+    }
+    Ok(())
+}
+
+fn handle_sessions(cmd: SessionsCmd) -> Result<()> {
+    use ansible_piloteer::session;
+    let dir = Config::get_config_dir()?.join("archive");
+    if !dir.exists() {
+        println!("No archived sessions in {}", dir.display());
+        return Ok(());
+    }
+    let session::ArchiveListing {
+        sessions,
+        unreadable,
+    } = session::list_archive(&dir)?;
+    for (path, err) in &unreadable {
+        eprintln!("Warning: skipping {}: {}", path.display(), err);
+    }
+    let file_name = |s: &session::ArchivedSession| {
+        s.path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+
+    match cmd {
+        SessionsCmd::List => {
+            if sessions.is_empty() {
+                println!("No archived sessions in {}", dir.display());
+                return Ok(());
+            }
+            println!(
+                "{:<32} | {:<20} | {:>5} | {:>8}",
+                "File", "Timestamp (UTC)", "Tasks", "Failures"
+            );
+            println!("{:-<32}-+-{:-<20}-+-{:->5}-+-{:->8}", "", "", "", "");
+            for s in &sessions {
+                println!(
+                    "{:<32} | {:<20} | {:>5} | {:>8}",
+                    file_name(s),
+                    s.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                    s.tasks,
+                    s.failures
+                );
+            }
+        }
+        SessionsCmd::Prune { keep, older_than } => {
+            let doomed = session::select_prune(&sessions, keep, older_than, chrono::Utc::now());
+            for s in &doomed {
+                match std::fs::remove_file(&s.path) {
+                    Ok(()) => println!("Deleted {}", file_name(s)),
+                    Err(e) => eprintln!("Failed to delete {}: {}", s.path.display(), e),
+                }
+            }
+            println!("Pruned {} of {} session(s)", doomed.len(), sessions.len());
+        }
+    }
+    Ok(())
+}
+
 fn handle_query(
     query: Option<String>,
     input: String,
@@ -353,44 +676,241 @@ fn handle_query(
         "json" => println!("{}", serde_json::to_string(&result).unwrap()),
         "pretty-json" => println!("{}", serde_json::to_string_pretty(&result).unwrap()),
         "yaml" => println!("{}", serde_yaml::to_string(&result).unwrap()),
+        "csv" => {
+            let value = serde_json::to_value(&*result)?;
+            let csv = ansible_piloteer::query::to_csv(&value).map_err(|e| anyhow::anyhow!(e))?;
+            print!("{}", csv);
+        }
         other => anyhow::bail!(
-            "Unknown format: {}. Supported: json, pretty-json, yaml",
+            "Unknown format: {}. Supported: json, pretty-json, yaml, csv",
             other
         ),
     }
     Ok(())
 }
 
+async fn handle_models(search: Option<String>, config: Config) -> Result<()> {
+    use ansible_piloteer::ai;
+
+    if config.no_ai {
+        anyhow::bail!("AI is disabled (--no-ai / PILOTEER_NO_AI)");
+    }
+
+    // Merge dynamically discovered models with the statically known ones
+    let mut models: std::collections::BTreeMap<String, String> = std::collections::BTreeMap::new();
+    for (provider_name, provider) in ai::configured_providers(&config) {
+        match provider.list_models().await {
+            Ok(found) => {
+                for m in found {
+                    models.insert(m, provider_name.to_string());
+                }
+            }
+            Err(e) => eprintln!("Warning: could not list {} models: {}", provider_name, e),
+        }
+    }
+    for model in ai::known_models() {
+        models
+            .entry(model.to_string())
+            .or_insert_with(|| "static".to_string());
+    }
+
+    let needle = search.map(|s| s.to_lowercase());
+    println!(
+        "{:<40} | {:<10} | {:<9} | {:<8} | {:<10} | {:<7}",
+        "Model", "Source", "Streaming", "Tools", "Context", "Pricing"
+    );
+    println!(
+        "{:-<40}-+-{:-<10}-+-{:-<9}-+-{:-<8}-+-{:-<10}-+-{:-<7}",
+        "", "", "", "", "", ""
+    );
+    for (model, source) in &models {
+        if let Some(n) = &needle
+            && !model.to_lowercase().contains(n)
+        {
+            continue;
+        }
+        let marker = if *model == config.model { "*" } else { "" };
+        match ai::model_capabilities(model) {
+            Some(c) => println!(
+                "{:<40} | {:<10} | {:<9} | {:<8} | {:<10} | {:<7}",
+                format!("{}{}", model, marker),
+                source,
+                if c.streaming { "yes" } else { "no" },
+                if c.tool_use { "yes" } else { "no" },
+                format!("~{}k", c.context_window / 1000),
+                c.pricing_tier
+            ),
+            None => println!(
+                "{:<40} | {:<10} | {:<9} | {:<8} | {:<10} | {:<7}",
+                format!("{}{}", model, marker),
+                source,
+                "?",
+                "?",
+                "?",
+                "?"
+            ),
+        }
+    }
+    Ok(())
+}
+
+// ── Dry run ──────────────────────────────────────────────────────────────────
+
+/// `--dry-run`: listen like the TUI would, optionally start the playbook in
+/// check mode, and report whether the plugin's handshake was accepted.
+async fn run_dry_run(
+    mut config: Config,
+    ansible_args: Vec<String>,
+    bind_addr: Option<String>,
+    secret_token: Option<String>,
+    verbose: u8,
+    debug_files: bool,
+    timeout: Duration,
+) -> Result<()> {
+    use ansible_piloteer::ipc::{AcceptedSecrets, IpcServer};
+    use ipc_handler::HandshakeCheck;
+
+    if let Some(addr) = bind_addr {
+        config.bind_addr = Some(addr);
+    }
+    if let Some(secret) = secret_token {
+        config.secret_token = Some(secret);
+    }
+    let endpoint = config
+        .bind_addr
+        .clone()
+        .unwrap_or_else(|| config.socket_path.clone());
+    let server = IpcServer::new(&config.socket_path, config.bind_addr.as_deref())
+        .await
+        .map_err(|e| anyhow::anyhow!("Cannot listen on {}: {}", endpoint, e))?
+        .with_tls(ansible_piloteer::ipc::server_tls(&config)?);
+    if server.is_tls() {
+        println!("Listening on {} (TLS)", endpoint);
+    } else {
+        println!("Listening on {}", endpoint);
+    }
+
+    let mut child = if ansible_args.is_empty() {
+        println!(
+            "Waiting for a plugin to connect (run ansible-playbook with ANSIBLE_STRATEGY=piloteer)..."
+        );
+        None
+    } else {
+        let mut args = ansible_args;
+        args.push("--check".to_string());
+        println!("Running: ansible-playbook {}", args.join(" "));
+        Some(
+            spawn_ansible(&args, verbose, &config, debug_files)
+                .map_err(|e| anyhow::anyhow!("Failed to start ansible-playbook: {}", e))?,
+        )
+    };
+
+    let secrets = AcceptedSecrets::from_config(&config);
+    let outcome = tokio::select! {
+        res = tokio::time::timeout(timeout, ipc_handler::await_handshake(&server, &secrets)) => {
+            res.ok()
+        }
+        status = async {
+            match child.as_mut() {
+                Some(c) => c.wait().await,
+                None => std::future::pending().await,
+            }
+        } => {
+            anyhow::bail!(
+                "ansible-playbook exited ({}) before the plugin connected; rerun with \
+                 --debug-files to see its output",
+                status.map_or_else(|e| e.to_string(), |s| s.to_string())
+            );
+        }
+    };
+    if let Some(mut c) = child {
+        let _ = c.kill().await;
+    }
+
+    match outcome {
+        Some(Ok(HandshakeCheck::Accepted { label })) => {
+            match label {
+                Some(l) => println!("✓ Handshake accepted (client: {})", l),
+                None => println!("✓ Handshake accepted"),
+            }
+            Ok(())
+        }
+        Some(Ok(HandshakeCheck::Rejected)) => anyhow::bail!(
+            "Handshake rejected: the plugin's PILOTEER_SECRET matches none of \
+             secret_token, secret_tokens or secrets_file"
+        ),
+        Some(Ok(HandshakeCheck::NoHandshake)) => {
+            anyhow::bail!("A client connected but did not send a handshake")
+        }
+        Some(Err(e)) => Err(e),
+        None => anyhow::bail!(
+            "No plugin connected within {}s; check that ANSIBLE_STRATEGY=piloteer and \
+             PILOTEER_SOCKET={} are set for ansible-playbook (set log_file to log connection attempts)",
+            timeout.as_secs(),
+            endpoint
+        ),
+    }
+}
+
 // ── TUI runner ───────────────────────────────────────────────────────────────
 
+#[allow(clippy::too_many_arguments)]
 async fn run_tui(
+    mut config: Config,
     ansible_args: Vec<String>,
     report_path: Option<String>,
     bind_addr: Option<String>,
     secret_token: Option<String>,
     verbose: u8,
     replay_path: Option<String>,
+    timeline_speed: Option<f64>,
     auto_analyze: bool,
-) -> Result<()> {
+    dump_facts: bool,
+    debug_files: bool,
+    reanalyze: bool,
+    step: bool,
+    headless_timeout: Option<u64>,
+    strict: bool,
+    replay_ipc: Option<String>,
+) -> Result<i32> {
     let headless = std::env::var("PILOTEER_HEADLESS").is_ok();
+    let headless_format = match std::env::var("PILOTEER_HEADLESS_FORMAT") {
+        Ok(v) => HeadlessFormat::parse(&v).unwrap_or_else(|| {
+            eprintln!("Unknown PILOTEER_HEADLESS_FORMAT '{}', using prose", v);
+            HeadlessFormat::Prose
+        }),
+        Err(_) => HeadlessFormat::Prose,
+    };
+    let archive = !config.no_archive;
 
-    let mut config = Config::new().unwrap_or_else(|e| {
-        eprintln!("Failed to load config: {}", e);
-        std::process::exit(1);
-    });
     if let Some(addr) = bind_addr {
         config.bind_addr = Some(addr);
     }
     if let Some(secret) = secret_token {
         config.secret_token = Some(secret);
     }
+    let tls = ansible_piloteer::ipc::server_tls(&config)?;
+    let audit_log = if config.audit_log && replay_path.is_none() && replay_ipc.is_none() {
+        let path = match &config.audit_log_path {
+            Some(p) => std::path::PathBuf::from(p),
+            None => Config::get_config_dir()?.join("audit.jsonl"),
+        };
+        let log = ansible_piloteer::audit::AuditLog::open(&path, config.audit_log_values)
+            .map_err(|e| anyhow::anyhow!("Failed to open audit log {}: {}", path.display(), e))?;
+        Some(log)
+    } else {
+        None
+    };
 
     let mut terminal: Option<DefaultTerminal> = if !headless {
         let t = ratatui::init();
         execute!(io::stdout(), crossterm::event::EnableMouseCapture)?;
         Some(t)
     } else {
-        println!("Running in HEADLESS mode");
+        match headless_format {
+            HeadlessFormat::Prose => println!("Running in HEADLESS mode"),
+            HeadlessFormat::Jsonl => eprintln!("Running in HEADLESS mode"),
+        }
         None
     };
 
@@ -401,38 +921,173 @@ async fn run_tui(
         }),
         None => App::new(config.clone()),
     };
+    if let Some(speed) = timeline_speed {
+        app.start_timeline(speed);
+    }
     app.load_test_script();
-
     if !app.replay_mode {
-        let (to_app_tx, to_app_rx) = mpsc::channel::<Message>(100);
-        let (from_app_tx, from_app_rx) = mpsc::channel::<Message>(100);
-        app.set_ipc_tx(Some(from_app_tx));
-
-        ipc_handler::spawn_ipc_server(
-            config.socket_path.clone(),
-            config.bind_addr.clone(),
-            config.secret_token.clone(),
-            to_app_tx,
-            from_app_rx,
-        );
+        app.load_breakpoint_file();
+    }
+    app.step_mode = step;
+    app.headless_timeout = headless_timeout.map(Duration::from_secs);
+    if headless {
+        app.headless_format = headless_format;
+    }
 
-        tokio::time::sleep(Duration::from_millis(500)).await;
+    if dump_facts && !app.replay_mode {
+        match Config::get_config_dir() {
+            Ok(dir) => app.facts_dump_dir = Some(dir.join("facts")),
+            Err(e) => eprintln!("Failed to resolve facts dump directory: {}", e),
+        }
+    }
 
-        if !ansible_args.is_empty() {
-            spawn_ansible(&ansible_args, verbose, &config);
+    app.audit_log = audit_log;
+    app.ipc_replay = replay_ipc.is_some();
+    if let Ok(path) = std::env::var("PILOTEER_RECORD_IPC")
+        && !app.replay_mode
+        && !app.ipc_replay
+    {
+        let recorder = ansible_piloteer::ipc_record::IpcRecorder::create(path.as_ref())
+            .map_err(|e| anyhow::anyhow!("Failed to open IPC recording {}: {}", path, e))?;
+        app.ipc_recorder = Some(recorder);
+    }
+
+    if !app.replay_mode {
+        let (to_app_tx, to_app_rx) = mpsc::channel::<(ClientId, Message)>(100);
+        let clients = ansible_piloteer::ipc::ClientRegistry::default();
+        app.ipc_clients = Some(clients.clone());
+
+        let mut child = None;
+        if let Some(path) = &replay_ipc {
+            // The recording stands in for the socket and the playbook
+            let records = ansible_piloteer::ipc_record::load(path.as_ref())?;
+            ansible_piloteer::ipc_record::spawn_replay(records, to_app_tx);
+        } else {
+            ipc_handler::spawn_ipc_server(
+                config.socket_path.clone(),
+                config.bind_addr.clone(),
+                tls,
+                ansible_piloteer::ipc::AcceptedSecrets::from_config(&config),
+                ansible_piloteer::ipc::AuthLimiter::from_config(&config),
+                std::time::Duration::from_secs(config.heartbeat_interval_secs),
+                to_app_tx,
+                clients.clone(),
+            );
+
+            tokio::time::sleep(Duration::from_millis(500)).await;
+
+            if !ansible_args.is_empty() {
+                let path = std::env::var_os("PATH");
+                let startup_error = if ansible_piloteer::execution::find_in_path(
+                    "ansible-playbook",
+                    path.as_deref(),
+                )
+                .is_none()
+                {
+                    Some(
+                        "ansible-playbook not found in PATH.\n\n\
+                             Install Ansible (e.g. `pipx install ansible-core` or your package \
+                             manager) or add its bin directory to PATH, then run Piloteer again."
+                            .to_string(),
+                    )
+                } else {
+                    match spawn_ansible(&ansible_args, verbose, &config, debug_files) {
+                        Ok(c) => {
+                            child = Some(c);
+                            None
+                        }
+                        Err(e) => Some(format!("Failed to start ansible-playbook: {}", e)),
+                    }
+                };
+                if let Some(err) = startup_error {
+                    if headless {
+                        // Nothing will connect: still write the report, then
+                        // fail through main so tracing is shut down
+                        cleanup(&mut terminal, headless, app, report_path, false, strict).await?;
+                        anyhow::bail!(err);
+                    }
+                    app.startup_error = Some(err);
+                }
+            }
         }
 
         let mut to_app_rx = to_app_rx;
         let final_app = run_app(&mut terminal, app, &mut to_app_rx, headless, auto_analyze).await?;
-        cleanup(&mut terminal, headless, final_app, report_path).await
+        shut_down_playbook(&clients, &mut to_app_rx, child).await;
+        cleanup(
+            &mut terminal,
+            headless,
+            final_app,
+            report_path,
+            archive,
+            strict,
+        )
+        .await
     } else {
-        let (_, mut dummy_rx) = mpsc::channel::<Message>(1);
-        let final_app = run_app(&mut terminal, app, &mut dummy_rx, headless, auto_analyze).await?;
-        cleanup(&mut terminal, headless, final_app, report_path).await
+        // Replay has no plugin connection; the channel only carries fresh
+        // AI analyses requested with --reanalyze.
+        let (reanalysis_tx, mut reanalysis_rx) = mpsc::channel::<Message>(10);
+        let (replay_tx, mut replay_rx) = mpsc::channel::<(ClientId, Message)>(10);
+        if reanalyze {
+            app.reanalyze = true;
+            app.reanalysis_tx = Some(reanalysis_tx);
+            tokio::spawn(async move {
+                while let Some(msg) = reanalysis_rx.recv().await {
+                    if replay_tx
+                        .send((ipc_handler::CONTROL_CLIENT, msg))
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+        }
+        let final_app = run_app(&mut terminal, app, &mut replay_rx, headless, auto_analyze).await?;
+        cleanup(
+            &mut terminal,
+            headless,
+            final_app,
+            report_path,
+            archive,
+            strict,
+        )
+        .await
+    }
+}
+
+/// Time the plugin gets to acknowledge an abort, and then the playbook to
+/// exit, before it is killed.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(3);
+
+/// On quit, let connected plugins fail their play cleanly before the
+/// `ansible-playbook` we started is killed. Clients that are already gone
+/// don't delay the exit.
+async fn shut_down_playbook(
+    clients: &ansible_piloteer::ipc::ClientRegistry,
+    from_clients: &mut mpsc::Receiver<(ClientId, Message)>,
+    child: Option<tokio::process::Child>,
+) {
+    let had_clients = !clients.is_empty();
+    let acknowledged =
+        had_clients && ipc_handler::abort_clients(clients, from_clients, SHUTDOWN_GRACE).await;
+    let Some(mut child) = child else {
+        return;
+    };
+    if acknowledged {
+        let _ = tokio::time::timeout(SHUTDOWN_GRACE, child.wait()).await;
+    }
+    if let Ok(None) = child.try_wait() {
+        let _ = child.kill().await;
     }
 }
 
-fn spawn_ansible(ansible_args: &[String], verbose: u8, config: &Config) {
+fn spawn_ansible(
+    ansible_args: &[String],
+    verbose: u8,
+    config: &Config,
+    debug_files: bool,
+) -> io::Result<tokio::process::Child> {
     use tokio::process::Command;
     let mut cmd = Command::new("ansible-playbook");
     if verbose > 0 {
@@ -446,22 +1101,49 @@ fn spawn_ansible(ansible_args: &[String], verbose: u8, config: &Config) {
         .join("strategies");
     cmd.env("ANSIBLE_STRATEGY_PLUGINS", plugin_path);
     if let Some(addr) = &config.bind_addr {
-        cmd.env("PILOTEER_SOCKET", addr);
+        cmd.env(
+            "PILOTEER_SOCKET",
+            ansible_piloteer::ipc::local_connect_addr(addr),
+        );
     } else {
         cmd.env("PILOTEER_SOCKET", &config.socket_path);
     }
-    if let Some(secret) = &config.secret_token {
+    let secrets = ansible_piloteer::ipc::AcceptedSecrets::from_config(config);
+    if let Some(secret) = secrets.launch_secret() {
         cmd.env("PILOTEER_SECRET", secret);
     }
-    let log = std::fs::File::create("ansible_child.log")
-        .unwrap_or_else(|_| std::fs::File::create("/dev/null").unwrap());
-    let log_err = log.try_clone().unwrap();
-    cmd.stdout(log)
-        .stderr(log_err)
-        .stdin(std::process::Stdio::null());
-    if let Err(e) = cmd.spawn() {
-        eprintln!("Failed to spawn ansible-playbook: {}", e);
+    if config.bind_addr.is_some()
+        && let Some(cert) = &config.tls_cert_path
+    {
+        cmd.env("PILOTEER_TLS", "1");
+        // A self-signed certificate is its own CA; a CA-signed one needs
+        // PILOTEER_TLS_CA set in our environment, which the child inherits
+        if std::env::var_os("PILOTEER_TLS_CA").is_none() {
+            cmd.env("PILOTEER_TLS_CA", cert);
+        }
     }
+    let cwd = std::env::current_dir().unwrap_or_default();
+    match ansible_piloteer::execution::open_child_log(&cwd, debug_files) {
+        Ok(Some((log, path))) => {
+            tracing::debug!(path = %path.display(), "Writing ansible-playbook output");
+            match log.try_clone() {
+                Ok(log_err) => cmd.stdout(log).stderr(log_err),
+                Err(_) => cmd.stdout(log).stderr(std::process::Stdio::null()),
+            };
+        }
+        Ok(None) => {
+            cmd.stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null());
+        }
+        Err(e) => {
+            tracing::debug!(error = %e, "Failed to open ansible-playbook log file");
+            cmd.stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null());
+        }
+    }
+    cmd.stdin(std::process::Stdio::null());
+    tracing::debug!(args = ?ansible_args, "Spawning ansible-playbook");
+    cmd.spawn()
 }
 
 async fn cleanup(
@@ -469,58 +1151,79 @@ async fn cleanup(
     headless: bool,
     app: App,
     report_path: Option<String>,
-) -> Result<()> {
+    archive: bool,
+    strict: bool,
+) -> Result<i32> {
     if !headless {
         execute!(io::stdout(), crossterm::event::DisableMouseCapture)?;
         ratatui::restore();
     }
 
-    if !app.replay_mode
-        && let Ok(config_dir) = Config::get_config_dir()
-    {
-        let archive_dir = config_dir.join("archive");
-        std::fs::create_dir_all(&archive_dir).ok();
-        let filename = format!(
-            "session_{}.json.gz",
-            chrono::Utc::now().format("%Y%m%d_%H%M%S")
-        );
-        let path = archive_dir.join(&filename);
-        match ansible_piloteer::session::Session::from_app(&app).save(&path.to_string_lossy()) {
-            Ok(_) => println!("Session archived to: {}", path.display()),
-            Err(e) => eprintln!("Failed to archive session: {}", e),
+    // Replays reproduce an old run: nothing new to archive, and only live
+    // runs report their outcome
+    let replayed = app.replay_mode || app.ipc_replay;
+    if archive && !replayed {
+        // A read-only config dir shouldn't fail the run or skip the report
+        match archive_session(&app) {
+            Ok(path) => ipc_handler::note(&app, format!("Session archived to: {}", path.display())),
+            Err(e) => {
+                tracing::warn!(error = %format!("{:#}", e), "Failed to archive session");
+                eprintln!("Warning: failed to archive session: {:#}", e);
+            }
         }
     }
 
-    print_drift_summary(&app.history);
+    ipc_handler::note(&app, drift_summary(&app.history));
 
     if let Some(path) = report_path {
         generate_report(&app, &path);
     }
 
-    Ok(())
+    Ok(if replayed { 0 } else { app.exit_code(strict) })
 }
 
-fn print_drift_summary(history: &[TaskHistory]) {
-    println!("\n--- Drift Summary ---");
+fn archive_session(app: &App) -> Result<std::path::PathBuf> {
+    let archive_dir = Config::get_config_dir()?.join("archive");
+    std::fs::create_dir_all(&archive_dir)
+        .with_context(|| format!("cannot create {}", archive_dir.display()))?;
+    let filename = format!(
+        "session_{}.json.gz",
+        chrono::Utc::now().format("%Y%m%d_%H%M%S")
+    );
+    let path = archive_dir.join(&filename);
+    ansible_piloteer::session::Session::from_app(app).save(&path.to_string_lossy())?;
+    Ok(path)
+}
+
+fn drift_summary(history: &[TaskHistory]) -> String {
+    let mut out = String::from("\n--- Drift Summary ---\n");
     let changed: Vec<_> = history.iter().filter(|t| t.changed).collect();
     if changed.is_empty() {
-        println!("No changes detected.");
+        out.push_str("No changes detected.");
     } else {
-        println!("The following tasks modified the system state:");
+        out.push_str("The following tasks modified the system state:\n");
         for t in &changed {
-            println!(" - {} [Task: {}]", t.host, t.name);
+            out.push_str(&format!(" - {} [Task: {}]\n", t.host, t.name));
         }
-        println!("Total Drift: {} tasks changed.", changed.len());
+        out.push_str(&format!("Total Drift: {} tasks changed.", changed.len()));
+    }
+    let skipped = history.iter().filter(|t| t.skipped).count();
+    if skipped > 0 {
+        out.push_str(&format!(
+            "\nSkipped: {} tasks (condition not met).",
+            skipped
+        ));
     }
+    out
 }
 
 fn generate_report(app: &App, path: &str) {
     use std::io::Write;
-    println!("Generating report at {}...", path);
+    ipc_handler::note(app, format!("Generating report at {}...", path));
     if path.ends_with(".json") {
         match std::fs::File::create(path) {
             Ok(mut f) => {
-                let json = serde_json::to_string_pretty(&app.history).unwrap_or_default();
+                let json = serde_json::to_string_pretty(&app.full_history()).unwrap_or_default();
                 if let Err(e) = f.write_all(json.as_bytes()) {
                     eprintln!("Failed to write JSON report: {}", e);
                 }
@@ -531,8 +1234,16 @@ fn generate_report(app: &App, path: &str) {
         if let Err(e) = ansible_piloteer::report::ReportGenerator::new(app).save_to_file(path) {
             eprintln!("Failed to write Markdown report: {}", e);
         }
+    } else if path.ends_with(".html") {
+        if let Err(e) = ansible_piloteer::report::ReportGenerator::new(app).save_html(path) {
+            eprintln!("Failed to write HTML report: {}", e);
+        }
+    } else if path.ends_with(".folded") {
+        if let Err(e) = app.span_timings.write_folded(path) {
+            eprintln!("Failed to write folded-stack timings: {}", e);
+        }
     } else {
-        eprintln!("Unsupported report format. Use .json or .md");
+        eprintln!("Unsupported report format. Use .json, .md, .html or .folded");
     }
 }
 
@@ -541,7 +1252,7 @@ fn generate_report(app: &App, path: &str) {
 async fn run_app(
     terminal: &mut Option<DefaultTerminal>,
     mut app: App,
-    ipc_rx: &mut mpsc::Receiver<Message>,
+    ipc_rx: &mut mpsc::Receiver<(ClientId, Message)>,
     headless: bool,
     auto_analyze: bool,
 ) -> Result<App> {
@@ -558,8 +1269,17 @@ async fn run_app(
     });
 
     let (ai_tx, mut ai_rx) = mpsc::channel::<anyhow::Result<ansible_piloteer::ai::ChatMessage>>(10);
+    let mut ipc_closed = false;
+    let (pilot_tx, mut pilot_rx) = mpsc::channel::<String>(100);
+    app.pilot_stream_tx = Some(pilot_tx);
+    let (pilot_result_tx, mut pilot_result_rx) = mpsc::channel::<Message>(10);
+    app.pilot_result_tx = Some(pilot_result_tx);
+    let mut timeline_tick = tokio::time::interval(Duration::from_millis(100));
 
     loop {
+        if headless {
+            ipc_handler::check_headless_timeout(&mut app).await;
+        }
         if !headless && let Some(t) = terminal {
             t.draw(|f| ui::draw(f, &mut app))?;
         }
@@ -568,14 +1288,23 @@ async fn run_app(
         }
 
         app.update_velocity();
-        let ipc_done = app.ipc_tx.is_none();
+        app.check_stall(std::time::Instant::now());
+        let ipc_done = ipc_closed || (app.replay_mode && app.reanalysis_tx.is_none());
 
         tokio::select! {
             Some(res) = ai_rx.recv() => handle_ai_response(&mut app, res),
 
+            Some(chunk) = pilot_rx.recv() => app.pilot_stream.push_str(&chunk),
+
+            Some(msg) = pilot_result_rx.recv() => {
+                ipc_handler::handle_message(&mut app, msg, headless, auto_analyze).await;
+            }
+
             msg_opt = ipc_rx.recv(), if !ipc_done => match msg_opt {
-                Some(msg) => {
-                    ipc_handler::handle_message(&mut app, msg, headless, auto_analyze).await;
+                Some((client, msg)) => {
+                    app.record_ipc(client, &msg);
+                    ipc_handler::handle_client_message(&mut app, client, msg, headless, auto_analyze)
+                        .await;
                 }
                 None => {
                     if headless {
@@ -587,6 +1316,7 @@ async fn run_app(
                         );
                         app.ipc_tx = None;
                     }
+                    ipc_closed = true;
                 }
             },
 
@@ -597,6 +1327,8 @@ async fn run_app(
                 }
             },
 
+            _ = timeline_tick.tick(), if app.timeline.is_some() => app.advance_timeline(),
+
             _ = tokio::time::sleep(Duration::from_millis(250)) => {},
         }
     }
@@ -606,14 +1338,26 @@ async fn run_app(
 
 fn handle_ai_response(app: &mut App, res: anyhow::Result<ansible_piloteer::ai::ChatMessage>) {
     app.chat_loading = false;
+    app.collect_ai_metrics();
     let msg = match res {
         Ok(m) => m,
-        Err(e) => ansible_piloteer::ai::ChatMessage {
-            role: "system".to_string(),
-            content: format!("Error: {}", e),
-            collapsed: false,
-        },
+        Err(e) => {
+            if e.downcast_ref::<ansible_piloteer::quota::QuotaExceeded>()
+                .is_some()
+                && let Some(client) = &app.ai_client
+            {
+                let reset_in = client.get_quota_status().reset_in;
+                app.mark_quota_exhausted(reset_in);
+            }
+            ansible_piloteer::ai::ChatMessage {
+                role: "system".to_string(),
+                content: format!("Error: {}", e),
+                collapsed: false,
+                reply_to: None,
+            }
+        }
     };
+    app.take_snippet_reply(&msg);
     app.chat_history.push(msg);
     if app.chat_auto_scroll {
         app.chat_scroll = app.chat_history.len().saturating_sub(1) as u16;
@@ -83,6 +83,25 @@ fn register_tools(core: &rs_fast_mcp::server::core::FastMCPServer) -> Result<()>
     core.add_tool(status_tool)
         .map_err(|e| anyhow::anyhow!("{}", e))?;
 
+    // piloteer_task_detail — Full result of one task in a session
+    let detail_tool = Tool::new(
+        "piloteer_task_detail",
+        "Get the verbose result (or error) of a single task in a Piloteer session.",
+    )
+    .add_parameter("input", "string", "Path to session file")
+    .add_parameter("task", "string", "Task name")
+    .add_parameter(
+        "host",
+        "string",
+        "Host the task ran on (optional, needed if it ran on several)",
+    )
+    .with_handler(Box::new(|_ctx, args| {
+        Box::pin(async move { tool_task_detail(args).await })
+            as Pin<Box<dyn Future<Output = Result<ToolResult, FastMCPError>> + Send>>
+    }));
+    core.add_tool(detail_tool)
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
     // piloteer_run — Start ansible-playbook execution
     let run_tool = Tool::new(
         "piloteer_run",
@@ -116,6 +135,43 @@ fn register_tools(core: &rs_fast_mcp::server::core::FastMCPServer) -> Result<()>
     core.add_tool(inspect_tool)
         .map_err(|e| anyhow::anyhow!("{}", e))?;
 
+    // piloteer_resume — Resume a paused live session
+    let resume_tool = Tool::new(
+        "piloteer_resume",
+        "Resume a live Piloteer session paused at a breakpoint or failure.",
+    )
+    .add_parameter("action", "string", "One of: proceed, retry, continue")
+    .add_parameter(
+        "socket",
+        "string",
+        "Controller socket path or host:port (default: /tmp/piloteer.sock)",
+    )
+    .add_parameter(
+        "secret",
+        "string",
+        "Handshake secret (optional, defaults to PILOTEER_SECRET)",
+    )
+    .with_handler(Box::new(|_ctx, args| {
+        Box::pin(async move { tool_resume(args).await })
+            as Pin<Box<dyn Future<Output = Result<ToolResult, FastMCPError>> + Send>>
+    }));
+    core.add_tool(resume_tool)
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    // piloteer_breakpoint — Pre-seed breakpoints for the next TUI run
+    let breakpoint_tool = Tool::new(
+        "piloteer_breakpoint",
+        "Add, remove or list task breakpoints that Piloteer loads at startup.",
+    )
+    .add_parameter("action", "string", "One of: add, remove, list")
+    .add_parameter("task", "string", "Task name (required for add/remove)")
+    .with_handler(Box::new(|_ctx, args| {
+        Box::pin(async move { tool_breakpoint(args).await })
+            as Pin<Box<dyn Future<Output = Result<ToolResult, FastMCPError>> + Send>>
+    }));
+    core.add_tool(breakpoint_tool)
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
     Ok(())
 }
 
@@ -188,6 +244,44 @@ fn register_resources(core: &rs_fast_mcp::server::core::FastMCPServer) -> Result
     core.add_resource_template(logs_template, logs_handler)
         .map_err(|e| anyhow::anyhow!("{}", e))?;
 
+    // Static resource: recent AI interactions from ai_history.jsonl
+    let history_resource = Resource {
+        uri: "piloteer://ai-history".to_string(),
+        base_metadata: BaseMetadata {
+            name: "AI History".to_string(),
+            title: Some("Recent AI Interactions".to_string()),
+        },
+        description: Some(format!(
+            "The most recent {} AI prompts, responses and token counts",
+            AI_HISTORY_DEFAULT_LIMIT
+        )),
+        mime_type: Some("application/json".to_string()),
+        annotations: None,
+        size: None,
+        icons: None,
+        tags: None,
+    };
+    let history_handler: Arc<ResourceReadHandler> = Arc::new(Box::new(|uri, _ctx| {
+        Box::pin(async move { resource_ai_history(&uri).await })
+            as Pin<Box<dyn Future<Output = Result<Vec<ResourceContents>, FastMCPError>> + Send>>
+    }));
+    core.add_resource(history_resource, Some(history_handler.clone()))
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    // Template: AI history with a query string, e.g. `?limit=10`. Static
+    // resources only match exactly, so the query needs its own template.
+    let history_template = ResourceTemplate {
+        uri_template: "piloteer://ai-history{query}".to_string(),
+        name: "AI History (limited)".to_string(),
+        description: Some(
+            "Recent AI interactions; append ?limit=N to change the count".to_string(),
+        ),
+        mime_type: Some("application/json".to_string()),
+        annotations: None,
+    };
+    core.add_resource_template(history_template, history_handler)
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
     Ok(())
 }
 
@@ -300,6 +394,68 @@ async fn tool_status(args: Value) -> Result<ToolResult, FastMCPError> {
     Ok(text_result(summary))
 }
 
+async fn tool_task_detail(args: Value) -> Result<ToolResult, FastMCPError> {
+    let input = args
+        .get("input")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| err("Missing 'input' argument".to_string()))?;
+    let task = args
+        .get("task")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| err("Missing 'task' argument".to_string()))?;
+    let host = args.get("host").and_then(|v| v.as_str());
+
+    let session = crate::session::Session::load(input)
+        .map_err(|e| err(format!("Failed to load session: {}", e)))?;
+
+    task_detail(&session.history, task, host).map(text_result)
+}
+
+/// Pretty JSON of the matching task's verbose result, falling back to its
+/// error. When the name matches on several hosts and none was given, the
+/// hosts are listed instead so the caller can pick one. Retries of the same
+/// task on a host resolve to the latest attempt.
+fn task_detail(
+    history: &[crate::app::TaskHistory],
+    task: &str,
+    host: Option<&str>,
+) -> Result<String, FastMCPError> {
+    let matches: Vec<_> = history
+        .iter()
+        .filter(|t| t.name == task && host.is_none_or(|h| t.host == h))
+        .collect();
+    let Some(entry) = matches.last() else {
+        return Err(match host {
+            Some(h) => err(format!("No task '{}' on host '{}'", task, h)),
+            None => err(format!("No task named '{}'", task)),
+        });
+    };
+
+    let mut hosts: Vec<&str> = matches.iter().map(|t| t.host.as_str()).collect();
+    hosts.sort();
+    hosts.dedup();
+    if hosts.len() > 1 {
+        return Ok(format!(
+            "Task '{}' ran on {} hosts; pass 'host' with one of: {}",
+            task,
+            hosts.len(),
+            hosts.join(", ")
+        ));
+    }
+
+    let detail = match (&entry.verbose_result, &entry.error) {
+        (Some(result), _) => result.0.clone(),
+        (None, Some(error)) => serde_json::json!({ "error": error }),
+        (None, None) => {
+            return Ok(format!(
+                "Task '{}' on {} has no recorded output",
+                task, entry.host
+            ));
+        }
+    };
+    serde_json::to_string_pretty(&detail).map_err(|e| err(format!("Serialization error: {}", e)))
+}
+
 async fn tool_run(args: Value) -> Result<ToolResult, FastMCPError> {
     let playbook = args
         .get("playbook")
@@ -330,6 +486,82 @@ async fn tool_run(args: Value) -> Result<ToolResult, FastMCPError> {
     Ok(text_result(text))
 }
 
+/// Map a `piloteer_resume` action name to the IPC message it sends.
+fn resume_message(action: &str) -> Option<crate::ipc::Message> {
+    use crate::ipc::Message;
+    match action.to_ascii_lowercase().as_str() {
+        "proceed" => Some(Message::Proceed),
+        "retry" => Some(Message::Retry),
+        "continue" => Some(Message::Continue),
+        _ => None,
+    }
+}
+
+async fn tool_resume(args: Value) -> Result<ToolResult, FastMCPError> {
+    use crate::ipc::{IpcConnection, Message};
+
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| err("Missing 'action' argument".to_string()))?;
+    let msg = resume_message(action).ok_or_else(|| {
+        err(format!(
+            "Unknown action '{}' (expected proceed, retry or continue)",
+            action
+        ))
+    })?;
+    let socket = args
+        .get("socket")
+        .and_then(|v| v.as_str())
+        .unwrap_or("/tmp/piloteer.sock");
+    let token = args
+        .get("secret")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .or_else(|| std::env::var("PILOTEER_SECRET").ok());
+
+    let mut conn = IpcConnection::connect(socket)
+        .await
+        .map_err(|e| err(format!("Cannot reach Piloteer at {}: {}", socket, e)))?;
+    conn.send(&Message::Handshake {
+        token,
+        label: Some("mcp".to_string()),
+        control: true,
+    })
+    .await
+    .map_err(|e| err(format!("Handshake failed: {}", e)))?;
+
+    // The controller acknowledges control handshakes; silence means it
+    // rejected the secret or has no playbook attached.
+    let ack = tokio::time::timeout(std::time::Duration::from_secs(5), conn.receive()).await;
+    match ack {
+        Ok(Ok(Some(Message::Handshake { control: true, .. }))) => {}
+        _ => {
+            return Err(err(format!(
+                "Piloteer at {} did not accept the connection (no playbook attached or invalid secret)",
+                socket
+            )));
+        }
+    }
+
+    conn.send(&msg)
+        .await
+        .map_err(|e| err(format!("Failed to send {:?}: {}", msg, e)))?;
+    // The controller says whether the command applied to the paused task
+    let reply = tokio::time::timeout(std::time::Duration::from_secs(10), conn.receive()).await;
+    match reply {
+        Ok(Ok(Some(Message::ControlReply {
+            applied: true,
+            detail,
+        }))) => Ok(text_result(format!("{} to {}", detail, socket))),
+        Ok(Ok(Some(Message::ControlReply { detail, .. }))) => Err(err(detail)),
+        _ => Err(err(format!(
+            "Piloteer at {} did not confirm {:?}",
+            socket, msg
+        ))),
+    }
+}
+
 async fn tool_inspect(args: Value) -> Result<ToolResult, FastMCPError> {
     let session_id = args
         .get("session_id")
@@ -431,6 +663,53 @@ async fn resource_session_data(
 
 // ── Helpers ─────────────────────────────────────────────────────────
 
+/// Entries returned by `piloteer://ai-history` without a `?limit=`.
+const AI_HISTORY_DEFAULT_LIMIT: usize = 50;
+
+async fn resource_ai_history(uri: &str) -> Result<Vec<ResourceContents>, FastMCPError> {
+    let limit = ai_history_limit(uri)?;
+    let config_dir = crate::config::Config::get_config_dir()
+        .map_err(|e| err(format!("Config dir error: {}", e)))?;
+
+    let entries = read_ai_history(&config_dir.join("ai_history.jsonl"), limit);
+    let text = serde_json::to_string_pretty(&entries).unwrap_or_else(|_| "[]".to_string());
+    Ok(vec![ResourceContents {
+        uri: uri.to_string(),
+        mime_type: Some("application/json".to_string()),
+        text: Some(text),
+        blob: None,
+    }])
+}
+
+fn ai_history_limit(uri: &str) -> Result<usize, FastMCPError> {
+    let Some((_, query)) = uri.split_once('?') else {
+        return Ok(AI_HISTORY_DEFAULT_LIMIT);
+    };
+    match query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("limit="))
+    {
+        Some(n) => n
+            .parse()
+            .map_err(|_| err(format!("Invalid limit '{}' in {}", n, uri))),
+        None => Ok(AI_HISTORY_DEFAULT_LIMIT),
+    }
+}
+
+/// The last `limit` entries of an `ai_history.jsonl` file, oldest first.
+/// A missing file reads as empty and unparsable lines are skipped.
+fn read_ai_history(path: &std::path::Path, limit: usize) -> Vec<Value> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let entries: Vec<Value> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let skip = entries.len().saturating_sub(limit);
+    entries.into_iter().skip(skip).collect()
+}
+
 fn extract_session_id(uri: &str) -> Result<String, FastMCPError> {
     // URI format: ansible://sessions/{id}/facts (or vars, logs)
     // parts: ["ansible:", "", "sessions", "{id}", "field"]
@@ -460,6 +739,56 @@ fn resolve_session_path(session_id: &str) -> Result<String, FastMCPError> {
     Ok(path.to_string_lossy().to_string())
 }
 
+async fn tool_breakpoint(args: Value) -> Result<ToolResult, FastMCPError> {
+    let path = crate::config::Config::get_breakpoints_path()
+        .map_err(|e| err(format!("Config dir error: {}", e)))?;
+    let breakpoints = update_breakpoints(&path, &args)?;
+    let text = serde_json::to_string_pretty(&breakpoints).unwrap_or_default();
+    Ok(text_result(text))
+}
+
+/// Apply the `action` in `args` to the breakpoints file at `path` and
+/// return the resulting set.
+fn update_breakpoints(
+    path: &std::path::Path,
+    args: &Value,
+) -> Result<std::collections::BTreeSet<String>, FastMCPError> {
+    use crate::config::Config;
+
+    let action = args
+        .get("action")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| err("Missing 'action' argument".to_string()))?;
+    let task = args.get("task").and_then(|v| v.as_str());
+
+    let mut breakpoints = Config::load_breakpoints(path)
+        .map_err(|e| err(format!("Failed to read breakpoints: {:#}", e)))?;
+    let changed = match action.to_lowercase().as_str() {
+        "list" => false,
+        "add" | "remove" => {
+            let task = task
+                .filter(|t| !t.is_empty())
+                .ok_or_else(|| err(format!("Missing 'task' argument for {}", action)))?;
+            if action.eq_ignore_ascii_case("add") {
+                breakpoints.insert(task.to_string())
+            } else {
+                breakpoints.remove(task)
+            }
+        }
+        other => {
+            return Err(err(format!(
+                "Unknown action '{}' (expected add, remove or list)",
+                other
+            )));
+        }
+    };
+    if changed {
+        Config::save_breakpoints(path, &breakpoints)
+            .map_err(|e| err(format!("Failed to write breakpoints: {}", e)))?;
+    }
+    Ok(breakpoints)
+}
+
 // ── Tests ───────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -496,6 +825,122 @@ mod tests {
         assert_eq!(result.content.len(), 1);
     }
 
+    #[test]
+    fn test_resume_message() {
+        use crate::ipc::Message;
+        assert!(matches!(resume_message("proceed"), Some(Message::Proceed)));
+        assert!(matches!(resume_message("Retry"), Some(Message::Retry)));
+        assert!(matches!(
+            resume_message("continue"),
+            Some(Message::Continue)
+        ));
+        assert!(resume_message("abort").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_resume_unreachable_socket() {
+        let args = serde_json::json!({
+            "action": "proceed",
+            "socket": "/nonexistent/piloteer.sock"
+        });
+        assert!(tool_resume(args).await.is_err());
+    }
+
+    #[test]
+    fn test_update_breakpoints() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("breakpoints.json");
+        let run = |args: Value| update_breakpoints(&path, &args);
+
+        assert!(
+            run(serde_json::json!({"action": "list"}))
+                .unwrap()
+                .is_empty()
+        );
+        run(serde_json::json!({"action": "add", "task": "Install nginx"})).unwrap();
+        let set = run(serde_json::json!({"action": "add", "task": "Restart nginx"})).unwrap();
+        assert_eq!(set.len(), 2);
+
+        let set = run(serde_json::json!({"action": "remove", "task": "Install nginx"})).unwrap();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec!["Restart nginx"]);
+        // What the TUI reads at startup
+        let on_disk = crate::config::Config::load_breakpoints(&path).unwrap();
+        assert!(on_disk.contains("Restart nginx"));
+
+        assert!(run(serde_json::json!({"action": "add"})).is_err());
+        assert!(run(serde_json::json!({"action": "toggle", "task": "x"})).is_err());
+    }
+
+    #[test]
+    fn test_ai_history_limit() {
+        assert_eq!(
+            ai_history_limit("piloteer://ai-history").unwrap(),
+            AI_HISTORY_DEFAULT_LIMIT
+        );
+        assert_eq!(
+            ai_history_limit("piloteer://ai-history?limit=3").unwrap(),
+            3
+        );
+        assert!(ai_history_limit("piloteer://ai-history?limit=many").is_err());
+    }
+
+    #[test]
+    fn test_read_ai_history() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("ai_history.jsonl");
+        assert!(read_ai_history(&path, 10).is_empty());
+
+        let lines: Vec<String> = (0..5)
+            .map(|i| serde_json::json!({"prompt": format!("q{}", i), "tokens": i}).to_string())
+            .collect();
+        std::fs::write(&path, lines.join("\n") + "\nnot json\n").unwrap();
+
+        let recent = read_ai_history(&path, 2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0]["prompt"], "q3");
+        assert_eq!(recent[1]["prompt"], "q4");
+    }
+
+    fn history_task(name: &str, host: &str, result: Option<Value>) -> crate::app::TaskHistory {
+        crate::app::TaskHistory {
+            failed: result.is_none(),
+            duration: 1.0,
+            error: result.is_none().then(|| "boom".to_string()),
+            verbose_result: result.map(crate::execution::ExecutionDetails::new),
+            ..crate::app::TaskHistory::new(name, host)
+        }
+    }
+
+    #[test]
+    fn test_task_detail() {
+        let history = vec![
+            history_task("Install", "web1", Some(serde_json::json!({"rc": 1}))),
+            history_task("Install", "web1", Some(serde_json::json!({"rc": 0}))),
+            history_task("Install", "web2", None),
+            history_task("Restart", "web1", Some(serde_json::json!({"rc": 0}))),
+        ];
+
+        let ambiguous = task_detail(&history, "Install", None).unwrap();
+        assert!(ambiguous.contains("web1, web2"));
+
+        // Latest attempt wins
+        let detail = task_detail(&history, "Install", Some("web1")).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(&detail).unwrap()["rc"], 0);
+
+        let failed = task_detail(&history, "Install", Some("web2")).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&failed).unwrap()["error"],
+            "boom"
+        );
+
+        assert!(
+            task_detail(&history, "Restart", None)
+                .unwrap()
+                .contains("\"rc\"")
+        );
+        assert!(task_detail(&history, "Missing", None).is_err());
+    }
+
     #[tokio::test]
     async fn test_list_sessions_no_archive() {
         let result = tool_list_sessions().await;
@@ -0,0 +1,327 @@
+//! `config validate`: preflight checks of the environment before a run,
+//! printed as a checklist with remediation hints.
+
+use crate::config::Config;
+use std::fmt::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CheckStatus {
+    Pass,
+    /// Works, but probably not as intended
+    Warn,
+    /// The run will fail or lose features; `config validate` exits non-zero
+    Fail,
+}
+
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Run every check against the current environment.
+pub fn validate(profile: Option<&str>) -> Vec<Check> {
+    let strategy = std::env::var("ANSIBLE_STRATEGY").ok();
+    let plugins = std::env::var("ANSIBLE_STRATEGY_PLUGINS").ok();
+    let mut checks = vec![
+        check_strategy(strategy.as_deref()),
+        check_strategy_plugins(plugins.as_deref()),
+    ];
+    match Config::load(profile) {
+        Ok(config) => {
+            checks.insert(0, Check::pass("Config", "loaded"));
+            checks.push(check_ai_provider(&config));
+            checks.push(check_listener(&config));
+        }
+        Err(e) => checks.insert(
+            0,
+            Check::fail(
+                "Config",
+                format!("{:#}", e),
+                "fix the config file or PILOTEER_* variable named above",
+            ),
+        ),
+    }
+    checks
+}
+
+pub fn check_strategy(strategy: Option<&str>) -> Check {
+    const NAME: &str = "ANSIBLE_STRATEGY";
+    match strategy {
+        Some("piloteer") => Check::pass(NAME, "piloteer"),
+        Some(other) => Check::fail(
+            NAME,
+            format!("set to '{}'", other),
+            "export ANSIBLE_STRATEGY=piloteer",
+        ),
+        None => Check::warn(
+            NAME,
+            "not set",
+            "ansible-piloteer sets it for the playbooks it runs; export \
+             ANSIBLE_STRATEGY=piloteer to run ansible-playbook directly",
+        ),
+    }
+}
+
+/// `plugins` is the raw `ANSIBLE_STRATEGY_PLUGINS` value (colon-separated).
+/// Without it, Ansible only finds the plugin if `init` installed it.
+pub fn check_strategy_plugins(plugins: Option<&str>) -> Check {
+    const NAME: &str = "Strategy plugin";
+    match plugins {
+        Some(paths) => {
+            match paths
+                .split(':')
+                .filter(|p| !p.is_empty())
+                .map(|p| Path::new(p).join("piloteer.py"))
+                .find(|p| std::fs::File::open(p).is_ok())
+            {
+                Some(found) => Check::pass(NAME, found.display().to_string()),
+                None => Check::fail(
+                    NAME,
+                    format!(
+                        "no readable piloteer.py in ANSIBLE_STRATEGY_PLUGINS={}",
+                        paths
+                    ),
+                    "point ANSIBLE_STRATEGY_PLUGINS at ansible_plugin/strategies, or unset it \
+                     and run `ansible-piloteer init`",
+                ),
+            }
+        }
+        None => match crate::plugin::plugin_path() {
+            Ok(path) if path.is_file() => Check::pass(NAME, path.display().to_string()),
+            _ => Check::warn(
+                NAME,
+                "not installed",
+                "run `ansible-piloteer init` (the TUI also installs it on startup)",
+            ),
+        },
+    }
+}
+
+/// The provider `AiClient` would pick must have credentials. AI being
+/// unconfigured altogether only warns: runs work without it.
+pub fn check_ai_provider(config: &Config) -> Check {
+    const NAME: &str = "AI provider";
+    if config.no_ai {
+        return Check::pass(NAME, "disabled (no_ai)");
+    }
+    let explicit = config.provider.is_some();
+    let provider = config.provider.as_deref().unwrap_or("openai");
+    let (ready, missing) = match provider {
+        "openai" => (
+            config.openai_api_key.is_some() || config.api_base != "https://api.openai.com/v1",
+            "OPENAI_API_KEY (or PILOTEER_API_BASE for a local endpoint)",
+        ),
+        "anthropic" => (
+            config.anthropic_api_key.is_some(),
+            "PILOTEER_ANTHROPIC_API_KEY",
+        ),
+        "google" => (
+            config.google_api_key.is_some() || config.auth_token.is_some(),
+            "PILOTEER_GOOGLE_API_KEY or `ansible-piloteer auth login`",
+        ),
+        "vertex" => (
+            config.vertex_project_id.is_some(),
+            "PILOTEER_VERTEX_PROJECT_ID",
+        ),
+        other => {
+            return Check::fail(
+                NAME,
+                format!("unknown provider '{}'", other),
+                "set provider to openai, anthropic, google or vertex",
+            );
+        }
+    };
+    match (ready, explicit) {
+        (true, _) => Check::pass(NAME, format!("{} ({})", provider, config.model)),
+        (false, true) => Check::fail(
+            NAME,
+            format!("{} selected but no credentials found", provider),
+            format!("set {}", missing),
+        ),
+        (false, false) => Check::warn(
+            NAME,
+            "no credentials; AI features will be off",
+            format!("set {}, or --no-ai to silence this", missing),
+        ),
+    }
+}
+
+/// The IPC endpoint the plugin connects to must be bindable.
+pub fn check_listener(config: &Config) -> Check {
+    if let Some(addr) = &config.bind_addr {
+        const NAME: &str = "Bind address";
+        return match std::net::TcpListener::bind(addr) {
+            Ok(_) => match crate::ipc::server_tls(config) {
+                Ok(Some(_)) => Check::pass(NAME, format!("{} (TLS)", addr)),
+                Ok(None) => Check::warn(
+                    NAME,
+                    format!("{} (plaintext)", addr),
+                    "the secret token and task vars cross the network unencrypted; set \
+                     tls_cert_path and tls_key_path",
+                ),
+                Err(e) => Check::fail(
+                    NAME,
+                    format!("{:#}", e),
+                    "point tls_cert_path/tls_key_path at a readable PEM certificate and key",
+                ),
+            },
+            Err(e) => Check::fail(
+                NAME,
+                format!("{}: {}", addr, e),
+                "pick a free port or stop the process using it (PILOTEER_BIND_ADDR)",
+            ),
+        };
+    }
+
+    const NAME: &str = "Socket path";
+    let socket = Path::new(&config.socket_path);
+    if socket.exists() && std::os::unix::net::UnixStream::connect(socket).is_ok() {
+        return Check::warn(
+            NAME,
+            format!("{}: another Piloteer is listening", config.socket_path),
+            "stop it, or set PILOTEER_SOCKET_PATH to a different path",
+        );
+    }
+    let dir = match socket.parent() {
+        Some(d) if !d.as_os_str().is_empty() => d,
+        _ => Path::new("."),
+    };
+    let probe = dir.join(format!(".piloteer-validate-{}.sock", std::process::id()));
+    match std::os::unix::net::UnixListener::bind(&probe) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            Check::pass(NAME, config.socket_path.clone())
+        }
+        Err(e) => Check::fail(
+            NAME,
+            format!("cannot create sockets in {}: {}", dir.display(), e),
+            "set PILOTEER_SOCKET_PATH to a path in a writable directory",
+        ),
+    }
+}
+
+/// Checklist lines: ✓ pass, ! warning, ✗ failure, each problem with a hint.
+pub fn render(checks: &[Check]) -> String {
+    let mut out = String::new();
+    for check in checks {
+        let mark = match check.status {
+            CheckStatus::Pass => "✓",
+            CheckStatus::Warn => "!",
+            CheckStatus::Fail => "✗",
+        };
+        let _ = writeln!(out, "{} {}: {}", mark, check.name, check.detail);
+        if let Some(hint) = &check.hint {
+            let _ = writeln!(out, "    → {}", hint);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategy_checks() {
+        assert_eq!(check_strategy(Some("piloteer")).status, CheckStatus::Pass);
+        assert_eq!(check_strategy(Some("linear")).status, CheckStatus::Fail);
+        assert_eq!(check_strategy(None).status, CheckStatus::Warn);
+
+        let dir = tempfile::tempdir().unwrap();
+        let paths = format!("/nonexistent:{}", dir.path().display());
+        let missing = check_strategy_plugins(Some(&paths));
+        assert_eq!(missing.status, CheckStatus::Fail);
+        assert!(missing.hint.unwrap().contains("ansible_plugin/strategies"));
+
+        std::fs::write(dir.path().join("piloteer.py"), "# plugin").unwrap();
+        let found = check_strategy_plugins(Some(&paths));
+        assert_eq!(found.status, CheckStatus::Pass);
+        assert!(found.detail.ends_with("piloteer.py"));
+    }
+
+    #[test]
+    fn test_ai_provider_check() {
+        let mut config = Config::default();
+        // Nothing configured: runs still work, so only a warning
+        assert_eq!(check_ai_provider(&config).status, CheckStatus::Warn);
+
+        config.provider = Some("anthropic".to_string());
+        let check = check_ai_provider(&config);
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.hint.unwrap().contains("PILOTEER_ANTHROPIC_API_KEY"));
+
+        config.anthropic_api_key = Some("key".to_string());
+        assert_eq!(check_ai_provider(&config).status, CheckStatus::Pass);
+
+        config.provider = Some("bogus".to_string());
+        assert_eq!(check_ai_provider(&config).status, CheckStatus::Fail);
+
+        config.no_ai = true;
+        assert_eq!(check_ai_provider(&config).status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_listener_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = Config {
+            socket_path: dir.path().join("p.sock").display().to_string(),
+            ..Config::default()
+        };
+        assert_eq!(check_listener(&config).status, CheckStatus::Pass);
+        // The probe socket is cleaned up
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+
+        config.socket_path = "/nonexistent/dir/p.sock".to_string();
+        assert_eq!(check_listener(&config).status, CheckStatus::Fail);
+
+        config.bind_addr = Some("127.0.0.1:0".to_string());
+        assert_eq!(check_listener(&config).status, CheckStatus::Warn);
+        config.tls_cert_path = Some("/nonexistent/cert.pem".to_string());
+        config.tls_key_path = Some("/nonexistent/key.pem".to_string());
+        assert_eq!(check_listener(&config).status, CheckStatus::Fail);
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tls");
+        config.tls_cert_path = Some(format!("{}/server.pem", fixtures));
+        config.tls_key_path = Some(format!("{}/server.key", fixtures));
+        assert_eq!(check_listener(&config).status, CheckStatus::Pass);
+
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        config.bind_addr = Some(taken.local_addr().unwrap().to_string());
+        assert_eq!(check_listener(&config).status, CheckStatus::Fail);
+
+        let text = render(&[Check::pass("A", "ok"), Check::fail("B", "broken", "fix it")]);
+        assert_eq!(text, "✓ A: ok\n✗ B: broken\n    → fix it\n");
+    }
+}
@@ -12,6 +12,7 @@ pub fn register_functions(runtime: &mut Runtime) {
     runtime.register_function("avg", Box::new(Avg::new()));
     runtime.register_function("min", Box::new(Min::new()));
     runtime.register_function("max", Box::new(Max::new()));
+    runtime.register_function("duration_stats", Box::new(DurationStats::new()));
     // New utility functions
     runtime.register_function("replace", Box::new(Replace::new()));
     runtime.register_function("split", Box::new(Split::new()));
@@ -310,6 +311,86 @@ impl Function for Max {
     }
 }
 
+/// `duration_stats(tasks)`: count/total/min/max/avg/p50/p95 over the numeric
+/// `duration` fields of an array of task-like objects. Items without one are
+/// skipped; an empty input yields all zeros.
+pub struct DurationStats;
+
+impl Default for DurationStats {
+    fn default() -> Self {
+        Self
+    }
+}
+
+impl DurationStats {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Function for DurationStats {
+    fn evaluate(&self, args: &[Rcvar], _ctx: &mut Context<'_>) -> Result<Rcvar, JmespathError> {
+        let items = args[0].as_array().ok_or_else(|| {
+            JmespathError::new(
+                "duration_stats",
+                0,
+                ErrorReason::Parse("Argument must be an array".to_string()),
+            )
+        })?;
+
+        let mut durations: Vec<f64> = items
+            .iter()
+            .filter_map(|item| match &**item {
+                Variable::Object(map) => match map.get("duration").map(|d| &**d) {
+                    Some(Variable::Number(n)) => n.as_f64(),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+        durations.sort_by(|a, b| a.total_cmp(b));
+
+        let count = durations.len();
+        let total: f64 = durations.iter().sum();
+        // Nearest-rank percentile
+        let percentile = |p: f64| {
+            if count == 0 {
+                0.0
+            } else {
+                let rank = ((p * count as f64).ceil() as usize).clamp(1, count);
+                durations[rank - 1]
+            }
+        };
+        let stats = [
+            ("count", count as f64),
+            ("total", total),
+            ("min", durations.first().copied().unwrap_or(0.0)),
+            ("max", durations.last().copied().unwrap_or(0.0)),
+            (
+                "avg",
+                if count == 0 {
+                    0.0
+                } else {
+                    total / count as f64
+                },
+            ),
+            ("p50", percentile(0.5)),
+            ("p95", percentile(0.95)),
+        ];
+
+        let mut result = BTreeMap::new();
+        for (key, value) in stats {
+            let number = if key == "count" {
+                serde_json::Number::from(count)
+            } else {
+                serde_json::Number::from_f64(value).unwrap_or_else(|| 0.into())
+            };
+            result.insert(key.to_string(), Rc::new(Variable::Number(number)));
+        }
+        Ok(Rc::new(Variable::Object(result)))
+    }
+}
+
 // Custom filter implementation
 // Redefine to hold String
 pub struct CustomFilter {
@@ -508,6 +589,30 @@ impl Function for Matches {
     }
 }
 
+/// Check that `query` compiles, without evaluating it.
+pub fn validate_query(query: &str) -> Result<(), String> {
+    let mut runtime = Runtime::new();
+    runtime.register_builtin_functions();
+    register_functions(&mut runtime);
+    runtime
+        .compile(query)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// JMESPath truthiness: `false`, `null` and empty strings, arrays and objects
+/// are false; everything else is true.
+pub fn is_truthy(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => false,
+        serde_json::Value::Bool(b) => *b,
+        serde_json::Value::String(s) => !s.is_empty(),
+        serde_json::Value::Array(a) => !a.is_empty(),
+        serde_json::Value::Object(o) => !o.is_empty(),
+        serde_json::Value::Number(_) => true,
+    }
+}
+
 pub fn run_query(query: &str, data: &serde_json::Value) -> Result<serde_json::Value, String> {
     let mut runtime = Runtime::new();
     runtime.register_builtin_functions();
@@ -529,6 +634,62 @@ pub fn run_query(query: &str, data: &serde_json::Value) -> Result<serde_json::Va
     serde_json::to_value(&*result).map_err(|e| e.to_string())
 }
 
+/// Render an array of flat objects as CSV: a header from the union of keys,
+/// then one row per object. Object keys come out alphabetically, so columns
+/// are the first object's keys by name, followed by any new keys from later
+/// objects in the order they appear. Missing keys and nulls are empty cells.
+pub fn to_csv(value: &serde_json::Value) -> Result<String, String> {
+    const HINT: &str = "flatten it first, e.g. `task_history[*].{name: name, host: host}`";
+    let rows = value
+        .as_array()
+        .ok_or_else(|| format!("CSV output needs an array of objects; {}", HINT))?;
+
+    let mut headers: Vec<&str> = Vec::new();
+    for row in rows {
+        let obj = row
+            .as_object()
+            .ok_or_else(|| format!("CSV output needs an array of objects; {}", HINT))?;
+        for (key, val) in obj {
+            if val.is_array() || val.is_object() {
+                return Err(format!(
+                    "Field '{}' is nested and can't be a CSV cell; {}",
+                    key, HINT
+                ));
+            }
+            if !headers.contains(&key.as_str()) {
+                headers.push(key);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let header: Vec<String> = headers.iter().map(|h| csv_field(h)).collect();
+    out.push_str(&header.join(","));
+    out.push('\n');
+    for row in rows {
+        let cells: Vec<String> = headers
+            .iter()
+            .map(|h| match row.get(*h) {
+                None | Some(serde_json::Value::Null) => String::new(),
+                Some(serde_json::Value::String(s)) => csv_field(s),
+                Some(other) => csv_field(&other.to_string()),
+            })
+            .collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -556,4 +717,68 @@ mod tests {
         let result = run_query("hosts[?status == 'failed'].name", &data).unwrap();
         assert_eq!(result, json!(["h2"]));
     }
+
+    #[test]
+    fn test_duration_stats() {
+        let data = json!({
+            "history": [
+                {"name": "a", "duration": 4.0},
+                {"name": "b", "duration": 1.0},
+                {"name": "c"},
+                {"name": "d", "duration": "slow"},
+                {"name": "e", "duration": 3.0},
+                {"name": "f", "duration": 2.0}
+            ]
+        });
+        let result = run_query("duration_stats(history)", &data).unwrap();
+        assert_eq!(
+            result,
+            json!({
+                "count": 4,
+                "total": 10.0,
+                "min": 1.0,
+                "max": 4.0,
+                "avg": 2.5,
+                "p50": 2.0,
+                "p95": 4.0
+            })
+        );
+
+        let empty = run_query("duration_stats(`[]`)", &json!({})).unwrap();
+        assert_eq!(
+            empty,
+            json!({"count": 0, "total": 0.0, "min": 0.0, "max": 0.0, "avg": 0.0, "p50": 0.0, "p95": 0.0})
+        );
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let data = json!([
+            {"name": "Install", "host": "web1", "changed": true},
+            {"name": "Say \"hi\", twice", "host": "web2", "duration": 1.5},
+            {"name": "Skip", "host": null}
+        ]);
+        assert_eq!(
+            to_csv(&data).unwrap(),
+            "changed,host,name,duration\n\
+             true,web1,Install,\n\
+             ,web2,\"Say \"\"hi\"\", twice\",1.5\n\
+             ,,Skip,\n"
+        );
+
+        assert_eq!(to_csv(&json!([])).unwrap(), "\n");
+        assert!(
+            to_csv(&json!({"a": 1}))
+                .unwrap_err()
+                .contains("array of objects")
+        );
+        assert!(
+            to_csv(&json!([1, 2]))
+                .unwrap_err()
+                .contains("array of objects")
+        );
+        let err = to_csv(&json!([{"a": {"b": 1}}])).unwrap_err();
+        assert!(err.contains("'a' is nested"));
+        assert!(err.contains("flatten"));
+    }
 }
@@ -2,9 +2,61 @@ use crate::config::Config;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+/// Blended (input + output averaged) USD per 1k tokens, keyed by model-name
+/// prefix. The longest matching prefix wins; unknown models cost nothing.
+pub const MODEL_RATES: &[(&str, f64)] = &[
+    ("gpt-3.5", 0.001),
+    ("gpt-4o-mini", 0.000375),
+    ("gpt-4o", 0.00625),
+    ("gpt-4", 0.02),
+    ("gpt", 0.00625),
+    ("claude-3-haiku", 0.00075),
+    ("claude-3-5-haiku", 0.0024),
+    ("claude-3-opus", 0.045),
+    ("claude", 0.009),
+    ("gemini-1.5-flash", 0.0002),
+    ("gemini-2.0-flash", 0.00025),
+    ("gemini", 0.003125),
+];
+
+/// Effective USD per 1k tokens for `model`. Prefixes from `overrides`
+/// (the `model_rates` config map) take precedence over [`MODEL_RATES`].
+pub fn rate_per_1k(model: &str, overrides: Option<&HashMap<String, f64>>) -> f64 {
+    let model = model.to_lowercase();
+    let longest = |rates: &mut dyn Iterator<Item = (&str, f64)>| {
+        rates
+            .filter(|(prefix, _)| model.starts_with(&prefix.to_lowercase()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, rate)| rate)
+    };
+    overrides
+        .and_then(|o| longest(&mut o.iter().map(|(k, v)| (k.as_str(), *v))))
+        .or_else(|| longest(&mut MODEL_RATES.iter().copied()))
+        .unwrap_or(0.0)
+}
+
+/// Estimated USD cost of `tokens` on `model`.
+pub fn estimate_cost(tokens: u32, model: &str, overrides: Option<&HashMap<String, f64>>) -> f64 {
+    (tokens as f64) / 1000.0 * rate_per_1k(model, overrides)
+}
+
+/// Error returned by [`QuotaTracker::check_limit`] once a daily limit is hit,
+/// so callers can tell quota exhaustion apart from provider failures.
+#[derive(Debug)]
+pub struct QuotaExceeded(pub String);
+
+impl std::fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for QuotaExceeded {}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct QuotaTracker {
     pub usage_today_tokens: u32,
@@ -50,7 +102,8 @@ impl QuotaTracker {
         Ok(Config::get_config_dir()?.join("quota.json"))
     }
 
-    fn check_reset(&mut self) {
+    /// Start a new day's counts once the UTC date has changed.
+    pub fn check_reset(&mut self) {
         let now = Utc::now();
         // Reset if it's a different day
         if self.last_reset.date_naive() != now.date_naive() {
@@ -60,10 +113,15 @@ impl QuotaTracker {
         }
     }
 
-    pub fn add_usage(&mut self, tokens: u32, model: &str) -> Result<()> {
+    pub fn add_usage(
+        &mut self,
+        tokens: u32,
+        model: &str,
+        overrides: Option<&HashMap<String, f64>>,
+    ) -> Result<()> {
         self.check_reset();
 
-        let cost = self.calculate_cost(tokens, model);
+        let cost = estimate_cost(tokens, model, overrides);
 
         self.usage_today_tokens += tokens;
         self.cost_today_usd += cost;
@@ -74,42 +132,25 @@ impl QuotaTracker {
         Ok(())
     }
 
-    fn calculate_cost(&self, tokens: u32, model: &str) -> f64 {
-        // Rough estimate based on OpenAI pricing (Input/Output split needed for accuracy,
-        // but for now we treat all as blended or just inputs)
-        // GPT-4 Turbo: $10/1M input, $30/1M output. Avg ~$20/1M = $0.00002 per token
-        // GPT-3.5 Turbo: $0.50/1M input, $1.50/1M output. Avg ~$1/1M = $0.000001 per token
-
-        let rate = if model.contains("gpt-4") {
-            0.00002
-        } else if model.contains("gpt-3.5") {
-            0.000001
-        } else {
-            0.0 // Local/Other
-        };
-
-        (tokens as f64) * rate
-    }
-
     pub fn check_limit(&self, config: &Config) -> Result<()> {
         if let Some(limit) = config.quota_limit_tokens
             && self.usage_today_tokens >= limit
         {
-            return Err(anyhow::anyhow!(
+            return Err(QuotaExceeded(format!(
                 "Daily token quota exceeded ({} / {})",
-                self.usage_today_tokens,
-                limit
-            ));
+                self.usage_today_tokens, limit
+            ))
+            .into());
         }
 
         if let Some(limit) = config.quota_limit_usd
             && self.cost_today_usd >= limit
         {
-            return Err(anyhow::anyhow!(
+            return Err(QuotaExceeded(format!(
                 "Daily cost quota exceeded (${:.2} / ${:.2})",
-                self.cost_today_usd,
-                limit
-            ));
+                self.cost_today_usd, limit
+            ))
+            .into());
         }
 
         Ok(())
@@ -130,3 +171,33 @@ impl QuotaTracker {
             .unwrap_or(std::time::Duration::from_secs(0))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cost_differs_by_model() {
+        let gpt4 = estimate_cost(10_000, "gpt-4-turbo-preview", None);
+        let mini = estimate_cost(10_000, "gpt-4o-mini", None);
+        let claude = estimate_cost(10_000, "claude-3-5-sonnet-20241022", None);
+        let gemini = estimate_cost(10_000, "gemini-1.5-flash-002", None);
+        assert!((gpt4 - 0.2).abs() < 1e-9);
+        // The longer "gpt-4o-mini" prefix wins over "gpt-4"
+        assert!((mini - 0.00375).abs() < 1e-9);
+        assert!((claude - 0.09).abs() < 1e-9);
+        assert!((gemini - 0.002).abs() < 1e-9);
+        assert_eq!(estimate_cost(10_000, "llama3:8b", None), 0.0);
+    }
+
+    #[test]
+    fn test_rate_overrides() {
+        let mut overrides = HashMap::new();
+        overrides.insert("claude".to_string(), 0.5);
+        overrides.insert("llama3".to_string(), 0.1);
+        assert_eq!(rate_per_1k("Claude-3-Opus", Some(&overrides)), 0.5);
+        assert_eq!(rate_per_1k("llama3:8b", Some(&overrides)), 0.1);
+        // Models without an override keep the built-in rate
+        assert_eq!(rate_per_1k("gpt-4", Some(&overrides)), 0.02);
+    }
+}
@@ -4,16 +4,40 @@ use rustyline::DefaultEditor;
 use rustyline::error::ReadlineError;
 
 use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Queries kept in the history file; older entries are dropped on save.
+const HISTORY_SIZE: usize = 1000;
+
+/// `~/.config/ansible-piloteer/repl_history`, if the config dir is usable.
+fn history_path() -> Option<PathBuf> {
+    crate::config::Config::get_config_dir()
+        .ok()
+        .map(|dir| dir.join("repl_history"))
+}
+
+/// Only queries go into history; meta-commands like `.help` would just
+/// crowd out the expressions worth recalling.
+fn should_persist(line: &str) -> bool {
+    !line.is_empty() && !line.starts_with('.')
+}
 
 pub fn run(
     session: &Session,
     filters: Option<&HashMap<String, String>>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut rl = DefaultEditor::new()?;
-    // Optional: Load history
-    // if rl.load_history("history.txt").is_err() {
-    //     println!("No previous history.");
-    // }
+    let rl_config = rustyline::Config::builder()
+        .max_history_size(HISTORY_SIZE)?
+        .auto_add_history(false)
+        .build();
+    let mut rl = DefaultEditor::with_config(rl_config)?;
+    let history = history_path();
+    if let Some(path) = &history
+        && path.exists()
+        && let Err(e) = rl.load_history(path)
+    {
+        eprintln!("Warning: could not load REPL history: {}", e);
+    }
 
     let mut runtime = Runtime::new();
     runtime.register_builtin_functions();
@@ -45,13 +69,16 @@ pub fn run(
     println!("Interactive Query Mode. Type '.help' for commands.");
 
     let mut format = "pretty-json";
+    let mut last_query: Option<String> = None;
 
     loop {
         let readline = rl.readline(">> ");
         match readline {
             Ok(line) => {
                 let line = line.trim();
-                rl.add_history_entry(line)?;
+                if should_persist(line) {
+                    let _ = rl.add_history_entry(line);
+                }
 
                 if line.is_empty() {
                     continue;
@@ -79,6 +106,7 @@ pub fn run(
                             println!("  .pretty         Set output to pretty JSON (default)");
                             println!("  .yaml           Set output to YAML");
                             println!("  .templates      Show available query templates");
+                            println!("  .save <file>    Write the last query result to a file");
                             println!("  .help           Show this help");
                             println!("\nAvailable Functions:");
                             println!("  group_by(arr, expr)  Group array items by expression");
@@ -88,6 +116,9 @@ pub fn run(
                             println!("  avg(arr)             Average of numeric values");
                             println!("  min(arr)             Minimum value in array");
                             println!("  max(arr)             Maximum value in array");
+                            println!(
+                                "  duration_stats(arr)  count/total/min/max/avg/p50/p95 of .duration"
+                            );
                         }
                         ".templates" => {
                             println!("\nQuery Templates:");
@@ -106,6 +137,19 @@ pub fn run(
                                 "   task_history[?error != null].{{name: name, error: error}}"
                             );
                         }
+                        cmd if cmd == ".save" || cmd.starts_with(".save ") => {
+                            let path = cmd[".save".len()..].trim();
+                            if path.is_empty() {
+                                eprintln!("Usage: .save <file>");
+                            } else if let Some(query) = &last_query {
+                                match save_query(&runtime, &root_var, query, format, path) {
+                                    Ok(()) => println!("Saved result of '{}' to {}", query, path),
+                                    Err(e) => eprintln!("Save failed: {}", e),
+                                }
+                            } else {
+                                eprintln!("No successful query to save yet");
+                            }
+                        }
                         _ => println!("Unknown command: {}", line),
                     }
                     continue;
@@ -114,12 +158,10 @@ pub fn run(
                 // Execute Query
                 match runtime.compile(line) {
                     Ok(expr) => match expr.search(&root_var) {
-                        Ok(result) => match format {
-                            "json" => println!("{}", serde_json::to_string(&result)?),
-                            "pretty-json" => println!("{}", serde_json::to_string_pretty(&result)?),
-                            "yaml" => println!("{}", serde_yaml::to_string(&result)?),
-                            _ => println!("{:?}", result),
-                        },
+                        Ok(result) => {
+                            println!("{}", format_result(&result, format)?);
+                            last_query = Some(line.to_string());
+                        }
                         Err(e) => eprintln!("Evaluation error: {}", e),
                     },
                     Err(e) => eprintln!("Compilation error: {}", e),
@@ -139,6 +181,89 @@ pub fn run(
             }
         }
     }
-    // rl.save_history("history.txt")?;
+    if let Some(path) = &history
+        && let Err(e) = rl.append_history(path)
+    {
+        eprintln!("Warning: could not save REPL history: {}", e);
+    }
+    Ok(())
+}
+
+/// Render a query result in one of the REPL output modes.
+fn format_result(
+    result: &jmespath::Variable,
+    format: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(match format {
+        "json" => serde_json::to_string(result)?,
+        "pretty-json" => serde_json::to_string_pretty(result)?,
+        "yaml" => serde_yaml::to_string(result)?,
+        _ => format!("{:?}", result),
+    })
+}
+
+/// Re-run `query` and write its result to `path` in the current format.
+fn save_query(
+    runtime: &Runtime,
+    root: &jmespath::Variable,
+    query: &str,
+    format: &str,
+    path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let result = runtime.compile(query)?.search(root)?;
+    let mut out = format_result(&result, format)?;
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    std::fs::write(path, out)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_persist_skips_meta_commands() {
+        assert!(should_persist("task_history[?failed]"));
+        assert!(!should_persist(".help"));
+        assert!(!should_persist(".exit"));
+        assert!(!should_persist(""));
+    }
+
+    #[test]
+    fn test_save_query_uses_output_format() {
+        let mut runtime = Runtime::new();
+        runtime.register_builtin_functions();
+        let root = jmespath::Variable::from_serializable(serde_json::json!({"hosts": ["a", "b"]}))
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let json_path = dir.path().join("out.json");
+        save_query(
+            &runtime,
+            &root,
+            "hosts",
+            "json",
+            json_path.to_str().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&json_path).unwrap(),
+            "[\"a\",\"b\"]\n"
+        );
+
+        let yaml_path = dir.path().join("out.yaml");
+        save_query(
+            &runtime,
+            &root,
+            "hosts",
+            "yaml",
+            yaml_path.to_str().unwrap(),
+        )
+        .unwrap();
+        assert_eq!(std::fs::read_to_string(&yaml_path).unwrap(), "- a\n- b\n");
+
+        assert!(save_query(&runtime, &root, "hosts[", "json", "unused").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::app::App;
+use crate::app::{App, HostStatus};
 use chrono::Local;
 use std::fs::File;
 use std::io::Write;
@@ -12,6 +12,13 @@ impl<'a> ReportGenerator<'a> {
         Self { app }
     }
 
+    /// Hosts by name, so report tables are stable between runs.
+    fn sorted_hosts(&self) -> Vec<&HostStatus> {
+        let mut hosts: Vec<&HostStatus> = self.app.hosts.values().collect();
+        hosts.sort_by(|a, b| a.name.cmp(&b.name));
+        hosts
+    }
+
     pub fn generate_markdown(&self) -> String {
         let mut md = String::new();
 
@@ -19,18 +26,26 @@ impl<'a> ReportGenerator<'a> {
         let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
         md.push_str("# Ansible Piloteer Execution Report\n\n");
         md.push_str(&format!("**Date:** {}\n\n", timestamp));
+        if let Some(note) = &self.app.session_note {
+            md.push_str(&format!("**Note:** {}\n\n", note));
+        }
 
         // 2. Host Summary
         md.push_str("## Host Summary\n\n");
         if self.app.hosts.is_empty() {
             md.push_str("_No host data captured._\n\n");
         } else {
-            md.push_str("| Host | OK | Changed | Failed |\n");
-            md.push_str("|---|---|---|---|\n");
-            for host in self.app.hosts.values() {
+            md.push_str("| Host | OK | Changed | Failed | Rescued | Skipped |\n");
+            md.push_str("|---|---|---|---|---|---|\n");
+            for host in self.sorted_hosts() {
                 md.push_str(&format!(
-                    "| {} | {} | {} | {} |\n",
-                    host.name, host.ok_tasks, host.changed_tasks, host.failed_tasks
+                    "| {} | {} | {} | {} | {} | {} |\n",
+                    host.name,
+                    host.ok_tasks,
+                    host.changed_tasks,
+                    host.failed_tasks,
+                    host.rescued_tasks,
+                    host.skipped_tasks
                 ));
             }
             md.push('\n');
@@ -38,11 +53,16 @@ impl<'a> ReportGenerator<'a> {
 
         // 3. Task History
         md.push_str("## Task Execution History\n\n");
-        if self.app.history.is_empty() {
+        let history = self.app.full_history();
+        if history.is_empty() {
             md.push_str("_No tasks executed._\n\n");
         } else {
-            for (i, task) in self.app.history.iter().enumerate() {
-                let status = if task.failed {
+            for (i, task) in history.iter().enumerate() {
+                let status = if task.skipped {
+                    "SKIPPED"
+                } else if task.rescued {
+                    "RESCUED"
+                } else if task.failed {
                     "FAILED"
                 } else if task.changed {
                     "CHANGED"
@@ -50,7 +70,11 @@ impl<'a> ReportGenerator<'a> {
                     "OK"
                 };
 
-                let icon = if task.failed {
+                let icon = if task.skipped {
+                    "⊘"
+                } else if task.rescued {
+                    "🛟"
+                } else if task.failed {
                     "❌"
                 } else if task.changed {
                     "⚠️"
@@ -75,8 +99,11 @@ impl<'a> ReportGenerator<'a> {
                 }
 
                 if let Some(analysis) = &task.analysis {
-                    md.push_str("\n#### 🤖 AI Analysis\n");
-                    md.push_str(&format!("> {}\n\n", analysis.analysis));
+                    md.push_str("\n#### 🤖 Pilot Analysis\n");
+                    for line in analysis.analysis.lines() {
+                        md.push_str(&format!("> {}\n", line));
+                    }
+                    md.push('\n');
                     if let Some(fix) = &analysis.fix {
                         md.push_str("**Suggested Fix:**\n");
                         md.push_str(&format!("- Variable: `{}`\n", fix.key));
@@ -88,7 +115,30 @@ impl<'a> ReportGenerator<'a> {
             }
         }
 
-        // 4. Play Recap (if available in logs or stored)
+        // 4. Ansible warnings and deprecations
+        let summary = self.app.warning_summary();
+        if !summary.is_empty() {
+            let (warnings, deprecations) = self.app.warning_counts();
+            md.push_str("## Warnings & Deprecations\n\n");
+            md.push_str(&format!(
+                "{} warning(s), {} deprecation(s)\n\n",
+                warnings, deprecations
+            ));
+            md.push_str("| Kind | Count | Message | Tasks |\n");
+            md.push_str("|---|---|---|---|\n");
+            for entry in summary {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} |\n",
+                    entry.kind.label(),
+                    entry.count,
+                    entry.message.replace('|', "\\|").replace('\n', " "),
+                    entry.tasks.join(", ")
+                ));
+            }
+            md.push('\n');
+        }
+
+        // 5. Play Recap (if available in logs or stored)
         // We have app.play_recap now
         if let Some(recap) = &self.app.play_recap {
             md.push_str("## Play Recap\n\n");
@@ -106,4 +156,205 @@ impl<'a> ReportGenerator<'a> {
         file.write_all(content.as_bytes())?;
         Ok(())
     }
+
+    /// Self-contained HTML report (inline CSS, no external assets) with one
+    /// collapsible section per task.
+    pub fn generate_html(&self) -> String {
+        let mut html = String::new();
+        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str("<title>Ansible Piloteer Execution Report</title>\n");
+        html.push_str(HTML_STYLE);
+        html.push_str("</head>\n<body>\n");
+        html.push_str("<h1>Ansible Piloteer Execution Report</h1>\n");
+        html.push_str(&format!("<p><b>Date:</b> {}</p>\n", timestamp));
+        if let Some(note) = &self.app.session_note {
+            html.push_str(&format!("<p><b>Note:</b> {}</p>\n", escape_html(note)));
+        }
+
+        // Host Summary
+        html.push_str("<h2>Host Summary</h2>\n");
+        if self.app.hosts.is_empty() {
+            html.push_str("<p><i>No host data captured.</i></p>\n");
+        } else {
+            html.push_str(
+                "<table>\n<tr><th>Host</th><th>OK</th><th>Changed</th><th>Failed</th><th>Rescued</th><th>Skipped</th></tr>\n",
+            );
+            for host in self.sorted_hosts() {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    escape_html(&host.name),
+                    host.ok_tasks,
+                    host.changed_tasks,
+                    host.failed_tasks,
+                    host.rescued_tasks,
+                    host.skipped_tasks
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        // Drift Summary
+        html.push_str("<h2>Drift Summary</h2>\n");
+        let history = self.app.full_history();
+        let changed: Vec<_> = history.iter().filter(|t| t.changed).collect();
+        if changed.is_empty() {
+            html.push_str("<p>No changes detected.</p>\n");
+        } else {
+            html.push_str("<ul>\n");
+            for t in &changed {
+                html.push_str(&format!(
+                    "<li>{} [Task: {}]</li>\n",
+                    escape_html(&t.host),
+                    escape_html(&t.name)
+                ));
+            }
+            html.push_str("</ul>\n");
+            html.push_str(&format!(
+                "<p>Total Drift: {} tasks changed.</p>\n",
+                changed.len()
+            ));
+        }
+
+        // Task History
+        html.push_str("<h2>Task Execution History</h2>\n");
+        if history.is_empty() {
+            html.push_str("<p><i>No tasks executed.</i></p>\n");
+        }
+        for (i, task) in history.iter().enumerate() {
+            let (status, class) = if task.skipped {
+                ("SKIPPED", "skipped")
+            } else if task.rescued {
+                ("RESCUED", "rescued")
+            } else if task.failed {
+                ("FAILED", "failed")
+            } else if task.changed {
+                ("CHANGED", "changed")
+            } else {
+                ("OK", "ok")
+            };
+            let open = if task.failed && !task.rescued {
+                " open"
+            } else {
+                ""
+            };
+            html.push_str(&format!(
+                "<details class=\"task\"{}>\n<summary><span class=\"badge {}\">{}</span> {}. {} <span class=\"host\">{}</span> <span class=\"dur\">{}</span></summary>\n",
+                open,
+                class,
+                status,
+                i + 1,
+                escape_html(&task.name),
+                escape_html(&task.host),
+                match task.started_at {
+                    Some(at) => format!("{} UTC · {:.2}s", at.format("%H:%M:%S"), task.duration),
+                    None => format!("{:.2}s", task.duration),
+                }
+            ));
+
+            if let Some(err) = &task.error {
+                html.push_str(&format!(
+                    "<p><b>Error:</b></p>\n<pre>{}</pre>\n",
+                    escape_html(err)
+                ));
+            }
+
+            if let Some(analysis) = &task.analysis {
+                html.push_str("<div class=\"analysis\">\n<h4>🤖 Pilot Analysis</h4>\n");
+                html.push_str(&format!("<p>{}</p>\n", escape_html(&analysis.analysis)));
+                if let Some(fix) = &analysis.fix {
+                    html.push_str(&format!(
+                        "<p><b>Suggested Fix:</b> <code>{}</code> = <code>{}</code></p>\n",
+                        escape_html(&fix.key),
+                        escape_html(&fix.value.to_string())
+                    ));
+                }
+                html.push_str("</div>\n");
+            }
+
+            if let Some(details) = &task.verbose_result {
+                let json = serde_json::to_string_pretty(details).unwrap_or_default();
+                html.push_str(&format!(
+                    "<details class=\"raw\">\n<summary>Result ({} bytes)</summary>\n<pre>{}</pre>\n</details>\n",
+                    json.len(),
+                    escape_html(&json)
+                ));
+            }
+
+            html.push_str("</details>\n");
+        }
+
+        // Ansible warnings and deprecations
+        let summary = self.app.warning_summary();
+        if !summary.is_empty() {
+            html.push_str("<h2>Warnings &amp; Deprecations</h2>\n");
+            html.push_str(
+                "<table>\n<tr><th>Kind</th><th>Count</th><th>Message</th><th>Tasks</th></tr>\n",
+            );
+            for entry in summary {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    entry.kind.label(),
+                    entry.count,
+                    escape_html(&entry.message),
+                    escape_html(&entry.tasks.join(", "))
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        if let Some(recap) = &self.app.play_recap {
+            html.push_str("<h2>Play Recap</h2>\n");
+            html.push_str(&format!(
+                "<pre>{}</pre>\n",
+                escape_html(&serde_json::to_string_pretty(recap).unwrap_or_default())
+            ));
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    pub fn save_html(&self, filename: &str) -> std::io::Result<()> {
+        let content = self.generate_html();
+        let mut file = File::create(filename)?;
+        file.write_all(content.as_bytes())?;
+        Ok(())
+    }
+}
+
+const HTML_STYLE: &str = r#"<style>
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2em auto; max-width: 1000px; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 4px 10px; text-align: left; }
+th { background: #f0f0f0; }
+details.task { border: 1px solid #ddd; border-radius: 4px; margin: 6px 0; padding: 6px 10px; }
+details.task > summary { cursor: pointer; font-weight: 600; }
+details.raw > summary { cursor: pointer; color: #555; }
+.badge { display: inline-block; min-width: 5.5em; text-align: center; border-radius: 3px; color: #fff; font-size: 0.8em; padding: 1px 6px; }
+.badge.ok { background: #2e7d32; }
+.badge.changed { background: #e6a700; }
+.badge.failed { background: #c62828; }
+.badge.rescued { background: #1565c0; }
+.badge.skipped { background: #757575; }
+.host, .dur { color: #666; font-weight: normal; }
+.analysis { background: #f5f3ff; border-left: 3px solid #7e57c2; padding: 4px 10px; }
+pre { background: #f6f8fa; padding: 8px; overflow-x: auto; white-space: pre-wrap; }
+</style>
+"#;
+
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
 }
@@ -0,0 +1,89 @@
+//! Search patterns shared by the log, inspector and JSON tree searches.
+//! Queries match case-insensitively as plain text; a `/re/` prefix makes the
+//! rest a regular expression.
+
+use regex::{Regex, RegexBuilder};
+
+/// Prefix that switches a search query to regex mode.
+pub const REGEX_PREFIX: &str = "/re/";
+
+#[derive(Debug, Clone)]
+pub struct SearchPattern {
+    regex: Regex,
+    /// Why a `/re/` query fell back to plain-text matching
+    error: Option<String>,
+}
+
+impl SearchPattern {
+    /// Compile `query`. An invalid regex falls back to matching its text
+    /// literally; `error()` then says why.
+    pub fn new(query: &str) -> Self {
+        let literal = |text: &str| {
+            RegexBuilder::new(&regex::escape(text))
+                .case_insensitive(true)
+                .build()
+                .expect("escaped pattern is valid")
+        };
+        match query.strip_prefix(REGEX_PREFIX) {
+            Some(expr) => match RegexBuilder::new(expr).case_insensitive(true).build() {
+                Ok(regex) => Self { regex, error: None },
+                Err(e) => Self {
+                    regex: literal(expr),
+                    error: Some(e.to_string()),
+                },
+            },
+            None => Self {
+                regex: literal(query),
+                error: None,
+            },
+        }
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// Byte ranges of every non-empty match in `text`.
+    pub fn match_ranges(&self, text: &str) -> Vec<(usize, usize)> {
+        self.regex
+            .find_iter(text)
+            .filter(|m| !m.is_empty())
+            .map(|m| (m.start(), m.end()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_pattern_modes() {
+        // Plain text: case-insensitive, regex metacharacters are literal
+        let plain = SearchPattern::new("B[0]");
+        assert!(plain.is_match("a.b[0]"));
+        assert!(!plain.is_match("b0"));
+        assert_eq!(plain.match_ranges("b[0] B[0]"), vec![(0, 4), (5, 9)]);
+
+        let re = SearchPattern::new("/re/failed.*timeout");
+        assert!(re.error().is_none());
+        assert!(re.is_match("Task FAILED after timeout"));
+        assert!(!re.is_match("timeout, then failed"));
+        assert_eq!(re.match_ranges("x failed: timeout"), vec![(2, 17)]);
+
+        // Ranges are byte offsets into the original text, even for non-ASCII
+        assert_eq!(
+            SearchPattern::new("/re/é+").match_ranges("caféé!"),
+            vec![(3, 7)]
+        );
+
+        // Invalid regex falls back to a literal match of the expression
+        let bad = SearchPattern::new("/re/(unclosed");
+        assert!(bad.error().is_some());
+        assert!(bad.is_match("an (unclosed paren"));
+    }
+}
@@ -9,8 +9,13 @@ use std::collections::VecDeque;
 use std::fs::File;
 use std::io::{Read, Write};
 
+/// Version of the session file layout written by this build. Files saved
+/// before versioning existed count as version 1.
+pub const SESSION_SCHEMA_VERSION: u32 = 2;
+
 #[derive(Serialize, Deserialize)]
 pub struct Session {
+    pub schema_version: u32,
     pub timestamp: DateTime<Utc>,
     pub history: Vec<TaskHistory>,
     pub logs: Vec<(String, Color)>,
@@ -19,25 +24,40 @@ pub struct Session {
     pub hosts: std::collections::HashMap<String, crate::app::HostStatus>,
     pub play_recap: Option<serde_json::Value>,
     pub unreachable_hosts: std::collections::HashSet<String>,
+    #[serde(default)]
+    pub session_note: Option<String>,
+    #[serde(default)]
+    pub warnings: Vec<crate::app::AnsibleWarning>,
 }
 
 impl Session {
     pub fn from_app(app: &App) -> Self {
         let logs: Vec<_> = app.logs.iter().cloned().collect();
         Self {
+            schema_version: SESSION_SCHEMA_VERSION,
             timestamp: Utc::now(),
-            history: app.history.clone(),
+            history: app.full_history(),
             logs,
             facts: app.facts.clone(),
             task_vars: app.task_vars.clone(),
             hosts: app.hosts.clone(),
             play_recap: app.play_recap.clone(),
             unreachable_hosts: app.unreachable_hosts.clone(),
+            session_note: app.session_note.clone(),
+            warnings: app.warnings.clone(),
         }
     }
 
+    /// Write the session as plain, pretty-printed JSON when `path` ends in
+    /// `.json`, and gzipped otherwise (the `.json.gz` archives).
     pub fn save(&self, path: &str) -> std::io::Result<()> {
         let file = File::create(path)?;
+        if path.ends_with(".json") {
+            let mut writer = std::io::BufWriter::new(file);
+            serde_json::to_writer_pretty(&mut writer, self)?;
+            // Dropping the writer would swallow a failed final write
+            return writer.flush();
+        }
         let mut encoder = GzEncoder::new(file, Compression::default());
         let json = serde_json::to_string(self)?;
         encoder.write_all(json.as_bytes())?;
@@ -45,22 +65,213 @@ impl Session {
         Ok(())
     }
 
+    /// Read a session saved either way, telling gzip from plain JSON by its
+    /// magic bytes rather than the file name, and upgrade it from older
+    /// schema versions.
     pub fn load(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let file = File::open(path)?;
-        let mut decoder = GzDecoder::new(file);
-        let mut json = String::new();
-        decoder.read_to_string(&mut json)?;
-        let session: Session = serde_json::from_str(&json)?;
-        Ok(session)
+        let bytes = std::fs::read(path)?;
+        let value: serde_json::Value = if bytes.starts_with(&[0x1f, 0x8b]) {
+            let mut json = String::new();
+            GzDecoder::new(bytes.as_slice()).read_to_string(&mut json)?;
+            serde_json::from_str(&json)?
+        } else {
+            serde_json::from_slice(&bytes)?
+        };
+        Self::from_value(value)
+    }
+
+    /// Deserialize a session of any supported schema version.
+    pub fn from_value(mut value: serde_json::Value) -> Result<Self, Box<dyn std::error::Error>> {
+        let version = value
+            .get("schema_version")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(1);
+        if version > SESSION_SCHEMA_VERSION as u64 {
+            return Err(format!(
+                "session schema version {} is newer than this build supports ({}); \
+                 upgrade ansible-piloteer to open it",
+                version, SESSION_SCHEMA_VERSION
+            )
+            .into());
+        }
+        if version < 2 {
+            migrate_v1(&mut value);
+        }
+        value["schema_version"] = SESSION_SCHEMA_VERSION.into();
+        Ok(serde_json::from_value(value)?)
     }
 
     pub fn restore_to_app(self, app: &mut App) {
+        app.host_last_task = self
+            .history
+            .iter()
+            .map(|t| (t.host.clone(), t.name.clone()))
+            .collect();
         app.history = self.history;
+        app.history_spill = None;
+        app.spill_cursor = 0;
         app.logs = VecDeque::from(self.logs);
         app.facts = self.facts;
         app.task_vars = self.task_vars;
         app.hosts = self.hosts;
         app.play_recap = self.play_recap;
         app.unreachable_hosts = self.unreachable_hosts;
+        app.session_note = self.session_note;
+        app.warnings = self.warnings;
+    }
+}
+
+/// Summary of one file in the session archive.
+#[derive(Debug, Clone)]
+pub struct ArchivedSession {
+    pub path: std::path::PathBuf,
+    pub timestamp: DateTime<Utc>,
+    pub tasks: usize,
+    pub failures: usize,
+}
+
+/// Contents of the session archive directory.
+#[derive(Debug, Default)]
+pub struct ArchiveListing {
+    /// Newest first
+    pub sessions: Vec<ArchivedSession>,
+    /// Files that failed to load, with the error
+    pub unreadable: Vec<(std::path::PathBuf, String)>,
+}
+
+/// Summarize every `session_*.json[.gz]` file in `dir`.
+pub fn list_archive(dir: &std::path::Path) -> std::io::Result<ArchiveListing> {
+    let mut sessions = Vec::new();
+    let mut unreadable = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        if !name.starts_with("session_") || !(name.ends_with(".json") || name.ends_with(".json.gz"))
+        {
+            continue;
+        }
+        match Session::load(&path.to_string_lossy()) {
+            Ok(s) => sessions.push(ArchivedSession {
+                timestamp: s.timestamp,
+                tasks: s.history.len(),
+                failures: s.history.iter().filter(|t| t.failed).count(),
+                path,
+            }),
+            Err(e) => unreadable.push((path, e.to_string())),
+        }
     }
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+    Ok(ArchiveListing {
+        sessions,
+        unreadable,
+    })
+}
+
+/// Sessions to delete from a newest-first list: everything past the newest
+/// `keep` AND, when given, older than `older_than`.
+pub fn select_prune(
+    sessions: &[ArchivedSession],
+    keep: usize,
+    older_than: Option<chrono::Duration>,
+    now: DateTime<Utc>,
+) -> Vec<&ArchivedSession> {
+    sessions
+        .iter()
+        .skip(keep)
+        .filter(|s| older_than.is_none_or(|age| now - s.timestamp > age))
+        .collect()
+}
+
+/// Parse an age such as `90s`, `30m`, `12h`, `7d` or `2w`.
+pub fn parse_age(s: &str) -> Result<chrono::Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let n: i64 = num
+        .parse()
+        .map_err(|_| format!("invalid age '{}': expected e.g. 7d or 12h", s))?;
+    let age = match unit {
+        "s" => chrono::Duration::try_seconds(n),
+        "m" => chrono::Duration::try_minutes(n),
+        "h" => chrono::Duration::try_hours(n),
+        "d" => chrono::Duration::try_days(n),
+        "w" => chrono::Duration::try_weeks(n),
+        _ => return Err(format!("invalid age '{}': unit must be s, m, h, d or w", s)),
+    };
+    age.ok_or_else(|| format!("invalid age '{}': too large", s))
+}
+
+/// v1 → v2: fill in the fields added before versioning (notes, warnings,
+/// rescue tracking and task timestamps) so nothing is left to chance.
+fn migrate_v1(value: &mut serde_json::Value) {
+    use serde_json::{Value, json};
+
+    let Some(session) = value.as_object_mut() else {
+        return;
+    };
+    session.entry("session_note").or_insert(Value::Null);
+    session.entry("warnings").or_insert(json!([]));
+    if let Some(history) = session.get_mut("history").and_then(Value::as_array_mut) {
+        for task in history.iter_mut().filter_map(Value::as_object_mut) {
+            task.entry("analysis").or_insert(Value::Null);
+            task.entry("rescued").or_insert(json!(false));
+            task.entry("started_at").or_insert(Value::Null);
+            task.entry("finished_at").or_insert(Value::Null);
+        }
+    }
+    if let Some(hosts) = session.get_mut("hosts").and_then(Value::as_object_mut) {
+        for host in hosts.values_mut().filter_map(Value::as_object_mut) {
+            host.entry("rescued_tasks").or_insert(json!(0));
+        }
+    }
+}
+
+/// Best-effort `ansible-playbook` selector for re-running the changed and
+/// failed part of a run. Ansible cannot run an arbitrary subset of tasks, so
+/// this starts at the first such task and limits the run to affected hosts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RerunHint {
+    pub start_at_task: String,
+    pub hosts: Vec<String>,
+    /// Every changed/failed task, in execution order, without duplicates
+    pub tasks: Vec<String>,
+}
+
+impl RerunHint {
+    pub fn from_history(history: &[TaskHistory]) -> Option<Self> {
+        let selected: Vec<&TaskHistory> =
+            history.iter().filter(|t| t.changed || t.failed).collect();
+        let start_at_task = selected.first()?.name.clone();
+
+        let mut hosts: Vec<String> = Vec::new();
+        let mut tasks: Vec<String> = Vec::new();
+        for t in selected {
+            if !hosts.contains(&t.host) {
+                hosts.push(t.host.clone());
+            }
+            if !tasks.contains(&t.name) {
+                tasks.push(t.name.clone());
+            }
+        }
+        hosts.sort();
+
+        Some(Self {
+            start_at_task,
+            hosts,
+            tasks,
+        })
+    }
+
+    /// Arguments to append to the original `ansible-playbook` command.
+    pub fn args(&self) -> String {
+        format!(
+            "--start-at-task {} --limit {}",
+            shell_quote(&self.start_at_task),
+            shell_quote(&self.hosts.join(","))
+        )
+    }
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
 }
@@ -0,0 +1,72 @@
+//! On-disk overflow for task history — keeps very long runs from holding every
+//! verbose result in memory.
+
+use crate::execution::ExecutionDetails;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// The bulky parts of a history entry: its verbose result and, for failures,
+/// the vars and facts it ran with.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpilledTask {
+    pub verbose_result: Option<ExecutionDetails>,
+    pub task_vars: Option<serde_json::Value>,
+    pub facts: Option<serde_json::Value>,
+}
+
+impl SpilledTask {
+    pub fn is_empty(&self) -> bool {
+        self.verbose_result.is_none() && self.task_vars.is_none() && self.facts.is_none()
+    }
+}
+
+/// Bulky parts of older history entries, appended to an anonymous temp file
+/// (removed automatically when dropped) and indexed by history position.
+#[derive(Debug)]
+pub struct HistorySpill {
+    file: File,
+    index: HashMap<usize, (u64, usize)>,
+}
+
+impl HistorySpill {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            file: tempfile::tempfile()?,
+            index: HashMap::new(),
+        })
+    }
+
+    pub fn store(&mut self, idx: usize, task: &SpilledTask) -> io::Result<()> {
+        let bytes = serde_json::to_vec(task)?;
+        let offset = self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(&bytes)?;
+        self.index.insert(idx, (offset, bytes.len()));
+        Ok(())
+    }
+
+    /// Read back what was spilled for history entry `idx`, if anything.
+    pub fn load(&self, idx: usize) -> io::Result<Option<SpilledTask>> {
+        let Some(&(offset, len)) = self.index.get(&idx) else {
+            return Ok(None);
+        };
+        let mut file = &self.file;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut buf = vec![0u8; len];
+        file.read_exact(&mut buf)?;
+        Ok(Some(serde_json::from_slice(&buf)?))
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        self.index.contains_key(&idx)
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+}
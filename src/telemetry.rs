@@ -4,15 +4,15 @@ use opentelemetry::{KeyValue, global};
 use opentelemetry_otlp::WithExportConfig;
 use opentelemetry_sdk::Resource;
 use opentelemetry_sdk::trace::{RandomIdGenerator, Sampler, TracerProvider};
+use std::time::Instant;
 
 use crate::config::Config;
 
-/// Initialize OpenTelemetry tracing with OTLP/Zipkin exporter
+/// Initialize OpenTelemetry tracing with the exporter picked by
+/// `config.otel_exporter`. Without an endpoint for it, tracing stays off.
 pub fn init_tracing(config: &Config) -> Result<()> {
-    // Only initialize if zipkin_endpoint is configured
-    let endpoint = match &config.zipkin_endpoint {
-        Some(ep) => ep,
-        None => return Ok(()), // Tracing disabled
+    let Some(exporter) = build_exporter(config)? else {
+        return Ok(()); // Tracing disabled
     };
 
     // Configure sampling based on sample_rate
@@ -24,13 +24,6 @@ pub fn init_tracing(config: &Config) -> Result<()> {
         Sampler::TraceIdRatioBased(config.zipkin_sample_rate)
     };
 
-    // Create OTLP exporter with Zipkin-compatible endpoint
-    let exporter = opentelemetry_otlp::SpanExporter::builder()
-        .with_http()
-        .with_endpoint(format!("{}/api/v2/spans", endpoint))
-        .build()
-        .context("Failed to create OTLP exporter")?;
-
     // Create tracer provider with sampling configuration
     let provider = TracerProvider::builder()
         .with_batch_exporter(exporter, opentelemetry_sdk::runtime::Tokio)
@@ -48,6 +41,61 @@ pub fn init_tracing(config: &Config) -> Result<()> {
     Ok(())
 }
 
+/// The span exporter for `config.otel_exporter`, or `None` when tracing is
+/// off (`none`, or no endpoint configured for the chosen exporter).
+fn build_exporter(config: &Config) -> Result<Option<opentelemetry_otlp::SpanExporter>> {
+    let exporter = match config.otel_exporter.as_str() {
+        "zipkin" => {
+            let Some(endpoint) = &config.zipkin_endpoint else {
+                return Ok(None);
+            };
+            // OTLP over HTTP to a Zipkin-compatible endpoint
+            opentelemetry_otlp::SpanExporter::builder()
+                .with_http()
+                .with_endpoint(format!("{}/api/v2/spans", endpoint))
+                .build()
+        }
+        "otlp" => {
+            let Some(endpoint) = &config.otlp_endpoint else {
+                return Ok(None);
+            };
+            opentelemetry_otlp::SpanExporter::builder()
+                .with_tonic()
+                .with_endpoint(endpoint.clone())
+                .build()
+        }
+        "none" => return Ok(None),
+        other => anyhow::bail!(
+            "Unknown otel_exporter '{}': expected zipkin, otlp or none",
+            other
+        ),
+    };
+    exporter
+        .map(Some)
+        .with_context(|| format!("Failed to create {} exporter", config.otel_exporter))
+}
+
+/// Send `tracing` events at `config.log_level` to `config.log_file`. The
+/// file is appended to, so concurrent or successive runs keep their history.
+pub fn init_logging(config: &Config) -> Result<()> {
+    let Some(path) = &config.log_file else {
+        return Ok(());
+    };
+    let filter = tracing_subscriber::EnvFilter::try_new(&config.log_level)
+        .with_context(|| format!("Invalid log_level '{}'", config.log_level))?;
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open log file {}", path))?;
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::sync::Mutex::new(file))
+        .with_ansi(false)
+        .try_init()
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
 /// Shutdown tracing and flush any pending spans
 pub fn shutdown_tracing() {
     global::shutdown_tracer_provider();
@@ -142,6 +190,42 @@ pub fn end_span(mut span: opentelemetry::global::BoxedSpan, attributes: Vec<KeyV
     span.end();
 }
 
+/// Longest variable value kept on a span event, in chars
+const VAR_EVENT_VALUE_MAX_CHARS: usize = 256;
+
+/// Add a `variable modified` event for a `ModifyVar` sent to the plugin.
+/// Does nothing when the span isn't recording, e.g. with tracing disabled.
+pub fn record_var_modification(
+    span: &mut opentelemetry::global::BoxedSpan,
+    key: &str,
+    value: &serde_json::Value,
+) {
+    if !span.is_recording() {
+        return;
+    }
+    span.add_event(
+        "variable modified",
+        vec![
+            KeyValue::new("var.key", key.to_string()),
+            KeyValue::new("var.value", var_event_value(key, value)),
+        ],
+    );
+}
+
+/// `value` as recorded on a span event: secret-looking keys redacted at any
+/// depth, then its JSON cut to [`VAR_EVENT_VALUE_MAX_CHARS`].
+fn var_event_value(key: &str, value: &serde_json::Value) -> String {
+    let text = if crate::audit::is_sensitive_key(key) {
+        crate::audit::REDACTED.to_string()
+    } else {
+        crate::audit::redact_secrets(key, value).to_string()
+    };
+    match text.char_indices().nth(VAR_EVENT_VALUE_MAX_CHARS) {
+        Some((cut, _)) => format!("{}… ({} chars)", &text[..cut], text.chars().count()),
+        None => text,
+    }
+}
+
 /// Attach a span to the current context and return a guard
 /// The guard must be kept alive for the span to remain active
 pub fn attach_span(span: opentelemetry::global::BoxedSpan) -> opentelemetry::ContextGuard {
@@ -149,37 +233,179 @@ pub fn attach_span(span: opentelemetry::global::BoxedSpan) -> opentelemetry::Con
     cx.attach()
 }
 
+/// An open task span and when it was started.
+pub struct TaskSpan {
+    pub span: opentelemetry::global::BoxedSpan,
+    pub started: Instant,
+}
+
+impl TaskSpan {
+    pub fn start(name: &str) -> Self {
+        Self {
+            span: create_child_span(
+                format!("task: {}", name),
+                vec![KeyValue::new("task.name", name.to_string())],
+            ),
+            started: Instant::now(),
+        }
+    }
+}
+
+/// Task timings accumulated as spans end, for exporting a folded-stack file
+/// (`playbook;play;task duration_ms`) that `inferno`/flamegraph tools can render
+/// without a trace collector.
+#[derive(Debug, Default)]
+pub struct SpanTimings {
+    current_play: Option<String>,
+    stacks: Vec<(String, String, u64)>,
+}
+
+impl SpanTimings {
+    const ROOT: &'static str = "playbook";
+
+    pub fn start_play(&mut self, name: &str) {
+        self.current_play = Some(name.to_string());
+    }
+
+    /// End task `name`'s span with `attributes` and record how long it was
+    /// open under the current play.
+    pub fn end_task(&mut self, name: &str, task: TaskSpan, attributes: Vec<KeyValue>) {
+        let elapsed = task.started.elapsed();
+        end_span(task.span, attributes);
+        let play = self.current_play.clone().unwrap_or_default();
+        self.record(&play, name, elapsed.as_millis() as u64);
+    }
+
+    pub fn record(&mut self, play: &str, task: &str, duration_ms: u64) {
+        self.stacks
+            .push((play.to_string(), task.to_string(), duration_ms));
+    }
+
+    /// Folded-stack lines, one per task, with identical stacks summed
+    pub fn folded_lines(&self) -> Vec<String> {
+        let mut totals: Vec<(String, u64)> = Vec::new();
+        for (play, task, ms) in &self.stacks {
+            let mut frames = vec![Self::ROOT.to_string()];
+            if !play.is_empty() {
+                frames.push(fold_frame(play));
+            }
+            frames.push(fold_frame(task));
+            let stack = frames.join(";");
+            match totals.iter_mut().find(|(s, _)| *s == stack) {
+                Some((_, total)) => *total += ms,
+                None => totals.push((stack, *ms)),
+            }
+        }
+        totals
+            .into_iter()
+            .map(|(stack, ms)| format!("{} {}", stack, ms))
+            .collect()
+    }
+
+    pub fn write_folded(&self, path: &str) -> std::io::Result<()> {
+        let mut out = self.folded_lines().join("\n");
+        out.push('\n');
+        std::fs::write(path, out)
+    }
+}
+
+/// `;` separates frames and newlines separate stacks in the folded format
+fn fold_frame(name: &str) -> String {
+    name.replace(';', ":").replace(['\n', '\r'], " ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_folded_stack_lines() {
+        let mut timings = SpanTimings::default();
+        timings.record("Setup", "Install packages", 1200);
+        timings.record("Setup", "Write config; reload", 30);
+        timings.record("Deploy", "Restart app", 450);
+        timings.record("Setup", "Install packages", 100);
+        timings.record("", "Gather Facts", 5);
+
+        assert_eq!(
+            timings.folded_lines(),
+            vec![
+                "playbook;Setup;Install packages 1300",
+                "playbook;Setup;Write config: reload 30",
+                "playbook;Deploy;Restart app 450",
+                "playbook;Gather Facts 5",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_var_event_value_redacts_and_truncates() {
+        assert_eq!(
+            var_event_value("db_password", &serde_json::json!("hunter2")),
+            "[REDACTED]"
+        );
+        assert_eq!(
+            var_event_value("Vault_Token", &serde_json::json!(1)),
+            "[REDACTED]"
+        );
+        assert_eq!(var_event_value("port", &serde_json::json!(8080)), "8080");
+        assert_eq!(
+            var_event_value("motd", &serde_json::json!("[REDACTED]")),
+            "\"[REDACTED]\""
+        );
+        assert_eq!(
+            var_event_value(
+                "db",
+                &serde_json::json!({"host": "db1", "users": [{"name": "app", "password": "x"}]})
+            ),
+            r#"{"host":"db1","users":[{"name":"app","password":"[REDACTED]"}]}"#
+        );
+
+        let long = var_event_value("motd", &serde_json::json!("é".repeat(1000)));
+        assert!(long.starts_with("\"éé"));
+        assert!(long.ends_with("… (1002 chars)"));
+
+        // Tracing is off in tests: recording on a no-op span is harmless
+        let mut span = start_span("task", SpanKind::Internal);
+        record_var_modification(&mut span, "port", &serde_json::json!(1));
+    }
+
     #[test]
     fn test_tracing_disabled_when_no_endpoint() {
-        let config = Config {
-            openai_api_key: None,
-            socket_path: "/tmp/test.sock".to_string(),
-            model: "gpt-4".to_string(),
-            api_base: "https://api.openai.com/v1".to_string(),
-            log_level: "info".to_string(),
-            auth_token: None,
-            bind_addr: None,
-            secret_token: None,
-            quota_limit_tokens: None,
-            quota_limit_usd: None,
-            google_api_key: None,
-            google_client_id: None,
-            google_client_secret: None,
-            zipkin_endpoint: None, // No endpoint = tracing disabled
-            zipkin_service_name: "test".to_string(),
-            zipkin_sample_rate: 1.0,
-            filters: None, // [NEW]
-            provider: None,
-            anthropic_api_key: None,
-            vertex_project_id: None,
-            vertex_location: Some("us-central1".to_string()),
-        };
+        // No endpoint = tracing disabled
+        let config = Config::default();
 
         // Should succeed without initializing tracing
         assert!(init_tracing(&config).is_ok());
+        assert!(init_logging(&config).is_ok());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("piloteer.log");
+        std::fs::write(&path, "previous run\n").unwrap();
+        let mut config = Config {
+            log_file: Some(path.display().to_string()),
+            log_level: "not a [filter".to_string(),
+            ..config
+        };
+        assert!(init_logging(&config).is_err());
+
+        config.log_level = "debug".to_string();
+        init_logging(&config).unwrap();
+        tracing::debug!(client = 1, "Connection accepted");
+        tracing::trace!("below the level");
+        let text = std::fs::read_to_string(&path).unwrap();
+        // Appended, never truncated
+        assert!(text.starts_with("previous run\n"));
+        assert!(text.contains("Connection accepted client=1"));
+        assert!(!text.contains("below the level"));
+
+        // Each exporter only switches on with its own endpoint
+        config.zipkin_endpoint = Some("http://zipkin:9411".to_string());
+        config.otel_exporter = "otlp".to_string();
+        assert!(build_exporter(&config).unwrap().is_none());
+        config.otel_exporter = "none".to_string();
+        assert!(build_exporter(&config).unwrap().is_none());
+        config.otel_exporter = "jaeger".to_string();
+        assert!(build_exporter(&config).is_err());
     }
 }
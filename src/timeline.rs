@@ -0,0 +1,210 @@
+//! `--replay-timeline`: play a saved session back in execution order, each
+//! task taking its recorded `duration` (divided by the speed multiplier), so
+//! a failure can be watched unfolding as if it were live.
+
+use crate::app::TaskHistory;
+use std::time::{Duration, Instant};
+
+/// Slowest and fastest playback `--speed`: far beyond these, playback
+/// either stalls or the scaled clock overflows.
+pub const MIN_SPEED: f64 = 0.1;
+pub const MAX_SPEED: f64 = 100.0;
+
+/// Parse a `--speed` multiplier between [`MIN_SPEED`] and [`MAX_SPEED`].
+pub fn parse_speed(s: &str) -> Result<f64, String> {
+    let speed: f64 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("invalid speed '{}': expected a number such as 2 or 0.5", s))?;
+    if (MIN_SPEED..=MAX_SPEED).contains(&speed) {
+        Ok(speed)
+    } else {
+        Err(format!(
+            "invalid speed '{}': must be between {} and {}",
+            s, MIN_SPEED, MAX_SPEED
+        ))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TimelineEvent {
+    Started { name: String, host: String },
+    Finished(Box<TaskHistory>),
+}
+
+#[derive(Debug, Clone)]
+pub struct TimelinePlayer {
+    tasks: Vec<TaskHistory>,
+    /// Index of the task the next event belongs to
+    cursor: usize,
+    /// Whether `tasks[cursor]` has started and is waiting to finish
+    running: bool,
+    /// Playback position on the session's own clock
+    clock: Duration,
+    /// Session-clock time at which the next event is due
+    next_at: Duration,
+    last_tick: Option<Instant>,
+    pub speed: f64,
+    pub paused: bool,
+}
+
+impl TimelinePlayer {
+    /// `speed` scales playback: 2.0 runs twice as fast as the original run.
+    /// It's clamped to [`MIN_SPEED`]..=[`MAX_SPEED`]; zero, negative or NaN
+    /// speeds play at 1.0.
+    pub fn new(tasks: Vec<TaskHistory>, speed: f64) -> Self {
+        Self {
+            tasks,
+            cursor: 0,
+            running: false,
+            clock: Duration::ZERO,
+            next_at: Duration::ZERO,
+            last_tick: None,
+            speed: if speed > 0.0 {
+                speed.clamp(MIN_SPEED, MAX_SPEED)
+            } else {
+                1.0
+            },
+            paused: false,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.cursor >= self.tasks.len()
+    }
+
+    /// Tasks finished so far, and the total.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.cursor, self.tasks.len())
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        // Time spent paused must not count towards the next event
+        self.last_tick = None;
+    }
+
+    /// Advance to wall-clock `now` and return every event that fell due.
+    pub fn poll(&mut self, now: Instant) -> Vec<TimelineEvent> {
+        let elapsed = self
+            .last_tick
+            .map(|t| now.saturating_duration_since(t))
+            .unwrap_or_default();
+        self.last_tick = Some(now);
+        if self.paused {
+            return Vec::new();
+        }
+        self.clock += elapsed.mul_f64(self.speed);
+
+        let mut events = Vec::new();
+        while !self.is_finished() && self.next_at <= self.clock {
+            events.extend(self.emit());
+        }
+        events
+    }
+
+    /// Emit the next event right away, paused or not.
+    pub fn step(&mut self) -> Option<TimelineEvent> {
+        if self.is_finished() {
+            return None;
+        }
+        self.clock = self.clock.max(self.next_at);
+        self.emit()
+    }
+
+    fn emit(&mut self) -> Option<TimelineEvent> {
+        let task = self.tasks.get(self.cursor)?;
+        if self.running {
+            let task = task.clone();
+            self.running = false;
+            self.cursor += 1;
+            Some(TimelineEvent::Finished(Box::new(task)))
+        } else {
+            self.running = true;
+            self.next_at =
+                self.clock.max(self.next_at) + Duration::from_secs_f64(task.duration.max(0.0));
+            Some(TimelineEvent::Started {
+                name: task.name.clone(),
+                host: task.host.clone(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str, duration: f64) -> TaskHistory {
+        TaskHistory {
+            duration,
+            ..TaskHistory::new(name, "web1")
+        }
+    }
+
+    fn names(events: &[TimelineEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|e| match e {
+                TimelineEvent::Started { name, .. } => format!("start {}", name),
+                TimelineEvent::Finished(t) => format!("finish {}", t.name),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_events_follow_task_durations() {
+        let mut player = TimelinePlayer::new(vec![task("a", 2.0), task("b", 1.0)], 2.0);
+        let t0 = Instant::now();
+        assert_eq!(names(&player.poll(t0)), vec!["start a"]);
+        // 2s of task time at 2x speed takes one real second
+        assert!(player.poll(t0 + Duration::from_millis(900)).is_empty());
+        assert_eq!(
+            names(&player.poll(t0 + Duration::from_millis(1000))),
+            vec!["finish a", "start b"]
+        );
+        assert_eq!(
+            names(&player.poll(t0 + Duration::from_millis(1500))),
+            vec!["finish b"]
+        );
+        assert!(player.is_finished());
+        assert_eq!(player.progress(), (2, 2));
+    }
+
+    #[test]
+    fn test_pause_and_step() {
+        let mut player = TimelinePlayer::new(vec![task("a", 1.0), task("b", 1.0)], 1.0);
+        let t0 = Instant::now();
+        player.poll(t0);
+        player.toggle_pause();
+        assert!(player.poll(t0 + Duration::from_secs(5)).is_empty());
+
+        // Stepping works while paused, one event at a time
+        assert_eq!(names(&[player.step().unwrap()]), vec!["finish a"]);
+        assert_eq!(names(&[player.step().unwrap()]), vec!["start b"]);
+
+        // Resuming doesn't count the time spent paused
+        player.toggle_pause();
+        assert!(player.poll(t0 + Duration::from_secs(6)).is_empty());
+        assert!(player.poll(t0 + Duration::from_millis(6500)).is_empty());
+        assert_eq!(
+            names(&player.poll(t0 + Duration::from_secs(7))),
+            vec!["finish b"]
+        );
+        assert!(player.step().is_none());
+    }
+
+    #[test]
+    fn test_speed_is_bounded() {
+        assert_eq!(parse_speed("2"), Ok(2.0));
+        assert_eq!(parse_speed("0.1"), Ok(0.1));
+        assert_eq!(parse_speed("100"), Ok(100.0));
+        for bad in ["0", "-1", "1e300", "NaN", "fast"] {
+            assert!(parse_speed(bad).is_err(), "{} accepted", bad);
+        }
+
+        // Library callers get the same bounds
+        assert_eq!(TimelinePlayer::new(Vec::new(), 1e300).speed, MAX_SPEED);
+        assert_eq!(TimelinePlayer::new(Vec::new(), -3.0).speed, 1.0);
+    }
+}
@@ -12,18 +12,23 @@ use ratatui::{
 use crate::widgets::json_tree::JsonTree;
 
 pub fn draw(frame: &mut Frame, app: &mut App) {
+    let [main, footer] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .areas(frame.area());
+
     match app.active_view {
         crate::app::ActiveView::Metrics => {
-            crate::widgets::metrics::MetricsDashboard::draw(frame, app, frame.area());
+            crate::widgets::metrics::MetricsDashboard::draw(frame, app, main);
         }
         crate::app::ActiveView::Analysis => {
-            draw_analysis(frame, app);
+            draw_analysis(frame, app, main);
         }
         crate::app::ActiveView::Dashboard => {
             let chunks = Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-                .split(frame.area());
+                .split(main);
 
             draw_logs(frame, app, chunks[0]);
 
@@ -35,7 +40,9 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
         }
     }
 
+    draw_footer(frame, app, footer);
     draw_notification(frame, app);
+    draw_quota_banner(frame, app);
 
     if app.show_host_list {
         draw_host_list(frame, app);
@@ -51,6 +58,22 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
         draw_variable_selector(frame, app);
     }
 
+    if app.note_input.is_some() {
+        draw_note_input(frame, app);
+    }
+
+    if app.pending_var_change.is_some() {
+        draw_var_conflict(frame, app);
+    }
+
+    if app.show_warnings {
+        draw_warnings(frame, app);
+    }
+
+    if app.startup_error.is_some() {
+        draw_startup_error(frame, app);
+    }
+
     // [NEW] Phase 3: Connection Alert
     // Check moved to Status Window
 }
@@ -58,7 +81,10 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
 fn draw_host_list(frame: &mut Frame, app: &mut App) {
     let area = centered_rect(60, 60, frame.area());
     let block = Block::default()
-        .title("Host List (j/k: Select, Enter: Filter, f: Facts, Esc: Close)")
+        .title(format!(
+            "Host List [Sort: {}] (j/k: Select, Enter: Filter, m: Metrics, s: Sort, F: Follow live, f: Facts, Esc: Close)",
+            app.host_sort.label()
+        ))
         .borders(Borders::ALL)
         .style(Style::default().bg(Color::Black));
 
@@ -72,14 +98,15 @@ fn draw_host_list(frame: &mut Frame, app: &mut App) {
         .margin(1)
         .split(area);
 
-    let mut hosts: Vec<&crate::app::HostStatus> = app.hosts.values().collect();
-    hosts.sort_by(|a, b| a.name.cmp(&b.name));
+    let hosts = app.host_list_order();
 
     let items: Vec<ListItem> = hosts
         .iter()
         .map(|h| {
             let status = if h.failed_tasks > 0 {
                 "FAILED"
+            } else if h.rescued_tasks > 0 {
+                "RESCUED"
             } else if h.changed_tasks > 0 {
                 "CHANGED"
             } else {
@@ -88,13 +115,20 @@ fn draw_host_list(frame: &mut Frame, app: &mut App) {
 
             let style = match status {
                 "FAILED" => Style::default().fg(Color::Red),
+                "RESCUED" => Style::default().fg(Color::Magenta),
                 "CHANGED" => Style::default().fg(Color::Yellow),
                 _ => Style::default().fg(Color::Green),
             };
 
             ListItem::new(format!(
-                "{:<20} | OK: {:<3} Changed: {:<3} Failed: {:<3} [{}]",
-                h.name, h.ok_tasks, h.changed_tasks, h.failed_tasks, status
+                "{:<20} | OK: {:<3} Changed: {:<3} Failed: {:<3} Rescued: {:<3} Skipped: {:<3} [{}]",
+                h.name,
+                h.ok_tasks,
+                h.changed_tasks,
+                h.failed_tasks,
+                h.rescued_tasks,
+                h.skipped_tasks,
+                status
             ))
             .style(style)
         })
@@ -105,7 +139,7 @@ fn draw_host_list(frame: &mut Frame, app: &mut App) {
         .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
 
     let mut state = ListState::default();
-    state.select(Some(app.host_list_index));
+    state.select(Some(app.host_list_index()));
 
     frame.render_stateful_widget(list, layout[0], &mut state);
 }
@@ -115,10 +149,15 @@ fn draw_notification(frame: &mut Frame, app: &mut App) {
         && time.elapsed() < std::time::Duration::from_secs(3)
     {
         let area = centered_rect(40, 10, frame.area());
+        let bg = if app.notification_alert {
+            Color::Red
+        } else {
+            Color::Blue
+        };
         let block = Block::default()
             .title("Notification")
             .borders(Borders::ALL)
-            .style(Style::default().bg(Color::Blue).fg(Color::White));
+            .style(Style::default().bg(bg).fg(Color::White));
         let p = Paragraph::new(msg.clone())
             .block(block)
             .alignment(ratatui::layout::Alignment::Center);
@@ -127,11 +166,74 @@ fn draw_notification(frame: &mut Frame, app: &mut App) {
     }
 }
 
-fn draw_analysis(frame: &mut Frame, app: &mut App) {
+/// Bottom row: key hints for the current view on the left, connection
+/// status on the right.
+fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
+    let hints = match app.active_view {
+        crate::app::ActiveView::Dashboard if app.timeline.is_some() => {
+            "space pause · n step · Tab analysis · ? help"
+        }
+        crate::app::ActiveView::Dashboard => "r retry · c continue · a ask AI · ? help",
+        crate::app::ActiveView::Analysis => "j/k nav · / search · y yank · v exit · ? help",
+        crate::app::ActiveView::Metrics if app.metrics_view == crate::app::MetricsView::Hosts => {
+            "j/k scroll hosts · t cycle view · m exit · ? help"
+        }
+        crate::app::ActiveView::Metrics => "t cycle view · m exit · ? help",
+    };
+    let (status, color) = if app.timeline.as_ref().is_some_and(|p| p.paused) {
+        ("PAUSED", Color::Yellow)
+    } else if app.replay_mode {
+        ("REPLAY", Color::Magenta)
+    } else if app.is_connected() {
+        ("CONNECTED", Color::Green)
+    } else {
+        ("DISCONNECTED", Color::Red)
+    };
+
+    let [left, right] = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(status.len() as u16 + 2),
+        ])
+        .areas(area);
+    frame.render_widget(
+        Paragraph::new(format!(" {}", hints)).style(Style::default().fg(Color::DarkGray)),
+        left,
+    );
+    frame.render_widget(
+        Paragraph::new(format!("{} ", status))
+            .style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+            .alignment(ratatui::layout::Alignment::Right),
+        right,
+    );
+}
+
+/// Persistent one-line banner over the top border while AI is quota-blocked.
+fn draw_quota_banner(frame: &mut Frame, app: &App) {
+    let Some(text) = app.quota_banner() else {
+        return;
+    };
+    let full = frame.area();
+    let width = (text.chars().count() as u16 + 4).min(full.width);
+    let area = Rect::new(full.x + (full.width - width) / 2, full.y, width, 1);
+    let p = Paragraph::new(format!(" {} ", text))
+        .style(
+            Style::default()
+                .bg(Color::Red)
+                .fg(Color::White)
+                .add_modifier(Modifier::BOLD),
+        )
+        .alignment(ratatui::layout::Alignment::Center);
+    frame.render_widget(Clear, area);
+    frame.render_widget(p, area);
+}
+
+fn draw_analysis(frame: &mut Frame, app: &mut App, area: Rect) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
-        .split(frame.area());
+        .split(area);
 
     // Define focus styles
     let active_style = Style::default()
@@ -145,25 +247,28 @@ fn draw_analysis(frame: &mut Frame, app: &mut App) {
     };
 
     // Left Pane: Task List
-    let tasks: Vec<ListItem> = app
-        .history
+    let order = app.task_display_order();
+    let now = chrono::Utc::now();
+    let tasks: Vec<ListItem> = order
         .iter()
-        .filter(|t| {
-            if let Some(filter_host) = &app.host_filter {
-                &t.host == filter_host
-            } else {
-                true
-            }
-        })
-        .map(|t| {
-            let style = if t.failed {
+        .map(|&i| (i, &app.history[i]))
+        .map(|(i, t)| {
+            let style = if t.skipped {
+                Style::default().fg(Color::DarkGray)
+            } else if t.rescued {
+                Style::default().fg(Color::Magenta)
+            } else if t.failed {
                 Style::default().fg(Color::Red)
             } else if t.changed {
                 Style::default().fg(Color::Yellow)
             } else {
                 Style::default().fg(Color::Green)
             };
-            let symbol = if t.failed {
+            let symbol = if t.skipped {
+                "⊘  "
+            } else if t.rescued {
+                "🛟 "
+            } else if t.failed {
                 "❌ "
             } else if t.changed {
                 "⚠️  "
@@ -179,6 +284,28 @@ fn draw_analysis(frame: &mut Frame, app: &mut App) {
             }
             spans.push(Span::raw(symbol));
             spans.push(Span::raw(t.name.clone()));
+            if t.analysis.is_some() {
+                if app.fresh_analyses.contains(&i) {
+                    spans.push(Span::styled(
+                        " [AI: fresh]",
+                        Style::default().fg(Color::Cyan),
+                    ));
+                } else {
+                    spans.push(Span::styled(
+                        " [AI: stored]",
+                        Style::default().fg(Color::DarkGray),
+                    ));
+                }
+            }
+            if let Some(started_at) = t.started_at {
+                spans.push(Span::styled(
+                    format!(
+                        " (finished {} ago)",
+                        format_finished_ago(started_at, t.duration, now)
+                    ),
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
 
             ListItem::new(Line::from(spans)).style(style)
         })
@@ -189,22 +316,35 @@ fn draw_analysis(frame: &mut Frame, app: &mut App) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(list_border_style)
-                .title(if let Some(h) = &app.host_filter {
-                    format!("History (Filter: {})", h)
-                } else {
-                    "History (Up/Down to Select)".to_string()
+                .title({
+                    let mut title = if let Some(h) = &app.host_filter {
+                        format!("History (Filter: {})", h)
+                    } else {
+                        "History (Up/Down to Select)".to_string()
+                    };
+                    if app.task_sort != crate::app::TaskSort::Execution {
+                        title.push_str(&format!(" [Sort: {}]", app.task_sort.label()));
+                    }
+                    title
                 }),
         )
         .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
         .highlight_symbol(">> ");
 
     let mut list_state = ListState::default();
-    list_state.select(Some(app.analysis_index));
+    list_state.select(order.iter().position(|&i| i == app.analysis_index));
 
     frame.render_stateful_widget(tasks_list, chunks[0], &mut list_state);
+    app.history_pane = Some(chunks[0]);
+    app.history_list_offset = list_state.offset();
 
     // Right Pane: Data Browser
-    let title_text = if app.search_active {
+    let title_text = if let Some(task) = &app.breakpoint_expr_task {
+        format!(
+            "Break on '{}' when (JMESPath, empty clears): {}_",
+            task, app.search_query
+        )
+    } else if app.search_active {
         format!("Data Browser (Search: {})", app.search_query)
     } else {
         "Data Browser (Enter to Expand, / to Search)".to_string()
@@ -228,6 +368,8 @@ fn draw_analysis(frame: &mut Frame, app: &mut App) {
 }
 
 fn draw_logs(frame: &mut Frame, app: &mut App, area: Rect) {
+    let search = (!app.search_query.is_empty())
+        .then(|| crate::search::SearchPattern::new(&app.search_query));
     let logs: Vec<Line> = app
         .logs
         .iter()
@@ -237,24 +379,24 @@ fn draw_logs(frame: &mut Frame, app: &mut App, area: Rect) {
             crate::app::LogFilter::Failed => *color == Color::Red,
             crate::app::LogFilter::Changed => *color == Color::Yellow || *color == Color::Red,
         })
+        .filter(|(_, (msg, _))| app.follows_log_line(msg))
         .map(|(i, (msg, color))| {
             // Check for search match
-            if !app.search_query.is_empty() {
-                let query = app.search_query.to_lowercase();
-                let msg_lower = msg.to_lowercase();
+            if let Some(pattern) = &search {
+                let matches = pattern.match_ranges(msg);
 
-                if msg_lower.contains(&query) {
+                if !matches.is_empty() {
                     let is_selected = app.search_index == Some(i);
                     // Split and highlight
                     let mut spans = Vec::new();
                     let mut last_idx = 0;
 
                     // Find all matches
-                    for (idx, match_str) in msg_lower.match_indices(&query) {
+                    for (start, end) in matches {
                         // Push text before match
-                        if idx > last_idx {
+                        if start > last_idx {
                             spans.push(Span::styled(
-                                &msg[last_idx..idx],
+                                &msg[last_idx..start],
                                 Style::default().fg(*color),
                             ));
                         }
@@ -264,8 +406,8 @@ fn draw_logs(frame: &mut Frame, app: &mut App, area: Rect) {
                         } else {
                             Style::default().bg(Color::DarkGray).fg(Color::Yellow)
                         };
-                        spans.push(Span::styled(&msg[idx..idx + match_str.len()], match_style));
-                        last_idx = idx + match_str.len();
+                        spans.push(Span::styled(&msg[start..end], match_style));
+                        last_idx = end;
                     }
                     // Push remaining text
                     if last_idx < msg.len() {
@@ -283,11 +425,15 @@ fn draw_logs(frame: &mut Frame, app: &mut App, area: Rect) {
         .collect();
 
     let title = {
-        let filter_text = match app.log_filter {
+        let mut filter_text = match app.log_filter {
             crate::app::LogFilter::All => "",
             crate::app::LogFilter::Failed => " [FILTER: FAILED]",
             crate::app::LogFilter::Changed => " [FILTER: CHANGED]",
-        };
+        }
+        .to_string();
+        if let Some(host) = &app.follow_host {
+            filter_text.push_str(&format!(" [FOLLOW: {}]", host));
+        }
 
         if app.search_active {
             format!("Ansible Logs (Search: {}){}", app.search_query, filter_text)
@@ -301,6 +447,7 @@ fn draw_logs(frame: &mut Frame, app: &mut App, area: Rect) {
         }
     };
 
+    let title_width = title.chars().count() as u16;
     let block = Block::default()
         .borders(Borders::ALL)
         .title(title)
@@ -316,7 +463,7 @@ fn draw_logs(frame: &mut Frame, app: &mut App, area: Rect) {
     let inner_height = area.height.saturating_sub(2); // Subtract borders
 
     let scroll = if app.auto_scroll {
-        let total_lines = logs.len() as u16;
+        let total_lines = u16::try_from(logs.len()).unwrap_or(u16::MAX);
         let s = total_lines.saturating_sub(inner_height);
         // Sync app.log_scroll so manual scrolling starts from the correct position
         app.log_scroll = s;
@@ -331,11 +478,136 @@ fn draw_logs(frame: &mut Frame, app: &mut App, area: Rect) {
         .scroll((scroll, 0)); // Use calculated scroll
 
     frame.render_widget(paragraph, area);
+    draw_event_rate(frame, app, area, title_width);
+}
+
+/// Widest the event-rate sparkline in the logs title bar gets.
+const EVENT_RATE_WIDTH: u16 = 20;
+
+/// `Events/s: <n>` and a sparkline of recent per-second event counts, drawn
+/// into the right end of the logs pane's top border. Skipped when the title
+/// leaves no room.
+fn draw_event_rate(frame: &mut Frame, app: &App, area: Rect, title_width: u16) {
+    let current = app.event_velocity.back().copied().unwrap_or(0);
+    let label = format!(" Events/s: {} ", current);
+    let label_width = label.chars().count() as u16;
+    // Corners, the title, and a gap of one column on either side
+    let free = area.width.saturating_sub(title_width + label_width + 4);
+    let spark_width = free.min(EVENT_RATE_WIDTH);
+    if spark_width < 5 {
+        return;
+    }
+
+    let x = area.x + area.width - 1 - spark_width - label_width;
+    frame.render_widget(
+        Paragraph::new(label).style(Style::default().fg(Color::Magenta)),
+        Rect::new(x, area.y, label_width, 1),
+    );
+    let history: Vec<u64> = app
+        .event_velocity
+        .iter()
+        .skip(
+            app.event_velocity
+                .len()
+                .saturating_sub(spark_width as usize),
+        )
+        .copied()
+        .collect();
+    frame.render_widget(
+        ratatui::widgets::Sparkline::default()
+            .data(&history)
+            .style(Style::default().fg(Color::Magenta)),
+        Rect::new(x + label_width, area.y, spark_width, 1),
+    );
+}
+
+/// Format the time between `start` and `now` as a compact relative duration
+/// ("12s", "3m 04s", "1h 02m").
+pub fn format_elapsed(start: std::time::Instant, now: std::time::Instant) -> String {
+    format_secs(now.saturating_duration_since(start).as_secs())
+}
+
+/// Format how long before `now` a task that started at `started_at` and took
+/// `duration` seconds finished, like [`format_elapsed`].
+pub fn format_finished_ago(
+    started_at: chrono::DateTime<chrono::Utc>,
+    duration: f64,
+    now: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let finished = started_at + chrono::Duration::milliseconds((duration * 1000.0) as i64);
+    format_secs((now - finished).num_seconds().max(0) as u64)
+}
+
+fn format_secs(secs: u64) -> String {
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    }
+}
+
+/// Live suffix for the current task, recomputed every draw: "(running for
+/// 12s)" until it has a result, then "(finished 3s ago)".
+fn running_for_span(app: &App) -> Span<'static> {
+    if app.replay_mode {
+        return Span::raw("");
+    }
+    let finished = app
+        .history
+        .last()
+        .filter(|t| Some(&t.name) == app.current_task.as_ref())
+        .and_then(|t| Some((t.started_at?, t.duration)));
+    let text = match (finished, app.task_start_time) {
+        (Some((started_at, duration)), _) => format!(
+            "  (finished {} ago)",
+            format_finished_ago(started_at, duration, chrono::Utc::now())
+        ),
+        (None, Some(start)) => format!(
+            "  (running for {})",
+            format_elapsed(start, std::time::Instant::now())
+        ),
+        (None, None) => return Span::raw(""),
+    };
+    Span::styled(text, Style::default().fg(Color::DarkGray))
+}
+
+fn inspector_title(app: &App) -> Line<'static> {
+    match &app.inspector_pin {
+        Some(pin) => Line::from(vec![
+            Span::raw("Inspector "),
+            Span::styled(
+                " PINNED ",
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Magenta)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(match pin.entry.name.as_str() {
+                "" => " (p to unpin)".to_string(),
+                task => format!(" {} (p to unpin)", task),
+            }),
+        ]),
+        None => match &app.follow_host {
+            Some(host) => Line::from(vec![
+                Span::raw("Inspector "),
+                Span::styled(
+                    format!(" FOLLOWING {} ", host),
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]),
+            None => Line::from("Inspector"),
+        },
+    }
 }
 
 fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
     // Determine layout: Status (Fixed), Variables (Min), Pilot (Fixed/Min if active)
-    let constraints = if app.asking_ai || app.suggestion.is_some() {
+    let constraints = if app.asking_ai || app.pilot_analysis().is_some() {
         vec![
             Constraint::Length(6),      // Status (Increased for multi-line)
             Constraint::Min(0),         // Vars
@@ -388,7 +660,11 @@ fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
             Line::from(vec![
                 Span::raw("Controls: "),
                 Span::styled(
-                    "[r]etry [e]dit [c]ontinue [a]sk Pilot",
+                    if app.ai_disabled {
+                        "[r]etry [e]dit [c]ontinue"
+                    } else {
+                        "[r]etry [e]dit [c]ontinue [a]sk Pilot"
+                    },
                     Style::default().fg(Color::DarkGray),
                 ),
             ]),
@@ -420,6 +696,7 @@ fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
                     app.current_task.as_deref().unwrap_or("None"),
                     Style::default().fg(Color::Cyan),
                 ),
+                running_for_span(app),
             ]),
             Line::from(vec![
                 Span::raw(" v       "),
@@ -467,15 +744,24 @@ fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
             ]),
         ]
     } else {
-        vec![Line::from(vec![
+        let mut lines = vec![Line::from(vec![
             Span::raw("Status: "),
             Span::styled("RUNNING", Style::default().fg(Color::Green)),
-        ])]
+        ])];
+        if let Some(task) = &app.current_task {
+            lines.push(Line::from(vec![
+                Span::raw("Task: "),
+                Span::styled(task.as_str(), Style::default().fg(Color::Cyan)),
+                running_for_span(app),
+            ]));
+        }
+        lines
     };
 
     // Calculate Drift
     let total_tasks = app.history.len();
     let changed_tasks = app.history.iter().filter(|t| t.changed).count();
+    let skipped_tasks = app.history.iter().filter(|t| t.skipped).count();
     let drift_style = if changed_tasks > 0 {
         Style::default().fg(Color::Yellow)
     } else {
@@ -490,20 +776,96 @@ fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
             format!("{} changed / {} total", changed_tasks, total_tasks),
             drift_style,
         ),
+        Span::styled(
+            format!(" ({} skipped)", skipped_tasks),
+            Style::default().fg(Color::DarkGray),
+        ),
     ]));
 
     // Add Quota Info
     if let Some(client) = &app.ai_client {
         let (tokens, cost) = client.get_usage();
+        let quota = client.get_quota_status();
+        let quota_color = if quota.is_exceeded() {
+            Color::Red
+        } else if quota.is_warning() {
+            Color::Yellow
+        } else {
+            Color::Cyan
+        };
         status_lines.push(Line::from(vec![
             Span::raw("AI Quota: "),
             Span::styled(
                 format!("{} tokens / ${:.4}", tokens, cost),
+                Style::default().fg(quota_color),
+            ),
+        ]));
+    }
+
+    if !app.conditional_breakpoints.is_empty() {
+        let conditions: Vec<String> = app
+            .conditional_breakpoints
+            .iter()
+            .map(|b| format!("{} when {}", b.task, b.expr))
+            .collect();
+        status_lines.push(Line::from(vec![
+            Span::raw("Breakpoints: "),
+            Span::styled(conditions.join("; "), Style::default().fg(Color::Magenta)),
+        ]));
+    }
+
+    let connected_clients = app.ipc_clients.as_ref().map_or(0, |c| c.len());
+    if connected_clients > 1 {
+        let paused = app
+            .client_states
+            .values()
+            .filter(|s| s.waiting_for_proceed)
+            .count();
+        status_lines.push(Line::from(vec![
+            Span::raw("Clients: "),
+            Span::styled(
+                format!(
+                    "{} connected, showing #{} ({} other paused)",
+                    connected_clients,
+                    app.active_client.unwrap_or_default(),
+                    paused
+                ),
                 Style::default().fg(Color::Cyan),
             ),
         ]));
     }
 
+    if app.step_mode {
+        status_lines.push(Line::from(vec![
+            Span::raw("Mode: "),
+            Span::styled(
+                "STEP (pausing before every task, S to stop)",
+                Style::default().fg(Color::Magenta),
+            ),
+        ]));
+    }
+
+    let (warning_count, deprecation_count) = app.warning_counts();
+    if warning_count + deprecation_count > 0 {
+        status_lines.push(Line::from(vec![
+            Span::raw("Ansible: "),
+            Span::styled(
+                format!(
+                    "{} warning(s), {} deprecation(s) (W to view)",
+                    warning_count, deprecation_count
+                ),
+                Style::default().fg(Color::Yellow),
+            ),
+        ]));
+    }
+
+    if let Some(note) = &app.session_note {
+        status_lines.push(Line::from(vec![
+            Span::raw("Note: "),
+            Span::styled(note.clone(), Style::default().fg(Color::Magenta)),
+        ]));
+    }
+
     let status_block = Block::default().borders(Borders::ALL).title("Status");
 
     let status_p = Paragraph::new(status_lines).block(status_block);
@@ -511,29 +873,35 @@ fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
 
     // Variables Area
     // Inspector
-    let content = if let Some(err) = &app.failed_result {
-        serde_json::to_string_pretty(err).unwrap_or_else(|_| "Invalid JSON".to_string())
-    } else {
-        "No Active Failure".to_string()
-    };
+    let text = app.inspector_text();
+    // Matched against the text just rendered rather than rendering it again
+    let matches = text
+        .as_deref()
+        .map(|text| app.inspector_matches_in(text))
+        .unwrap_or_default();
+    let content = text.unwrap_or_else(|| "No Active Failure".to_string());
 
     // Highlight content
-    let mut highlighted_text = app.highlighter.highlight(&content, "json");
+    let mut highlighted_text = app
+        .highlighter
+        .highlight(&content, app.inspector_format.extension());
+
+    // Text width inside the borders, for wrap-aware search scrolling
+    app.inspector_width = chunks[1].width.saturating_sub(2);
 
     // Apply Search Highlighting
-    if !app.search_query.is_empty() {
-        let query = app.search_query.to_lowercase();
+    if !matches.is_empty() {
         // Modify lines
-        for line in &mut highlighted_text.lines {
-            let line_str: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
-            let line_lower = line_str.to_lowercase();
-
-            let matches: Vec<(usize, usize)> = line_lower
-                .match_indices(&query)
-                .map(|(i, m)| (i, i + m.len()))
+        for (line_idx, line) in highlighted_text.lines.iter_mut().enumerate() {
+            // (match index, start, end) of this line's matches
+            let line_matches: Vec<(usize, usize, usize)> = matches
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| m.0 == line_idx)
+                .map(|(k, &(_, start, end))| (k, start, end))
                 .collect();
 
-            if !matches.is_empty() {
+            if !line_matches.is_empty() {
                 let mut new_spans = Vec::new();
                 let mut current_offset = 0;
 
@@ -544,7 +912,7 @@ fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
 
                     let mut last_processed_in_span = 0;
 
-                    for &(m_start, m_end) in &matches {
+                    for &(k, m_start, m_end) in &line_matches {
                         // Check for overlap
                         let overlap_start = current_offset.max(m_start);
                         let overlap_end = span_end.min(m_end);
@@ -564,11 +932,15 @@ fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
                             }
 
                             // 2. The Matched Text
-                            // Preserve FG, Add BG
-                            // Determine selection style
-                            // (Simplification: We don't have line index here easily to check selected match,
-                            //  but for Inspector we just highlight all matches for now or use simplified selection)
-                            let match_style = span.style.bg(Color::DarkGray).fg(Color::Yellow);
+                            // Preserve FG, Add BG; the match n/N landed on stands out
+                            let match_style = if app.inspector_match == Some(k) {
+                                span.style
+                                    .bg(Color::Yellow)
+                                    .fg(Color::Black)
+                                    .add_modifier(Modifier::BOLD)
+                            } else {
+                                span.style.bg(Color::DarkGray).fg(Color::Yellow)
+                            };
 
                             new_spans.push(Span::styled(
                                 span_content[relative_start..relative_end].to_string(),
@@ -598,7 +970,7 @@ fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Inspector")
+                .title(inspector_title(app))
                 .border_style(
                     if app.active_view == crate::app::ActiveView::Dashboard
                         && app.dashboard_focus == crate::app::DashboardFocus::Inspector
@@ -615,18 +987,20 @@ fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
     frame.render_widget(inspector, chunks[1]);
 
     // Pilot Area (if active)
-    if app.asking_ai || app.suggestion.is_some() {
+    if app.asking_ai || app.pilot_analysis().is_some() {
         let title = if app.asking_ai {
             "Pilot (Thinking...)".to_string()
-        } else if let Some(s) = &app.suggestion {
+        } else if let Some(s) = app.pilot_analysis() {
             format!("Pilot (Analysis) - {} tokens", s.tokens_used)
         } else {
             "Pilot (Analysis)".to_string()
         };
 
-        let mut content = if app.asking_ai {
+        let mut content = if app.asking_ai && !app.pilot_stream.is_empty() {
+            app.pilot_stream.clone()
+        } else if app.asking_ai {
             "Contacting Pilot...".to_string()
-        } else if let Some(s) = &app.suggestion {
+        } else if let Some(s) = app.pilot_analysis() {
             s.analysis.clone()
         } else {
             "".to_string()
@@ -634,13 +1008,17 @@ fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
 
         // Append Fix Hint if available
         let mut border_style = Style::default().fg(Color::Yellow);
-        if let Some(suggestion) = &app.suggestion {
+        if let Some(suggestion) = app.pilot_analysis() {
             border_style = Style::default().fg(Color::Green);
             if let Some(fix) = &suggestion.fix {
                 content.push_str(&format!(
-                    "\n\n[PROPOSED FIX]\nKey: {}\nValue: {}\n\nPress [f] to Apply Fix",
+                    "\n\n[PROPOSED FIX]\nKey: {}\nValue: {}",
                     fix.key, fix.value
                 ));
+                // [f] applies the live suggestion, not a pinned one
+                if app.inspector_pin.is_none() {
+                    content.push_str("\n\nPress [f] to Apply Fix");
+                }
             }
         }
 
@@ -661,8 +1039,8 @@ fn draw_inspector(frame: &mut Frame, app: &mut App, area: Rect) {
         draw_help(frame);
     }
 
-    if app.show_detail_view {
-        draw_detail_view(frame, app);
+    if let Some(viewer) = &mut app.value_viewer {
+        viewer.draw(frame, centered_rect(80, 80, frame.area()));
     }
 }
 
@@ -690,6 +1068,11 @@ fn draw_help(frame: &mut Frame) {
             Cell::from("?").style(key_style),
             Cell::from("Toggle Help"),
         ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("` / Backspace").style(key_style),
+            Cell::from("Previous View"),
+        ]),
         Row::new(vec![Cell::from(""), Cell::from(""), Cell::from("")]),
         Row::new(vec![
             Cell::from(""),
@@ -701,11 +1084,26 @@ fn draw_help(frame: &mut Frame) {
             Cell::from("p").style(key_style),
             Cell::from("Proceed (Step)"),
         ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("S").style(key_style),
+            Cell::from("Toggle Step Mode (pause every task)"),
+        ]),
         Row::new(vec![
             Cell::from(""),
             Cell::from("c").style(key_style),
             Cell::from("Continue (Auto)"),
         ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("u").style(key_style),
+            Cell::from("Undo Last Variable Change"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("A").style(key_style),
+            Cell::from("Ask AI About Failure in Chat"),
+        ]),
         Row::new(vec![
             Cell::from(""),
             Cell::from("l").style(key_style),
@@ -716,6 +1114,52 @@ fn draw_help(frame: &mut Frame) {
             Cell::from("H").style(key_style),
             Cell::from("Host List"),
         ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("W").style(key_style),
+            Cell::from("Ansible Warnings / Deprecations"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("p").style(key_style),
+            Cell::from("Pin/Unpin Inspector"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("t").style(key_style),
+            Cell::from("Inspector JSON / YAML"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("M").style(key_style),
+            Cell::from("Edit Session Note"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("Space / n").style(key_style),
+            Cell::from("Timeline: Pause / Step"),
+        ]),
+        Row::new(vec![Cell::from(""), Cell::from(""), Cell::from("")]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("Metrics").style(header_style),
+            Cell::from(""),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("m").style(key_style),
+            Cell::from("Toggle Metrics"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("t").style(key_style),
+            Cell::from("Cycle Summary / Heatmap / Histogram / Hosts"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("j / k").style(key_style),
+            Cell::from("Scroll Hosts Timeline"),
+        ]),
         Row::new(vec![Cell::from(""), Cell::from(""), Cell::from("")]),
         Row::new(vec![
             Cell::from(""),
@@ -732,6 +1176,21 @@ fn draw_help(frame: &mut Frame) {
             Cell::from("Tab / Shift+Arr").style(key_style),
             Cell::from("Switch Pane"),
         ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("s").style(key_style),
+            Cell::from("Sort Task List"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("F / Ctrl+f").style(key_style),
+            Cell::from("Next / Previous Failed Task"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("b / B").style(key_style),
+            Cell::from("Breakpoint / Conditional Breakpoint"),
+        ]),
         Row::new(vec![
             Cell::from(""),
             Cell::from("j / k").style(key_style),
@@ -747,6 +1206,26 @@ fn draw_help(frame: &mut Frame) {
             Cell::from("Shift+h/l").style(key_style),
             Cell::from("Deep Collapse/Expand"),
         ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("z").style(key_style),
+            Cell::from("Collapse Siblings"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("o").style(key_style),
+            Cell::from("Flat path = value View"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("i").style(key_style),
+            Cell::from("Full Value Viewer (w wrap, x hex)"),
+        ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("e").style(key_style),
+            Cell::from("Edit Var/Fact Leaf (sends ModifyVar)"),
+        ]),
         Row::new(vec![
             Cell::from(""),
             Cell::from("w").style(key_style),
@@ -755,7 +1234,7 @@ fn draw_help(frame: &mut Frame) {
         Row::new(vec![
             Cell::from(""),
             Cell::from("/").style(key_style),
-            Cell::from("Search"),
+            Cell::from("Search (/re/ prefix for regex)"),
         ]),
         Row::new(vec![
             Cell::from(""),
@@ -798,6 +1277,11 @@ fn draw_help(frame: &mut Frame) {
             Cell::from("    v→5j→y").style(indent_style),
             Cell::from("  → Visual select 5 lines down, then yank"),
         ]),
+        Row::new(vec![
+            Cell::from(""),
+            Cell::from("Y").style(key_style),
+            Cell::from("Yank selected subtree as JSON"),
+        ]),
     ];
 
     let table = Table::new(
@@ -846,34 +1330,129 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
-fn draw_detail_view(frame: &mut Frame, app: &mut App) {
-    if let Some(tree) = &app.analysis_tree
-        && tree.selected_line < tree.lines.len()
-    {
-        let line = &tree.lines[tree.selected_line];
-        let area = centered_rect(60, 60, frame.area());
-        let block = Block::default()
-            .title("Detail View (Esc/w to Close)")
-            .borders(Borders::ALL)
-            .style(Style::default().bg(Color::Black));
-
-        frame.render_widget(Clear, area);
-        frame.render_widget(block.clone(), area);
+fn draw_warnings(frame: &mut Frame, app: &App) {
+    let (warning_count, deprecation_count) = app.warning_counts();
+    let mut lines = Vec::new();
+    for entry in app.warning_summary() {
+        let color = match entry.kind {
+            crate::app::WarningKind::Warning => Color::Yellow,
+            crate::app::WarningKind::Deprecation => Color::Magenta,
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("x{:<3} [{}] ", entry.count, entry.kind.label()),
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(entry.message),
+        ]));
+        lines.push(Line::from(Span::styled(
+            format!("       tasks: {}", entry.tasks.join(", ")),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    if lines.is_empty() {
+        lines.push(Line::from("No warnings or deprecations reported."));
+    }
 
-        let inner_area = block.inner(area);
+    let area = centered_rect(70, 60, frame.area());
+    let p = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title(format!(
+                    "Ansible Warnings: {} warning(s), {} deprecation(s) (Esc/W: Close)",
+                    warning_count, deprecation_count
+                ))
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(Clear, area);
+    frame.render_widget(p, area);
+}
 
-        let content = if let Some(key) = &line.key {
-            format!("{}:\n{}", key, line.value_str)
-        } else {
-            line.value_str.clone()
-        };
+fn draw_note_input(frame: &mut Frame, app: &App) {
+    let Some(buffer) = &app.note_input else {
+        return;
+    };
+    let area = centered_rect(60, 20, frame.area());
+    let input = Paragraph::new(format!("{}_", buffer))
+        .block(
+            Block::default()
+                .title("Session Note (Enter: Save, Esc: Cancel, empty clears)")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::Black)),
+        )
+        .style(Style::default().fg(Color::Yellow))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(Clear, area);
+    frame.render_widget(input, area);
+}
 
-        let p = Paragraph::new(content)
-            .wrap(Wrap { trim: false })
-            .scroll((0, 0));
+fn draw_startup_error(frame: &mut Frame, app: &App) {
+    let Some(err) = &app.startup_error else {
+        return;
+    };
+    let mut lines: Vec<Line> = err.lines().map(|l| Line::from(l.to_string())).collect();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Press q / Esc / Enter to quit",
+        Style::default().fg(Color::DarkGray),
+    )));
+    let area = centered_rect(60, 30, frame.area());
+    let p = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Cannot Start Playbook")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(Clear, area);
+    frame.render_widget(p, area);
+}
 
-        frame.render_widget(p, inner_area);
-    }
+fn draw_var_conflict(frame: &mut Frame, app: &App) {
+    let Some((change, _)) = &app.pending_var_change else {
+        return;
+    };
+    let previous = app
+        .find_var_conflict(&change.key)
+        .map(|c| c.value.to_string())
+        .unwrap_or_default();
+    let lines = vec![
+        Line::from(vec![
+            Span::raw("'"),
+            Span::styled(
+                change.key.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw("' has an earlier change not yet picked up."),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("Previous: "),
+            Span::styled(previous, Style::default().fg(Color::Red)),
+        ]),
+        Line::from(vec![
+            Span::raw("New:      "),
+            Span::styled(change.value.to_string(), Style::default().fg(Color::Green)),
+        ]),
+        Line::from(""),
+        Line::from("y / Enter: Overwrite    any other key: Keep previous"),
+    ];
+    let area = centered_rect(60, 30, frame.area());
+    let p = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .title("Conflicting Variable Change")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+                .style(Style::default().bg(Color::Black)),
+        )
+        .wrap(Wrap { trim: false });
+    frame.render_widget(Clear, area);
+    frame.render_widget(p, area);
 }
 
 fn draw_variable_selector(frame: &mut Frame, app: &mut App) {
@@ -932,3 +1511,123 @@ fn draw_variable_selector(frame: &mut Frame, app: &mut App) {
         frame.render_stateful_widget(list, chunks[1], &mut state);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_format_elapsed() {
+        let start = Instant::now();
+        assert_eq!(format_elapsed(start, start), "0s");
+        assert_eq!(
+            format_elapsed(start, start + Duration::from_secs(12)),
+            "12s"
+        );
+        assert_eq!(
+            format_elapsed(start, start + Duration::from_secs(184)),
+            "3m 04s"
+        );
+        assert_eq!(
+            format_elapsed(start, start + Duration::from_secs(3720)),
+            "1h 02m"
+        );
+        // A reference instant before the start never underflows
+        assert_eq!(format_elapsed(start + Duration::from_secs(5), start), "0s");
+
+        let started_at = chrono::Utc::now();
+        let now = started_at + chrono::Duration::seconds(75);
+        assert_eq!(format_finished_ago(started_at, 3.5, now), "1m 11s");
+        assert_eq!(format_finished_ago(started_at, 100.0, now), "0s");
+    }
+
+    fn test_app() -> App {
+        let config = crate::config::Config {
+            no_ai: true,
+            ..Default::default()
+        };
+        App::new(config)
+    }
+
+    fn row_text(terminal: &ratatui::Terminal<ratatui::backend::TestBackend>, y: u16) -> String {
+        let buf = terminal.backend().buffer();
+        (0..buf.area.width)
+            .map(|x| buf[(x, y)].symbol().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_task_list_shows_when_tasks_finished() {
+        let mut app = test_app();
+        app.history.push(crate::app::TaskHistory {
+            duration: 5.0,
+            started_at: Some(chrono::Utc::now() - chrono::Duration::seconds(3605)),
+            ..crate::app::TaskHistory::new("install", "web1")
+        });
+        // Older sessions have no timestamps: nothing to show
+        app.history
+            .push(crate::app::TaskHistory::new("restart", "web1"));
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(160, 8)).unwrap();
+        terminal
+            .draw(|f| draw_analysis(f, &mut app, f.area()))
+            .unwrap();
+
+        let rows: Vec<String> = (0..8).map(|y| row_text(&terminal, y)).collect();
+        let install = rows.iter().find(|r| r.contains("install")).unwrap();
+        assert!(install.contains("(finished 1h 00m ago)"));
+        let restart = rows.iter().find(|r| r.contains("restart")).unwrap();
+        assert!(!restart.contains("finished"));
+    }
+
+    #[test]
+    fn test_event_rate_in_logs_title() {
+        let mut app = test_app();
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 10)).unwrap();
+        let top_row =
+            |terminal: &ratatui::Terminal<ratatui::backend::TestBackend>| row_text(terminal, 0);
+
+        // No samples yet: a flat zero, not a panic
+        terminal.draw(|f| draw_logs(f, &mut app, f.area())).unwrap();
+        assert!(top_row(&terminal).contains("Events/s: 0"));
+
+        app.event_velocity.extend([1, 4, 12]);
+        terminal.draw(|f| draw_logs(f, &mut app, f.area())).unwrap();
+        let row = top_row(&terminal);
+        assert!(row.starts_with("┌Ansible Logs"));
+        assert!(row.contains("Events/s: 12"));
+        assert!(row.contains('█'));
+
+        // Too narrow to fit beside the title: only the title is drawn
+        let mut narrow = ratatui::Terminal::new(ratatui::backend::TestBackend::new(30, 5)).unwrap();
+        narrow.draw(|f| draw_logs(f, &mut app, f.area())).unwrap();
+        assert!(!top_row(&narrow).contains("Events/s"));
+    }
+
+    #[test]
+    fn test_footer_hints_follow_view_and_connection() {
+        let mut app = test_app();
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(100, 20)).unwrap();
+
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let footer = row_text(&terminal, 19);
+        assert!(footer.contains("r retry · c continue · a ask AI · ? help"));
+        assert!(footer.trim_end().ends_with("DISCONNECTED"));
+        // The panes end above the footer
+        assert!(row_text(&terminal, 18).starts_with('└'));
+
+        app.active_view = crate::app::ActiveView::Analysis;
+        app.replay_mode = true;
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        let footer = row_text(&terminal, 19);
+        assert!(footer.contains("j/k nav · / search"));
+        assert!(footer.trim_end().ends_with("REPLAY"));
+
+        app.active_view = crate::app::ActiveView::Metrics;
+        terminal.draw(|f| draw(f, &mut app)).unwrap();
+        assert!(row_text(&terminal, 19).contains("t cycle view"));
+    }
+}
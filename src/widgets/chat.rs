@@ -73,6 +73,14 @@ pub fn draw_chat(frame: &mut Frame, app: &mut App, area: Rect) {
                 ));
             }
 
+            stats_spans.push(Span::raw(" │ "));
+            stats_spans.push(Span::styled(
+                format!(
+                    "Cost: ${:.4} @ ${}/1k ",
+                    status.used_usd, status.rate_per_1k_usd
+                ),
+                Style::default().fg(Color::White),
+            ));
             stats_spans.push(Span::raw(" │ "));
             stats_spans.push(Span::styled(
                 format!("Reset: {}h{}m ", reset_hours, reset_mins),
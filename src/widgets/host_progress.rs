@@ -0,0 +1,129 @@
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Gauge, Paragraph},
+};
+
+use crate::app::{App, HostStatus};
+
+/// Progress of a single host for the hosts timeline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostProgress {
+    pub name: String,
+    pub done: usize,
+    pub failed: usize,
+    /// Tasks done relative to the furthest-along host (0.0–1.0)
+    pub ratio: f64,
+    /// Task the host is running, or else the last one it finished
+    pub task: Option<String>,
+    pub running: bool,
+    pub unreachable: bool,
+}
+
+/// Per-host progress, sorted by host name. The playbook's total task count is
+/// not known up front, so progress is measured against the host that has
+/// completed the most tasks.
+pub fn host_progress(app: &App) -> Vec<HostProgress> {
+    let max_done = app.hosts.values().map(tasks_done).max().unwrap_or(0);
+
+    let mut progress: Vec<HostProgress> = app
+        .hosts
+        .values()
+        .map(|h| {
+            let done = tasks_done(h);
+            let task = app.host_task(&h.name);
+            HostProgress {
+                name: h.name.clone(),
+                done,
+                failed: h.failed_tasks,
+                ratio: if max_done == 0 {
+                    0.0
+                } else {
+                    done as f64 / max_done as f64
+                },
+                task: task.map(|(name, _)| name.to_string()),
+                running: task.is_some_and(|(_, running)| running),
+                unreachable: app.unreachable_hosts.contains(&h.name),
+            }
+        })
+        .collect();
+    progress.sort_by(|a, b| a.name.cmp(&b.name));
+    progress
+}
+
+/// Tasks `host` has finished in any way, rescued failures included.
+fn tasks_done(host: &HostStatus) -> usize {
+    host.ok_tasks + host.changed_tasks + host.failed_tasks + host.rescued_tasks + host.skipped_tasks
+}
+
+/// First row and number of rows shown when `total` rows, `fit` of which fit
+/// on screen, are scrolled to `scroll`; the last page is kept full.
+pub fn visible_rows(total: usize, fit: usize, scroll: usize) -> (usize, usize) {
+    let shown = total.min(fit);
+    (scroll.min(total - shown), shown)
+}
+
+pub struct HostProgressWidget;
+
+impl HostProgressWidget {
+    pub fn draw(frame: &mut Frame, app: &App, area: Rect) {
+        let hosts = host_progress(app);
+        let inner_height = area.height.saturating_sub(2) as usize;
+        let (first, shown) = visible_rows(hosts.len(), inner_height / 3, app.host_progress_scroll);
+
+        let title = if shown < hosts.len() {
+            format!(
+                "Hosts Timeline ({}-{} of {}, j/k: scroll)",
+                first + 1,
+                first + shown,
+                hosts.len()
+            )
+        } else {
+            "Hosts Timeline".to_string()
+        };
+        let block = Block::default().title(title).borders(Borders::ALL);
+        let inner_area = block.inner(area);
+        frame.render_widget(block, area);
+
+        if hosts.is_empty() {
+            let p =
+                Paragraph::new("No hosts seen yet.").alignment(ratatui::layout::Alignment::Center);
+            frame.render_widget(p, inner_area);
+            return;
+        }
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(3); shown])
+            .split(inner_area);
+
+        for (host, row) in hosts[first..first + shown].iter().zip(rows.iter()) {
+            let color = if host.unreachable {
+                Color::DarkGray
+            } else if host.failed > 0 {
+                Color::Red
+            } else {
+                Color::Green
+            };
+            let mut title = format!("{} — {} done", host.name, host.done);
+            if host.failed > 0 {
+                title.push_str(&format!(", {} failed", host.failed));
+            }
+            if host.unreachable {
+                title.push_str(" [UNREACHABLE]");
+            }
+            let label = match &host.task {
+                Some(task) if host.running => format!("▶ {}", task),
+                Some(task) => task.clone(),
+                None => String::new(),
+            };
+            let gauge = Gauge::default()
+                .block(Block::default().title(title).borders(Borders::ALL))
+                .gauge_style(Style::default().fg(color))
+                .ratio(host.ratio.clamp(0.0, 1.0))
+                .label(label);
+            frame.render_widget(gauge, *row);
+        }
+    }
+}
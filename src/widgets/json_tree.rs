@@ -5,10 +5,27 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::StatefulWidget;
 use std::collections::HashSet;
 
+use super::value_viewer::slice_chars;
+use crate::search::SearchPattern;
+
+/// `(top-level key, its current value, selected leaf, leaf path within the
+/// key's value)`, as returned by [`JsonTreeState::edit_target`].
+pub type EditTarget = (
+    String,
+    serde_json::Value,
+    serde_json::Value,
+    Vec<PathSegment>,
+);
+
 #[derive(Debug, Clone)]
 pub struct JsonTreeState {
     pub value: serde_json::Value,
-    pub collapsed_paths: HashSet<String>,
+    pub collapsed_paths: HashSet<Vec<PathSegment>>,
+    /// Containers the user has expanded. Everything below the root starts
+    /// collapsed and is only flattened once expanded, so a huge result (e.g.
+    /// a package list) doesn't stall the first render; `collapsed_paths`
+    /// wins if a path is in both
+    pub expanded_paths: HashSet<Vec<PathSegment>>,
     pub selected_line: usize,
     pub scroll_offset: usize,
     // Flattened lines cache
@@ -19,11 +36,52 @@ pub struct JsonTreeState {
     pub current_match_index: Option<usize>,
     pub height: usize,
     pub text_wrap: bool, // [NEW]
+    /// Show one `path = value` line per leaf instead of the nested tree
+    pub flat_view: bool,
+    /// Screen area of the last render, for mouse hit-testing
+    pub area: Rect,
+    /// Line index shown on each screen row of the last render; wrapped
+    /// continuation rows repeat their owning line
+    pub rendered_rows: Vec<usize>,
+    /// Whether the top-level keys are playbook variables or facts, so leaf
+    /// edits can be sent back with `ModifyVar`. Task results and query
+    /// output are read-only.
+    pub editable: bool,
+}
+
+/// One step from a parent to a child value. Paths are kept as segments so a
+/// key containing `.` or `[` never gets confused with nesting.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Display form of a path: keys joined by `.`, indices as `[i]`.
+pub fn display_path(segments: &[PathSegment]) -> String {
+    let mut out = String::new();
+    for segment in segments {
+        match segment {
+            PathSegment::Key(k) => {
+                if !out.is_empty() {
+                    out.push('.');
+                }
+                out.push_str(k);
+            }
+            PathSegment::Index(i) => out.push_str(&format!("[{}]", i)),
+        }
+    }
+    out
 }
 
 #[derive(Debug, Clone)]
 pub struct JsonLine {
+    /// Display path, derived from `segments`
     pub path: String,
+    pub segments: Vec<PathSegment>,
+    /// Synthetic `}` / `]` line ending an expanded container; shares the
+    /// container's `segments`
+    pub is_closing: bool,
     pub depth: usize,
     pub key: Option<String>,
     pub value_str: String,
@@ -37,6 +95,7 @@ impl JsonTreeState {
         let mut state = Self {
             value,
             collapsed_paths: HashSet::new(),
+            expanded_paths: HashSet::new(),
             selected_line: 0,
             scroll_offset: 0,
             lines: Vec::new(),
@@ -45,15 +104,31 @@ impl JsonTreeState {
             current_match_index: None,
             height: 0,
             text_wrap: false,
+            flat_view: false,
+            area: Rect::default(),
+            rendered_rows: Vec::new(),
+            editable: false,
         };
         state.recalc_lines();
         state
     }
 
+    /// Allow leaf edits: the root's keys are variables or facts.
+    pub fn editable(mut self) -> Self {
+        self.editable = true;
+        self
+    }
+
     pub fn recalc_lines(&mut self) {
         self.lines.clear();
-        let val = self.value.clone(); // Clone to avoid borrow issues
-        self.flatten_value(&val, String::new(), 0, None);
+        // Moved out for the walk rather than cloned: the value can be huge
+        let val = std::mem::take(&mut self.value);
+        if self.flat_view {
+            self.flatten_leaves(&val, Vec::new());
+        } else {
+            self.flatten_value(&val, Vec::new(), 0, None);
+        }
+        self.value = val;
 
         // If we have a search query, re-run search logic
         if !self.search_query.is_empty() {
@@ -64,12 +139,13 @@ impl JsonTreeState {
     fn flatten_value(
         &mut self,
         val: &serde_json::Value,
-        path: String,
+        segments: Vec<PathSegment>,
         depth: usize,
         key: Option<String>,
     ) {
         let is_collapsible = val.is_object() || val.is_array();
-        let is_expanded = !self.collapsed_paths.contains(&path);
+        let is_expanded = self.is_expanded(&segments);
+        let path = display_path(&segments);
 
         // Format value string
         // For collapsible, show { ... } or [ ... ] if collapsed
@@ -81,24 +157,27 @@ impl JsonTreeState {
                 } else {
                     "[".to_string()
                 }
+            } else if !self.collapsed_paths.contains(&segments) {
+                // Collapsed by default: say how much is behind it
+                let n = child_count(val);
+                if val.is_object() {
+                    format!("{{ ... {} keys }}", n)
+                } else {
+                    format!("[ ... {} items ]", n)
+                }
             } else if val.is_object() {
                 "{ ... }".to_string()
             } else {
                 "[ ... ]".to_string()
             }
         } else {
-            // Primitive
-            match val {
-                serde_json::Value::Null => "null".to_string(),
-                serde_json::Value::Bool(b) => b.to_string(),
-                serde_json::Value::Number(n) => n.to_string(),
-                serde_json::Value::String(s) => format!("\"{}\"", s), // Quote strings?
-                _ => "".to_string(),
-            }
+            scalar_str(val)
         };
 
         self.lines.push(JsonLine {
             path: path.clone(),
+            segments: segments.clone(),
+            is_closing: false,
             depth,
             key: key.clone(),
             value_str,
@@ -111,16 +190,15 @@ impl JsonTreeState {
             match val {
                 serde_json::Value::Object(map) => {
                     for (k, v) in map {
-                        let new_path = if path.is_empty() {
-                            k.clone()
-                        } else {
-                            format!("{}.{}", path, k)
-                        };
-                        self.flatten_value(v, new_path, depth + 1, Some(format!("\"{}\"", k)));
+                        let mut child = segments.clone();
+                        child.push(PathSegment::Key(k.clone()));
+                        self.flatten_value(v, child, depth + 1, Some(format!("\"{}\"", k)));
                     }
                     // Closing brace
                     self.lines.push(JsonLine {
-                        path: format!("{}.}}", path), // Hacky path for closing
+                        path: format!("{}.}}", path),
+                        segments: segments.clone(),
+                        is_closing: true,
                         depth,
                         key: None,
                         value_str: "}".to_string(),
@@ -131,16 +209,15 @@ impl JsonTreeState {
                 }
                 serde_json::Value::Array(arr) => {
                     for (i, v) in arr.iter().enumerate() {
-                        let new_path = if path.is_empty() {
-                            format!("[{}]", i)
-                        } else {
-                            format!("{}[{}]", path, i)
-                        };
-                        self.flatten_value(v, new_path, depth + 1, None); // Array items have no key
+                        let mut child = segments.clone();
+                        child.push(PathSegment::Index(i));
+                        self.flatten_value(v, child, depth + 1, None); // Array items have no key
                     }
                     // Closing bracket
                     self.lines.push(JsonLine {
                         path: format!("{}.]", path),
+                        segments: segments.clone(),
+                        is_closing: true,
                         depth,
                         key: None,
                         value_str: "]".to_string(),
@@ -154,104 +231,184 @@ impl JsonTreeState {
         }
     }
 
-    pub fn expand_all(&mut self) {
-        self.collapsed_paths.clear();
-        self.recalc_lines();
-    }
-
-    pub fn collapse_all(&mut self) {
-        // Collect all paths that are objects/arrays
-        let val = self.value.clone();
-        self.collect_collapsible_paths(&val, String::new());
-        self.recalc_lines();
+    /// Whether the container at `segments` shows its children: the root
+    /// unless collapsed, other containers only once the user expands them.
+    fn is_expanded(&self, segments: &[PathSegment]) -> bool {
+        !self.collapsed_paths.contains(segments)
+            && (segments.is_empty() || self.expanded_paths.contains(segments))
     }
 
-    fn collect_collapsible_paths(&mut self, val: &serde_json::Value, path: String) {
-        if (val.is_object() || val.is_array()) && !path.is_empty() {
-            self.collapsed_paths.insert(path.clone());
+    fn set_expanded(&mut self, segments: &[PathSegment], expanded: bool) {
+        if expanded {
+            self.collapsed_paths.remove(segments);
+            self.expanded_paths.insert(segments.to_vec());
+        } else {
+            self.expanded_paths.remove(segments);
+            self.collapsed_paths.insert(segments.to_vec());
         }
+    }
 
+    /// Compact view: one line per leaf, keyed by its full path. Empty objects
+    /// and arrays count as leaves so they don't vanish from the listing.
+    fn flatten_leaves(&mut self, val: &serde_json::Value, segments: Vec<PathSegment>) {
         match val {
-            serde_json::Value::Object(map) => {
+            serde_json::Value::Object(map) if !map.is_empty() => {
                 for (k, v) in map {
-                    let new_path = if path.is_empty() {
-                        k.clone()
-                    } else {
-                        format!("{}.{}", path, k)
-                    };
-                    self.collect_collapsible_paths(v, new_path);
+                    let mut child = segments.clone();
+                    child.push(PathSegment::Key(k.clone()));
+                    self.flatten_leaves(v, child);
                 }
             }
-            serde_json::Value::Array(arr) => {
+            serde_json::Value::Array(arr) if !arr.is_empty() => {
                 for (i, v) in arr.iter().enumerate() {
-                    let new_path = if path.is_empty() {
-                        format!("[{}]", i)
-                    } else {
-                        format!("{}[{}]", path, i)
-                    };
-                    self.collect_collapsible_paths(v, new_path);
+                    let mut child = segments.clone();
+                    child.push(PathSegment::Index(i));
+                    self.flatten_leaves(v, child);
                 }
             }
-            _ => {}
+            _ => {
+                let value_str = match val {
+                    serde_json::Value::String(s) => format!("\"{}\"", s),
+                    other => other.to_string(),
+                };
+                let path = display_path(&segments);
+                self.lines.push(JsonLine {
+                    key: Some(if path.is_empty() {
+                        ".".to_string()
+                    } else {
+                        path.clone()
+                    }),
+                    path,
+                    segments,
+                    is_closing: false,
+                    depth: 0,
+                    value_str,
+                    is_collapsible: false,
+                    is_expanded: false,
+                    index_in_full: 0,
+                });
+            }
+        }
+    }
+
+    /// Switch between the nested tree and the flattened `path = value` view,
+    /// keeping the cursor on the same leaf when it exists in both.
+    pub fn toggle_flat_view(&mut self) {
+        let selected = self
+            .lines
+            .get(self.selected_line)
+            .map(|l| l.segments.clone());
+        self.flat_view = !self.flat_view;
+        self.recalc_lines();
+        self.selected_line = selected.and_then(|s| self.position_of(&s)).unwrap_or(0);
+    }
+
+    /// Text for a line as shown and yanked: `key: value` in the tree,
+    /// `path = value` in the flat view.
+    fn line_text(&self, line: &JsonLine) -> String {
+        match &line.key {
+            Some(key) if self.flat_view => format!("{} = {}", key, line.value_str),
+            Some(key) => format!("{}: {}", key, line.value_str),
+            None => line.value_str.clone(),
         }
     }
 
+    pub fn expand_all(&mut self) {
+        self.collapsed_paths.clear();
+        self.expand_all_under(&[]);
+        self.recalc_lines();
+    }
+
+    pub fn collapse_all(&mut self) {
+        self.expanded_paths.clear();
+        self.collapse_all_under(&[]);
+        // The root itself stays open
+        self.collapsed_paths.remove(&Vec::new());
+        self.recalc_lines();
+    }
+
     pub fn expand_current_recursive(&mut self) {
         if self.selected_line < self.lines.len() {
-            let line = &self.lines[self.selected_line].clone();
+            let prefix = self.lines[self.selected_line].segments.clone();
             // Remove current path and all children from collapsed_paths
-            let path_prefix = &line.path;
-            self.collapsed_paths.retain(|p| !p.starts_with(path_prefix));
+            self.collapsed_paths.retain(|p| !p.starts_with(&prefix));
+            self.expand_all_under(&prefix);
             self.recalc_lines();
         }
     }
 
     pub fn collapse_current_recursive(&mut self) {
         if self.selected_line < self.lines.len() {
-            let line = &self.lines[self.selected_line].clone();
-            // Re-traverse from this value to find all paths to collapse
-            // We need to find the value at this path first.
-            // Better: just add all child paths of current line.path to collapsed
-            // This requires knowing the structure or re-traversing.
-            // Simplified: Traverse whole value, if path starts with current path, collapse it.
-            let val = self.value.clone();
-            self.collect_collapsible_paths_under(&val, String::new(), &line.path);
+            let prefix = self.lines[self.selected_line].segments.clone();
+            self.expanded_paths.retain(|p| !p.starts_with(&prefix));
+            self.collapse_all_under(&prefix);
             self.recalc_lines();
         }
     }
 
-    fn collect_collapsible_paths_under(
-        &mut self,
-        val: &serde_json::Value,
-        current_path: String,
-        target_prefix: &str,
-    ) {
-        if (val.is_object() || val.is_array()) && current_path.starts_with(target_prefix) {
-            self.collapsed_paths.insert(current_path.clone());
+    /// Collapse every container at or below `prefix`.
+    fn collapse_all_under(&mut self, prefix: &[PathSegment]) {
+        let Some(val) = value_at(&self.value, prefix) else {
+            return;
+        };
+        let collapsed = &mut self.collapsed_paths;
+        visit_containers(val, &mut prefix.to_vec(), &mut |path, _| {
+            collapsed.insert(path.to_vec());
+        });
+    }
+
+    /// Mark every container at or below `prefix` as expanded.
+    fn expand_all_under(&mut self, prefix: &[PathSegment]) {
+        let Some(val) = value_at(&self.value, prefix) else {
+            return;
+        };
+        let expanded = &mut self.expanded_paths;
+        visit_containers(val, &mut prefix.to_vec(), &mut |path, _| {
+            expanded.insert(path.to_vec());
+        });
+    }
+
+    /// Line index of the (non-closing) line for `segments`.
+    fn position_of(&self, segments: &[PathSegment]) -> Option<usize> {
+        self.lines
+            .iter()
+            .position(|l| !l.is_closing && l.segments == segments)
+    }
+
+    /// Accordion-style collapse: collapse every collapsible sibling of the
+    /// selected node (same parent, same depth), keeping the selected branch and
+    /// its ancestors expanded.
+    pub fn collapse_siblings(&mut self) {
+        let Some(selected) = self.lines.get(self.selected_line) else {
+            return;
+        };
+        let depth = selected.depth;
+        let selected_path = selected.segments.clone();
+        if depth == 0 {
+            return;
         }
 
-        match val {
-            serde_json::Value::Object(map) => {
-                for (k, v) in map {
-                    let new_path = if current_path.is_empty() {
-                        k.clone()
-                    } else {
-                        format!("{}.{}", current_path, k)
-                    };
-                    self.collect_collapsible_paths_under(v, new_path, target_prefix);
-                }
-            }
-            serde_json::Value::Array(arr) => {
-                for (i, v) in arr.iter().enumerate() {
-                    let new_path = if current_path.is_empty() {
-                        format!("[{}]", i)
-                    } else {
-                        format!("{}[{}]", current_path, i)
-                    };
-                    self.collect_collapsible_paths_under(v, new_path, target_prefix);
-                }
-            }
-            _ => {}
+        // Parent is the nearest preceding line with a smaller depth
+        let parent = (0..self.selected_line)
+            .rev()
+            .find(|&i| self.lines[i].depth < depth)
+            .unwrap_or(0);
+
+        let siblings: Vec<Vec<PathSegment>> = self.lines[parent + 1..]
+            .iter()
+            .take_while(|l| l.depth >= depth)
+            .filter(|l| l.depth == depth && l.is_collapsible && l.segments != selected_path)
+            .map(|l| l.segments.clone())
+            .collect();
+        if siblings.is_empty() {
+            return;
+        }
+        self.collapsed_paths.extend(siblings);
+        self.recalc_lines();
+
+        // Keep the cursor on the same node after lines above it shrink
+        if let Some(idx) = self.position_of(&selected_path) {
+            self.selected_line = idx;
         }
     }
 
@@ -259,16 +416,37 @@ impl JsonTreeState {
         if self.selected_line < self.lines.len() {
             let line = &self.lines[self.selected_line];
             if line.is_collapsible {
-                if self.collapsed_paths.contains(&line.path) {
-                    self.collapsed_paths.remove(&line.path);
-                } else {
-                    self.collapsed_paths.insert(line.path.clone());
-                }
+                let (segments, expanded) = (line.segments.clone(), line.is_expanded);
+                self.set_expanded(&segments, !expanded);
                 self.recalc_lines();
             }
         }
     }
 
+    /// Line drawn at screen cell (`column`, `row`) in the last render.
+    pub fn line_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !self
+            .area
+            .contains(ratatui::layout::Position::new(column, row))
+        {
+            return None;
+        }
+        self.rendered_rows
+            .get((row - self.area.y) as usize)
+            .copied()
+    }
+
+    /// Select the clicked line and expand/collapse it if it is an object or
+    /// array. Returns `false` when the click missed the tree.
+    pub fn click(&mut self, column: u16, row: u16) -> bool {
+        let Some(idx) = self.line_at(column, row) else {
+            return false;
+        };
+        self.selected_line = idx;
+        self.toggle_collapse();
+        true
+    }
+
     pub fn select_next(&mut self) {
         if self.selected_line < self.lines.len().saturating_sub(1) {
             self.selected_line += 1;
@@ -283,7 +461,31 @@ impl JsonTreeState {
 
     pub fn set_search(&mut self, query: String) {
         self.search_query = query;
-        self.perform_search();
+        if self.reveal_matches() {
+            self.recalc_lines();
+        } else {
+            self.perform_search();
+        }
+    }
+
+    /// Expand the containers collapsed by default that hide a match of the
+    /// search query, so every match has a line. This walks the whole value;
+    /// containers the user collapsed stay collapsed. Returns whether
+    /// anything was expanded.
+    fn reveal_matches(&mut self) -> bool {
+        if self.search_query.is_empty() || self.flat_view {
+            return false;
+        }
+        let pattern = SearchPattern::new(&self.search_query);
+        let mut hiding = Vec::new();
+        containers_with_match(&self.value, &mut Vec::new(), &pattern, &mut hiding);
+        let mut revealed = false;
+        for path in hiding {
+            if !self.collapsed_paths.contains(&path) {
+                revealed |= self.expanded_paths.insert(path);
+            }
+        }
+        revealed
     }
 
     pub fn perform_search(&mut self) {
@@ -293,24 +495,37 @@ impl JsonTreeState {
             return;
         }
 
-        let query = self.search_query.to_lowercase();
+        let pattern = SearchPattern::new(&self.search_query);
         for (i, line) in self.lines.iter().enumerate() {
-            if line.value_str.to_lowercase().contains(&query)
-                || line
-                    .key
-                    .as_deref()
-                    .unwrap_or("")
-                    .to_lowercase()
-                    .contains(&query)
+            if pattern.is_match(&line.value_str)
+                || pattern.is_match(line.key.as_deref().unwrap_or(""))
             {
                 self.matched_lines.push(i);
             }
         }
 
         if !self.matched_lines.is_empty() {
-            self.current_match_index = Some(0);
-            // Jump to first match?
-            self.selected_line = self.matched_lines[0];
+            self.focus_match(0);
+        }
+    }
+
+    /// Make match `index` current: select it and scroll it into view.
+    fn focus_match(&mut self, index: usize) {
+        self.current_match_index = Some(index);
+        self.selected_line = self.matched_lines[index];
+        self.scroll_into_view(self.selected_line);
+    }
+
+    /// Adjust `scroll_offset` so `line` is on screen, even before the next
+    /// render. Wrapped values may push it down, so keep it off the last row.
+    fn scroll_into_view(&mut self, line: usize) {
+        if self.height == 0 {
+            return;
+        }
+        if line < self.scroll_offset {
+            self.scroll_offset = line;
+        } else if line >= self.scroll_offset + self.height.saturating_sub(1) {
+            self.scroll_offset = line.saturating_sub(self.height / 2);
         }
     }
 
@@ -320,8 +535,7 @@ impl JsonTreeState {
         }
         if let Some(curr) = self.current_match_index {
             let next = (curr + 1) % self.matched_lines.len();
-            self.current_match_index = Some(next);
-            self.selected_line = self.matched_lines[next];
+            self.focus_match(next);
         }
     }
 
@@ -335,8 +549,7 @@ impl JsonTreeState {
             } else {
                 curr - 1
             };
-            self.current_match_index = Some(prev);
-            self.selected_line = self.matched_lines[prev];
+            self.focus_match(prev);
         }
     }
     pub fn page_up(&mut self) {
@@ -361,7 +574,8 @@ impl JsonTreeState {
     pub fn collapse_or_parent(&mut self) {
         if let Some(line) = self.lines.get(self.selected_line) {
             if line.is_collapsible && line.is_expanded {
-                self.collapsed_paths.insert(line.path.clone());
+                let segments = line.segments.clone();
+                self.set_expanded(&segments, false);
                 self.recalc_lines();
             } else {
                 // Find parent: scan backwards for depth < current.depth
@@ -381,7 +595,8 @@ impl JsonTreeState {
     pub fn expand_or_child(&mut self) {
         if let Some(line) = self.lines.get(self.selected_line) {
             if line.is_collapsible && !line.is_expanded {
-                self.collapsed_paths.remove(&line.path);
+                let segments = line.segments.clone();
+                self.set_expanded(&segments, true);
                 self.recalc_lines();
             } else {
                 // Move down if possible
@@ -391,13 +606,54 @@ impl JsonTreeState {
     }
 
     pub fn get_selected_content(&self) -> Option<String> {
-        self.lines.get(self.selected_line).map(|line| {
-            if let Some(key) = &line.key {
-                format!("{}: {}", key, line.value_str)
-            } else {
-                line.value_str.clone()
-            }
-        })
+        self.lines
+            .get(self.selected_line)
+            .map(|line| self.line_text(line))
+    }
+
+    /// The selected node and everything under it as pretty JSON, so yanking
+    /// a collapsed `{ ... }` copies the real data. Closing-brace lines have no
+    /// node of their own and fall back to [`Self::get_selected_content`].
+    pub fn get_selected_subtree_json(&self) -> Option<String> {
+        match self.selected_value() {
+            Some(value) => serde_json::to_string_pretty(value).ok(),
+            None => self.get_selected_content(),
+        }
+    }
+
+    /// The JSON value behind the selected line (`None` for closing braces).
+    pub fn selected_value(&self) -> Option<&serde_json::Value> {
+        let line = self.lines.get(self.selected_line)?;
+        if line.is_closing {
+            return None;
+        }
+        value_at(&self.value, &line.segments)
+    }
+
+    /// Variable edit for the selected line: the top-level key the plugin can
+    /// set, that key's current value, the selected leaf and the leaf's path
+    /// within that value. Only primitives under an object root of variables
+    /// or facts (see [`JsonTreeState::editable`]) are editable.
+    pub fn edit_target(&self) -> Result<EditTarget, String> {
+        if !self.editable {
+            return Err("Only variables and facts can be edited".to_string());
+        }
+        let leaf = self
+            .selected_value()
+            .ok_or_else(|| "No value on this line".to_string())?;
+        if leaf.is_object() || leaf.is_array() {
+            return Err("Only leaf values can be edited".to_string());
+        }
+        let segments = &self.lines[self.selected_line].segments;
+        match (segments.first(), self.value.as_object()) {
+            (Some(PathSegment::Key(key)), Some(map)) => Ok((
+                key.clone(),
+                map[key].clone(),
+                leaf.clone(),
+                segments[1..].to_vec(),
+            )),
+            _ => Err(format!("Path '{}' is not editable", display_path(segments))),
+        }
     }
 
     pub fn get_selected_path(&self) -> Option<String> {
@@ -418,12 +674,7 @@ impl JsonTreeState {
         let mut result = Vec::new();
         for i in start_idx..=end_idx {
             if let Some(line) = self.lines.get(i) {
-                let content = if let Some(key) = &line.key {
-                    format!("{}: {}", key, line.value_str)
-                } else {
-                    line.value_str.clone()
-                };
-                result.push(content);
+                result.push(self.line_text(line));
             }
         }
 
@@ -438,6 +689,121 @@ impl JsonTreeState {
     }
 }
 
+/// Number of direct children of an object or array (0 for scalars).
+fn child_count(val: &serde_json::Value) -> usize {
+    match val {
+        serde_json::Value::Object(map) => map.len(),
+        serde_json::Value::Array(arr) => arr.len(),
+        _ => 0,
+    }
+}
+
+/// A scalar as shown in the tree, strings quoted.
+fn scalar_str(val: &serde_json::Value) -> String {
+    match val {
+        serde_json::Value::Null => "null".to_string(),
+        serde_json::Value::Bool(b) => b.to_string(),
+        serde_json::Value::Number(n) => n.to_string(),
+        serde_json::Value::String(s) => format!("\"{}\"", s),
+        _ => "".to_string(),
+    }
+}
+
+/// Call `f` with the path and value of every object/array at or below
+/// `val`, whose path is `current` (restored before returning).
+fn visit_containers(
+    val: &serde_json::Value,
+    current: &mut Vec<PathSegment>,
+    f: &mut impl FnMut(&[PathSegment], &serde_json::Value),
+) {
+    match val {
+        serde_json::Value::Object(map) => {
+            f(current, val);
+            for (k, v) in map {
+                current.push(PathSegment::Key(k.clone()));
+                visit_containers(v, current, f);
+                current.pop();
+            }
+        }
+        serde_json::Value::Array(arr) => {
+            f(current, val);
+            for (i, v) in arr.iter().enumerate() {
+                current.push(PathSegment::Index(i));
+                visit_containers(v, current, f);
+                current.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Collect the non-root containers whose subtree has a key or scalar
+/// matching `pattern`. Returns whether `val` itself contains a match.
+fn containers_with_match(
+    val: &serde_json::Value,
+    current: &mut Vec<PathSegment>,
+    pattern: &SearchPattern,
+    out: &mut Vec<Vec<PathSegment>>,
+) -> bool {
+    let found = match val {
+        serde_json::Value::Object(map) => {
+            let mut found = false;
+            for (k, v) in map {
+                current.push(PathSegment::Key(k.clone()));
+                found |= pattern.is_match(&format!("\"{}\"", k))
+                    | containers_with_match(v, current, pattern, out);
+                current.pop();
+            }
+            found
+        }
+        serde_json::Value::Array(arr) => {
+            let mut found = false;
+            for (i, v) in arr.iter().enumerate() {
+                current.push(PathSegment::Index(i));
+                found |= containers_with_match(v, current, pattern, out);
+                current.pop();
+            }
+            found
+        }
+        scalar => pattern.is_match(&scalar_str(scalar)),
+    };
+    if found && !current.is_empty() {
+        out.push(current.clone());
+    }
+    found
+}
+
+/// The value `segments` lead to from `val`.
+fn value_at<'a>(
+    val: &'a serde_json::Value,
+    segments: &[PathSegment],
+) -> Option<&'a serde_json::Value> {
+    segments.iter().try_fold(val, |v, segment| match segment {
+        PathSegment::Key(k) => v.as_object()?.get(k),
+        PathSegment::Index(i) => v.as_array()?.get(*i),
+    })
+}
+
+/// Replace the value `segments` lead to from `val`, returning whether it was
+/// found.
+pub fn set_by_path(
+    val: &mut serde_json::Value,
+    segments: &[PathSegment],
+    new: serde_json::Value,
+) -> bool {
+    let target = segments.iter().try_fold(val, |v, segment| match segment {
+        PathSegment::Key(k) => v.as_object_mut()?.get_mut(k),
+        PathSegment::Index(i) => v.as_array_mut()?.get_mut(*i),
+    });
+    match target {
+        Some(slot) => {
+            *slot = new;
+            true
+        }
+        None => false,
+    }
+}
+
 pub struct JsonTree;
 
 impl StatefulWidget for JsonTree {
@@ -446,6 +812,8 @@ impl StatefulWidget for JsonTree {
     fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
         let height = area.height as usize;
         state.height = height; // Track height for paging logic
+        state.area = area;
+        state.rendered_rows.clear();
 
         // Ensure selection is visible
         if state.selected_line >= state.scroll_offset + height {
@@ -459,6 +827,11 @@ impl StatefulWidget for JsonTree {
         let gutter_width = total_lines.to_string().len() + 1; // +1 for padding
 
         let mut current_y = 0; // Track current display row
+        let search =
+            (!state.search_query.is_empty()).then(|| SearchPattern::new(&state.search_query));
+        let current_match = state
+            .current_match_index
+            .and_then(|i| state.matched_lines.get(i).copied());
 
         for line_idx in state.scroll_offset..total_lines {
             if current_y >= height {
@@ -467,9 +840,6 @@ impl StatefulWidget for JsonTree {
 
             let line = &state.lines[line_idx];
 
-            let search_query_lower = state.search_query.to_lowercase();
-            let is_searching = !state.search_query.is_empty();
-
             // Build the base spans (line number, indent, collapser, key)
             let mut base_spans = Vec::new();
 
@@ -497,10 +867,11 @@ impl StatefulWidget for JsonTree {
             // Key
             let key_str = if let Some(key) = &line.key {
                 let mut key_style = Style::default().fg(Color::Blue);
-                if is_searching && key.to_lowercase().contains(&search_query_lower) {
+                if search.as_ref().is_some_and(|p| p.is_match(key)) {
                     key_style = key_style.add_modifier(Modifier::BOLD).bg(Color::DarkGray);
                 }
-                Some((format!("{}: ", key), key_style))
+                let sep = if state.flat_view { " = " } else { ": " };
+                Some((format!("{}{}", key, sep), key_style))
             } else {
                 None
             };
@@ -516,40 +887,36 @@ impl StatefulWidget for JsonTree {
             } else {
                 Style::default().fg(Color::Green)
             };
-            if is_searching && line.value_str.to_lowercase().contains(&search_query_lower) {
+            if search.as_ref().is_some_and(|p| p.is_match(&line.value_str)) {
                 val_style = val_style.bg(Color::DarkGray);
             }
 
             // Determine if we need to wrap
-            let value_lines: Vec<String> = if state.text_wrap
-                && line.value_str.len() > available_width
-            {
-                // Split value into chunks that fit
-                let mut chunks = Vec::new();
-                let mut remaining = line.value_str.as_str();
-                while !remaining.is_empty() {
-                    let chunk_len = remaining.len().min(available_width);
-                    chunks.push(remaining[..chunk_len].to_string());
-                    remaining = &remaining[chunk_len..];
-                }
-                chunks
-            } else {
-                // No wrapping - truncate if needed
-                if !state.text_wrap && line.value_str.len() > available_width {
-                    if available_width > 3 {
-                        vec![format!(
-                            "{}...",
-                            &line.value_str[..available_width.saturating_sub(3)]
-                        )]
+            // Measured and split in chars so multi-byte values never panic
+            let value_chars = line.value_str.chars().count();
+            let value_lines: Vec<String> =
+                if state.text_wrap && value_chars > available_width && available_width > 0 {
+                    // Split value into chunks that fit
+                    let chars: Vec<char> = line.value_str.chars().collect();
+                    chars
+                        .chunks(available_width)
+                        .map(|c| c.iter().collect())
+                        .collect()
+                } else {
+                    // No wrapping - truncate if needed
+                    if value_chars > available_width {
+                        if available_width > 3 {
+                            vec![format!(
+                                "{}...",
+                                slice_chars(&line.value_str, 0, available_width - 3)
+                            )]
+                        } else {
+                            vec![slice_chars(&line.value_str, 0, available_width).to_string()]
+                        }
                     } else {
-                        vec![
-                            line.value_str[..available_width.min(line.value_str.len())].to_string(),
-                        ]
+                        vec![line.value_str.clone()]
                     }
-                } else {
-                    vec![line.value_str.clone()]
-                }
-            };
+                };
 
             // Line style for selection/highlighting
             let mut line_style = Style::default();
@@ -561,6 +928,12 @@ impl StatefulWidget for JsonTree {
                 if line_idx == state.selected_line {
                     line_style = line_style.bg(Color::Magenta);
                 }
+                if current_match == Some(line_idx) {
+                    line_style = Style::default()
+                        .fg(Color::Yellow)
+                        .bg(Color::Black)
+                        .add_modifier(Modifier::BOLD);
+                }
             }
 
             // Render each wrapped line
@@ -592,9 +965,13 @@ impl StatefulWidget for JsonTree {
                 spans.push(Span::styled(value_chunk.clone(), val_style));
 
                 buf.set_line(area.x, y, &Line::from(spans), area.width);
+                state.rendered_rows.push(line_idx);
 
-                // Apply line style to the full width
-                if let Some(bg) = line_style.bg {
+                // Apply line style to the full width; the current match
+                // overrides text color too so it stands out from the rest
+                if current_match == Some(line_idx) {
+                    buf.set_style(Rect::new(area.x, y, area.width, 1), line_style);
+                } else if let Some(bg) = line_style.bg {
                     for x_pos in area.x..area.x + area.width {
                         if let Some(cell) = buf.cell_mut((x_pos, y)) {
                             cell.set_bg(bg);
@@ -612,6 +989,10 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    fn keys(ks: &[&str]) -> Vec<PathSegment> {
+        ks.iter().map(|k| PathSegment::Key(k.to_string())).collect()
+    }
+
     #[test]
     fn test_flatten_simple() {
         let value = json!({
@@ -675,7 +1056,16 @@ mod tests {
         });
         let mut state = JsonTreeState::new(value);
 
-        // Initial:
+        // Initial: only the root is expanded
+        // 0: {
+        // 1: "nested": { ... 1 keys }
+        // 2: }
+        assert_eq!(state.lines.len(), 3);
+        assert_eq!(state.lines[1].value_str, "{ ... 1 keys }");
+
+        // Select line 1 ("nested") and expand it
+        state.selected_line = 1;
+        state.toggle_collapse();
         // 0: {
         // 1: "nested": {
         // 2:   "foo": "bar"
@@ -683,9 +1073,7 @@ mod tests {
         // 4: }
         assert_eq!(state.lines.len(), 5);
 
-        // Select line 1 ("nested") and collapse it
-        // Line 1 path should be "nested".
-        state.selected_line = 1;
+        // Collapse it again
         state.toggle_collapse();
 
         // "nested" path added to collapsed_paths
@@ -723,8 +1111,7 @@ mod tests {
             assert_eq!(state.selected_line, 1); // Auto-jump
         } else {
             // Debug if failed
-            // panic!("Search failed to find 'ansible'");
-            assert!(false, "Search failed to find 'ansible'");
+            panic!("Search failed to find 'ansible'");
         }
 
         state.set_search("stat".to_string());
@@ -732,7 +1119,283 @@ mod tests {
         if !state.matched_lines.is_empty() {
             assert_eq!(state.matched_lines[0], 2);
         } else {
-            assert!(false, "Search failed to find 'stat'");
+            panic!("Search failed to find 'stat'");
         }
     }
+
+    #[test]
+    fn test_collapse_siblings() {
+        let value = json!({
+            "a": {"x": 1},
+            "b": {"y": {"deep": true}},
+            "c": [1, 2],
+            "d": "leaf"
+        });
+        let mut state = JsonTreeState::new(value);
+        state.expand_all();
+
+        // Select "b.y" — its ancestors ("", "b") must stay expanded
+        state.selected_line = state.lines.iter().position(|l| l.path == "b.y").unwrap();
+        state.collapse_siblings();
+        assert_eq!(state.lines[state.selected_line].path, "b.y");
+        assert!(state.collapsed_paths.is_empty()); // "b.y" has no siblings
+
+        // Select "b": siblings "a" and "c" collapse, "b" stays open
+        state.selected_line = state.lines.iter().position(|l| l.path == "b").unwrap();
+        state.collapse_siblings();
+        assert!(state.collapsed_paths.contains(&keys(&["a"])));
+        assert!(state.collapsed_paths.contains(&keys(&["c"])));
+        assert!(!state.collapsed_paths.contains(&keys(&["b"])));
+        assert!(!state.collapsed_paths.contains(&keys(&["b", "y"])));
+        assert_eq!(state.lines[state.selected_line].path, "b");
+        assert!(state.lines.iter().any(|l| l.path == "b.y.deep"));
+    }
+
+    #[test]
+    fn test_flat_view_lines() {
+        let value = json!({
+            "a": { "b": [1, "two"] },
+            "empty": {},
+            "ok": true
+        });
+        let mut state = JsonTreeState::new(value);
+        state.expand_all();
+        state.selected_line = state.lines.iter().position(|l| l.path == "a.b[1]").unwrap();
+        state.toggle_flat_view();
+
+        let lines: Vec<String> = (0..state.lines.len())
+            .map(|i| state.get_range_content(i, i).unwrap())
+            .collect();
+        assert_eq!(
+            lines,
+            vec!["a.b[0] = 1", "a.b[1] = \"two\"", "empty = {}", "ok = true"]
+        );
+        // Cursor stays on the same leaf and yank uses the flat form
+        assert_eq!(state.get_selected_content().unwrap(), "a.b[1] = \"two\"");
+        assert_eq!(state.selected_value(), Some(&json!("two")));
+
+        // Search matches full paths
+        state.set_search("b[0]".to_string());
+        assert_eq!(state.matched_lines, vec![0]);
+
+        // Regex mode matches across keys and values
+        state.set_search(r"/re/^a\.b\[\d\]$".to_string());
+        assert_eq!(state.matched_lines, vec![0, 1]);
+
+        state.toggle_flat_view();
+        assert_eq!(state.lines[state.selected_line].path, "a.b[0]");
+        assert_eq!(state.lines[0].value_str, "{");
+    }
+
+    #[test]
+    fn test_current_match_scrolls_into_view_and_renders_distinctly() {
+        let value = json!(
+            (0..60)
+                .map(|i| format!("item{}", i % 3))
+                .collect::<Vec<_>>()
+        );
+        let mut state = JsonTreeState::new(value);
+        let area = Rect::new(0, 0, 40, 10);
+        let mut buf = Buffer::empty(area);
+        JsonTree.render(area, &mut buf, &mut state);
+
+        state.set_search("item2".to_string());
+        assert_eq!(state.matched_lines.len(), 20);
+        for _ in 0..10 {
+            state.next_match();
+        }
+        // Scrolled before any render, so the match is never off-screen
+        let current = state.matched_lines[10];
+        assert_eq!(state.selected_line, current);
+        assert!(current >= state.scroll_offset && current < state.scroll_offset + 9);
+
+        // Wrapping back to the first match scrolls up again
+        for _ in 0..10 {
+            state.next_match();
+        }
+        assert_eq!(state.selected_line, state.matched_lines[0]);
+        assert!(state.scroll_offset <= state.matched_lines[0]);
+
+        state.prev_match();
+        let current = state.matched_lines[19];
+        let mut buf = Buffer::empty(area);
+        JsonTree.render(area, &mut buf, &mut state);
+        let cell = &buf[(0, (current - state.scroll_offset) as u16)];
+        assert_eq!(cell.bg, Color::Black);
+        assert_eq!(cell.fg, Color::Yellow);
+        assert!(cell.modifier.contains(Modifier::BOLD));
+
+        // The previous match, three lines up, keeps the general highlight
+        let other = state.matched_lines[18];
+        let cell = &buf[(0, (other - state.scroll_offset) as u16)];
+        assert_eq!(cell.bg, Color::Red);
+    }
+
+    #[test]
+    fn test_click_selects_and_toggles_lines() {
+        let mut state = JsonTreeState::new(json!({"a": {"x": 1}, "long": "y".repeat(40)}));
+        state.expand_all();
+        state.text_wrap = true;
+        let area = Rect::new(2, 1, 30, 10);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 40, 12));
+        JsonTree.render(area, &mut buf, &mut state);
+        // "long" wraps onto three rows, all owned by line 4
+        assert_eq!(state.rendered_rows, vec![0, 1, 2, 3, 4, 4, 4, 5]);
+
+        // A continuation row selects its logical line; leaves don't toggle
+        assert!(state.click(10, 1 + 6));
+        assert_eq!(state.selected_line, 4);
+        assert_eq!(state.lines.len(), 6);
+
+        // Clicking an object collapses it, and again expands it
+        assert!(state.click(10, 1 + 1));
+        assert_eq!(state.selected_line, 1);
+        assert_eq!(state.lines[1].value_str, "{ ... }");
+        assert_eq!(state.lines.len(), 4);
+        assert!(state.click(10, 1 + 1));
+        assert_eq!(state.lines.len(), 6);
+
+        // Outside the tree, or below its last row
+        assert!(!state.click(1, 2));
+        assert!(!state.click(10, 1 + 9));
+        assert_eq!(state.selected_line, 1);
+
+        // Rows follow the scroll position of the last render
+        state.scroll_offset = 2;
+        state.selected_line = 2;
+        JsonTree.render(area, &mut buf, &mut state);
+        assert_eq!(state.line_at(10, 1), Some(2));
+    }
+
+    #[test]
+    fn test_selected_subtree_json() {
+        let mut state = JsonTreeState::new(json!({"a": {"b": [1, 2]}, "c": "x"}));
+        state.expand_all();
+        let a = state.lines.iter().position(|l| l.path == "a").unwrap();
+        state.selected_line = a;
+        state.toggle_collapse();
+        assert_eq!(state.get_selected_content().unwrap(), "\"a\": { ... }");
+        // The collapsed node still yanks its full data
+        assert_eq!(
+            state.get_selected_subtree_json().unwrap(),
+            "{\n  \"b\": [\n    1,\n    2\n  ]\n}"
+        );
+
+        state.selected_line = state.lines.iter().position(|l| l.path == "c").unwrap();
+        assert_eq!(state.get_selected_subtree_json().unwrap(), "\"x\"");
+
+        // Closing braces fall back to the line text
+        state.selected_line = state.lines.len() - 1;
+        assert!(state.selected_value().is_none());
+        assert_eq!(
+            state.get_selected_subtree_json(),
+            state.get_selected_content()
+        );
+    }
+
+    #[test]
+    fn test_keys_containing_dots_are_distinct_paths() {
+        // "x" -> "a.b" and "x" -> "a" -> "b" display alike but are different nodes
+        let mut state = JsonTreeState::new(json!({
+            "x": {"a.b": {"leaf": 1}, "a": {"b": {"other": 2}}}
+        }));
+        state.expand_all();
+        let dotted = state
+            .lines
+            .iter()
+            .position(|l| l.segments == keys(&["x", "a.b"]))
+            .unwrap();
+        let nested = state
+            .lines
+            .iter()
+            .position(|l| l.segments == keys(&["x", "a", "b"]))
+            .unwrap();
+        assert_eq!(state.lines[dotted].path, state.lines[nested].path);
+
+        // Collapsing the dotted key leaves the nested "a.b" open
+        state.selected_line = dotted;
+        state.toggle_collapse();
+        assert_eq!(state.lines[dotted].value_str, "{ ... }");
+        assert!(
+            state
+                .lines
+                .iter()
+                .any(|l| l.key.as_deref() == Some("\"other\""))
+        );
+        assert!(
+            !state
+                .lines
+                .iter()
+                .any(|l| l.key.as_deref() == Some("\"leaf\""))
+        );
+        state.toggle_collapse();
+        assert!(
+            state
+                .lines
+                .iter()
+                .any(|l| l.key.as_deref() == Some("\"leaf\""))
+        );
+
+        // Recursive collapse of "a" doesn't touch its "a.b" sibling
+        state.selected_line = state.position_of(&keys(&["x", "a"])).unwrap();
+        state.collapse_current_recursive();
+        assert!(state.collapsed_paths.contains(&keys(&["x", "a", "b"])));
+        assert!(!state.collapsed_paths.contains(&keys(&["x", "a.b"])));
+
+        // Values resolve through the structured path
+        state.expand_all();
+        state.selected_line = state.position_of(&keys(&["x", "a.b", "leaf"])).unwrap();
+        assert_eq!(state.selected_value(), Some(&json!(1)));
+        assert!(state.edit_target().is_err());
+        state.editable = true;
+        let (key, _, leaf, path) = state.edit_target().unwrap();
+        assert_eq!((key.as_str(), leaf), ("x", json!(1)));
+        let mut base = state.value["x"].clone();
+        assert!(set_by_path(&mut base, &path, json!(5)));
+        assert_eq!(base["a.b"]["leaf"], 5);
+        assert_eq!(base["a"]["b"]["other"], 2);
+    }
+
+    #[test]
+    fn test_containers_flatten_lazily() {
+        let packages: Vec<_> = (0..500)
+            .map(|i| json!({ "name": format!("pkg{}", i) }))
+            .collect();
+        let mut state = JsonTreeState::new(json!({ "changed": false, "packages": packages }));
+
+        // Only the top level is flattened at first
+        assert_eq!(state.lines.len(), 4);
+        assert_eq!(state.lines[2].value_str, "[ ... 500 items ]");
+        assert!(!state.lines[2].is_expanded);
+
+        // Expanding computes the children on demand
+        state.selected_line = 2;
+        state.toggle_collapse();
+        assert!(state.lines[2].is_expanded);
+        // Each package is itself collapsed until expanded
+        assert_eq!(state.lines.len(), 4 + 500 + 1);
+        assert_eq!(state.lines[3].value_str, "{ ... 1 keys }");
+        state.toggle_collapse();
+        // Once the user has toggled it, it's an ordinary collapsed node
+        assert_eq!(state.lines[2].value_str, "[ ... ]");
+
+        // Search reaches into containers collapsed by default, but not ones
+        // the user collapsed
+        let mut state = JsonTreeState::new(json!({ "packages": (0..500).collect::<Vec<_>>() }));
+        state.set_search("499".to_string());
+        assert_eq!(state.matched_lines.len(), 1);
+        assert_eq!(
+            state.lines[state.matched_lines[0]].segments,
+            vec![PathSegment::Key("packages".into()), PathSegment::Index(499)]
+        );
+        state.collapse_all();
+        state.set_search("498".to_string());
+        assert!(state.matched_lines.is_empty());
+
+        state.expand_all();
+        assert_eq!(state.lines.len(), 2 + 500 + 2);
+        state.collapse_all();
+        assert_eq!(state.lines.len(), 3);
+        assert_eq!(state.lines[1].value_str, "[ ... ]");
+    }
 }
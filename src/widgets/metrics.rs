@@ -5,21 +5,84 @@ use ratatui::{
     widgets::{BarChart, Block, Borders, Gauge, Paragraph, Sparkline},
 };
 
-use crate::app::App;
+use crate::app::{App, TaskHistory};
+
+/// Upper bounds (seconds, exclusive) and labels of the duration histogram
+/// buckets; the last bucket is open-ended.
+const DURATION_BUCKETS: [(f64, &str); 5] = [
+    (0.1, "<0.1s"),
+    (1.0, "0.1-1s"),
+    (5.0, "1-5s"),
+    (30.0, "5-30s"),
+    (f64::INFINITY, ">30s"),
+];
+
+/// Count task durations per [`DURATION_BUCKETS`] bucket, leaving out the
+/// synthetic "Play Recap" entries and skipped tasks, which never ran.
+pub fn duration_histogram<'a>(
+    history: impl IntoIterator<Item = &'a TaskHistory>,
+) -> Vec<(&'static str, u64)> {
+    let mut counts = [0u64; DURATION_BUCKETS.len()];
+    for task in history
+        .into_iter()
+        .filter(|t| t.name != "Play Recap" && !t.skipped)
+    {
+        let bucket = DURATION_BUCKETS
+            .iter()
+            .position(|(limit, _)| task.duration < *limit)
+            .unwrap_or(DURATION_BUCKETS.len() - 1);
+        counts[bucket] += 1;
+    }
+    DURATION_BUCKETS
+        .iter()
+        .zip(counts)
+        .map(|((_, label), count)| (*label, count))
+        .collect()
+}
+
+/// The history the metrics aggregate: every task, or only the tasks of
+/// `app.host_filter` when one is set. "Play Recap" entries aren't tasks and
+/// are left out either way.
+pub fn metrics_history(app: &App) -> Vec<&TaskHistory> {
+    app.history
+        .iter()
+        .filter(|t| t.name != "Play Recap")
+        .filter(|t| app.host_filter.as_ref().is_none_or(|host| &t.host == host))
+        .collect()
+}
+
+/// `base`, plus the host filter when one is active.
+fn panel_title(app: &App, base: &str) -> String {
+    match &app.host_filter {
+        Some(host) => format!("{} (Host: {})", base, host),
+        None => base.to_string(),
+    }
+}
 
 pub struct MetricsDashboard;
 
 impl MetricsDashboard {
     pub fn draw(frame: &mut Frame, app: &App, area: Rect) {
-        if app.metrics_view == crate::app::MetricsView::Heatmap {
-            crate::widgets::heatmap::HeatmapWidget::draw(frame, app, area);
-            return;
+        match app.metrics_view {
+            crate::app::MetricsView::Heatmap => {
+                crate::widgets::heatmap::HeatmapWidget::draw(frame, app, area);
+                return;
+            }
+            crate::app::MetricsView::Histogram => {
+                Self::draw_duration_histogram(frame, app, area);
+                return;
+            }
+            crate::app::MetricsView::Hosts => {
+                crate::widgets::host_progress::HostProgressWidget::draw(frame, app, area);
+                return;
+            }
+            crate::app::MetricsView::Dashboard => {}
         }
 
         // Layout:
         // Top: Status Distribution (Gauge/Text) - 15%
         // Middle: Task Duration (BarChart) - 50%
-        // Bottom: Event/Log Velocity (Sparkline) - 35%
+        // Bottom: Event/Log Velocity | AI Performance (Sparklines) - 35%
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
@@ -31,18 +94,24 @@ impl MetricsDashboard {
 
         Self::draw_status_distribution(frame, app, chunks[0]);
         Self::draw_task_durations(frame, app, chunks[1]);
-        Self::draw_event_velocity(frame, app, chunks[2]);
+        let bottom = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[2]);
+        Self::draw_event_velocity(frame, app, bottom[0]);
+        Self::draw_ai_performance(frame, app, bottom[1]);
     }
 
     fn draw_status_distribution(frame: &mut Frame, app: &App, area: Rect) {
         let block = Block::default()
-            .title("Status Distribution")
+            .title(panel_title(app, "Status Distribution"))
             .borders(Borders::ALL);
         let inner_area = block.inner(area);
         frame.render_widget(block, area);
 
         // Calculate stats
-        let total_tasks = app.history.len();
+        let history = metrics_history(app);
+        let total_tasks = history.len();
         if total_tasks == 0 {
             let p = Paragraph::new("No tasks executed yet.")
                 .alignment(ratatui::layout::Alignment::Center);
@@ -50,24 +119,22 @@ impl MetricsDashboard {
             return;
         }
 
-        let failed = app.history.iter().filter(|t| t.failed).count();
-        let changed = app.history.iter().filter(|t| t.changed).count();
-        let ok = total_tasks - failed - changed; // Roughly
+        let failed = history.iter().filter(|t| t.failed).count();
+        let changed = history.iter().filter(|t| t.changed).count();
+        let skipped = history.iter().filter(|t| t.skipped).count();
+        let ok = total_tasks.saturating_sub(failed + changed + skipped); // Roughly
 
         let failed_pct = (failed as f64 / total_tasks as f64) * 100.0;
         let changed_pct = (changed as f64 / total_tasks as f64) * 100.0;
+        let skipped_pct = (skipped as f64 / total_tasks as f64) * 100.0;
         let ok_pct = (ok as f64 / total_tasks as f64) * 100.0;
 
         // Use a Gauge for visual or just text for now.
-        // Let's use 3 Gauges side-by-side or stacked?
+        // Let's use 4 Gauges side-by-side or stacked?
         // Side-by-side is better.
         let gauge_layout = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(33),
-                Constraint::Percentage(33),
-                Constraint::Percentage(34),
-            ])
+            .constraints([Constraint::Percentage(25); 4])
             .split(inner_area);
 
         let g_ok = Gauge::default()
@@ -85,24 +152,34 @@ impl MetricsDashboard {
             .gauge_style(Style::default().fg(Color::Red))
             .percent(failed_pct as u16);
 
+        let g_skipped = Gauge::default()
+            .block(Block::default().title("Skipped").borders(Borders::ALL))
+            .gauge_style(Style::default().fg(Color::DarkGray))
+            .percent(skipped_pct as u16);
+
         frame.render_widget(g_ok, gauge_layout[0]);
         frame.render_widget(g_changed, gauge_layout[1]);
         frame.render_widget(g_failed, gauge_layout[2]);
+        frame.render_widget(g_skipped, gauge_layout[3]);
     }
 
     fn draw_task_durations(frame: &mut Frame, app: &App, area: Rect) {
         let block = Block::default()
-            .title("Top Tasks by Duration")
+            .title(panel_title(app, "Top Tasks by Duration"))
             .borders(Borders::ALL);
         let inner_area = block.inner(area);
         frame.render_widget(block, area);
 
-        if app.history.is_empty() {
+        // Skipped tasks report no duration; they'd only crowd out real ones
+        let mut tasks: Vec<_> = metrics_history(app)
+            .into_iter()
+            .filter(|t| !t.skipped)
+            .collect();
+        if tasks.is_empty() {
             return;
         }
 
         // Sort by duration desc
-        let mut tasks: Vec<_> = app.history.iter().collect();
         tasks.sort_by(|a, b| b.duration.partial_cmp(&a.duration).unwrap());
 
         // Take top 5 or however many fit
@@ -146,6 +223,38 @@ impl MetricsDashboard {
         frame.render_widget(barchart, inner_area);
     }
 
+    fn draw_duration_histogram(frame: &mut Frame, app: &App, area: Rect) {
+        let block = Block::default()
+            .title(panel_title(app, "Task Duration Histogram (t: next view)"))
+            .borders(Borders::ALL);
+
+        let buckets = duration_histogram(metrics_history(app));
+        if buckets.iter().all(|(_, count)| *count == 0) {
+            let p = Paragraph::new("No tasks executed yet.")
+                .alignment(ratatui::layout::Alignment::Center)
+                .block(block);
+            frame.render_widget(p, area);
+            return;
+        }
+
+        let barchart = BarChart::default()
+            .block(block)
+            .direction(Direction::Horizontal)
+            .data(&buckets)
+            .bar_width(1)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(Color::Cyan))
+            .value_style(
+                Style::default()
+                    .fg(Color::Black)
+                    .bg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .label_style(Style::default().fg(Color::White));
+
+        frame.render_widget(barchart, area);
+    }
+
     fn draw_event_velocity(frame: &mut Frame, app: &App, area: Rect) {
         let block = Block::default()
             .title("Event Velocity")
@@ -167,4 +276,80 @@ impl MetricsDashboard {
 
         frame.render_widget(sparkline, inner_area);
     }
+
+    fn draw_ai_performance(frame: &mut Frame, app: &App, area: Rect) {
+        let block = Block::default()
+            .title("AI Performance")
+            .borders(Borders::ALL);
+        let inner_area = block.inner(area);
+        frame.render_widget(block, area);
+
+        if app.ai_latencies.is_empty() {
+            let p =
+                Paragraph::new("No AI calls yet.").alignment(ratatui::layout::Alignment::Center);
+            frame.render_widget(p, inner_area);
+            return;
+        }
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(inner_area);
+
+        let calls = app.ai_latencies.len() as u64;
+        let avg_latency = app.ai_latencies.iter().sum::<u64>() / calls;
+        let total_tokens: u64 = app.ai_tokens.iter().map(|&t| t as u64).sum();
+        let summary = Paragraph::new(format!(
+            "Calls: {}  Avg latency: {} ms  Tokens: {}",
+            calls, avg_latency, total_tokens
+        ))
+        .style(Style::default().fg(Color::Cyan));
+        frame.render_widget(summary, rows[0]);
+
+        let history: Vec<u64> = app.ai_latencies.iter().cloned().collect();
+        let sparkline = Sparkline::default()
+            .data(&history)
+            .style(Style::default().fg(Color::Yellow));
+        frame.render_widget(sparkline, rows[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(name: &str, duration: f64) -> TaskHistory {
+        TaskHistory {
+            duration,
+            ..TaskHistory::new(name, "web1")
+        }
+    }
+
+    #[test]
+    fn test_duration_histogram_buckets() {
+        let history = vec![
+            task("a", 0.05),
+            task("b", 0.1),
+            task("c", 0.9),
+            task("d", 4.0),
+            task("e", 12.0),
+            task("f", 45.0),
+            task("g", 300.0),
+            task("Play Recap", 0.0),
+            TaskHistory {
+                skipped: true,
+                ..task("h", 0.0)
+            },
+        ];
+        assert_eq!(
+            duration_histogram(&history),
+            vec![
+                ("<0.1s", 1),
+                ("0.1-1s", 2),
+                ("1-5s", 1),
+                ("5-30s", 1),
+                (">30s", 2),
+            ]
+        );
+    }
 }
@@ -1,4 +1,6 @@
 pub mod chat;
 pub mod heatmap;
+pub mod host_progress;
 pub mod json_tree;
 pub mod metrics;
+pub mod value_viewer;
@@ -0,0 +1,224 @@
+//! Full-value viewer for a single Data Browser node. The tree truncates long
+//! values to one row; this shows the whole thing, scrollable, with a hexdump
+//! mode for binary-ish content.
+
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+
+/// Bytes per hexdump row.
+const HEX_WIDTH: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct ValueViewer {
+    pub path: String,
+    /// Raw string content for strings, pretty JSON for everything else
+    pub text: String,
+    pub wrap: bool,
+    pub hex: bool,
+    pub scroll: usize,
+    pub h_scroll: usize,
+    /// Visible rows, updated on draw for paging
+    pub height: usize,
+}
+
+impl ValueViewer {
+    pub fn new(path: String, value: &serde_json::Value) -> Self {
+        let text = match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => serde_json::to_string_pretty(other).unwrap_or_default(),
+        };
+        Self {
+            path,
+            text,
+            wrap: true,
+            hex: false,
+            scroll: 0,
+            h_scroll: 0,
+            height: 0,
+        }
+    }
+
+    pub fn char_len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    /// Control characters other than newline/tab — a hint that the hexdump is
+    /// the better way to read this value.
+    pub fn has_non_printable(&self) -> bool {
+        self.text
+            .chars()
+            .any(|c| c.is_control() && c != '\n' && c != '\t')
+    }
+
+    pub fn toggle_wrap(&mut self) {
+        self.wrap = !self.wrap;
+        self.scroll = 0;
+        self.h_scroll = 0;
+    }
+
+    pub fn toggle_hex(&mut self) {
+        self.hex = !self.hex;
+        self.scroll = 0;
+        self.h_scroll = 0;
+    }
+
+    /// Rows to display for a viewport `width` columns wide. Text is split on
+    /// char boundaries, never bytes, so multi-byte content can't panic.
+    pub fn display_lines(&self, width: usize) -> Vec<String> {
+        if self.hex {
+            return hexdump_lines(self.text.as_bytes());
+        }
+        let width = width.max(1);
+        let mut rows = Vec::new();
+        for raw in self.text.split('\n') {
+            let line: String = raw
+                .chars()
+                .map(|c| match c {
+                    '\t' => ' ',
+                    c if c.is_control() => '·',
+                    c => c,
+                })
+                .collect();
+            if self.wrap {
+                let chars: Vec<char> = line.chars().collect();
+                if chars.is_empty() {
+                    rows.push(String::new());
+                }
+                rows.extend(chars.chunks(width).map(|c| c.iter().collect::<String>()));
+            } else {
+                rows.push(slice_chars(&line, self.h_scroll, width).to_string());
+            }
+        }
+        rows
+    }
+
+    pub fn scroll_down(&mut self, n: usize) {
+        self.scroll = self.scroll.saturating_add(n);
+    }
+
+    pub fn scroll_up(&mut self, n: usize) {
+        self.scroll = self.scroll.saturating_sub(n);
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let mut title = format!(
+            " {} — {} chars, {} bytes",
+            if self.path.is_empty() {
+                "."
+            } else {
+                &self.path
+            },
+            self.char_len(),
+            self.text.len()
+        );
+        if self.hex {
+            title.push_str(" [hex]");
+        } else if self.has_non_printable() {
+            title.push_str(" [non-printable: x for hex]");
+        }
+        title.push(' ');
+
+        let block = Block::default()
+            .title(title)
+            .title_bottom(" j/k scroll · w wrap · x hex · ←/→ pan · Esc close ")
+            .borders(Borders::ALL)
+            .style(Style::default().bg(Color::Black));
+        let inner = block.inner(area);
+        self.height = inner.height as usize;
+
+        let rows = self.display_lines(inner.width as usize);
+        self.scroll = self.scroll.min(rows.len().saturating_sub(1));
+        let visible: Vec<Line> = rows
+            .into_iter()
+            .skip(self.scroll)
+            .take(self.height)
+            .map(|r| Line::from(Span::raw(r)))
+            .collect();
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(Paragraph::new(visible).block(block), area);
+    }
+}
+
+/// `len` chars of `s` starting at char `start`, sliced on char boundaries.
+pub fn slice_chars(s: &str, start: usize, len: usize) -> &str {
+    let begin = s.char_indices().nth(start).map_or(s.len(), |(i, _)| i);
+    let rest = &s[begin..];
+    let end = rest.char_indices().nth(len).map_or(rest.len(), |(i, _)| i);
+    &rest[..end]
+}
+
+/// Classic `offset  hex bytes  |ascii|` rows.
+pub fn hexdump_lines(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(HEX_WIDTH)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!(
+                "{:08x}  {:<width$}  |{}|",
+                i * HEX_WIDTH,
+                hex.join(" "),
+                ascii,
+                width = HEX_WIDTH * 3 - 1
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_viewer_content_and_hexdump() {
+        let viewer = ValueViewer::new("msg".to_string(), &json!("héllo\u{0}\nab"));
+        assert_eq!(viewer.char_len(), 9);
+        assert_eq!(viewer.text.len(), 10);
+        assert!(viewer.has_non_printable());
+
+        // Wrapping splits on chars, not bytes
+        assert_eq!(viewer.display_lines(3), vec!["hél", "lo·", "ab"]);
+
+        // Unwrapped: horizontal pan is char-safe too
+        let mut viewer = viewer;
+        viewer.toggle_wrap();
+        viewer.h_scroll = 1;
+        assert_eq!(viewer.display_lines(3), vec!["éll", "b"]);
+
+        viewer.toggle_hex();
+        let hex = viewer.display_lines(80);
+        assert_eq!(hex.len(), 1);
+        assert_eq!(
+            hex[0],
+            format!(
+                "00000000  {:<47}  |h..llo..ab|",
+                "68 c3 a9 6c 6c 6f 00 0a 61 62"
+            )
+        );
+
+        let long = hexdump_lines(&[b'A'; 17]);
+        assert_eq!(long.len(), 2);
+        assert!(long[1].starts_with("00000010  41 "));
+
+        // Non-strings are shown as pretty JSON
+        let obj = ValueViewer::new(String::new(), &json!({"a": 1}));
+        assert_eq!(obj.text, "{\n  \"a\": 1\n}");
+    }
+}
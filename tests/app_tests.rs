@@ -1,31 +1,8 @@
-use ansible_piloteer::app::{App, EditState, TaskHistory};
+use ansible_piloteer::app::{Action, App, EditState, TaskHistory};
 use ansible_piloteer::config::Config;
 
-fn make_config() -> Config {
-    Config {
-        openai_api_key: None,
-        socket_path: "/tmp/piloteer.sock".to_string(),
-        model: "gpt-4".to_string(),
-        api_base: "https://api.openai.com/v1".to_string(),
-        log_level: "info".to_string(),
-        auth_token: None,
-        bind_addr: None,
-        secret_token: None,
-        quota_limit_tokens: None,
-        quota_limit_usd: None,
-        google_api_key: None,
-        google_client_id: None,
-        google_client_secret: None,
-        zipkin_endpoint: None,
-        zipkin_service_name: "ansible-piloteer".to_string(),
-        zipkin_sample_rate: 1.0,
-        filters: None,
-        provider: None,
-        anthropic_api_key: None,
-        vertex_project_id: None,
-        vertex_location: Some("us-central1".to_string()),
-    }
-}
+mod common;
+use common::test_config as make_config;
 
 fn make_app() -> App {
     App::new(make_config())
@@ -78,16 +55,7 @@ fn test_set_task() {
 #[test]
 fn test_toggle_breakpoint() {
     let mut app = make_app();
-    app.history.push(TaskHistory {
-        name: "Task 1".to_string(),
-        host: "localhost".to_string(),
-        changed: false,
-        failed: false,
-        duration: 0.0,
-        error: None,
-        verbose_result: None,
-        analysis: None,
-    });
+    app.history.push(TaskHistory::new("Task 1", "localhost"));
 
     app.analysis_index = 0;
     assert!(!app.breakpoints.contains("Task 1"));
@@ -131,3 +99,2289 @@ fn test_variable_editing_flow() {
     assert!(matches!(app.edit_state, EditState::Idle));
     assert!(!temp_path.exists());
 }
+
+#[test]
+fn test_dump_facts_per_host() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mut app = make_app();
+    app.facts_dump_dir = Some(tmp.path().join("facts"));
+
+    let facts = serde_json::json!({"inventory_hostname": "web01", "ansible_os_family": "Debian"});
+    app.set_task(
+        "Gather".to_string(),
+        serde_json::json!({}),
+        Some(facts.clone()),
+    );
+
+    let path = tmp.path().join("facts").join("web01.json");
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(written, facts);
+
+    // Newer facts replace the previous dump
+    let newer = serde_json::json!({"inventory_hostname": "web01", "ansible_os_family": "RedHat"});
+    app.set_task(
+        "Gather".to_string(),
+        serde_json::json!({}),
+        Some(newer.clone()),
+    );
+    let written: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(written, newer);
+    assert!(!tmp.path().join("facts").join(".web01.json.tmp").exists());
+}
+
+#[test]
+fn test_reanalyze_trigger_on_failed_task() {
+    let mut app = App::new(Config {
+        openai_api_key: Some("test-key".to_string()),
+        ..make_config()
+    });
+    for (name, failed) in [("ok task", false), ("broken task", true)] {
+        app.history.push(TaskHistory {
+            failed,
+            error: failed.then(|| "boom".to_string()),
+            ..TaskHistory::new(name, "localhost")
+        });
+    }
+
+    // Not a replay: never triggers
+    app.analysis_index = 1;
+    assert_eq!(app.take_reanalysis_request(), None);
+
+    app.replay_mode = true;
+    app.reanalyze = true;
+
+    // Successful tasks are skipped
+    app.analysis_index = 0;
+    assert_eq!(app.take_reanalysis_request(), None);
+
+    // Navigating to the failure triggers exactly once
+    app.analysis_index = 1;
+    assert_eq!(app.take_reanalysis_request(), Some(1));
+    assert_eq!(app.take_reanalysis_request(), None);
+}
+
+#[tokio::test]
+async fn test_failed_reanalysis_keeps_previous_analysis() {
+    let mut app = App::new(Config {
+        openai_api_key: Some("test-key".to_string()),
+        ..make_config()
+    });
+    let previous = ansible_piloteer::ai::Analysis {
+        analysis: "Port already in use".to_string(),
+        fix: None,
+        tokens_used: 10,
+    };
+    app.history.push(TaskHistory {
+        failed: true,
+        analysis: Some(previous.clone()),
+        ..TaskHistory::new("broken task", "localhost")
+    });
+    app.replay_mode = true;
+    app.reanalyze = true;
+    assert_eq!(app.take_reanalysis_request(), Some(0));
+
+    ansible_piloteer::ipc_handler::handle_message(
+        &mut app,
+        ansible_piloteer::ipc::Message::AiAnalysisFailed {
+            task: "broken task".to_string(),
+            error: "HTTP 503".to_string(),
+        },
+        false,
+        false,
+    )
+    .await;
+    assert_eq!(
+        app.history[0].analysis.as_ref().unwrap().analysis,
+        previous.analysis
+    );
+    assert!(!app.fresh_analyses.contains(&0));
+    assert!(app.notification.clone().unwrap().0.contains("HTTP 503"));
+    // Coming back to the entry asks again
+    assert_eq!(app.take_reanalysis_request(), Some(0));
+}
+
+#[test]
+fn test_failures_keep_their_vars_for_reanalysis() {
+    let mut app = make_app();
+    let run = |app: &mut App, name: &str, port: u16, failed: bool| {
+        app.set_task(
+            name.to_string(),
+            serde_json::json!({ "port": port }),
+            Some(serde_json::json!({ "os": "debian" })),
+        );
+        app.record_task_result(
+            name.to_string(),
+            "web1".to_string(),
+            false,
+            failed,
+            0.1,
+            None,
+            None,
+            None,
+        );
+    };
+    run(&mut app, "Start", 80, true);
+    run(&mut app, "Check", 8080, false);
+
+    assert_eq!(
+        app.history[0].task_vars,
+        Some(serde_json::json!({ "port": 80 }))
+    );
+    assert_eq!(
+        app.history[0].facts,
+        Some(serde_json::json!({ "os": "debian" }))
+    );
+    assert!(app.history[1].task_vars.is_none() && app.history[1].facts.is_none());
+
+    // Saved with the session, so a replay can re-analyze with them
+    let json = serde_json::to_string(&app.history).unwrap();
+    let back: Vec<TaskHistory> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back[0].task_vars, app.history[0].task_vars);
+    assert!(!json.contains("8080"));
+}
+
+#[tokio::test]
+async fn test_ask_ai_without_provider_uses_heuristics() {
+    let mut app = App::new(make_config());
+    assert!(app.ai_client.is_none());
+    let (ai_tx, _ai_rx) = tokio::sync::mpsc::channel(10);
+    let mut terminal = None;
+
+    app.failed_task = Some("Write config".to_string());
+    app.failed_result = Some(serde_json::json!({
+        "msg": "Could not replace file: [Errno 13] Permission denied: '/etc/app.conf'"
+    }));
+    ansible_piloteer::actions::dispatch(Action::AskAi, &mut app, &mut terminal, &ai_tx).await;
+    let suggestion = app.suggestion.clone().unwrap();
+    assert!(suggestion.analysis.starts_with("[Offline heuristic]"));
+    assert_eq!(suggestion.fix.unwrap().key, "ansible_become");
+    assert!(!app.asking_ai);
+
+    app.suggestion = None;
+    app.failed_result = Some(serde_json::json!({"msg": "something unusual"}));
+    ansible_piloteer::actions::dispatch(Action::AskAi, &mut app, &mut terminal, &ai_tx).await;
+    assert!(app.suggestion.is_none());
+    assert!(
+        app.notification
+            .clone()
+            .unwrap()
+            .0
+            .contains("No known failure signature")
+    );
+}
+
+#[tokio::test]
+async fn test_quota_exhausted_disables_ai_actions() {
+    let mut app = App::new(Config {
+        openai_api_key: Some("test-key".to_string()),
+        ..make_config()
+    });
+    let (ipc_tx, mut ipc_rx) = tokio::sync::mpsc::channel(10);
+    app.set_ipc_tx(Some(ipc_tx));
+    let (ai_tx, _ai_rx) = tokio::sync::mpsc::channel(10);
+    let mut terminal = None;
+
+    app.mark_quota_exhausted(std::time::Duration::from_secs(3 * 3600));
+    assert!(app.ai_quota_blocked());
+    assert!(app.quota_banner().unwrap().contains("resets in 2h 59m"));
+
+    ansible_piloteer::actions::dispatch(Action::AskAi, &mut app, &mut terminal, &ai_tx).await;
+    assert!(!app.asking_ai);
+    let (note, _) = app.notification.clone().unwrap();
+    assert!(note.contains("AI quota exhausted"));
+
+    app.chat_input = "why did it fail?".to_string();
+    ansible_piloteer::actions::dispatch(Action::SubmitChat, &mut app, &mut terminal, &ai_tx).await;
+    assert!(app.chat_history.is_empty());
+    assert_eq!(app.chat_input, "why did it fail?");
+
+    // Nothing that stays local is blocked: slash commands and applying a
+    // suggestion that has already arrived
+    app.chat_input = "/help".to_string();
+    ansible_piloteer::actions::dispatch(Action::SubmitChat, &mut app, &mut terminal, &ai_tx).await;
+    assert!(app.chat_history[0].content.starts_with("Chat Commands:"));
+
+    app.suggestion = Some(ansible_piloteer::ai::Analysis {
+        analysis: "Wrong port".to_string(),
+        fix: Some(ansible_piloteer::ai::Fix {
+            key: "port".to_string(),
+            value: serde_json::json!(8080),
+        }),
+        tokens_used: 0,
+    });
+    ansible_piloteer::actions::dispatch(Action::ApplyFix, &mut app, &mut terminal, &ai_tx).await;
+    assert!(matches!(
+        ipc_rx.try_recv(),
+        Ok(ansible_piloteer::ipc::Message::ModifyVar { key, .. }) if key == "port"
+    ));
+
+    // Once the reset time passes, actions are available again
+    app.quota_exhausted_until = Some(std::time::Instant::now());
+    assert!(!app.ai_quota_blocked());
+}
+
+#[test]
+fn test_quota_warning_repeats_on_a_new_day() {
+    use ansible_piloteer::ai::QuotaStatus;
+
+    let mut app = make_app();
+    let day = chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+    let mut status = QuotaStatus {
+        used_tokens: 950,
+        limit_tokens: Some(1000),
+        used_usd: 0.0,
+        limit_usd: None,
+        reset_in: std::time::Duration::from_secs(60),
+        rate_per_1k_usd: 0.0,
+        warn_pct: 90.0,
+        day,
+    };
+
+    assert!(app.apply_quota_status(&status));
+    assert!(app.notification.take().is_some());
+    assert!(app.apply_quota_status(&status));
+    assert!(app.notification.is_none());
+
+    // The tracker rolled over and today's usage is close to the limit again
+    status.day = day.succ_opt().unwrap();
+    assert!(app.apply_quota_status(&status));
+    assert_eq!(
+        app.notification.take().unwrap().0,
+        "AI quota nearly used: 950 / 1000 tokens"
+    );
+}
+
+#[test]
+fn test_pinned_inspector_ignores_new_tasks() {
+    let mut app = make_app();
+    app.set_task("Task A".to_string(), serde_json::json!({"port": 80}), None);
+    app.set_failed(
+        "Task A".to_string(),
+        None,
+        serde_json::json!({"msg": "A broke"}),
+        None,
+    );
+    app.suggestion = Some(ansible_piloteer::ai::Analysis {
+        analysis: "Port 80 is taken".to_string(),
+        fix: None,
+        tokens_used: 0,
+    });
+
+    app.toggle_inspector_pin();
+    assert_eq!(app.inspector_pin.as_ref().unwrap().entry.name, "Task A");
+
+    app.set_task("Task B".to_string(), serde_json::json!({"port": 81}), None);
+    app.set_failed(
+        "Task B".to_string(),
+        None,
+        serde_json::json!({"msg": "B broke"}),
+        None,
+    );
+    app.suggestion = None;
+    assert_eq!(
+        app.inspector_result(),
+        Some(&serde_json::json!({"msg": "A broke"}))
+    );
+    // The whole entry is frozen, not just the result
+    let pin = &app.inspector_pin.as_ref().unwrap().entry;
+    assert!(pin.failed);
+    assert_eq!(pin.task_vars, Some(serde_json::json!({"port": 80})));
+    assert_eq!(
+        app.pilot_analysis().map(|a| a.analysis.as_str()),
+        Some("Port 80 is taken")
+    );
+
+    // Unpinning resumes following the live failure
+    app.toggle_inspector_pin();
+    assert!(app.inspector_pin.is_none());
+    assert_eq!(
+        app.inspector_result(),
+        Some(&serde_json::json!({"msg": "B broke"}))
+    );
+    assert!(app.pilot_analysis().is_none());
+}
+
+#[tokio::test]
+async fn test_conflicting_var_change_requires_confirmation() {
+    use ansible_piloteer::ai::{Analysis, Fix};
+    use ansible_piloteer::ipc::Message;
+
+    let mut app = make_app();
+    let (ipc_tx, mut ipc_rx) = tokio::sync::mpsc::channel(10);
+    app.set_ipc_tx(Some(ipc_tx));
+    let (ai_tx, _ai_rx) = tokio::sync::mpsc::channel(10);
+    let mut terminal = None;
+
+    app.set_task("Deploy".to_string(), serde_json::json!({}), None);
+    let fix = |value| Analysis {
+        analysis: String::new(),
+        fix: Some(Fix {
+            key: "port".to_string(),
+            value,
+        }),
+        tokens_used: 0,
+    };
+
+    // First edit goes straight through and is recorded
+    app.suggestion = Some(fix(serde_json::json!(8080)));
+    ansible_piloteer::actions::dispatch(Action::ApplyFix, &mut app, &mut terminal, &ai_tx).await;
+    assert!(matches!(ipc_rx.try_recv(), Ok(Message::ModifyVar { .. })));
+    assert!(app.pending_var_change.is_none());
+
+    // Second edit of the same key is held back, showing the previous value
+    app.suggestion = Some(fix(serde_json::json!(9090)));
+    ansible_piloteer::actions::dispatch(Action::ApplyFix, &mut app, &mut terminal, &ai_tx).await;
+    assert!(ipc_rx.try_recv().is_err());
+    assert_eq!(
+        app.pending_var_change.as_ref().unwrap().0.value,
+        serde_json::json!(9090)
+    );
+    assert_eq!(
+        app.find_var_conflict("port").unwrap().value,
+        serde_json::json!(8080)
+    );
+
+    ansible_piloteer::actions::dispatch(Action::ConfirmVarChange, &mut app, &mut terminal, &ai_tx)
+        .await;
+    match ipc_rx.try_recv() {
+        Ok(Message::ModifyVar { key, value }) => {
+            assert_eq!(key, "port");
+            assert_eq!(value, serde_json::json!(9090));
+        }
+        other => panic!("Expected ModifyVar, got {:?}", other),
+    }
+    assert!(app.pending_var_change.is_none());
+
+    // Moving on to another task doesn't make a pending change safe to clobber
+    app.set_task("Restart".to_string(), serde_json::json!({}), None);
+    assert_eq!(
+        app.find_var_conflict("port").unwrap().value,
+        serde_json::json!(9090)
+    );
+
+    // Once a TaskStart shows the playbook picked it up, there's no conflict
+    app.set_task(
+        "Restart".to_string(),
+        serde_json::json!({"port": 9090}),
+        None,
+    );
+    app.check_var_expectations();
+    assert!(app.find_var_conflict("port").is_none());
+}
+
+#[test]
+fn test_host_progress_fractions() {
+    use ansible_piloteer::widgets::host_progress::host_progress;
+
+    let mut app = make_app();
+    for task in ["a", "b", "c", "d"] {
+        app.record_task_result(
+            task.to_string(),
+            "web1".to_string(),
+            false,
+            false,
+            0.1,
+            None,
+            None,
+            None,
+        );
+    }
+    app.record_task_result(
+        "a".to_string(),
+        "web2".to_string(),
+        false,
+        true,
+        0.1,
+        None,
+        None,
+        None,
+    );
+
+    let progress = host_progress(&app);
+    assert_eq!(progress.len(), 2);
+    assert_eq!(progress[0].name, "web1");
+    assert_eq!(progress[0].done, 4);
+    assert_eq!(progress[0].ratio, 1.0);
+    assert_eq!(progress[0].task.as_deref(), Some("d"));
+    assert!(!progress[0].running);
+    assert_eq!(progress[1].name, "web2");
+    assert_eq!(progress[1].failed, 1);
+    assert_eq!(progress[1].ratio, 0.25);
+
+    // A rescue moves the failure, it doesn't undo the work
+    app.mark_last_rescued();
+    let progress = host_progress(&app);
+    assert_eq!(progress[1].done, 1);
+    assert_eq!(progress[1].failed, 0);
+
+    // The current task shows as running until a host finishes it
+    app.current_task = Some("e".to_string());
+    app.record_task_result(
+        "e".to_string(),
+        "web1".to_string(),
+        false,
+        false,
+        0.1,
+        None,
+        None,
+        None,
+    );
+    let progress = host_progress(&app);
+    assert_eq!(progress[0].task.as_deref(), Some("e"));
+    assert!(!progress[0].running);
+    assert_eq!(progress[1].task.as_deref(), Some("e"));
+    assert!(progress[1].running);
+}
+
+#[test]
+fn test_host_progress_scrolls() {
+    use ansible_piloteer::app::{ActiveView, MetricsView};
+    use ansible_piloteer::widgets::host_progress::visible_rows;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    let press = |app: &mut App, code: KeyCode| {
+        app.handle_event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)));
+    };
+
+    let mut app = make_app();
+    for i in 0..10 {
+        app.record_task_result(
+            "ping".to_string(),
+            format!("web{}", i),
+            false,
+            false,
+            0.1,
+            None,
+            None,
+            None,
+        );
+    }
+    app.set_view(ActiveView::Metrics);
+    app.metrics_view = MetricsView::Hosts;
+
+    press(&mut app, KeyCode::Char('j'));
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.host_progress_scroll, 2);
+    press(&mut app, KeyCode::Char('k'));
+    assert_eq!(app.host_progress_scroll, 1);
+    for _ in 0..20 {
+        press(&mut app, KeyCode::Char('j'));
+    }
+    assert_eq!(app.host_progress_scroll, 9);
+
+    // The last page stays full, and everything shows when it fits
+    assert_eq!(visible_rows(10, 4, 9), (6, 4));
+    assert_eq!(visible_rows(10, 4, 1), (1, 4));
+    assert_eq!(visible_rows(3, 4, 2), (0, 3));
+    assert_eq!(visible_rows(0, 4, 0), (0, 0));
+
+    // Elsewhere j/k keep their usual meaning
+    app.metrics_view = MetricsView::Dashboard;
+    press(&mut app, KeyCode::Char('k'));
+    assert_eq!(app.host_progress_scroll, 9);
+}
+
+#[test]
+fn test_task_list_sort_orders() {
+    use ansible_piloteer::app::TaskSort;
+
+    let mut app = make_app();
+    // (name, changed, failed, duration)
+    for (name, changed, failed, duration) in [
+        ("setup", false, false, 2.0),
+        ("deploy", true, false, 9.0),
+        ("migrate", false, true, 0.5),
+        ("check", false, true, 4.0),
+    ] {
+        app.record_task_result(
+            name.to_string(),
+            "web1".to_string(),
+            changed,
+            failed,
+            duration,
+            None,
+            None,
+            None,
+        );
+    }
+
+    assert_eq!(app.task_display_order(), vec![0, 1, 2, 3]);
+
+    app.task_sort = TaskSort::DurationDesc;
+    assert_eq!(app.task_display_order(), vec![1, 3, 0, 2]);
+
+    app.task_sort = TaskSort::Status;
+    assert_eq!(app.task_display_order(), vec![3, 2, 1, 0]);
+
+    // Navigation follows the displayed order; the index keeps pointing at history
+    app.analysis_index = 2; // "migrate"
+    assert!(app.step_analysis(true));
+    assert_eq!(app.analysis_index, 1);
+    assert_eq!(app.history[app.analysis_index].name, "deploy");
+    assert!(app.step_analysis(false));
+    assert!(app.step_analysis(false));
+    assert_eq!(app.history[app.analysis_index].name, "check");
+}
+
+#[tokio::test]
+async fn test_exit_code_reflects_run_outcome() {
+    use ansible_piloteer::ipc::Message;
+    use ansible_piloteer::ipc_handler::handle_message;
+
+    let result = |name: &str, changed: bool, failed: bool, rescued: bool| {
+        serde_json::from_value::<Message>(serde_json::json!({
+            "TaskResult": {
+                "name": name,
+                "host": "web1",
+                "changed": changed,
+                "failed": failed,
+                "verbose_result": null,
+                "rescued": rescued
+            }
+        }))
+        .unwrap()
+    };
+
+    let mut app = make_app();
+    assert_eq!(app.exit_code(true), 0);
+    handle_message(&mut app, result("config", true, false, false), false, false).await;
+    handle_message(&mut app, result("flaky", false, true, true), false, false).await;
+    // Changes and rescued failures only matter with --strict
+    assert_eq!(app.exit_code(false), 0);
+    assert_eq!(app.exit_code(true), 4);
+
+    handle_message(&mut app, result("broken", false, true, false), false, false).await;
+    assert_eq!(app.exit_code(false), 2);
+    assert_eq!(app.exit_code(true), 2);
+
+    app.set_unreachable(
+        "ping".to_string(),
+        "db1".to_string(),
+        "timed out".to_string(),
+        serde_json::json!({"unreachable": true}),
+    );
+    assert_eq!(app.exit_code(false), 3);
+}
+
+#[tokio::test]
+async fn test_rescued_task_accounting() {
+    use ansible_piloteer::ipc::Message;
+    use ansible_piloteer::ipc_handler::handle_message;
+
+    let mut app = make_app();
+    let result = |name: &str, failed: bool, rescued: bool| {
+        serde_json::from_value::<Message>(serde_json::json!({
+            "TaskResult": {
+                "name": name,
+                "host": "web1",
+                "changed": false,
+                "failed": failed,
+                "verbose_result": null,
+                "rescued": rescued
+            }
+        }))
+        .unwrap()
+    };
+
+    handle_message(&mut app, result("ok task", false, false), false, false).await;
+    handle_message(&mut app, result("flaky", true, true), false, false).await;
+    handle_message(&mut app, result("broken", true, false), false, false).await;
+
+    let host = &app.hosts["web1"];
+    assert_eq!(host.ok_tasks, 1);
+    assert_eq!(host.rescued_tasks, 1);
+    assert_eq!(host.failed_tasks, 1);
+
+    // Rescued tasks stay visible in history for debugging
+    assert!(app.history[1].failed && app.history[1].rescued);
+    assert!(!app.history[2].rescued);
+
+    // Older plugins omit the field entirely
+    let legacy: Message = serde_json::from_value(serde_json::json!({
+        "TaskResult": {
+            "name": "x", "host": "web1", "changed": false, "failed": true,
+            "verbose_result": null
+        }
+    }))
+    .unwrap();
+    assert!(matches!(legacy, Message::TaskResult { rescued: false, .. }));
+}
+
+#[test]
+fn test_history_spill_round_trip() {
+    use ansible_piloteer::execution::ExecutionDetails;
+
+    let mut app = App::new(Config {
+        max_history_in_memory: Some(2),
+        ..make_config()
+    });
+    for i in 0..5 {
+        // Task 1 fails, so its vars and facts are kept with it
+        app.set_task(
+            format!("task {}", i),
+            serde_json::json!({"attempt": i}),
+            Some(serde_json::json!({"hostname": "web1"})),
+        );
+        app.record_task_result(
+            format!("task {}", i),
+            "web1".to_string(),
+            false,
+            i == 1,
+            0.1,
+            None,
+            Some(ExecutionDetails::new(
+                serde_json::json!({"stdout": format!("output {}", i)}),
+            )),
+            None,
+        );
+    }
+
+    // Only the two most recent results stay in memory; the index is complete
+    assert_eq!(app.history.len(), 5);
+    assert!(app.history[..3].iter().all(|t| t.verbose_result.is_none()));
+    assert!(app.history[3..].iter().all(|t| t.verbose_result.is_some()));
+    assert!(app.history[1].task_vars.is_none() && app.history[1].facts.is_none());
+    assert_eq!(app.history_spill.as_ref().unwrap().len(), 3);
+
+    // Spilled entries load transparently
+    let entry = app.history_entry(1).unwrap();
+    assert_eq!(entry.name, "task 1");
+    assert_eq!(entry.verbose_result.unwrap().stdout(), Some("output 1"));
+    assert_eq!(entry.task_vars, Some(serde_json::json!({"attempt": 1})));
+    assert_eq!(entry.facts, Some(serde_json::json!({"hostname": "web1"})));
+
+    // Session snapshots include everything
+    let session = ansible_piloteer::session::Session::from_app(&app);
+    for (i, task) in session.history.iter().enumerate() {
+        let expected = format!("output {}", i);
+        assert_eq!(
+            task.verbose_result.as_ref().unwrap().stdout(),
+            Some(expected.as_str())
+        );
+    }
+}
+
+#[test]
+fn test_follow_host_log_filter() {
+    use ansible_piloteer::app::log_line_host;
+
+    assert_eq!(log_line_host("Task 'ping' on web1: OK"), Some("web1"));
+    assert_eq!(
+        log_line_host("Task 'say ' on me' on db1: CHANGED"),
+        Some("db1")
+    );
+    assert_eq!(
+        log_line_host("⚠️  Host web2 unreachable during task 'ping': timeout"),
+        Some("web2")
+    );
+    assert_eq!(log_line_host("Task: ping"), None);
+
+    let mut app = make_app();
+    // Without a follow everything is shown
+    assert!(app.follows_log_line("Task 'ping' on web2: OK"));
+
+    app.toggle_follow_host("web1");
+    assert!(app.follows_log_line("Task 'ping' on web1: OK"));
+    assert!(!app.follows_log_line("Task 'ping' on web2: OK"));
+    assert!(!app.follows_log_line("⚠️  Host web2 unreachable during task 'ping': x"));
+    assert!(app.follows_log_line("Task: ping"));
+    assert!(app.follows_log_line("Play Started: site (Hosts: all)"));
+
+    // Other hosts are still recorded; the inspector tracks only web1
+    app.record_task_result(
+        "ping".to_string(),
+        "web1".to_string(),
+        false,
+        false,
+        0.1,
+        None,
+        Some(ansible_piloteer::execution::ExecutionDetails::new(
+            serde_json::json!({"ping": "web1"}),
+        )),
+        None,
+    );
+    app.record_task_result(
+        "ping".to_string(),
+        "web2".to_string(),
+        false,
+        false,
+        0.1,
+        None,
+        Some(ansible_piloteer::execution::ExecutionDetails::new(
+            serde_json::json!({"ping": "web2"}),
+        )),
+        None,
+    );
+    assert_eq!(app.history.len(), 2);
+    assert_eq!(
+        app.inspector_result(),
+        Some(&serde_json::json!({"ping": "web1"}))
+    );
+
+    // Another host's failure doesn't replace it; web1's own does
+    app.set_failed(
+        "ping".to_string(),
+        Some("web2".to_string()),
+        serde_json::json!({"msg": "web2 broke"}),
+        None,
+    );
+    assert_eq!(
+        app.inspector_result(),
+        Some(&serde_json::json!({"ping": "web1"}))
+    );
+    app.set_failed(
+        "ping".to_string(),
+        Some("web1".to_string()),
+        serde_json::json!({"msg": "web1 broke"}),
+        None,
+    );
+    assert_eq!(
+        app.inspector_result(),
+        Some(&serde_json::json!({"msg": "web1 broke"}))
+    );
+
+    // Toggling the same host clears the follow
+    app.toggle_follow_host("web1");
+    assert!(app.follow_host.is_none());
+    assert!(app.follows_log_line("Task 'ping' on web2: OK"));
+    assert_eq!(
+        app.inspector_result(),
+        Some(&serde_json::json!({"msg": "web1 broke"}))
+    );
+}
+
+#[test]
+fn test_var_expectation_check() {
+    let mut app = make_app();
+    app.expect_var("foo".to_string(), serde_json::json!("bar"));
+    app.expect_var("retries".to_string(), serde_json::json!(3));
+
+    // Nothing to compare against yet: expectations stay pending
+    assert!(app.check_var_expectations().is_empty());
+    assert_eq!(app.expected_vars.len(), 2);
+
+    app.set_task(
+        "deploy".to_string(),
+        serde_json::json!({"foo": "bar", "retries": 1}),
+        None,
+    );
+    let results = app.check_var_expectations();
+    assert_eq!(
+        results,
+        vec![
+            ("var `foo` now = bar as expected".to_string(), true),
+            (
+                "warning: var `retries` is still 1 (expected 3)".to_string(),
+                false
+            ),
+        ]
+    );
+    assert!(app.expected_vars.is_empty());
+    assert!(
+        app.logs
+            .iter()
+            .any(|(msg, _)| msg == "var `foo` now = bar as expected")
+    );
+
+    // Checked once: the next task start reports nothing
+    assert!(app.check_var_expectations().is_empty());
+
+    app.expect_var("missing".to_string(), serde_json::json!(true));
+    app.set_task("deploy".to_string(), serde_json::json!({}), None);
+    assert_eq!(
+        app.check_var_expectations(),
+        vec![(
+            "warning: var `missing` is still unset (expected true)".to_string(),
+            false
+        )]
+    );
+}
+
+#[test]
+fn test_no_ai_overrides_configured_key() {
+    let mut config = make_config();
+    config.openai_api_key = Some("sk-test".to_string());
+    assert!(App::new(config.clone()).ai_client.is_some());
+
+    config.no_ai = true;
+    let mut app = App::new(config);
+    assert!(app.ai_client.is_none());
+    assert!(app.ai_disabled);
+
+    // AI controls are inert
+    app.failed_task = Some("deploy".to_string());
+    let key = crossterm::event::KeyEvent::new(
+        crossterm::event::KeyCode::Char('a'),
+        crossterm::event::KeyModifiers::NONE,
+    );
+    let action = app.handle_event(crossterm::event::Event::Key(key));
+    assert!(matches!(action, Action::None));
+}
+
+#[tokio::test]
+async fn test_warnings_aggregate_across_tasks() {
+    use ansible_piloteer::app::WarningKind;
+    use ansible_piloteer::ipc::Message;
+    use ansible_piloteer::ipc_handler::handle_message;
+
+    let mut app = make_app();
+    let result = |name: &str, host: &str, warnings: Vec<&str>, deprecations: Vec<&str>| {
+        serde_json::from_value::<Message>(serde_json::json!({
+            "TaskResult": {
+                "name": name,
+                "host": host,
+                "changed": false,
+                "failed": false,
+                "verbose_result": null,
+                "warnings": warnings,
+                "deprecations": deprecations
+            }
+        }))
+        .unwrap()
+    };
+
+    let old_module = "Module 'foo' is deprecated (removal in 2.19)";
+    handle_message(
+        &mut app,
+        result("a", "web1", vec!["no python"], vec![old_module]),
+        false,
+        false,
+    )
+    .await;
+    handle_message(
+        &mut app,
+        result("b", "web1", vec![], vec![old_module]),
+        false,
+        false,
+    )
+    .await;
+    handle_message(
+        &mut app,
+        result("b", "web2", vec![], vec![old_module]),
+        false,
+        false,
+    )
+    .await;
+    handle_message(&mut app, result("c", "web1", vec![], vec![]), false, false).await;
+
+    assert_eq!(app.warnings.len(), 4);
+    assert_eq!(app.warning_counts(), (1, 3));
+
+    let summary = app.warning_summary();
+    assert_eq!(summary.len(), 2);
+    assert_eq!(summary[0].kind, WarningKind::Deprecation);
+    assert_eq!(summary[0].message, old_module);
+    assert_eq!(summary[0].count, 3);
+    assert_eq!(summary[0].tasks, vec!["a", "b"]);
+    assert_eq!(summary[1].kind, WarningKind::Warning);
+    assert_eq!(summary[1].count, 1);
+
+    let report = ansible_piloteer::report::ReportGenerator::new(&app).generate_markdown();
+    assert!(report.contains("## Warnings & Deprecations"));
+    assert!(report.contains(&format!("| DEPRECATION | 3 | {} | a, b |", old_module)));
+}
+
+#[test]
+fn test_toggle_last_view() {
+    use ansible_piloteer::app::ActiveView;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    let press = |app: &mut App, code: KeyCode| {
+        app.handle_event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)));
+    };
+
+    let mut app = make_app();
+    // Nothing to go back to yet
+    press(&mut app, KeyCode::Char('`'));
+    assert_eq!(app.active_view, ActiveView::Dashboard);
+
+    press(&mut app, KeyCode::Tab); // Dashboard -> Analysis
+    press(&mut app, KeyCode::Tab); // Analysis -> Metrics
+    assert_eq!(app.active_view, ActiveView::Metrics);
+
+    press(&mut app, KeyCode::Char('`'));
+    assert_eq!(app.active_view, ActiveView::Analysis);
+    press(&mut app, KeyCode::Backspace);
+    assert_eq!(app.active_view, ActiveView::Metrics);
+
+    // Switching by other means is tracked too
+    app.set_view(ActiveView::Dashboard);
+    press(&mut app, KeyCode::Char('`'));
+    assert_eq!(app.active_view, ActiveView::Metrics);
+    assert_eq!(app.previous_view, Some(ActiveView::Dashboard));
+
+    // Re-selecting the current view keeps the previous one
+    app.set_view(ActiveView::Metrics);
+    assert_eq!(app.previous_view, Some(ActiveView::Dashboard));
+
+    // `m` leaving Metrics is remembered like any other switch
+    press(&mut app, KeyCode::Char('m'));
+    assert_eq!(app.active_view, ActiveView::Dashboard);
+    press(&mut app, KeyCode::Char('`'));
+    assert_eq!(app.active_view, ActiveView::Metrics);
+}
+
+#[tokio::test]
+async fn test_task_spans_close_on_every_outcome() {
+    use ansible_piloteer::ipc::Message;
+    use ansible_piloteer::ipc_handler::handle_message;
+
+    let start = |name: &str| Message::TaskStart {
+        name: name.to_string(),
+        task_vars: serde_json::json!({}),
+        facts: None,
+    };
+
+    let mut app = make_app();
+    let play = Message::PlayStart {
+        name: "Setup".to_string(),
+        host_pattern: "all".to_string(),
+    };
+    handle_message(&mut app, play, true, false).await;
+    handle_message(&mut app, start("ping"), true, false).await;
+    let unreachable = Message::TaskUnreachable {
+        name: "ping".to_string(),
+        host: "web1".to_string(),
+        error: "timed out".to_string(),
+        result: serde_json::json!({}),
+    };
+    handle_message(&mut app, unreachable, true, false).await;
+    assert!(app.task_spans.is_empty());
+    assert_eq!(app.span_timings.folded_lines().len(), 1);
+    assert!(app.span_timings.folded_lines()[0].starts_with("playbook;Setup;ping "));
+
+    // A task that never reports back is closed with its play, untimed
+    handle_message(&mut app, start("hang"), true, false).await;
+    assert_eq!(app.task_spans.len(), 1);
+    let recap = Message::PlayRecap {
+        stats: serde_json::json!({}),
+    };
+    handle_message(&mut app, recap, true, false).await;
+    assert!(app.task_spans.is_empty());
+    assert_eq!(app.span_timings.folded_lines().len(), 1);
+}
+
+#[tokio::test]
+async fn test_step_mode_pauses_every_task() {
+    use ansible_piloteer::ipc::Message;
+    use ansible_piloteer::ipc_handler::handle_message;
+
+    let start = |name: &str| Message::TaskStart {
+        name: name.to_string(),
+        task_vars: serde_json::json!({}),
+        facts: None,
+    };
+
+    let mut app = make_app();
+    handle_message(&mut app, start("gather"), false, false).await;
+    assert!(!app.waiting_for_proceed);
+
+    app.step_mode = true;
+    handle_message(&mut app, start("install"), false, false).await;
+    assert!(app.waiting_for_proceed);
+
+    // Headless runs never pause
+    handle_message(&mut app, start("configure"), true, false).await;
+    assert!(!app.waiting_for_proceed);
+}
+
+#[tokio::test]
+async fn test_conditional_breakpoint() {
+    use ansible_piloteer::ipc::Message;
+    use ansible_piloteer::ipc_handler::handle_message;
+
+    let start = |env: &str| Message::TaskStart {
+        name: "Deploy".to_string(),
+        task_vars: serde_json::json!({"env": env}),
+        facts: Some(serde_json::json!({"os_family": "Debian"})),
+    };
+
+    let mut app = make_app();
+    assert!(
+        app.set_conditional_breakpoint("Deploy".to_string(), "env ==")
+            .is_err()
+    );
+    app.set_conditional_breakpoint("Deploy".to_string(), "env == 'prod'")
+        .unwrap();
+
+    handle_message(&mut app, start("staging"), false, false).await;
+    assert!(!app.waiting_for_proceed);
+
+    handle_message(&mut app, start("prod"), false, false).await;
+    assert!(app.waiting_for_proceed);
+    assert!(
+        app.logs
+            .iter()
+            .any(|(m, _)| m == "Conditional Breakpoint Hit: Deploy (env == 'prod')")
+    );
+
+    // Facts are reachable; setting again replaces the old condition
+    app.set_conditional_breakpoint("Deploy".to_string(), "ansible_facts.os_family == 'RedHat'")
+        .unwrap();
+    assert_eq!(app.conditional_breakpoints.len(), 1);
+    app.waiting_for_proceed = false;
+    handle_message(&mut app, start("prod"), false, false).await;
+    assert!(!app.waiting_for_proceed);
+
+    // Empty expression clears it
+    app.set_conditional_breakpoint("Deploy".to_string(), " ")
+        .unwrap();
+    assert!(app.conditional_breakpoints.is_empty());
+}
+
+#[tokio::test]
+async fn test_remote_resume_only_when_paused() {
+    use ansible_piloteer::ipc::Message;
+
+    let mut app = make_app();
+    let (ipc_tx, mut ipc_rx) = tokio::sync::mpsc::channel(10);
+    app.set_ipc_tx(Some(ipc_tx));
+    let clients = ansible_piloteer::ipc::ClientRegistry::default();
+    app.ipc_clients = Some(clients.clone());
+    // Send `msg` as a remote client would and return the app's reply
+    async fn remote(
+        app: &mut App,
+        clients: &ansible_piloteer::ipc::ClientRegistry,
+        msg: Message,
+    ) -> (bool, String) {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(1);
+        clients.set_control_reply(Some(tx));
+        ansible_piloteer::ipc_handler::handle_message(app, msg, false, false).await;
+        match rx.try_recv() {
+            Ok(Message::ControlReply { applied, detail }) => (applied, detail),
+            other => panic!("no control reply: {:?}", other),
+        }
+    }
+
+    let (applied, detail) = remote(&mut app, &clients, Message::Proceed).await;
+    assert!(!applied);
+    assert_eq!(detail, "Proceed ignored: not paused");
+    assert!(ipc_rx.try_recv().is_err());
+
+    // At a breakpoint there is nothing to retry, and continue means proceed
+    app.waiting_for_proceed = true;
+    let (applied, detail) = remote(&mut app, &clients, Message::Retry).await;
+    assert!(!applied);
+    assert_eq!(detail, "Retry ignored: not paused on a failure");
+    assert!(app.waiting_for_proceed);
+    assert!(ipc_rx.try_recv().is_err());
+
+    let (applied, detail) = remote(&mut app, &clients, Message::Continue).await;
+    assert!(applied);
+    assert_eq!(detail, "Sent Proceed");
+    assert!(!app.waiting_for_proceed);
+    assert!(matches!(ipc_rx.try_recv(), Ok(Message::Proceed)));
+
+    // Paused on a failure, both go through as sent
+    app.waiting_for_proceed = true;
+    app.failed_task = Some("deploy".to_string());
+    let (applied, _) = remote(&mut app, &clients, Message::Retry).await;
+    assert!(applied);
+    assert!(matches!(ipc_rx.try_recv(), Ok(Message::Retry)));
+    app.waiting_for_proceed = true;
+    remote(&mut app, &clients, Message::Continue).await;
+    assert!(matches!(ipc_rx.try_recv(), Ok(Message::Continue)));
+}
+
+#[test]
+fn test_data_browser_edit_rebuilds_top_level_var() {
+    use ansible_piloteer::widgets::json_tree::JsonTreeState;
+
+    let mut app = make_app();
+    let select = |app: &mut App, path: &str| {
+        let tree = app.analysis_tree.as_mut().unwrap();
+        tree.selected_line = tree.lines.iter().position(|l| l.path == path).unwrap();
+    };
+    app.analysis_tree = Some(JsonTreeState::new(serde_json::json!({
+        "svc": {"name": "web", "ports": [80, 443]},
+        "debug": false
+    })));
+
+    // Task results aren't variables
+    select(&mut app, "debug");
+    assert_eq!(
+        app.prepare_tree_edit(),
+        Err("Only variables and facts can be edited".to_string())
+    );
+    let mut tree = app.analysis_tree.take().unwrap().editable();
+    tree.expand_all();
+    app.analysis_tree = Some(tree);
+
+    // Nothing is sent back during a replay
+    app.replay_mode = true;
+    assert_eq!(
+        app.prepare_tree_edit(),
+        Err("Cannot edit variables while replaying".to_string())
+    );
+    app.replay_mode = false;
+
+    // Containers can't be edited
+    select(&mut app, "svc.ports");
+    assert_eq!(
+        app.prepare_tree_edit(),
+        Err("Only leaf values can be edited".to_string())
+    );
+
+    select(&mut app, "svc.ports[1]");
+    app.prepare_tree_edit().unwrap();
+    let EditState::EditingValue { key, temp_file } = &app.edit_state else {
+        panic!("App not in EditingValue state");
+    };
+    assert_eq!(key, "svc");
+    assert_eq!(std::fs::read_to_string(temp_file).unwrap(), "443");
+    std::fs::write(temp_file, "8443").unwrap();
+
+    let (key, value) = app.apply_edit().unwrap();
+    assert_eq!(key, "svc");
+    assert_eq!(
+        value,
+        serde_json::json!({"name": "web", "ports": [80, 8443]})
+    );
+    assert!(app.tree_edit.is_none());
+
+    // Without a named top-level variable there is nothing to send
+    app.analysis_tree = Some(JsonTreeState::new(serde_json::json!([1, 2])).editable());
+    select(&mut app, "[0]");
+    assert_eq!(
+        app.prepare_tree_edit(),
+        Err("Path '[0]' is not editable".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_interleaved_clients_keep_separate_task_state() {
+    use ansible_piloteer::ipc::{ClientRegistry, Message};
+    use ansible_piloteer::ipc_handler::handle_client_message;
+
+    let mut app = make_app();
+    let clients = ClientRegistry::default();
+    let (tx1, mut rx1) = tokio::sync::mpsc::channel(10);
+    let (tx2, mut rx2) = tokio::sync::mpsc::channel(10);
+    clients.insert(1, tx1);
+    clients.insert(2, tx2);
+    app.ipc_clients = Some(clients);
+    app.breakpoints.insert("Deploy A".to_string());
+
+    let task = |name: &str, play: &str| Message::TaskStart {
+        name: name.to_string(),
+        task_vars: serde_json::json!({ "play": play }),
+        facts: None,
+    };
+
+    // Client 1 stops at a breakpoint
+    handle_client_message(&mut app, 1, task("Deploy A", "a"), false, false).await;
+    assert!(app.waiting_for_proceed);
+    assert!(rx1.try_recv().is_err());
+
+    // Client 2's task runs through and is answered on its own connection,
+    // then the app returns to the paused client with its state intact
+    handle_client_message(&mut app, 2, task("Setup B", "b"), false, false).await;
+    assert!(matches!(rx2.try_recv(), Ok(Message::Proceed)));
+    assert_eq!(app.active_client, Some(1));
+    assert_eq!(app.current_task.as_deref(), Some("Deploy A"));
+    assert_eq!(app.task_vars, Some(serde_json::json!({ "play": "a" })));
+    assert!(app.waiting_for_proceed);
+
+    // Proceeding resumes client 1 only
+    let (ai_tx, _ai_rx) = tokio::sync::mpsc::channel(10);
+    let mut terminal = None;
+    ansible_piloteer::actions::dispatch(Action::Proceed, &mut app, &mut terminal, &ai_tx).await;
+    assert!(matches!(rx1.try_recv(), Ok(Message::Proceed)));
+    assert!(rx2.try_recv().is_err());
+
+    // Client 2's state was kept separately
+    handle_client_message(&mut app, 2, Message::ClientDisconnected, false, false).await;
+    assert!(!app.client_states.contains_key(&2));
+    handle_client_message(&mut app, 1, task("Verify A", "a"), false, false).await;
+    assert_eq!(app.current_task.as_deref(), Some("Verify A"));
+    assert!(matches!(rx1.try_recv(), Ok(Message::Proceed)));
+}
+
+#[tokio::test]
+async fn test_headless_timeout_auto_proceeds() {
+    use ansible_piloteer::ipc::Message;
+    use ansible_piloteer::ipc_handler::{check_headless_timeout, handle_message};
+
+    let mut app = make_app();
+    let (ipc_tx, mut ipc_rx) = tokio::sync::mpsc::channel(10);
+    app.set_ipc_tx(Some(ipc_tx));
+    app.headless_timeout = Some(std::time::Duration::from_secs(60));
+    let start = |name: &str| Message::TaskStart {
+        name: name.to_string(),
+        task_vars: serde_json::json!({}),
+        facts: None,
+    };
+
+    // Held until the deadline
+    handle_message(&mut app, start("Slow"), true, false).await;
+    assert!(app.waiting_for_proceed);
+    check_headless_timeout(&mut app).await;
+    assert!(ipc_rx.try_recv().is_err());
+
+    // A remote command resumes it; the timeout then has nothing to do
+    handle_message(&mut app, Message::Proceed, true, false).await;
+    assert!(matches!(ipc_rx.try_recv(), Ok(Message::Proceed)));
+    check_headless_timeout(&mut app).await;
+    assert!(app.headless_wait.is_none());
+    assert!(ipc_rx.try_recv().is_err());
+
+    // Nothing arrives before the deadline: proceed and say so
+    app.headless_timeout = Some(std::time::Duration::ZERO);
+    handle_message(&mut app, start("Stalled"), true, false).await;
+    check_headless_timeout(&mut app).await;
+    assert!(matches!(ipc_rx.try_recv(), Ok(Message::Proceed)));
+    assert!(!app.waiting_for_proceed);
+    assert!(
+        app.logs
+            .iter()
+            .any(|(l, _)| l == "Headless: timeout waiting on task Stalled")
+    );
+}
+
+#[tokio::test]
+async fn test_headless_timeout_is_per_client() {
+    use ansible_piloteer::ipc::{ClientRegistry, Message};
+    use ansible_piloteer::ipc_handler::{check_headless_timeout, handle_client_message};
+
+    let mut app = make_app();
+    let clients = ClientRegistry::default();
+    let (tx1, mut rx1) = tokio::sync::mpsc::channel(10);
+    let (tx2, mut rx2) = tokio::sync::mpsc::channel(10);
+    clients.insert(1, tx1);
+    clients.insert(2, tx2);
+    app.ipc_clients = Some(clients);
+    app.headless_timeout = Some(std::time::Duration::ZERO);
+    let start = |name: &str| Message::TaskStart {
+        name: name.to_string(),
+        task_vars: serde_json::json!({}),
+        facts: None,
+    };
+
+    // The second client's wait does not replace the first one's
+    handle_client_message(&mut app, 1, start("Deploy A"), true, false).await;
+    handle_client_message(&mut app, 2, start("Deploy B"), true, false).await;
+    check_headless_timeout(&mut app).await;
+    assert!(matches!(rx1.try_recv(), Ok(Message::Proceed)));
+    assert!(matches!(rx2.try_recv(), Ok(Message::Proceed)));
+    assert!(!app.waiting_for_proceed);
+    assert!(app.client_states.values().all(|s| !s.waiting_for_proceed));
+}
+
+#[test]
+fn test_log_buffer_size_drops_oldest() {
+    let mut app = App::new(Config {
+        log_buffer_size: 500,
+        ..make_config()
+    });
+    for i in 0..1500 {
+        app.log(format!("line {}", i), None);
+    }
+    assert_eq!(app.logs.len(), 500);
+    assert_eq!(app.logs.front().unwrap().0, "line 1000");
+    assert_eq!(app.logs.back().unwrap().0, "line 1499");
+
+    // 0 keeps everything
+    let mut app = App::new(Config {
+        log_buffer_size: 0,
+        ..make_config()
+    });
+    for i in 0..1500 {
+        app.log(format!("line {}", i), None);
+    }
+    assert_eq!(app.logs.len(), 1500);
+    assert_eq!(app.logs.front().unwrap().0, "line 0");
+
+    // Past u16::MAX lines the follow position saturates instead of wrapping
+    for i in 1500..70_000 {
+        app.log(format!("line {}", i), None);
+    }
+    assert_eq!(app.log_scroll, u16::MAX);
+}
+
+#[tokio::test]
+async fn test_undo_var_change_restores_previous_values() {
+    use ansible_piloteer::ipc::Message;
+
+    let mut app = make_app();
+    let (ipc_tx, mut ipc_rx) = tokio::sync::mpsc::channel(10);
+    app.set_ipc_tx(Some(ipc_tx));
+    let (ai_tx, _ai_rx) = tokio::sync::mpsc::channel(10);
+    let mut terminal = None;
+
+    // Nothing recorded yet
+    ansible_piloteer::actions::dispatch(Action::UndoVarChange, &mut app, &mut terminal, &ai_tx)
+        .await;
+    assert!(ipc_rx.try_recv().is_err());
+    assert_eq!(app.notification.as_ref().unwrap().0, "Nothing to undo");
+
+    app.set_task("Deploy".to_string(), serde_json::json!({"port": 80}), None);
+    let fix = |key: &str, value| ansible_piloteer::ai::Analysis {
+        analysis: String::new(),
+        fix: Some(ansible_piloteer::ai::Fix {
+            key: key.to_string(),
+            value,
+        }),
+        tokens_used: 0,
+    };
+    app.suggestion = Some(fix("port", serde_json::json!(8080)));
+    ansible_piloteer::actions::dispatch(Action::ApplyFix, &mut app, &mut terminal, &ai_tx).await;
+    app.suggestion = Some(fix("user", serde_json::json!("deploy")));
+    ansible_piloteer::actions::dispatch(Action::ApplyFix, &mut app, &mut terminal, &ai_tx).await;
+    while ipc_rx.try_recv().is_ok() {}
+
+    // Newest first: `user` didn't exist, so it is unset again
+    ansible_piloteer::actions::dispatch(Action::UndoVarChange, &mut app, &mut terminal, &ai_tx)
+        .await;
+    match ipc_rx.try_recv() {
+        Ok(Message::UnsetVar { key }) => assert_eq!(key, "user"),
+        other => panic!("Expected UnsetVar, got {:?}", other),
+    }
+    assert_eq!(
+        app.notification.as_ref().unwrap().0,
+        "Reverted user to unset"
+    );
+    ansible_piloteer::actions::dispatch(Action::UndoVarChange, &mut app, &mut terminal, &ai_tx)
+        .await;
+    match ipc_rx.try_recv() {
+        Ok(Message::ModifyVar { key, value }) => {
+            assert_eq!(key, "port");
+            assert_eq!(value, serde_json::json!(80));
+        }
+        other => panic!("Expected ModifyVar, got {:?}", other),
+    }
+    assert_eq!(app.notification.as_ref().unwrap().0, "Reverted port to 80");
+    assert!(app.var_undo.is_empty());
+    // The undone fix no longer conflicts with a fresh edit
+    assert!(app.find_var_conflict("port").is_none());
+
+    // Scripted changes can be undone like interactive ones
+    app.test_script.push(ansible_piloteer::app::ScriptAction {
+        task_name: "Configure".to_string(),
+        on_failure: false,
+        actions: vec![ansible_piloteer::app::ScriptActionType::EditVar {
+            key: "port".to_string(),
+            value: serde_json::json!(9090),
+        }],
+    });
+    ansible_piloteer::ipc_handler::handle_message(
+        &mut app,
+        Message::TaskStart {
+            name: "Configure".to_string(),
+            task_vars: serde_json::json!({"port": 80}),
+            facts: None,
+        },
+        true,
+        false,
+    )
+    .await;
+    assert_eq!(
+        app.var_undo,
+        vec![("port".to_string(), Some(serde_json::json!(80)))]
+    );
+}
+
+#[tokio::test]
+async fn test_chat_about_failure_prefills_context() {
+    let (ai_tx, _ai_rx) = tokio::sync::mpsc::channel(10);
+    let mut terminal = None;
+
+    let mut app = App::new(make_config());
+    app.failed_task = Some("Install nginx".to_string());
+    ansible_piloteer::actions::dispatch(Action::ChatAboutFailure, &mut app, &mut terminal, &ai_tx)
+        .await;
+    assert!(!app.chat_active);
+    assert_eq!(
+        app.notification.clone().unwrap().0,
+        "AI Client not configured."
+    );
+
+    let mut app = App::new(Config {
+        openai_api_key: Some("test-key".to_string()),
+        api_base: "http://127.0.0.1:9".to_string(),
+        ..make_config()
+    });
+    ansible_piloteer::actions::dispatch(Action::ChatAboutFailure, &mut app, &mut terminal, &ai_tx)
+        .await;
+    assert!(!app.chat_active);
+    assert!(app.chat_history.is_empty());
+
+    app.failed_task = Some("Install nginx".to_string());
+    app.failed_result = Some(serde_json::json!({"msg": "No package matching 'nginx'"}));
+    ansible_piloteer::actions::dispatch(Action::ChatAboutFailure, &mut app, &mut terminal, &ai_tx)
+        .await;
+    assert!(app.chat_active);
+    assert!(app.chat_loading);
+    assert_eq!(app.chat_history.len(), 1);
+    let question = &app.chat_history[0];
+    assert_eq!(question.role, "user");
+    assert!(question.content.contains("**Failed Task:** Install nginx"));
+    assert!(question.content.contains("No package matching"));
+    assert!(question.content.ends_with("Why did this fail?"));
+}
+
+#[tokio::test]
+async fn test_chat_context_and_clear_commands() {
+    let (ai_tx, _ai_rx) = tokio::sync::mpsc::channel(10);
+    let mut terminal = None;
+    let mut app = App::new(Config {
+        openai_api_key: Some("test-key".to_string()),
+        api_base: "http://127.0.0.1:9".to_string(),
+        ..make_config()
+    });
+    app.failed_task = Some("Install nginx".to_string());
+
+    app.chat_input = "/context".to_string();
+    ansible_piloteer::actions::dispatch(Action::SubmitChat, &mut app, &mut terminal, &ai_tx).await;
+    assert_eq!(app.chat_history.len(), 1);
+    assert_eq!(app.chat_history[0].role, "system");
+    assert!(app.chat_history[0].content.contains("Install nginx"));
+    assert_eq!(app.notification.clone().unwrap().0, "Context added to chat");
+    // Slash commands never reach the provider
+    assert!(!app.chat_loading);
+
+    app.chat_history.push(ansible_piloteer::ai::ChatMessage {
+        role: "user".to_string(),
+        content: "why?".to_string(),
+        collapsed: false,
+        reply_to: None,
+    });
+    app.chat_history.push(ansible_piloteer::ai::ChatMessage {
+        role: "assistant".to_string(),
+        content: "because".to_string(),
+        collapsed: false,
+        reply_to: None,
+    });
+    app.chat_input = "/clear".to_string();
+    ansible_piloteer::actions::dispatch(Action::SubmitChat, &mut app, &mut terminal, &ai_tx).await;
+    assert_eq!(app.chat_history.len(), 1);
+    assert!(app.chat_history[0].content.contains("Install nginx"));
+    assert_eq!(app.notification.clone().unwrap().0, "Chat cleared");
+    assert!(app.chat_input.is_empty());
+}
+
+#[tokio::test]
+async fn test_chat_model_command_switches_provider() {
+    let (ai_tx, _ai_rx) = tokio::sync::mpsc::channel(10);
+    let mut terminal = None;
+    let mut app = App::new(Config {
+        openai_api_key: Some("test-key".to_string()),
+        api_base: "http://127.0.0.1:9".to_string(),
+        ..make_config()
+    });
+    app.chat_history.push(ansible_piloteer::ai::ChatMessage {
+        role: "user".to_string(),
+        content: "why?".to_string(),
+        collapsed: false,
+        reply_to: None,
+    });
+
+    app.chat_input = "/model claude-sonnet-4-5".to_string();
+    ansible_piloteer::actions::dispatch(Action::SubmitChat, &mut app, &mut terminal, &ai_tx).await;
+    let client = app.ai_client.as_ref().unwrap();
+    assert_eq!(client.get_model(), "claude-sonnet-4-5");
+    assert_eq!(client.config().provider.as_deref(), Some("anthropic"));
+    // The earlier question survives the switch
+    assert_eq!(app.chat_history.len(), 2);
+    assert_eq!(app.chat_history[0].content, "why?");
+    let reply = &app.chat_history[1].content;
+    assert!(reply.contains("Switched to model: claude-sonnet-4-5 (anthropic)"));
+    assert!(!reply.contains("Unrecognised"));
+
+    app.chat_input = "/model my-local-llama".to_string();
+    ansible_piloteer::actions::dispatch(Action::SubmitChat, &mut app, &mut terminal, &ai_tx).await;
+    let client = app.ai_client.as_ref().unwrap();
+    assert_eq!(client.config().provider.as_deref(), Some("openai"));
+    assert!(
+        app.chat_history[2]
+            .content
+            .contains("Unrecognised model name")
+    );
+    assert!(!app.chat_loading);
+}
+
+#[test]
+fn test_ai_call_metrics_are_capped() {
+    let mut app = App::new(make_config());
+    for i in 0..105u64 {
+        app.record_ai_call(i * 10, i as u32);
+    }
+    assert_eq!(app.ai_latencies.len(), 100);
+    assert_eq!(app.ai_tokens.len(), 100);
+    // The oldest five calls were dropped
+    assert_eq!(app.ai_latencies.front(), Some(&50));
+    assert_eq!(app.ai_tokens.back(), Some(&104));
+
+    // Without an AI client there is nothing to collect
+    app.collect_ai_metrics();
+    assert_eq!(app.ai_latencies.len(), 100);
+}
+
+#[tokio::test]
+async fn test_mouse_click_selects_history_task() {
+    use crossterm::event::{Event, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    let click = |column, row| {
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+    };
+
+    let mut app = App::new(make_config());
+    for name in ["one", "two", "three", "four"] {
+        app.history.push(TaskHistory::new(name, "localhost"));
+    }
+    app.active_view = ansible_piloteer::app::ActiveView::Analysis;
+    app.history_pane = Some(ratatui::layout::Rect::new(0, 0, 30, 10));
+    app.history_list_offset = 1;
+
+    // Row 1 is the first task row; the list is scrolled down by one
+    let action = app.handle_event(click(5, 1));
+    assert!(matches!(action, Action::AnalysisSelect(1)));
+    assert!(matches!(
+        app.handle_event(click(5, 3)),
+        Action::AnalysisSelect(3)
+    ));
+    // Border, past the last task, and outside the pane are ignored
+    assert!(matches!(app.handle_event(click(5, 0)), Action::None));
+    assert!(matches!(app.handle_event(click(5, 5)), Action::None));
+    assert!(matches!(app.handle_event(click(40, 2)), Action::None));
+
+    let (ai_tx, _ai_rx) = tokio::sync::mpsc::channel(10);
+    let mut terminal = None;
+    ansible_piloteer::actions::dispatch(Action::AnalysisSelect(2), &mut app, &mut terminal, &ai_tx)
+        .await;
+    assert_eq!(app.analysis_index, 2);
+    assert!(app.analysis_tree.is_some());
+
+    // Clicks only select in the Analysis view
+    app.active_view = ansible_piloteer::app::ActiveView::Dashboard;
+    assert!(matches!(app.handle_event(click(5, 1)), Action::None));
+}
+
+#[test]
+fn test_jump_to_failed_task_wraps_and_respects_host_filter() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let mut app = App::new(make_config());
+    app.active_view = ansible_piloteer::app::ActiveView::Analysis;
+    for (name, host, failed) in [
+        ("a", "web1", false),
+        ("b", "web1", true),
+        ("c", "db1", true),
+        ("d", "web1", false),
+        ("e", "web1", true),
+    ] {
+        app.history.push(TaskHistory {
+            failed,
+            ..TaskHistory::new(name, host)
+        });
+    }
+    let press = |app: &mut App, code, modifiers| {
+        app.handle_event(Event::Key(KeyEvent::new(code, modifiers)))
+    };
+
+    app.analysis_index = 0;
+    assert!(matches!(
+        press(&mut app, KeyCode::Char('F'), KeyModifiers::SHIFT),
+        Action::AnalysisSelect(1)
+    ));
+    app.analysis_index = 1;
+    assert!(matches!(
+        press(&mut app, KeyCode::Char('F'), KeyModifiers::SHIFT),
+        Action::AnalysisSelect(2)
+    ));
+
+    // Filtered to web1: db1's failure is skipped, and the search wraps
+    app.host_filter = Some("web1".to_string());
+    assert!(matches!(
+        press(&mut app, KeyCode::Char('F'), KeyModifiers::SHIFT),
+        Action::AnalysisSelect(4)
+    ));
+    app.analysis_index = 4;
+    assert!(matches!(
+        press(&mut app, KeyCode::Char('F'), KeyModifiers::SHIFT),
+        Action::AnalysisSelect(1)
+    ));
+    assert!(matches!(
+        press(&mut app, KeyCode::Char('f'), KeyModifiers::CONTROL),
+        Action::AnalysisSelect(1)
+    ));
+    app.analysis_index = 1;
+    assert!(matches!(
+        press(&mut app, KeyCode::Char('f'), KeyModifiers::CONTROL),
+        Action::AnalysisSelect(4)
+    ));
+
+    for task in &mut app.history {
+        task.failed = false;
+    }
+    assert!(matches!(
+        press(&mut app, KeyCode::Char('F'), KeyModifiers::SHIFT),
+        Action::None
+    ));
+    assert_eq!(app.notification.clone().unwrap().0, "No failed tasks");
+}
+
+#[tokio::test]
+async fn test_analysis_navigation_stays_within_host_filter() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let (ai_tx, _ai_rx) = tokio::sync::mpsc::channel(10);
+    let mut terminal = None;
+    let mut app = App::new(make_config());
+    app.active_view = ansible_piloteer::app::ActiveView::Analysis;
+    for (name, host) in [
+        ("a", "db1"),
+        ("b", "web1"),
+        ("c", "db1"),
+        ("d", "db1"),
+        ("e", "web1"),
+    ] {
+        app.history.push(TaskHistory::new(name, host));
+        app.hosts
+            .entry(host.to_string())
+            .or_insert(ansible_piloteer::app::HostStatus {
+                name: host.to_string(),
+                ok_tasks: 0,
+                changed_tasks: 0,
+                failed_tasks: 0,
+                rescued_tasks: 0,
+                skipped_tasks: 0,
+            });
+    }
+
+    // Picking web1 in the host list moves the selection off db1's task
+    app.show_host_list = true;
+    app.host_list_selected = Some("web1".to_string());
+    let action = app.handle_event(Event::Key(KeyEvent::new(
+        KeyCode::Enter,
+        KeyModifiers::NONE,
+    )));
+    assert_eq!(app.host_filter.as_deref(), Some("web1"));
+    ansible_piloteer::actions::dispatch(action, &mut app, &mut terminal, &ai_tx).await;
+    assert_eq!(app.analysis_index, 1);
+    assert!(app.analysis_tree.is_some());
+
+    // Next/previous only land on web1 tasks
+    ansible_piloteer::actions::dispatch(Action::AnalysisNext, &mut app, &mut terminal, &ai_tx)
+        .await;
+    assert_eq!(app.analysis_index, 4);
+    ansible_piloteer::actions::dispatch(Action::AnalysisNext, &mut app, &mut terminal, &ai_tx)
+        .await;
+    assert_eq!(app.analysis_index, 4);
+    ansible_piloteer::actions::dispatch(Action::AnalysisPrev, &mut app, &mut terminal, &ai_tx)
+        .await;
+    assert_eq!(app.analysis_index, 1);
+}
+
+#[test]
+fn test_timeline_replay_reemits_history() {
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    let mut app = make_app();
+    app.replay_mode = true;
+    for (name, failed) in [("install", false), ("restart", true)] {
+        app.history.push(TaskHistory {
+            failed,
+            duration: 3600.0,
+            error: failed.then(|| "boom".to_string()),
+            verbose_result: Some(ansible_piloteer::execution::ExecutionDetails::new(
+                serde_json::json!({"msg": "result"}),
+            )),
+            task_vars: failed.then(|| serde_json::json!({"port": 8080})),
+            facts: failed.then(|| serde_json::json!({"os": "linux"})),
+            ..TaskHistory::new(name, "web1")
+        });
+    }
+    app.start_timeline(1.0);
+    assert!(app.history.is_empty());
+
+    // Hour-long tasks: nothing finishes on its own during the test
+    app.advance_timeline();
+    assert_eq!(app.current_task.as_deref(), Some("install"));
+    assert!(app.history.is_empty());
+
+    let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    app.handle_event(key(' '));
+    assert!(app.timeline.as_ref().unwrap().paused);
+    app.handle_event(key('n'));
+    assert_eq!(app.history.len(), 1);
+    assert_eq!(app.hosts["web1"].ok_tasks, 1);
+
+    app.handle_event(key('n'));
+    app.handle_event(key('n'));
+    assert_eq!(app.history.len(), 2);
+    assert_eq!(app.hosts["web1"].failed_tasks, 1);
+    // The failure keeps the context it ran with, not its result
+    assert_eq!(
+        app.history[1].task_vars,
+        Some(serde_json::json!({"port": 8080}))
+    );
+    assert_eq!(
+        app.history[1].facts,
+        Some(serde_json::json!({"os": "linux"}))
+    );
+    assert_eq!(app.current_task, None);
+    let logs: Vec<&str> = app.logs.iter().map(|(l, _)| l.as_str()).collect();
+    assert!(logs.contains(&"Task 'restart' on web1: FAILED"));
+    assert!(logs.contains(&"  boom"));
+    assert_eq!(logs.last(), Some(&"Timeline replay complete"));
+}
+
+#[test]
+fn test_inspector_yaml_toggle_and_search() {
+    use ansible_piloteer::app::InspectorFormat;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    let mut app = make_app();
+    app.failed_result = Some(serde_json::json!({
+        "changed": false,
+        "msg": "boom",
+        "results": [{"item": "a"}, {"item": "needle"}]
+    }));
+    let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+
+    app.handle_event(key('t'));
+    assert_eq!(app.inspector_format, InspectorFormat::Yaml);
+    let yaml = app.inspector_text().unwrap();
+    assert!(yaml.contains("msg: boom"));
+    assert!(!yaml.contains('{'));
+
+    // Search scrolls to the match's line in the YAML rendering
+    app.dashboard_focus = ansible_piloteer::app::DashboardFocus::Inspector;
+    app.search_query = "needle".to_string();
+    app.handle_event(key('n'));
+    let line = yaml.lines().position(|l| l.contains("needle")).unwrap();
+    assert_eq!(app.scroll_offset as usize, line);
+
+    // The choice survives the next task
+    app.set_task("next".to_string(), serde_json::json!({}), None);
+    assert_eq!(app.inspector_format, InspectorFormat::Yaml);
+    app.failed_result = Some(serde_json::json!({"msg": "again"}));
+    app.handle_event(key('t'));
+    assert!(app.inspector_text().unwrap().starts_with('{'));
+}
+
+#[test]
+fn test_inspector_search_cycles_and_accounts_for_wrapping() {
+    use ansible_piloteer::app::{DashboardFocus, wrapped_row};
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    // The 28-column middle line takes two rows at width 20
+    assert_eq!(
+        wrapped_row("x\n    aaaa bbbb cccc dddd eeee\ny", 2, 0, 20),
+        3
+    );
+    assert_eq!(wrapped_row("aaaa bbbb cccc dddd eeee", 0, 24, 10), 2);
+    assert_eq!(wrapped_row("one\ntwo", 1, 3, 0), 1);
+
+    let mut app = make_app();
+    app.failed_result = Some(serde_json::json!({
+        "a": "needle one",
+        "b": "word ".repeat(30),
+        "c": "needle two",
+    }));
+    app.dashboard_focus = DashboardFocus::Inspector;
+    app.search_query = "needle".to_string();
+    app.inspector_width = 20;
+    let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+
+    app.handle_event(key('n'));
+    assert_eq!(app.inspector_match, Some(0));
+    assert_eq!(app.scroll_offset, 1);
+
+    // The long "b" line wraps onto several rows, pushing "c" further down
+    // than its line number
+    app.handle_event(key('n'));
+    assert_eq!(app.inspector_match, Some(1));
+    assert!(app.scroll_offset > 3);
+
+    // Cycling wraps around in both directions
+    app.handle_event(key('n'));
+    assert_eq!(app.inspector_match, Some(0));
+    app.handle_event(key('N'));
+    assert_eq!(app.inspector_match, Some(1));
+    app.handle_event(key('N'));
+    assert_eq!(app.inspector_match, Some(0));
+}
+
+#[test]
+fn test_task_timestamps_recorded_and_optional() {
+    let mut app = make_app();
+    let before = chrono::Utc::now();
+    app.set_task("install".to_string(), serde_json::json!({}), None);
+    app.record_task_result(
+        "install".to_string(),
+        "web1".to_string(),
+        false,
+        false,
+        0.1,
+        None,
+        None,
+        None,
+    );
+    let task = &app.history[0];
+    let started = task.started_at.expect("start time recorded");
+    let finished = task.finished_at.expect("finish time recorded");
+    assert!(before <= started && started <= finished);
+
+    // Round-trips through the session format
+    let value = serde_json::to_value(task).unwrap();
+    let loaded: TaskHistory = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(loaded.started_at, Some(started));
+
+    // Sessions saved before timestamps existed still load
+    let mut old = value;
+    let obj = old.as_object_mut().unwrap();
+    obj.remove("started_at");
+    obj.remove("finished_at");
+    let loaded: TaskHistory = serde_json::from_value(old).unwrap();
+    assert!(loaded.started_at.is_none() && loaded.finished_at.is_none());
+}
+
+#[test]
+fn test_stall_detector_warns_once_per_silence() {
+    use std::time::Duration;
+    let mut app = make_app();
+    let start = app.last_ipc_message;
+    let later = start + Duration::from_secs(61);
+
+    // Not connected yet: nothing to stall
+    app.check_stall(later);
+    assert!(app.notification.is_none());
+
+    app.client_connected = true;
+    app.check_stall(start + Duration::from_secs(59));
+    assert!(app.notification.is_none());
+    app.check_stall(later);
+    let (msg, _) = app.notification.clone().unwrap();
+    assert_eq!(msg, "No activity for 60s — controller may be stalled");
+    assert!(app.notification_alert);
+    assert_eq!(app.logs.back().unwrap().0, msg);
+
+    // Reported once until the plugin speaks again
+    let logs = app.logs.len();
+    app.check_stall(later + Duration::from_secs(60));
+    assert_eq!(app.logs.len(), logs);
+    app.note_ipc_activity();
+    assert!(!app.stall_warned);
+
+    // Waiting on the user, or replaying, is not a stall
+    app.waiting_for_proceed = true;
+    app.check_stall(app.last_ipc_message + Duration::from_secs(120));
+    assert!(!app.stall_warned);
+    app.waiting_for_proceed = false;
+    app.replay_mode = true;
+    app.check_stall(app.last_ipc_message + Duration::from_secs(120));
+    assert!(!app.stall_warned);
+}
+
+#[tokio::test]
+async fn test_audit_log_records_control_actions() {
+    use ansible_piloteer::audit::{AuditEntry, AuditLog, REDACTED};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit").join("audit.jsonl");
+    let mut app = make_app();
+    app.audit_log = Some(AuditLog::open(&path, false).unwrap());
+    let (ipc_tx, _ipc_rx) = tokio::sync::mpsc::channel(10);
+    app.set_ipc_tx(Some(ipc_tx));
+    let (ai_tx, _ai_rx) = tokio::sync::mpsc::channel(10);
+    let mut terminal = None;
+
+    app.set_task(
+        "Install nginx".to_string(),
+        serde_json::json!({"inventory_hostname": "web1", "db_password": "old"}),
+        None,
+    );
+    ansible_piloteer::actions::dispatch(Action::Proceed, &mut app, &mut terminal, &ai_tx).await;
+    // Not paused any more: nothing is sent, so nothing is recorded
+    ansible_piloteer::actions::dispatch(Action::Retry, &mut app, &mut terminal, &ai_tx).await;
+
+    app.waiting_for_proceed = true;
+    app.suggestion = Some(ansible_piloteer::ai::Analysis {
+        analysis: String::new(),
+        fix: Some(ansible_piloteer::ai::Fix {
+            key: "db_password".to_string(),
+            value: serde_json::json!("hunter2"),
+        }),
+        tokens_used: 0,
+    });
+    ansible_piloteer::actions::dispatch(Action::ApplyFix, &mut app, &mut terminal, &ai_tx).await;
+    ansible_piloteer::actions::dispatch(Action::Continue, &mut app, &mut terminal, &ai_tx).await;
+
+    let entries: Vec<AuditEntry> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    let actions: Vec<&str> = entries.iter().map(|e| e.action.as_str()).collect();
+    assert_eq!(actions, vec!["Proceed", "ApplyFix", "Continue"]);
+    assert!(
+        entries
+            .iter()
+            .all(|e| e.task.as_deref() == Some("Install nginx"))
+    );
+    assert!(entries.iter().all(|e| e.host.as_deref() == Some("web1")));
+    assert_eq!(entries[1].key.as_deref(), Some("db_password"));
+    assert_eq!(entries[1].value, Some(serde_json::json!(REDACTED)));
+    assert!(entries[0].key.is_none());
+
+    // Values are written only when asked for, and the log is appended to
+    let mut log = AuditLog::open(&path, true).unwrap();
+    log.record(
+        "ModifyVar",
+        Some("t"),
+        None,
+        Some(("port", &serde_json::json!(8080))),
+    )
+    .unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(text.lines().count(), 4);
+    assert!(text.lines().last().unwrap().contains("\"value\":8080"));
+}
+
+#[tokio::test]
+async fn test_audit_log_records_scripted_and_headless_actions() {
+    use ansible_piloteer::app::{ScriptAction, ScriptActionType};
+    use ansible_piloteer::audit::{AuditEntry, AuditLog};
+    use ansible_piloteer::ipc::Message;
+    use ansible_piloteer::ipc_handler::handle_message;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let mut app = make_app();
+    app.audit_log = Some(AuditLog::open(&path, false).unwrap());
+    let (ipc_tx, _ipc_rx) = tokio::sync::mpsc::channel(10);
+    app.set_ipc_tx(Some(ipc_tx));
+    app.test_script.push(ScriptAction {
+        task_name: "Configure".to_string(),
+        on_failure: false,
+        actions: vec![
+            ScriptActionType::EditVar {
+                key: "port".to_string(),
+                value: serde_json::json!(8080),
+            },
+            ScriptActionType::Continue,
+        ],
+    });
+    let start = |name: &str| Message::TaskStart {
+        name: name.to_string(),
+        task_vars: serde_json::json!({}),
+        facts: None,
+    };
+
+    handle_message(&mut app, start("Configure"), true, false).await;
+    handle_message(&mut app, start("Deploy"), true, false).await;
+
+    let entries: Vec<AuditEntry> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    let actions: Vec<&str> = entries.iter().map(|e| e.action.as_str()).collect();
+    assert_eq!(
+        actions,
+        vec![
+            "ModifyVar (script)",
+            "Continue (script)",
+            "Proceed (headless)"
+        ]
+    );
+    assert_eq!(entries[0].key.as_deref(), Some("port"));
+    assert_eq!(entries[2].task.as_deref(), Some("Deploy"));
+}
+
+#[test]
+fn test_headless_event_formats() {
+    use ansible_piloteer::app::HeadlessFormat;
+    use ansible_piloteer::ipc_handler::HeadlessEvent;
+
+    let result = HeadlessEvent::TaskResult {
+        task: "Install nginx",
+        host: "web1",
+        status: "CHANGED",
+        changed: true,
+        failed: false,
+        rescued: false,
+        duration: 1.5,
+    };
+    assert_eq!(
+        result.render(HeadlessFormat::Prose).as_deref(),
+        Some("Headless: Task Result: CHANGED")
+    );
+    let line = result.render(HeadlessFormat::Jsonl).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "event": "task_result",
+            "task": "Install nginx",
+            "host": "web1",
+            "status": "CHANGED",
+            "changed": true,
+            "failed": false,
+            "rescued": false,
+            "duration": 1.5,
+        })
+    );
+
+    let stats = serde_json::json!({"web1": {"ok": 3, "changed": 1}});
+    let recap = HeadlessEvent::PlayRecap { stats: &stats };
+    assert_eq!(recap.render(HeadlessFormat::Prose), None);
+    let json: serde_json::Value =
+        serde_json::from_str(&recap.render(HeadlessFormat::Jsonl).unwrap()).unwrap();
+    assert_eq!(json["event"], "play_recap");
+    assert_eq!(json["stats"], stats);
+
+    let fail = HeadlessEvent::TaskFail {
+        task: "Install",
+        host: Some("web1"),
+    };
+    assert_eq!(
+        fail.render(HeadlessFormat::Prose).unwrap(),
+        "Headless: Task Failed: Install on web1"
+    );
+    let json: serde_json::Value =
+        serde_json::from_str(&fail.render(HeadlessFormat::Jsonl).unwrap()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({"event": "task_fail", "task": "Install", "host": "web1"})
+    );
+
+    assert_eq!(HeadlessFormat::parse("JSONL"), Some(HeadlessFormat::Jsonl));
+    assert_eq!(HeadlessFormat::parse("xml"), None);
+}
+
+#[tokio::test]
+async fn test_skipped_tasks_are_recorded() {
+    use ansible_piloteer::app::TaskSort;
+    use ansible_piloteer::ipc::Message;
+    use ansible_piloteer::ipc_handler::handle_message;
+
+    let mut app = make_app();
+    handle_message(
+        &mut app,
+        Message::TaskSkipped {
+            name: "Install on RedHat".to_string(),
+            host: "web1".to_string(),
+        },
+        false,
+        false,
+    )
+    .await;
+    app.record_task_result(
+        "Install on Debian".to_string(),
+        "web1".to_string(),
+        true,
+        false,
+        1.0,
+        None,
+        None,
+        None,
+    );
+
+    let skipped = &app.history[0];
+    assert!(skipped.skipped && !skipped.failed && !skipped.changed);
+    let host = &app.hosts["web1"];
+    assert_eq!(
+        (host.ok_tasks, host.changed_tasks, host.skipped_tasks),
+        (0, 1, 1)
+    );
+    assert_eq!(app.exit_code(true), 4);
+
+    // Skipped tasks sort after everything that ran
+    app.task_sort = TaskSort::Status;
+    assert_eq!(app.task_display_order(), vec![1, 0]);
+}
+
+#[test]
+fn test_metrics_follow_host_filter() {
+    use ansible_piloteer::widgets::metrics::metrics_history;
+
+    let mut app = make_app();
+    app.record_task_result(
+        "deploy".to_string(),
+        "web1".to_string(),
+        true,
+        false,
+        1.0,
+        None,
+        None,
+        None,
+    );
+    app.record_task_result(
+        "migrate".to_string(),
+        "db1".to_string(),
+        false,
+        false,
+        2.0,
+        None,
+        None,
+        None,
+    );
+    app.record_task_result(
+        "Play Recap".to_string(),
+        "db1".to_string(),
+        false,
+        false,
+        0.0,
+        None,
+        None,
+        None,
+    );
+    // The recap isn't a task, filtered or not
+    assert_eq!(metrics_history(&app).len(), 2);
+
+    app.host_filter = Some("db1".to_string());
+    let names: Vec<_> = metrics_history(&app)
+        .iter()
+        .map(|t| t.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["migrate"]);
+}
+
+#[test]
+fn test_host_list_sort_and_metrics_drill_down() {
+    use ansible_piloteer::app::{ActiveView, HostSort};
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    let mut app = make_app();
+    for (name, host, failed) in [
+        ("install", "alpha", false),
+        ("install", "beta", true),
+        ("configure", "beta", true),
+        ("install", "gamma", true),
+    ] {
+        app.record_task_result(
+            name.to_string(),
+            host.to_string(),
+            false,
+            failed,
+            1.0,
+            None,
+            None,
+            None,
+        );
+    }
+    let press = |app: &mut App, c: char| {
+        app.handle_event(Event::Key(KeyEvent::new(
+            KeyCode::Char(c),
+            KeyModifiers::NONE,
+        )))
+    };
+    let order = |app: &App| -> Vec<String> {
+        app.host_list_order()
+            .iter()
+            .map(|h| h.name.clone())
+            .collect()
+    };
+
+    app.show_host_list = true;
+    assert_eq!(order(&app), ["alpha", "beta", "gamma"]);
+
+    // Selection follows gamma through each re-sort
+    app.host_list_selected = Some("gamma".to_string());
+    for _ in 0..3 {
+        press(&mut app, 's');
+    }
+    assert_eq!(app.host_sort, HostSort::Failed);
+    assert_eq!(order(&app), ["beta", "gamma", "alpha"]);
+    assert_eq!(app.host_list_index(), 1);
+
+    // Live results that reorder the list keep gamma selected
+    for name in ["configure", "restart"] {
+        app.record_task_result(
+            name.to_string(),
+            "gamma".to_string(),
+            false,
+            true,
+            1.0,
+            None,
+            None,
+            None,
+        );
+    }
+    assert_eq!(order(&app), ["gamma", "beta", "alpha"]);
+    assert_eq!(app.host_list_index(), 0);
+
+    press(&mut app, 'j');
+    assert_eq!(app.host_list_selected.as_deref(), Some("beta"));
+
+    press(&mut app, 'k');
+    press(&mut app, 'm');
+    assert_eq!(app.host_filter.as_deref(), Some("gamma"));
+    assert_eq!(app.active_view, ActiveView::Metrics);
+    assert!(!app.show_host_list);
+}
+
+#[test]
+fn test_dashboard_count_prefix_scrolls() {
+    use ansible_piloteer::app::DashboardFocus;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    let mut app = make_app();
+    let press = |app: &mut App, code: KeyCode| {
+        app.handle_event(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+    };
+
+    press(&mut app, KeyCode::Char('2'));
+    press(&mut app, KeyCode::Char('0'));
+    assert_eq!(app.pending_count, Some(20));
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.log_scroll, 20);
+    assert_eq!(app.pending_count, None);
+
+    press(&mut app, KeyCode::Char('5'));
+    press(&mut app, KeyCode::Up);
+    assert_eq!(app.log_scroll, 15);
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.log_scroll, 16);
+
+    // A count typed before another key is dropped, not carried over
+    app.dashboard_focus = DashboardFocus::Inspector;
+    press(&mut app, KeyCode::Char('9'));
+    press(&mut app, KeyCode::Char('t'));
+    assert_eq!(app.pending_count, None);
+    press(&mut app, KeyCode::Down);
+    assert_eq!(app.scroll_offset, 1);
+
+    // Global keys drop it too, so it can't leak into another view
+    press(&mut app, KeyCode::Char('2'));
+    press(&mut app, KeyCode::Tab);
+    assert_eq!(app.pending_count, None);
+    press(&mut app, KeyCode::BackTab);
+
+    // Long digit runs saturate instead of overflowing
+    for _ in 0..30 {
+        press(&mut app, KeyCode::Char('9'));
+    }
+    assert_eq!(app.pending_count, Some(usize::MAX));
+}
+
+#[test]
+fn test_snippet_reply_only_consumes_its_own_request() {
+    let mut app = make_app();
+    app.snippet_pending = Some(3);
+    app.notification = None;
+    let reply = |reply_to, content: &str| ansible_piloteer::ai::ChatMessage {
+        role: "assistant".to_string(),
+        content: content.to_string(),
+        collapsed: false,
+        reply_to,
+    };
+
+    // An ordinary chat reply or a stale snippet reply arriving first is left alone
+    app.take_snippet_reply(&reply(None, "```yaml\na: 1\n```"));
+    app.take_snippet_reply(&reply(Some(2), "```yaml\na: 1\n```"));
+    assert_eq!(app.snippet_pending, Some(3));
+    assert!(app.notification.is_none());
+
+    app.take_snippet_reply(&reply(Some(3), "no code here"));
+    assert_eq!(app.snippet_pending, None);
+    assert_eq!(
+        app.notification.take().unwrap().0,
+        "AI reply contained no YAML snippet"
+    );
+}
@@ -0,0 +1,11 @@
+//! Helpers shared by the integration tests.
+
+use ansible_piloteer::config::Config;
+
+/// Built-in defaults with a fixed model, independent of any local config.
+pub fn test_config() -> Config {
+    Config {
+        model: "gpt-4".to_string(),
+        ..Config::default()
+    }
+}
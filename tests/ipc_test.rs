@@ -3,6 +3,8 @@ use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::UnixStream;
 
+mod common;
+
 #[tokio::test]
 async fn test_ipc_unix_socket_handshake() {
     // Create a temporary path for the socket
@@ -15,7 +17,13 @@ async fn test_ipc_unix_socket_handshake() {
         let server = IpcServer::new(socket_path, None)
             .await
             .expect("Failed to create server");
-        let mut conn = server.accept().await.expect("Failed to accept connection");
+        let mut conn = server
+            .accept()
+            .await
+            .expect("Failed to accept connection")
+            .establish()
+            .await
+            .expect("Failed to establish connection");
 
         // Expect Handshake
         let msg = conn
@@ -23,7 +31,7 @@ async fn test_ipc_unix_socket_handshake() {
             .await
             .expect("Failed to receive")
             .expect("Stream Closed");
-        if let Message::Handshake { token } = msg {
+        if let Message::Handshake { token, .. } = msg {
             assert_eq!(token.as_deref(), Some("secret123"));
             // Send Proceed
             conn.send(&Message::Proceed)
@@ -66,3 +74,566 @@ async fn test_ipc_unix_socket_handshake() {
     server_handle.await.expect("Server task failed");
     let _ = tokio::fs::remove_file(socket_path).await;
 }
+
+#[test]
+fn test_handshake_accepts_any_configured_secret() {
+    use ansible_piloteer::ipc::{AcceptedSecrets, HandshakeAuth};
+
+    let open = AcceptedSecrets::default();
+    assert_eq!(
+        open.authorize(None),
+        HandshakeAuth::Accepted { label: None }
+    );
+
+    let mut secrets = AcceptedSecrets::default();
+    secrets.add("old-secret".to_string(), None);
+    secrets.add("new-secret".to_string(), Some("ci-runner".to_string()));
+
+    assert_eq!(
+        secrets.authorize(Some("old-secret")),
+        HandshakeAuth::Accepted { label: None }
+    );
+    assert_eq!(
+        secrets.authorize(Some("new-secret")),
+        HandshakeAuth::Accepted {
+            label: Some("ci-runner")
+        }
+    );
+    assert_eq!(secrets.authorize(Some("wrong")), HandshakeAuth::Rejected);
+    assert_eq!(secrets.authorize(None), HandshakeAuth::Rejected);
+}
+
+#[test]
+fn test_launch_secret_without_secret_token() {
+    use ansible_piloteer::ipc::{AcceptedSecrets, HandshakeAuth};
+
+    assert_eq!(
+        AcceptedSecrets::from_config(&common::test_config()).launch_secret(),
+        None
+    );
+
+    // Only a secrets file: a launched playbook still gets a secret it accepts
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("secrets");
+    std::fs::write(&path, "# rotated weekly\nci=s3cret\nlegacy\n").unwrap();
+    let config = ansible_piloteer::config::Config {
+        secrets_file: Some(path.display().to_string()),
+        ..common::test_config()
+    };
+    let secrets = AcceptedSecrets::from_config(&config);
+    assert_eq!(secrets.launch_secret(), Some("s3cret"));
+    assert!(matches!(
+        secrets.authorize(secrets.launch_secret()),
+        HandshakeAuth::Accepted { .. }
+    ));
+
+    // secret_token wins when set
+    let config = ansible_piloteer::config::Config {
+        secret_token: Some("primary".to_string()),
+        ..config
+    };
+    assert_eq!(
+        AcceptedSecrets::from_config(&config).launch_secret(),
+        Some("primary")
+    );
+}
+
+#[test]
+fn test_parse_secret_line() {
+    use ansible_piloteer::ipc::parse_secret_line;
+
+    assert_eq!(
+        parse_secret_line("ci-runner=s3cret"),
+        ("s3cret".to_string(), Some("ci-runner".to_string()))
+    );
+    assert_eq!(parse_secret_line("s3cret"), ("s3cret".to_string(), None));
+    // Padded base64 is a token, not `label=token`
+    assert_eq!(parse_secret_line("abc=="), ("abc==".to_string(), None));
+    assert_eq!(parse_secret_line("abc="), ("abc=".to_string(), None));
+    assert_eq!(
+        parse_secret_line("ci=dGVzdA=="),
+        ("dGVzdA==".to_string(), Some("ci".to_string()))
+    );
+    assert_eq!(parse_secret_line("a+b/c=d"), ("a+b/c=d".to_string(), None));
+}
+
+#[tokio::test]
+async fn test_control_client_forwards_resume_commands() {
+    use ansible_piloteer::ipc::{AcceptedSecrets, AuthLimiter, ClientRegistry, IpcConnection};
+
+    let socket_path = "test_ipc_control.sock";
+    let _ = tokio::fs::remove_file(socket_path).await;
+
+    let (to_app_tx, mut to_app_rx) = tokio::sync::mpsc::channel(10);
+    let clients = ClientRegistry::default();
+    ansible_piloteer::ipc_handler::spawn_ipc_server(
+        socket_path.to_string(),
+        None,
+        None,
+        AcceptedSecrets::default(),
+        AuthLimiter::default(),
+        Duration::ZERO,
+        to_app_tx,
+        clients.clone(),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // A control client with no playbook attached is turned away
+    let mut early = IpcConnection::connect(socket_path).await.unwrap();
+    early
+        .send(&Message::Handshake {
+            token: None,
+            label: None,
+            control: true,
+        })
+        .await
+        .unwrap();
+    assert!(early.receive().await.unwrap().is_none());
+
+    // Plugin attaches
+    let mut plugin = IpcConnection::connect(socket_path).await.unwrap();
+    plugin
+        .send(&Message::Handshake {
+            token: None,
+            label: None,
+            control: false,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(
+        to_app_rx.recv().await,
+        Some((_, Message::Handshake { .. }))
+    ));
+
+    // Control client is acknowledged and its command reaches the app
+    let mut control = IpcConnection::connect(socket_path).await.unwrap();
+    control
+        .send(&Message::Handshake {
+            token: None,
+            label: Some("mcp".to_string()),
+            control: true,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(
+        control.receive().await.unwrap(),
+        Some(Message::Handshake { control: true, .. })
+    ));
+    control.send(&Message::Retry).await.unwrap();
+    assert!(matches!(
+        to_app_rx.recv().await,
+        Some((
+            ansible_piloteer::ipc_handler::CONTROL_CLIENT,
+            Message::Retry
+        ))
+    ));
+
+    // The app's verdict goes back to the control client
+    let reply = clients.take_control_reply().unwrap();
+    reply
+        .send(Message::ControlReply {
+            applied: false,
+            detail: "Retry ignored: not paused".to_string(),
+        })
+        .await
+        .unwrap();
+    assert!(matches!(
+        control.receive().await.unwrap(),
+        Some(Message::ControlReply { applied: false, detail }) if detail.contains("not paused")
+    ));
+
+    drop(plugin);
+    let _ = tokio::fs::remove_file(socket_path).await;
+}
+
+#[tokio::test]
+async fn test_concurrent_clients_are_served_independently() {
+    use ansible_piloteer::ipc::{AcceptedSecrets, AuthLimiter, ClientRegistry, IpcConnection};
+
+    let socket_path = "test_ipc_multi.sock";
+    let _ = tokio::fs::remove_file(socket_path).await;
+
+    let (to_app_tx, mut to_app_rx) = tokio::sync::mpsc::channel(10);
+    let clients = ClientRegistry::default();
+    ansible_piloteer::ipc_handler::spawn_ipc_server(
+        socket_path.to_string(),
+        None,
+        None,
+        AcceptedSecrets::default(),
+        AuthLimiter::default(),
+        Duration::ZERO,
+        to_app_tx,
+        clients.clone(),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let handshake = Message::Handshake {
+        token: None,
+        label: None,
+        control: false,
+    };
+    let mut first = IpcConnection::connect(socket_path).await.unwrap();
+    first.send(&handshake).await.unwrap();
+    let Some((first_id, Message::Handshake { .. })) = to_app_rx.recv().await else {
+        panic!("expected first handshake");
+    };
+
+    // The second playbook is accepted while the first is still connected
+    let mut second = IpcConnection::connect(socket_path).await.unwrap();
+    second.send(&handshake).await.unwrap();
+    let Some((second_id, Message::Handshake { .. })) = to_app_rx.recv().await else {
+        panic!("expected second handshake");
+    };
+    assert_ne!(first_id, second_id);
+    assert_eq!(clients.len(), 2);
+
+    // Replies are routed to the right connection
+    clients
+        .sender(second_id)
+        .unwrap()
+        .send(Message::Retry)
+        .await
+        .unwrap();
+    assert!(matches!(
+        second.receive().await.unwrap(),
+        Some(Message::Retry)
+    ));
+
+    drop(first);
+    assert!(matches!(
+        to_app_rx.recv().await,
+        Some((id, Message::ClientDisconnected)) if id == first_id
+    ));
+    assert!(clients.sender(first_id).is_none());
+
+    let _ = tokio::fs::remove_file(socket_path).await;
+}
+
+#[tokio::test]
+async fn test_dry_run_handshake_check() {
+    use ansible_piloteer::ipc::{AcceptedSecrets, IpcConnection};
+    use ansible_piloteer::ipc_handler::{HandshakeCheck, await_handshake};
+
+    let socket_path = "test_ipc_dry_run.sock";
+    let server = IpcServer::new(socket_path, None).await.unwrap();
+    let mut secrets = AcceptedSecrets::default();
+    secrets.add("s3cret".to_string(), Some("ci".to_string()));
+
+    let handshake = |token: &str, control: bool| Message::Handshake {
+        token: Some(token.to_string()),
+        label: None,
+        control,
+    };
+    let client = |msgs: Vec<Message>| {
+        tokio::spawn(async move {
+            for msg in msgs {
+                let mut conn = IpcConnection::connect(socket_path).await.unwrap();
+                conn.send(&msg).await.unwrap();
+            }
+        })
+    };
+
+    // Control clients are skipped; the plugin's token decides
+    client(vec![handshake("s3cret", true), handshake("s3cret", false)]);
+    assert_eq!(
+        await_handshake(&server, &secrets).await.unwrap(),
+        HandshakeCheck::Accepted {
+            label: Some("ci".to_string())
+        }
+    );
+
+    client(vec![handshake("wrong", false)]);
+    assert_eq!(
+        await_handshake(&server, &secrets).await.unwrap(),
+        HandshakeCheck::Rejected
+    );
+
+    client(vec![Message::Proceed]);
+    assert_eq!(
+        await_handshake(&server, &secrets).await.unwrap(),
+        HandshakeCheck::NoHandshake
+    );
+
+    let _ = tokio::fs::remove_file(socket_path).await;
+}
+
+#[tokio::test]
+async fn test_tls_listener_handshake() {
+    use ansible_piloteer::ipc::{IpcConnection, client_tls, server_tls};
+
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/tls");
+    let mut config = ansible_piloteer::config::Config {
+        bind_addr: Some("127.0.0.1:0".to_string()),
+        tls_cert_path: Some(format!("{}/server.pem", fixtures)),
+        ..common::test_config()
+    };
+    // Half a TLS configuration is an error, not a silent plaintext fallback
+    assert!(server_tls(&config).is_err());
+    config.tls_key_path = Some(format!("{}/server.key", fixtures));
+    let tls = server_tls(&config).unwrap();
+    assert!(tls.is_some());
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    drop(listener);
+    let server = IpcServer::new("unused", Some(&addr))
+        .await
+        .unwrap()
+        .with_tls(tls);
+    assert!(server.is_tls());
+
+    // A client that never starts its handshake doesn't hold up the next one
+    let stalled = tokio::net::TcpStream::connect(&addr).await.unwrap();
+    let stalled_conn = server.accept().await.unwrap();
+
+    let connector = client_tls(&format!("{}/ca.pem", fixtures)).unwrap();
+    let client_addr = addr.clone();
+    let client = tokio::spawn(async move {
+        let mut conn = IpcConnection::connect_tls(&client_addr, &connector)
+            .await
+            .unwrap();
+        conn.send(&Message::Handshake {
+            token: Some("secret".to_string()),
+            label: None,
+            control: false,
+        })
+        .await
+        .unwrap();
+        conn.receive().await.unwrap()
+    });
+
+    let mut conn = server.accept().await.unwrap().establish().await.unwrap();
+    assert!(matches!(
+        conn.receive().await.unwrap(),
+        Some(Message::Handshake { token: Some(t), .. }) if t == "secret"
+    ));
+    conn.send(&Message::Proceed).await.unwrap();
+    assert!(matches!(client.await.unwrap(), Some(Message::Proceed)));
+
+    // A plaintext client can't talk to the TLS listener
+    let plain = tokio::spawn(async move {
+        let mut conn = IpcConnection::connect(&addr).await.unwrap();
+        let _ = conn
+            .send(&Message::Handshake {
+                token: None,
+                label: None,
+                control: false,
+            })
+            .await;
+    });
+    assert!(server.accept().await.unwrap().establish().await.is_err());
+    plain.await.unwrap();
+    drop((stalled, stalled_conn));
+}
+
+#[tokio::test]
+async fn test_repeated_bad_handshakes_lock_out_peer() {
+    use ansible_piloteer::ipc::{AcceptedSecrets, AuthLimiter, ClientRegistry, IpcConnection};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    drop(listener);
+
+    let mut secrets = AcceptedSecrets::default();
+    secrets.add("right".to_string(), None);
+    let (to_app_tx, mut to_app_rx) = tokio::sync::mpsc::channel(10);
+    ansible_piloteer::ipc_handler::spawn_ipc_server(
+        "test_ipc_lockout.sock".to_string(),
+        Some(addr.clone()),
+        None,
+        secrets,
+        AuthLimiter::new(3, Duration::from_secs(60), Duration::from_secs(300)),
+        Duration::ZERO,
+        to_app_tx,
+        ClientRegistry::default(),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let handshake = |token: &str| Message::Handshake {
+        token: Some(token.to_string()),
+        label: None,
+        control: false,
+    };
+    for _ in 0..3 {
+        let mut conn = IpcConnection::connect(&addr).await.unwrap();
+        conn.send(&handshake("wrong")).await.unwrap();
+        // The server hangs up on a rejected token
+        assert!(conn.receive().await.unwrap_or(None).is_none());
+    }
+
+    // Even the right token is refused while the address is locked out
+    let mut conn = IpcConnection::connect(&addr).await.unwrap();
+    let _ = conn.send(&handshake("right")).await;
+    assert!(conn.receive().await.unwrap_or(None).is_none());
+    assert!(
+        tokio::time::timeout(Duration::from_millis(200), to_app_rx.recv())
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_heartbeat_drops_silent_client() {
+    use ansible_piloteer::ipc::{AcceptedSecrets, AuthLimiter, ClientRegistry, IpcConnection};
+
+    let socket_path = "test_ipc_heartbeat.sock";
+    let _ = tokio::fs::remove_file(socket_path).await;
+
+    let (to_app_tx, mut to_app_rx) = tokio::sync::mpsc::channel(10);
+    ansible_piloteer::ipc_handler::spawn_ipc_server(
+        socket_path.to_string(),
+        None,
+        None,
+        AcceptedSecrets::default(),
+        AuthLimiter::default(),
+        Duration::from_millis(100),
+        to_app_tx,
+        ClientRegistry::default(),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut plugin = IpcConnection::connect(socket_path).await.unwrap();
+    plugin
+        .send(&Message::Handshake {
+            token: None,
+            label: None,
+            control: false,
+        })
+        .await
+        .unwrap();
+    let Some((id, Message::Handshake { .. })) = to_app_rx.recv().await else {
+        panic!("expected handshake");
+    };
+
+    // Answered pings keep the connection up and never reach the app
+    for _ in 0..5 {
+        assert!(matches!(
+            plugin.receive().await.unwrap(),
+            Some(Message::Ping)
+        ));
+        plugin.send(&Message::Pong).await.unwrap();
+    }
+    assert!(to_app_rx.try_recv().is_err());
+
+    // A plugin that stops answering is disconnected
+    let dropped = tokio::time::timeout(Duration::from_secs(2), to_app_rx.recv()).await;
+    assert!(matches!(
+        dropped,
+        Ok(Some((c, Message::ClientDisconnected))) if c == id
+    ));
+
+    let _ = tokio::fs::remove_file(socket_path).await;
+}
+
+#[tokio::test]
+async fn test_abort_waits_for_clients_to_disconnect() {
+    use ansible_piloteer::ipc::{AcceptedSecrets, AuthLimiter, ClientRegistry, IpcConnection};
+    use ansible_piloteer::ipc_handler::abort_clients;
+
+    let socket_path = "test_ipc_abort.sock";
+    let _ = tokio::fs::remove_file(socket_path).await;
+
+    let (to_app_tx, mut to_app_rx) = tokio::sync::mpsc::channel(10);
+    let clients = ClientRegistry::default();
+    ansible_piloteer::ipc_handler::spawn_ipc_server(
+        socket_path.to_string(),
+        None,
+        None,
+        AcceptedSecrets::default(),
+        AuthLimiter::default(),
+        Duration::ZERO,
+        to_app_tx,
+        clients.clone(),
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // Nobody connected: nothing to wait for
+    assert!(abort_clients(&clients, &mut to_app_rx, Duration::from_secs(5)).await);
+
+    let mut plugin = IpcConnection::connect(socket_path).await.unwrap();
+    plugin
+        .send(&Message::Handshake {
+            token: None,
+            label: None,
+            control: false,
+        })
+        .await
+        .unwrap();
+    assert!(matches!(
+        to_app_rx.recv().await,
+        Some((_, Message::Handshake { .. }))
+    ));
+
+    // A plugin that ignores the abort holds shutdown only for the grace period
+    let start = std::time::Instant::now();
+    assert!(!abort_clients(&clients, &mut to_app_rx, Duration::from_millis(200)).await);
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(matches!(
+        plugin.receive().await.unwrap(),
+        Some(Message::Abort)
+    ));
+
+    // One that hangs up acknowledges it
+    let waiter = tokio::spawn(async move {
+        abort_clients(&clients, &mut to_app_rx, Duration::from_secs(5)).await
+    });
+    assert!(matches!(
+        plugin.receive().await.unwrap(),
+        Some(Message::Abort)
+    ));
+    drop(plugin);
+    assert!(waiter.await.unwrap());
+
+    let _ = tokio::fs::remove_file(socket_path).await;
+}
+
+#[tokio::test]
+async fn test_receive_keeps_buffered_and_partial_lines() {
+    use ansible_piloteer::ipc::{ConnectionStream, IpcConnection};
+
+    let (server, mut plugin) = UnixStream::pair().unwrap();
+    let mut conn = IpcConnection::new(ConnectionStream::Unix(server));
+
+    // Two messages in one write both arrive
+    plugin
+        .write_all(b"\"Pong\"\n{\"PlayStart\":{\"name\":\"Deploy\",\"host_pattern\":\"all\"}}\n")
+        .await
+        .unwrap();
+    assert!(matches!(conn.receive().await.unwrap(), Some(Message::Pong)));
+    assert!(matches!(
+        conn.receive().await.unwrap(),
+        Some(Message::PlayStart { name, .. }) if name == "Deploy"
+    ));
+
+    // A receive cancelled mid-line picks the line up again
+    plugin
+        .write_all(b"{\"PlayStart\":{\"name\":\"Ha")
+        .await
+        .unwrap();
+    let cancelled = tokio::time::timeout(Duration::from_millis(50), conn.receive()).await;
+    assert!(cancelled.is_err());
+    plugin
+        .write_all(b"lf\",\"host_pattern\":\"web\"}}\n")
+        .await
+        .unwrap();
+    assert!(matches!(
+        conn.receive().await.unwrap(),
+        Some(Message::PlayStart { name, .. }) if name == "Half"
+    ));
+
+    drop(plugin);
+    assert!(conn.receive().await.unwrap().is_none());
+}
+
+#[test]
+fn test_local_connect_addr() {
+    use ansible_piloteer::ipc::local_connect_addr;
+
+    assert_eq!(local_connect_addr("0.0.0.0:9000"), "localhost:9000");
+    assert_eq!(local_connect_addr("[::]:9000"), "localhost:9000");
+    assert_eq!(local_connect_addr("192.168.1.5:9000"), "192.168.1.5:9000");
+    assert_eq!(
+        local_connect_addr("ctl.example.com:9000"),
+        "ctl.example.com:9000"
+    );
+}
@@ -3,35 +3,11 @@ use ansible_piloteer::app::{App, TaskHistory};
 use ansible_piloteer::config::Config;
 use ansible_piloteer::report::ReportGenerator;
 
-fn create_test_config() -> Config {
-    Config::new().unwrap_or_else(|_| Config {
-        openai_api_key: None,
-        socket_path: "/tmp/test.sock".to_string(),
-        model: "gpt-4".to_string(),
-        api_base: "https://api.openai.com/v1".to_string(),
-        log_level: "info".to_string(),
-        auth_token: None,
-        bind_addr: None,
-        secret_token: None,
-        quota_limit_tokens: None,
-        quota_limit_usd: None,
-        google_api_key: None,
-        google_client_id: None,
-        google_client_secret: None,
-        zipkin_endpoint: None,
-        zipkin_service_name: "ansible-piloteer".to_string(),
-        zipkin_sample_rate: 1.0,
-        filters: None,
-        provider: None,
-        anthropic_api_key: None,
-        vertex_project_id: None,
-        vertex_location: Some("us-central1".to_string()),
-    })
-}
+mod common;
 
 #[test]
 fn test_report_with_unreachable_hosts() {
-    let config = create_test_config();
+    let config = common::test_config();
     let mut app = App::new(config);
 
     // Add unreachable host
@@ -43,16 +19,8 @@ fn test_report_with_unreachable_hosts() {
     );
 
     // Add successful task
-    app.history.push(TaskHistory {
-        name: "successful_task".to_string(),
-        host: "working_host".to_string(),
-        changed: false,
-        failed: false,
-        duration: 0.0,
-        error: None,
-        verbose_result: None,
-        analysis: None,
-    });
+    app.history
+        .push(TaskHistory::new("successful_task", "working_host"));
 
     // Generate report
     let generator = ReportGenerator::new(&app);
@@ -70,18 +38,13 @@ fn test_report_with_unreachable_hosts() {
 
 #[test]
 fn test_report_with_ai_analysis() {
-    let config = create_test_config();
+    let config = common::test_config();
     let mut app = App::new(config);
 
     // Add failed task with AI analysis
     app.history.push(TaskHistory {
-        name: "failed_task".to_string(),
-        host: "test_host".to_string(),
-        changed: false,
         failed: true,
-        duration: 0.0,
         error: Some("Module failed".to_string()),
-        verbose_result: None,
         analysis: Some(Analysis {
             analysis: "The task failed because the package is not available in the repository."
                 .to_string(),
@@ -91,6 +54,7 @@ fn test_report_with_ai_analysis() {
             }),
             tokens_used: 100,
         }),
+        ..TaskHistory::new("failed_task", "test_host")
     });
 
     // Generate report
@@ -105,43 +69,23 @@ fn test_report_with_ai_analysis() {
 
 #[test]
 fn test_report_with_mixed_results() {
-    let config = create_test_config();
+    let config = common::test_config();
     let mut app = App::new(config);
 
     // Add OK task
-    app.history.push(TaskHistory {
-        name: "ok_task".to_string(),
-        host: "host1".to_string(),
-        changed: false,
-        failed: false,
-        duration: 0.0,
-        error: None,
-        verbose_result: None,
-        analysis: None,
-    });
+    app.history.push(TaskHistory::new("ok_task", "host1"));
 
     // Add changed task
     app.history.push(TaskHistory {
-        name: "changed_task".to_string(),
-        host: "host2".to_string(),
         changed: true,
-        failed: false,
-        duration: 0.0,
-        error: None,
-        verbose_result: None,
-        analysis: None,
+        ..TaskHistory::new("changed_task", "host2")
     });
 
     // Add failed task
     app.history.push(TaskHistory {
-        name: "failed_task".to_string(),
-        host: "host3".to_string(),
-        changed: false,
         failed: true,
-        duration: 0.0,
         error: Some("Task error".to_string()),
-        verbose_result: None,
-        analysis: None,
+        ..TaskHistory::new("failed_task", "host3")
     });
 
     // Add unreachable host
@@ -171,19 +115,13 @@ fn test_report_with_mixed_results() {
 
 #[test]
 fn test_report_structure() {
-    let config = create_test_config();
+    let config = common::test_config();
     let mut app = App::new(config);
 
     // Add some tasks
     app.history.push(TaskHistory {
-        name: "test_task".to_string(),
-        host: "localhost".to_string(),
         changed: true,
-        failed: false,
-        duration: 0.0,
-        error: None,
-        verbose_result: None,
-        analysis: None,
+        ..TaskHistory::new("test_task", "localhost")
     });
 
     // Generate report
@@ -198,7 +136,7 @@ fn test_report_structure() {
 
 #[test]
 fn test_empty_report() {
-    let config = create_test_config();
+    let config = common::test_config();
     let app = App::new(config);
 
     // Generate report with no history
@@ -212,18 +150,13 @@ fn test_empty_report() {
 
 #[test]
 fn test_report_with_multiple_analyses() {
-    let config = create_test_config();
+    let config = common::test_config();
     let mut app = App::new(config);
 
     // Add multiple failed tasks with analyses
     app.history.push(TaskHistory {
-        name: "fail1".to_string(),
-        host: "host1".to_string(),
-        changed: false,
         failed: true,
-        duration: 0.0,
         error: Some("Error 1".to_string()),
-        verbose_result: None,
         analysis: Some(Analysis {
             analysis: "Analysis 1".to_string(),
             fix: Some(Fix {
@@ -232,16 +165,12 @@ fn test_report_with_multiple_analyses() {
             }),
             tokens_used: 50,
         }),
+        ..TaskHistory::new("fail1", "host1")
     });
 
     app.history.push(TaskHistory {
-        name: "fail2".to_string(),
-        host: "host2".to_string(),
-        changed: false,
         failed: true,
-        duration: 0.0,
         error: Some("Error 2".to_string()),
-        verbose_result: None,
         analysis: Some(Analysis {
             analysis: "Analysis 2".to_string(),
             fix: Some(Fix {
@@ -250,6 +179,7 @@ fn test_report_with_multiple_analyses() {
             }),
             tokens_used: 60,
         }),
+        ..TaskHistory::new("fail2", "host2")
     });
 
     // Generate report
@@ -260,3 +190,139 @@ fn test_report_with_multiple_analyses() {
     assert!(report.contains("Analysis 1"));
     assert!(report.contains("Analysis 2"));
 }
+
+#[test]
+fn test_html_report_is_self_contained() {
+    let config = common::test_config();
+    let mut app = App::new(config);
+
+    app.history.push(TaskHistory {
+        changed: true,
+        duration: 1.25,
+        verbose_result: Some(ansible_piloteer::execution::ExecutionDetails::new(
+            serde_json::json!({"stdout": "a & b"}),
+        )),
+        ..TaskHistory::new("install <nginx>", "web1")
+    });
+    app.history.push(TaskHistory {
+        failed: true,
+        duration: 0.5,
+        error: Some("Service not found".to_string()),
+        ..TaskHistory::new("start nginx", "web1")
+    });
+
+    let html = ReportGenerator::new(&app).generate_html();
+
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<style>"));
+    assert!(!html.contains("<link") && !html.contains("<script"));
+
+    // Names are escaped and badges are color-coded by status
+    assert!(html.contains("install &lt;nginx&gt;"));
+    assert!(html.contains("<span class=\"badge changed\">CHANGED</span>"));
+    assert!(html.contains("<span class=\"badge failed\">FAILED</span>"));
+
+    // Drift summary lists the changed task; raw results sit in <details>
+    assert!(html.contains("Total Drift: 1 tasks changed."));
+    assert!(html.contains("<details class=\"raw\">"));
+    assert!(html.contains("a &amp; b"));
+    assert!(html.contains("Service not found"));
+}
+
+#[test]
+fn test_pilot_analysis_in_markdown_and_json() {
+    let config = common::test_config();
+    let mut app = App::new(config);
+
+    app.history.push(TaskHistory {
+        failed: true,
+        duration: 0.3,
+        error: Some("No package matching 'nginx'".to_string()),
+        analysis: Some(Analysis {
+            analysis: "The repository index is stale.\nRefresh it first.".to_string(),
+            fix: Some(Fix {
+                key: "update_cache".to_string(),
+                value: serde_json::json!(true),
+            }),
+            tokens_used: 42,
+        }),
+        ..TaskHistory::new("install package", "web1")
+    });
+
+    let report = ReportGenerator::new(&app).generate_markdown();
+    assert!(report.contains("#### 🤖 Pilot Analysis"));
+    assert!(report.contains("> The repository index is stale.\n> Refresh it first.\n"));
+    assert!(report.contains("- Variable: `update_cache`"));
+    assert!(report.contains("- Value: `true`"));
+
+    // The JSON report serializes history directly; analysis must survive it
+    let json = serde_json::to_string_pretty(&app.history).unwrap();
+    let back: Vec<TaskHistory> = serde_json::from_str(&json).unwrap();
+    let analysis = back[0].analysis.as_ref().expect("analysis round-trips");
+    assert_eq!(
+        analysis.analysis,
+        "The repository index is stale.\nRefresh it first."
+    );
+    let fix = analysis.fix.as_ref().unwrap();
+    assert_eq!(fix.key, "update_cache");
+    assert_eq!(fix.value, serde_json::json!(true));
+}
+
+#[test]
+fn test_reports_include_spilled_results() {
+    let mut app = App::new(Config {
+        max_history_in_memory: Some(1),
+        ..common::test_config()
+    });
+    for i in 0..3 {
+        app.record_task_result(
+            format!("task {}", i),
+            "web1".to_string(),
+            false,
+            false,
+            0.1,
+            None,
+            Some(ansible_piloteer::execution::ExecutionDetails::new(
+                serde_json::json!({"stdout": format!("output {}", i)}),
+            )),
+            None,
+        );
+    }
+    assert!(app.history[0].verbose_result.is_none());
+
+    let report = ReportGenerator::new(&app).generate_markdown();
+    assert_eq!(report.matches("Details Captured").count(), 3);
+
+    let html = ReportGenerator::new(&app).generate_html();
+    assert!(html.contains("output 0"));
+    assert!(html.contains("output 2"));
+}
+
+#[test]
+fn test_report_host_tables_sorted_by_name() {
+    let mut app = App::new(common::test_config());
+    for host in ["web3", "db1", "web1", "app2"] {
+        app.record_task_result(
+            "ping".to_string(),
+            host.to_string(),
+            false,
+            false,
+            0.1,
+            None,
+            None,
+            None,
+        );
+    }
+
+    let md = ReportGenerator::new(&app).generate_markdown();
+    let pos = |text: &str, host: &str| text.find(&format!("| {} |", host)).unwrap();
+    assert!(pos(&md, "app2") < pos(&md, "db1"));
+    assert!(pos(&md, "db1") < pos(&md, "web1"));
+    assert!(pos(&md, "web1") < pos(&md, "web3"));
+
+    let html = ReportGenerator::new(&app).generate_html();
+    let pos = |host: &str| html.find(&format!("<tr><td>{}</td>", host)).unwrap();
+    assert!(pos("app2") < pos("db1"));
+    assert!(pos("db1") < pos("web1"));
+    assert!(pos("web1") < pos("web3"));
+}
@@ -1,32 +1,11 @@
 use ansible_piloteer::app::{App, HostStatus, TaskHistory};
-use ansible_piloteer::config::Config;
+
+mod common;
 
 #[test]
 fn test_session_save_and_load() {
     // 1. Create a dummy App with data
-    let config = Config::new().unwrap_or_else(|_| Config {
-        openai_api_key: None,
-        socket_path: "/tmp/test.sock".to_string(),
-        model: "gpt-4".to_string(),
-        api_base: "https://api.openai.com/v1".to_string(),
-        log_level: "info".to_string(),
-        auth_token: None,
-        bind_addr: None,
-        secret_token: None,
-        quota_limit_tokens: None,
-        quota_limit_usd: None,
-        google_api_key: None,
-        google_client_id: None,
-        google_client_secret: None,
-        zipkin_endpoint: None,
-        zipkin_service_name: "ansible-piloteer".to_string(),
-        zipkin_sample_rate: 1.0,
-        filters: None,
-        provider: None,
-        anthropic_api_key: None,
-        vertex_project_id: None,
-        vertex_location: Some("us-central1".to_string()),
-    });
+    let config = common::test_config();
 
     // We need to construct App manually or via new
     // App::new requires valid config.
@@ -34,14 +13,8 @@ fn test_session_save_and_load() {
 
     // Populate history
     app.history.push(TaskHistory {
-        name: "Test Task 1".to_string(),
-        host: "localhost".to_string(),
         changed: true,
-        failed: false,
-        duration: 0.0,
-        error: None,
-        verbose_result: None,
-        analysis: None,
+        ..TaskHistory::new("Test Task 1", "localhost")
     });
 
     // Populate hosts
@@ -52,6 +25,8 @@ fn test_session_save_and_load() {
             ok_tasks: 1,
             changed_tasks: 0,
             failed_tasks: 0,
+            rescued_tasks: 0,
+            skipped_tasks: 0,
         },
     );
 
@@ -78,3 +53,233 @@ fn test_session_save_and_load() {
     // Cleanup
     std::fs::remove_file(filename).unwrap_or(());
 }
+
+#[test]
+fn test_session_note_round_trip() {
+    use ansible_piloteer::session::Session;
+
+    let session = Session {
+        schema_version: ansible_piloteer::session::SESSION_SCHEMA_VERSION,
+        timestamp: chrono::Utc::now(),
+        history: Vec::new(),
+        logs: Vec::new(),
+        facts: None,
+        task_vars: None,
+        hosts: Default::default(),
+        play_recap: None,
+        unreachable_hosts: Default::default(),
+        session_note: Some("investigating prod outage, ticket #123".to_string()),
+        warnings: Vec::new(),
+    };
+
+    let filename = "test_session_note.json.gz";
+    session.save(filename).expect("Failed to save session");
+    let loaded = Session::load(filename).expect("Failed to load session");
+    std::fs::remove_file(filename).unwrap_or(());
+
+    assert_eq!(
+        loaded.session_note.as_deref(),
+        Some("investigating prod outage, ticket #123")
+    );
+
+    // Archives written before notes existed still load
+    let mut value = serde_json::to_value(&loaded).unwrap();
+    value.as_object_mut().unwrap().remove("session_note");
+    let old: Session = serde_json::from_value(value).unwrap();
+    assert!(old.session_note.is_none());
+}
+
+#[test]
+fn test_session_encoding_follows_extension() {
+    use ansible_piloteer::session::Session;
+
+    let session = Session {
+        schema_version: ansible_piloteer::session::SESSION_SCHEMA_VERSION,
+        timestamp: chrono::Utc::now(),
+        history: Vec::new(),
+        logs: Vec::new(),
+        facts: None,
+        task_vars: None,
+        hosts: Default::default(),
+        play_recap: None,
+        unreachable_hosts: Default::default(),
+        session_note: Some("encoding".to_string()),
+        warnings: Vec::new(),
+    };
+
+    // `.json` is plain JSON, readable by jq
+    let plain = "test_session_plain.json";
+    session.save(plain).expect("Failed to save plain session");
+    let text = std::fs::read_to_string(plain).unwrap();
+    assert!(serde_json::from_str::<serde_json::Value>(&text).is_ok());
+    let loaded = Session::load(plain).expect("Failed to load plain session");
+    std::fs::remove_file(plain).unwrap_or(());
+    assert_eq!(loaded.session_note.as_deref(), Some("encoding"));
+
+    // `.json.gz` stays gzipped
+    let gz = "test_session_gz.json.gz";
+    session.save(gz).expect("Failed to save gzipped session");
+    let bytes = std::fs::read(gz).unwrap();
+    assert_eq!(&bytes[..2], &[0x1f, 0x8b]);
+    let loaded = Session::load(gz).expect("Failed to load gzipped session");
+    assert_eq!(loaded.session_note.as_deref(), Some("encoding"));
+
+    // A gzipped archive renamed to `.json` is still detected as gzip
+    let renamed = "test_session_renamed.json";
+    std::fs::rename(gz, renamed).unwrap();
+    let loaded = Session::load(renamed).expect("Failed to load renamed session");
+    std::fs::remove_file(renamed).unwrap_or(());
+    assert_eq!(loaded.session_note.as_deref(), Some("encoding"));
+}
+
+#[test]
+fn test_v1_session_is_migrated_on_load() {
+    use ansible_piloteer::session::{SESSION_SCHEMA_VERSION, Session};
+
+    // Written before schema versions, notes, warnings, rescues and timestamps
+    let v1 = r#"{
+        "timestamp": "2024-01-01T12:00:00Z",
+        "history": [{
+            "name": "install nginx",
+            "host": "web1",
+            "changed": true,
+            "failed": false,
+            "duration": 1.5,
+            "error": null,
+            "verbose_result": null
+        }],
+        "logs": [["Task: install nginx", "Reset"]],
+        "facts": null,
+        "task_vars": null,
+        "hosts": {"web1": {"name": "web1", "ok_tasks": 1, "changed_tasks": 1, "failed_tasks": 0}},
+        "play_recap": null,
+        "unreachable_hosts": []
+    }"#;
+    let filename = "test_session_v1.json";
+    std::fs::write(filename, v1).unwrap();
+    let loaded = Session::load(filename);
+    std::fs::remove_file(filename).unwrap_or(());
+    let loaded = loaded.expect("v1 session should load");
+
+    assert_eq!(loaded.schema_version, SESSION_SCHEMA_VERSION);
+    assert_eq!(loaded.history[0].name, "install nginx");
+    assert!(!loaded.history[0].rescued);
+    assert!(loaded.history[0].started_at.is_none());
+    assert!(loaded.session_note.is_none());
+    assert!(loaded.warnings.is_empty());
+    assert_eq!(loaded.hosts["web1"].rescued_tasks, 0);
+
+    // Files from a newer build are refused rather than half-read
+    let mut future: serde_json::Value = serde_json::from_str(v1).unwrap();
+    future["schema_version"] = (SESSION_SCHEMA_VERSION + 1).into();
+    let err = Session::from_value(future)
+        .err()
+        .expect("newer version must fail");
+    assert!(err.to_string().contains("newer than this build supports"));
+}
+
+#[test]
+fn test_rerun_hint_from_changed_tasks() {
+    use ansible_piloteer::session::RerunHint;
+
+    let task = |name: &str, host: &str, changed: bool, failed: bool| TaskHistory {
+        changed,
+        failed,
+        duration: 0.1,
+        ..TaskHistory::new(name, host)
+    };
+    let history = vec![
+        task("Gather facts", "web1", false, false),
+        task("Install nginx", "web2", true, false),
+        task("Install nginx", "web1", true, false),
+        task("Write owner's config", "web1", false, true),
+        task("Verify", "web1", false, false),
+    ];
+
+    let hint = RerunHint::from_history(&history).unwrap();
+    assert_eq!(hint.start_at_task, "Install nginx");
+    assert_eq!(hint.hosts, vec!["web1", "web2"]);
+    assert_eq!(hint.tasks, vec!["Install nginx", "Write owner's config"]);
+    assert_eq!(
+        hint.args(),
+        "--start-at-task 'Install nginx' --limit 'web1,web2'"
+    );
+
+    let quoted = RerunHint::from_history(&history[3..]).unwrap();
+    assert_eq!(
+        quoted.args(),
+        "--start-at-task 'Write owner'\\''s config' --limit 'web1'"
+    );
+
+    assert!(RerunHint::from_history(&history[..1]).is_none());
+}
+
+#[test]
+fn test_archive_list_and_prune() {
+    use ansible_piloteer::session::{
+        SESSION_SCHEMA_VERSION, Session, list_archive, parse_age, select_prune,
+    };
+
+    let tmp = tempfile::tempdir().unwrap();
+    let now = chrono::Utc::now();
+    for (name, days_ago, failed) in [
+        ("session_a.json.gz", 10, true),
+        ("session_b.json", 3, false),
+        ("session_c.json.gz", 0, false),
+    ] {
+        let session = Session {
+            schema_version: SESSION_SCHEMA_VERSION,
+            timestamp: now - chrono::Duration::days(days_ago),
+            history: vec![ansible_piloteer::app::TaskHistory {
+                failed,
+                duration: 1.0,
+                ..ansible_piloteer::app::TaskHistory::new("t", "web1")
+            }],
+            logs: Vec::new(),
+            facts: None,
+            task_vars: None,
+            hosts: Default::default(),
+            play_recap: None,
+            unreachable_hosts: Default::default(),
+            session_note: None,
+            warnings: Vec::new(),
+        };
+        session
+            .save(&tmp.path().join(name).to_string_lossy())
+            .unwrap();
+    }
+    std::fs::write(tmp.path().join("session_corrupt.json.gz"), b"not a session").unwrap();
+    std::fs::write(tmp.path().join("notes.txt"), b"ignored").unwrap();
+
+    let ansible_piloteer::session::ArchiveListing {
+        sessions,
+        unreadable,
+    } = list_archive(tmp.path()).unwrap();
+    let names: Vec<_> = sessions
+        .iter()
+        .map(|s| s.path.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    assert_eq!(
+        names,
+        vec!["session_c.json.gz", "session_b.json", "session_a.json.gz"]
+    );
+    assert_eq!((sessions[2].tasks, sessions[2].failures), (1, 1));
+    assert_eq!(unreadable.len(), 1);
+    assert!(unreadable[0].0.ends_with("session_corrupt.json.gz"));
+
+    // Keep the newest one
+    let doomed = select_prune(&sessions, 1, None, now);
+    assert_eq!(doomed.len(), 2);
+
+    // Past the newest one, only what is older than a week
+    let doomed = select_prune(&sessions, 1, Some(parse_age("7d").unwrap()), now);
+    assert_eq!(doomed.len(), 1);
+    assert!(doomed[0].path.ends_with("session_a.json.gz"));
+
+    assert_eq!(parse_age("12h").unwrap(), chrono::Duration::hours(12));
+    assert!(parse_age("7").is_err());
+    assert!(parse_age("d").is_err());
+    // Too large for a duration: an error, not a panic
+    assert!(parse_age("9999999999999999d").is_err());
+    assert!(parse_age("9223372036854775807w").is_err());
+}
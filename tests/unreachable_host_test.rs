@@ -1,32 +1,7 @@
 use ansible_piloteer::app::{App, TaskHistory};
-use ansible_piloteer::config::Config;
 use ansible_piloteer::ipc::Message;
 
-fn create_test_config() -> Config {
-    Config::new().unwrap_or_else(|_| Config {
-        openai_api_key: None,
-        socket_path: "/tmp/test.sock".to_string(),
-        model: "gpt-4".to_string(),
-        api_base: "https://api.openai.com/v1".to_string(),
-        log_level: "info".to_string(),
-        auth_token: None,
-        bind_addr: None,
-        secret_token: None,
-        quota_limit_tokens: None,
-        quota_limit_usd: None,
-        google_api_key: None,
-        google_client_id: None,
-        google_client_secret: None,
-        zipkin_endpoint: None,
-        zipkin_service_name: "ansible-piloteer".to_string(),
-        zipkin_sample_rate: 1.0,
-        filters: None,
-        provider: None,
-        anthropic_api_key: None,
-        vertex_project_id: None,
-        vertex_location: Some("us-central1".to_string()),
-    })
-}
+mod common;
 
 #[test]
 fn test_unreachable_message_parsing() {
@@ -73,7 +48,7 @@ fn test_unreachable_message_with_details() {
 
 #[test]
 fn test_unreachable_app_state_tracking() {
-    let config = create_test_config();
+    let config = common::test_config();
 
     let mut app = App::new(config);
 
@@ -103,7 +78,7 @@ fn test_unreachable_app_state_tracking() {
 
 #[test]
 fn test_multiple_unreachable_hosts() {
-    let config = create_test_config();
+    let config = common::test_config();
 
     let mut app = App::new(config);
 
@@ -141,7 +116,7 @@ fn test_multiple_unreachable_hosts() {
 
 #[test]
 fn test_unreachable_session_persistence() {
-    let config = create_test_config();
+    let config = common::test_config();
 
     let mut app = App::new(config);
 
@@ -202,21 +177,13 @@ fn test_unreachable_session_persistence() {
 
 #[test]
 fn test_unreachable_with_normal_tasks() {
-    let config = create_test_config();
+    let config = common::test_config();
 
     let mut app = App::new(config);
 
     // Add successful task
-    app.history.push(TaskHistory {
-        name: "successful_task".to_string(),
-        host: "host1".to_string(),
-        changed: false,
-        failed: false,
-        duration: 0.0,
-        error: None,
-        verbose_result: None,
-        analysis: None,
-    });
+    app.history
+        .push(TaskHistory::new("successful_task", "host1"));
 
     // Add unreachable host
     app.set_unreachable(
@@ -228,14 +195,9 @@ fn test_unreachable_with_normal_tasks() {
 
     // Add failed task (different from unreachable)
     app.history.push(TaskHistory {
-        name: "failed_task".to_string(),
-        host: "host3".to_string(),
-        changed: false,
         failed: true,
-        duration: 0.0,
         error: Some("Task error".to_string()),
-        verbose_result: None,
-        analysis: None,
+        ..TaskHistory::new("failed_task", "host3")
     });
 
     // Verify counts
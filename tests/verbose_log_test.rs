@@ -50,3 +50,56 @@ fn test_execution_details_cmd_string() {
     let details = ExecutionDetails::new(raw_json);
     assert_eq!(details.cmd(), Some("echo 'Hello World'".to_string()));
 }
+
+#[test]
+fn test_no_child_log_without_debug_files() {
+    let tmp = tempfile::tempdir().unwrap();
+
+    let log = ansible_piloteer::execution::open_child_log(tmp.path(), false).unwrap();
+    assert!(log.is_none());
+    assert_eq!(std::fs::read_dir(tmp.path()).unwrap().count(), 0);
+
+    let (_, path) = ansible_piloteer::execution::open_child_log(tmp.path(), true)
+        .unwrap()
+        .expect("log file should be opened when enabled");
+    assert!(path.exists());
+    assert!(
+        path.file_name()
+            .unwrap()
+            .to_string_lossy()
+            .contains(&std::process::id().to_string())
+    );
+}
+
+#[test]
+fn test_find_in_path() {
+    use ansible_piloteer::execution::find_in_path;
+
+    let empty = tempfile::tempdir().unwrap();
+    let bin = tempfile::tempdir().unwrap();
+    let exe = bin.path().join("ansible-playbook");
+    std::fs::write(&exe, "#!/bin/sh\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let path = std::env::join_paths([empty.path(), bin.path()]).unwrap();
+    assert_eq!(find_in_path("ansible-playbook", Some(&path)), Some(exe));
+    assert_eq!(find_in_path("ansible-galaxy", Some(&path)), None);
+
+    let only_empty = std::env::join_paths([empty.path()]).unwrap();
+    assert_eq!(find_in_path("ansible-playbook", Some(&only_empty)), None);
+    assert_eq!(find_in_path("ansible-playbook", None), None);
+
+    // Non-executable files are not picked up
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let plain = empty.path().join("ansible-playbook");
+        std::fs::write(&plain, "").unwrap();
+        std::fs::set_permissions(&plain, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(find_in_path("ansible-playbook", Some(&only_empty)), None);
+    }
+}